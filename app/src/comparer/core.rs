@@ -1,18 +1,34 @@
-use crate::comparer::production::{self, ChildRef, PartitionContext};
+use crate::comparer::creation_script;
+use crate::comparer::data_checks;
+use crate::comparer::fingerprint;
+use crate::comparer::liquibase;
+use crate::comparer::lock_levels;
+use crate::comparer::migration;
+use crate::comparer::production::{
+    self, ChildRef, PartitionContext, PostCommitPhase, PostCommitScript,
+};
+use crate::comparer::progress_ledger;
+use crate::config::column_order_mode::ColumnOrderMode;
 use crate::config::grants_mode::GrantsMode;
+use crate::config::lock_settings::LockSettings;
+use crate::config::migration_format::MigrationFormat;
+use crate::config::object_filter::ObjectFilter;
 use crate::dump::acl;
 use crate::dump::column_dependent::ColumnDependentKind;
-use crate::dump::table::IndexAlterPlan;
+use crate::dump::metadata;
+use crate::dump::secrets::Secrets;
+use crate::dump::table::{IndexAlterPlan, PartitionMembershipChange};
 use crate::dump::table_column::TableColumn;
 use crate::dump::table_constraint::TableConstraint;
 use crate::dump::table_index::TableIndex;
 use crate::dump::table_policy::TablePolicy;
-use crate::dump::{core::Dump, routine::Routine, table::Table, view::View};
+use crate::dump::{core::Dump, routine::Routine, sequence::Sequence, table::Table, view::View};
 use crate::utils::string_extensions::StringExt;
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     fs::File,
     io::{Error, Write},
+    path::Path,
 };
 
 // This is a Dump comparer that generates a script comparing two PostgreSQL dumps.
@@ -39,8 +55,12 @@ pub struct Comparer {
     script: String,
     // Statements that must run after the main transaction commits, because
     // CONCURRENTLY / VALIDATE CONSTRAINT / ATTACH PARTITION cannot run inside a
-    // transaction block. Only populated when `output_for_production` is set.
-    production_post_script: String,
+    // transaction block, ordered by phase. Only populated when
+    // `output_for_production` is set.
+    production_post_script: PostCommitScript,
+    // Byte offset in `script` where the rendered post-commit section starts
+    // (the end of the script when there is none). Set by `compare`.
+    post_commit_start: Option<usize>,
     enum_pre_script: String,
     enum_post_script: String,
     type_post_script: String,
@@ -51,6 +71,57 @@ pub struct Comparer {
     // (e.g. partition key change).  These will receive auto-grants from
     // default privileges and must be accounted for in compare_grants.
     recreated_tables: HashSet<String>,
+    // What to do when altering a table in place cannot reproduce the TO
+    // column order.
+    column_order_mode: ColumnOrderMode,
+    // Lock and statement timeouts, and lock retries, for production output.
+    lock_settings: LockSettings,
+    // Whether every statement that locks an existing relation gets a
+    // `-- lock: …` comment naming the lock mode and relations.
+    lock_annotations: bool,
+    // Whether consecutive single-table statements are grouped per table.
+    group_by_table: bool,
+    // Whether this script is the rollback of a migration (FROM and TO
+    // swapped); adds a header listing the steps that lose data.
+    rollback: bool,
+    // Objects the include/exclude filters left out of both dumps, listed in
+    // the script header.
+    excluded_objects: Vec<String>,
+    // The include/exclude filters themselves, so the fingerprint guard
+    // leaves the same objects out of the target's catalog.
+    object_filter: ObjectFilter,
+    // Values for the placeholders of redacted secrets, injected when the
+    // script is rendered.
+    secrets: Secrets,
+    // Whether every statement is guarded by a check of the progress ledger,
+    // so a failed migration can be rerun from where it stopped.
+    progress_ledger: bool,
+    // Whether the script starts with a DO block that aborts it when the
+    // target database does not match the FROM dump.
+    fingerprint_guard: bool,
+    // Whether the script starts with checks that abort it when existing rows
+    // violate the NOT NULL and constraints it adds.
+    data_checks: bool,
+    // Whether this is the creation script of the TO dump (FROM is empty):
+    // empty sections are left out.
+    creation_script: bool,
+    // Whether the script is made re-runnable without the other production
+    // changes: `if [not] exists` guards, and DO blocks that skip the objects
+    // that already exist.
+    idempotent: bool,
+    // Tables rebuilt with a data copy: plain ↔ partitioned conversions (see
+    // `Table::partitioning_change`) and column order rebuilds. The
+    // replacement table starts without foreign keys, so compare_foreign_keys
+    // adds all of them as for a new table.
+    rebuilt_tables: HashSet<String>,
+    // Unchanged foreign keys of other tables that reference a rebuilt table.
+    // They are dropped before the rebuild and re-added by
+    // compare_foreign_keys. Key: "schema.table.constraint".
+    repointed_foreign_keys: HashSet<String>,
+    // Tables whose new UNIQUE / PRIMARY KEY constraints the production path
+    // attaches after commit; foreign keys referencing them must wait for it.
+    // Key: quote-stripped (schema, table).
+    deferred_unique_tables: HashSet<(String, String)>,
     // Tracks columns that should use serial/bigserial/smallserial type.
     // Key: (schema, table, column); Value: "serial", "bigserial", or "smallserial".
     // The tuple form avoids ambiguity when any identifier contains a literal '.'.
@@ -76,7 +147,8 @@ impl Comparer {
             grants_mode,
             output_for_production: false,
             script: String::new(),
-            production_post_script: String::new(),
+            production_post_script: PostCommitScript::default(),
+            post_commit_start: None,
             enum_pre_script: String::new(),
             enum_post_script: String::new(),
             type_post_script: String::new(),
@@ -84,6 +156,22 @@ impl Comparer {
             trigger_post_script: String::new(),
             dropped_views: HashMap::new(),
             recreated_tables: HashSet::new(),
+            column_order_mode: ColumnOrderMode::Ignore,
+            lock_settings: LockSettings::default(),
+            lock_annotations: false,
+            group_by_table: false,
+            rollback: false,
+            excluded_objects: Vec::new(),
+            object_filter: ObjectFilter::default(),
+            secrets: Secrets::default(),
+            progress_ledger: false,
+            fingerprint_guard: false,
+            data_checks: false,
+            creation_script: false,
+            idempotent: false,
+            rebuilt_tables: HashSet::new(),
+            repointed_foreign_keys: HashSet::new(),
+            deferred_unique_tables: HashSet::new(),
             serial_columns: HashMap::new(),
         };
 
@@ -95,6 +183,11 @@ impl Comparer {
         comparer.script.push_str(&comparer.from.get_info());
         comparer.script.push_str("\n\n To dump:\n");
         comparer.script.push_str(&comparer.to.get_info());
+        if let Some(warning) = comparer.major_version_warning() {
+            comparer
+                .script
+                .push_str(&format!("\n\n WARNING: {warning}."));
+        }
         comparer.script.push_str(&format!(
             "\n\n Comparison results (use_drop: {}, grants_mode: {}):\n",
             comparer.use_drop, comparer.grants_mode
@@ -104,6 +197,24 @@ impl Comparer {
         comparer
     }
 
+    /// Creates a Comparer that writes the full creation script of `to`: the
+    /// comparison of an empty dump with it, without drops and with every
+    /// grant (see [`creation_script`]).
+    pub fn new_creation_script(to: Dump, use_single_transaction: bool, use_comments: bool) -> Self {
+        let from = Dump::new(to.configuration.clone());
+        let mut comparer = Self::new(
+            from,
+            to,
+            false,
+            use_single_transaction,
+            use_comments,
+            GrantsMode::Full,
+        );
+        comparer.creation_script = true;
+        comparer.script = creation_script::header(&comparer.to);
+        comparer
+    }
+
     /// Enable or disable production-friendly output. When enabled, indexes are
     /// built concurrently (partition-aware), foreign keys are added `NOT VALID`
     /// then validated after commit, and indexes are dropped concurrently — all
@@ -113,8 +224,256 @@ impl Comparer {
         self
     }
 
+    /// Choose how column order mismatches are handled: ignored (default),
+    /// reported as a comment, or fixed by rebuilding the table with a data
+    /// copy.
+    pub fn set_column_order_mode(&mut self, value: ColumnOrderMode) -> &mut Self {
+        self.column_order_mode = value;
+        self
+    }
+
+    /// Set the lock safety net for production output: `lock_timeout` and
+    /// `statement_timeout` at the top of the script and before each
+    /// post-commit statement, and how often DDL is retried after
+    /// `lock_not_available`. Ignored unless production output is enabled.
+    pub fn set_lock_settings(&mut self, value: LockSettings) -> &mut Self {
+        self.lock_settings = value;
+        self
+    }
+
+    /// Annotate every statement that locks an existing relation with a
+    /// `-- lock: MODE on relation` comment.
+    pub fn set_lock_annotations(&mut self, value: bool) -> &mut Self {
+        self.lock_annotations = value;
+        self
+    }
+
+    /// Group the statements of the main section per table, so each table's
+    /// changes are applied together instead of interleaved with other tables.
+    pub fn set_group_by_table(&mut self, value: bool) -> &mut Self {
+        self.group_by_table = value;
+        self
+    }
+
+    /// Give every statement a stable step id and guard it with a check of the
+    /// `pgc_migration_progress` ledger, so a rerun skips completed steps.
+    pub fn set_progress_ledger(&mut self, value: bool) -> &mut Self {
+        self.progress_ledger = value;
+        self
+    }
+
+    /// Start the script with a DO block that recomputes a fingerprint of the
+    /// target's tables and sequences and aborts when it differs from the
+    /// FROM dump's.
+    pub fn set_fingerprint_guard(&mut self, value: bool) -> &mut Self {
+        self.fingerprint_guard = value;
+        self
+    }
+
+    /// Start the script with a DO block per new NOT NULL, UNIQUE, PRIMARY
+    /// KEY, CHECK or FOREIGN KEY constraint of an altered table that aborts
+    /// it, with a sample of the offending keys, when existing rows violate
+    /// the constraint.
+    pub fn set_data_checks(&mut self, value: bool) -> &mut Self {
+        self.data_checks = value;
+        self
+    }
+
+    /// Make the script re-runnable: inject the `if [not] exists` / `or
+    /// replace` guards of production output, and wrap the `CREATE` and `ADD
+    /// CONSTRAINT` statements that have no such clause in a DO block that
+    /// skips them when the object already exists.
+    pub fn set_idempotent(&mut self, value: bool) -> &mut Self {
+        self.idempotent = value;
+        self
+    }
+
+    /// Mark the script as the rollback of a migration: the comparer was
+    /// created with the dumps swapped, and the script starts with a header
+    /// listing the steps that cannot be reversed without data loss.
+    pub fn set_rollback(&mut self, value: bool) -> &mut Self {
+        self.rollback = value;
+        self
+    }
+
+    /// List the objects the include/exclude filters removed from the dumps
+    /// (see [`crate::dump::filter::apply_filter`]) in the script header.
+    pub fn set_excluded_objects(&mut self, value: Vec<String>) -> &mut Self {
+        self.excluded_objects = value;
+        self
+    }
+
+    /// The include/exclude filters the dumps were filtered with; the
+    /// fingerprint guard ignores the objects of the target they exclude.
+    pub fn set_object_filter(&mut self, value: ObjectFilter) -> &mut Self {
+        self.object_filter = value;
+        self
+    }
+
+    /// Values for the placeholders of redacted secrets (see
+    /// [`crate::dump::secrets`]). Placeholders are replaced from the
+    /// environment even without a secrets file.
+    pub fn set_secrets(&mut self, value: Secrets) -> &mut Self {
+        self.secrets = value;
+        self
+    }
+
+    /// The redacted secrets the script needs that have no value in the
+    /// environment or the secrets file.
+    pub fn missing_secrets(&self) -> Vec<String> {
+        self.secrets.inject(&self.script).1
+    }
+
+    /// Set when the two dumps were taken from different PostgreSQL major
+    /// versions.
+    pub fn major_version_warning(&self) -> Option<String> {
+        metadata::major_version_warning(&self.from.metadata, &self.to.metadata)
+    }
+
+    /// The steps of this comparison that lose data: dropped tables,
+    /// sequences and columns, and column type changes that may narrow the
+    /// stored values (see [`TableColumn::narrowing_from`]). A rollback also
+    /// lists what the forward migration destroyed and it cannot bring back:
+    /// the objects it recreates empty and the values a narrowing cut off.
+    pub fn data_loss_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let to_tables: HashMap<(&str, &str), &Table> = self
+            .to
+            .tables
+            .iter()
+            .map(|t| ((t.schema.as_str(), t.name.as_str()), t))
+            .collect();
+        for table in &self.from.tables {
+            let Some(to_table) = to_tables.get(&(table.schema.as_str(), table.name.as_str()))
+            else {
+                warnings.push(format!(
+                    "table {}.{} is dropped with all its rows",
+                    table.schema, table.name
+                ));
+                continue;
+            };
+            for column in &table.columns {
+                match to_table.columns.iter().find(|c| c.name == column.name) {
+                    None => warnings.push(format!(
+                        "column {}.{}.{} is dropped with its values",
+                        table.schema, table.name, column.name
+                    )),
+                    Some(to_column) => {
+                        if let Some((old_type, new_type)) = to_column.narrowing_from(column) {
+                            warnings.push(format!(
+                                "column {}.{}.{} changes type from {} to {}; values that do not fit are lost or fail the conversion",
+                                table.schema, table.name, column.name, old_type, new_type
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        for sequence in &self.from.sequences {
+            if !self
+                .to
+                .sequences
+                .iter()
+                .any(|s| s.schema == sequence.schema && s.name == sequence.name)
+            {
+                warnings.push(format!(
+                    "sequence {}.{} is dropped with its current value",
+                    sequence.schema, sequence.name
+                ));
+            }
+        }
+        if self.rollback {
+            warnings.extend(self.forward_data_loss());
+        }
+        warnings
+    }
+
+    /// The data the forward migration of a rollback lost: the dumps are
+    /// swapped, so its drops are the `to` objects missing from `from` and
+    /// its narrowings go from the `to` columns to the `from` ones.
+    fn forward_data_loss(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let from_tables: HashMap<(&str, &str), &Table> = self
+            .from
+            .tables
+            .iter()
+            .map(|t| ((t.schema.as_str(), t.name.as_str()), t))
+            .collect();
+        for table in &self.to.tables {
+            let Some(from_table) = from_tables.get(&(table.schema.as_str(), table.name.as_str()))
+            else {
+                warnings.push(format!(
+                    "table {}.{} was dropped by the migration; it is recreated empty",
+                    table.schema, table.name
+                ));
+                continue;
+            };
+            for column in &table.columns {
+                match from_table.columns.iter().find(|c| c.name == column.name) {
+                    None => warnings.push(format!(
+                        "column {}.{}.{} was dropped by the migration; it is recreated without its values",
+                        table.schema, table.name, column.name
+                    )),
+                    Some(from_column) => {
+                        if let Some((old_type, new_type)) = from_column.narrowing_from(column) {
+                            warnings.push(format!(
+                                "column {}.{}.{} was narrowed from {} to {} by the migration; restoring the type does not restore the values it cut off",
+                                table.schema, table.name, column.name, old_type, new_type
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        for sequence in &self.to.sequences {
+            if !self
+                .from
+                .sequences
+                .iter()
+                .any(|s| s.schema == sequence.schema && s.name == sequence.name)
+            {
+                warnings.push(format!(
+                    "sequence {}.{} was dropped by the migration; it is recreated at its start value",
+                    sequence.schema, sequence.name
+                ));
+            }
+        }
+        warnings
+    }
+
     // Compare dumps and generate the script
     pub async fn compare(&mut self) -> Result<(), Error> {
+        if self.rollback {
+            let warnings = self.data_loss_warnings();
+            let mut header =
+                String::from("/* Rollback script: reverts the \"to\" database back to \"from\".\n");
+            if warnings.is_empty() {
+                header.push_str("   No step of this rollback loses data.\n");
+            } else {
+                header.push_str("   WARNING: these steps cannot be reversed without data loss:\n");
+                for warning in &warnings {
+                    header.push_str(&format!("     - {warning}\n"));
+                }
+                if !self.use_drop {
+                    header.push_str(
+                        "   (use_drop is off, so the drops above are emitted commented out.)\n",
+                    );
+                }
+            }
+            header.push_str("*/");
+            self.script.append_block(&header);
+        }
+        if !self.excluded_objects.is_empty() {
+            let mut header = format!(
+                "/* Excluded by filters ({}), not compared:\n",
+                self.excluded_objects.len()
+            );
+            for object in &self.excluded_objects {
+                header.push_str(&format!("     - {object}\n"));
+            }
+            header.push_str("*/");
+            self.script.append_block(&header);
+        }
         if self.output_for_production {
             // The statements that cannot run inside a transaction block are
             // emitted in a trailing section. With --use-single-transaction the
@@ -133,9 +492,36 @@ impl Comparer {
                  transaction {tail}. */"
             ));
         }
+        if self.progress_ledger {
+            self.script
+                .append_block(&progress_ledger::ledger_table_statement());
+        }
+        // After the ledger table: with the ledger the guard is a recorded
+        // step, so rerunning a half-applied migration does not trip on it.
+        if self.fingerprint_guard {
+            self.script
+                .append_block(&fingerprint::guard_block(&self.from, &self.object_filter));
+        }
+        if self.data_checks {
+            for block in data_checks::data_check_blocks(&self.from, &self.to) {
+                self.script.append_block(&block);
+            }
+        }
         if self.use_single_transaction {
             self.script.append_block("begin;");
         }
+        if self.output_for_production {
+            // Fail fast instead of queueing behind a long-running query: with
+            // a transaction the timeouts are scoped to it, otherwise they hold
+            // for the session.
+            let settings = production::lock_settings_statements(
+                &self.lock_settings,
+                self.use_single_transaction,
+            );
+            if !settings.is_empty() {
+                self.script.append_block(settings.trim_end());
+            }
+        }
 
         self.compare_schemas().await?;
         self.compare_extensions().await?;
@@ -191,6 +577,16 @@ impl Comparer {
         self.compare_grants().await?;
         self.compare_default_privileges().await?;
 
+        if self.creation_script {
+            self.script = creation_script::strip_empty_sections(&self.script);
+        }
+
+        // Only the main section is regrouped: the post-commit section is
+        // ordered by phase and each of its statements runs on its own.
+        if self.group_by_table {
+            self.script = lock_levels::group_by_table(&self.script);
+        }
+
         if self.use_single_transaction {
             self.script.push_str("\ncommit;");
         }
@@ -200,14 +596,17 @@ impl Comparer {
         // here in a trailing section — after COMMIT when --use-single-transaction
         // wrapped the migration, otherwise just at the end. Either way each runs
         // in its own implicit transaction.
+        let mut post_commit = String::new();
         if !self.production_post_script.is_empty() {
-            self.script.append_block(
+            post_commit.append_block(
                 "\n/* ---> Production post-commit (run outside a transaction): Start --------------- */",
             );
             let post = std::mem::take(&mut self.production_post_script);
-            self.script.push_str(&post);
-            self.script
-                .append_block("/* ---> Production post-commit: End --------------- */");
+            post_commit.push_str(&production::with_lock_settings(
+                &post.render(),
+                &self.lock_settings,
+            ));
+            post_commit.append_block("/* ---> Production post-commit: End --------------- */");
         }
 
         // Production migrations are meant to be re-runnable against a live,
@@ -215,10 +614,34 @@ impl Comparer {
         // `or replace` guards into every DDL form PostgreSQL supports them for.
         // The pass is literal-/comment-aware so commented-out drops and quoted
         // text are left untouched. Done last so it also covers the post-commit
-        // (concurrent index) statements appended above.
-        if self.output_for_production {
-            self.script = production::make_idempotent(&self.script);
+        // (concurrent index) statements.
+        // Retry wrapping comes after, so the guarded statements are what gets
+        // retried. Both passes work statement by statement, so the two
+        // sections are rewritten separately and stay apart for
+        // `get_sections`.
+        // The duplicate-object guards go before the retries: a statement
+        // wrapped in a DO block is no longer retried.
+        if self.output_for_production || self.idempotent {
+            for section in [&mut self.script, &mut post_commit] {
+                *section = production::make_idempotent(section);
+                if self.idempotent {
+                    *section = creation_script::guard_duplicate_objects(section);
+                }
+                if self.output_for_production {
+                    *section = production::wrap_lock_retries(section, self.lock_settings.retries);
+                }
+            }
+        }
+        // The ledger guards go around everything else, retry blocks included;
+        // step ids are derived from the finished, unguarded script.
+        if self.progress_ledger {
+            let mut ids = progress_ledger::StepIds::new(&format!("{}{post_commit}", self.script));
+            for section in [&mut self.script, &mut post_commit] {
+                *section = progress_ledger::with_progress_ledger(section, &mut ids);
+            }
         }
+        self.post_commit_start = Some(self.script.len());
+        self.script.push_str(&post_commit);
 
         Ok(())
     }
@@ -387,12 +810,22 @@ impl Comparer {
             }
         }
         // Children come from the TO side — the schema we are migrating toward.
+        // An existing table that changes parent or bound is attached in the
+        // post-commit Partitions phase, after the index builds.
         let mut children: HashMap<String, Vec<ChildRef>> = HashMap::new();
         for table in &self.to.tables {
             if let Some(parent) = &table.partition_of {
+                let attached_later = self
+                    .from
+                    .tables
+                    .iter()
+                    .find(|t| t.schema == table.schema && t.name == table.name)
+                    .and_then(|from_table| from_table.partition_membership_change(table))
+                    .is_some_and(|change| change.attach_to.is_some());
                 children.entry(parent.clone()).or_default().push(ChildRef {
                     schema: table.schema.clone(),
                     table: table.name.clone(),
+                    attached_later,
                 });
             }
         }
@@ -405,7 +838,7 @@ impl Comparer {
     /// emission loops.
     fn emit_new_table_create_prod(
         script: &mut String,
-        post_commit: &mut String,
+        post_commit: &mut PostCommitScript,
         table: &Table,
         ctx: &PartitionContext,
     ) {
@@ -413,26 +846,44 @@ impl Comparer {
         for index in table.creatable_indexes() {
             let split = production::index_create_split(index, ctx, true);
             script.push_str(&split.in_txn);
-            post_commit.push_str(&split.post_commit);
+            post_commit.push(PostCommitPhase::Indexes, &split.post_commit);
         }
     }
 
     /// Emit the index changes of an ALTER for production: drops (concurrent
     /// unless on a partitioned table), comment-only changes (in-txn), then
     /// (re)creates (concurrent / partition-aware). Mirrors the ordering of
-    /// `build_alter_script` (drops before creates).
+    /// `build_alter_script` (drops before creates). A changed definition is
+    /// replaced without a gap when `use_drop` allows removing the old index
+    /// (see [`production::index_replace_split`]), and dropped and re-created
+    /// otherwise.
     fn emit_index_alter_plan_prod(
         script: &mut String,
-        post_commit: &mut String,
+        post_commit: &mut PostCommitScript,
         plan: &IndexAlterPlan,
         use_drop: bool,
         ctx: &PartitionContext,
     ) {
-        for old_index in &plan.drop {
+        let mut drop = plan.drop.clone();
+        let mut create = plan.create.clone();
+        for (old_index, new_index) in &plan.replace {
+            match production::index_replace_split(old_index, new_index, ctx) {
+                Some(split) if use_drop => {
+                    script.push_str(&split.in_txn);
+                    post_commit.push(PostCommitPhase::Indexes, &split.post_commit);
+                }
+                _ => {
+                    drop.push(old_index);
+                    create.push(new_index);
+                }
+            }
+        }
+
+        for old_index in &drop {
             let (stmt, post) = production::index_drop_statement(old_index, ctx);
             let block = stmt.with_empty_lines();
             let target = if post {
-                &mut *post_commit
+                post_commit.phase_mut(PostCommitPhase::Indexes)
             } else {
                 &mut *script
             };
@@ -457,25 +908,164 @@ impl Comparer {
                 ));
             }
         }
-        for index in &plan.create {
+        for index in &create {
             let split = production::index_create_split(index, ctx, false);
             script.push_str(&split.in_txn);
-            post_commit.push_str(&split.post_commit);
+            post_commit.push(PostCommitPhase::Indexes, &split.post_commit);
+        }
+    }
+
+    /// True when a partition of `parent` (a `partition_of` reference) can be
+    /// detached concurrently: the parent stays in place, and neither side has
+    /// a default partition (PostgreSQL rejects `CONCURRENTLY` then).
+    fn can_detach_concurrently(&self, parent: &str) -> bool {
+        let key = Self::normalise_partition_of(parent);
+        let find = |tables: &[Table]| {
+            tables
+                .iter()
+                .find(|t| Self::table_key(&t.schema, &t.name) == key)
+                .map(|t| t.partition_key.clone())
+        };
+        let has_default = |tables: &[Table]| {
+            tables.iter().any(|t| {
+                t.partition_of
+                    .as_deref()
+                    .is_some_and(|p| Self::normalise_partition_of(p) == key)
+                    && t.partition_bound
+                        .as_deref()
+                        .is_some_and(|b| b.trim().eq_ignore_ascii_case("default"))
+            })
+        };
+        let from_key = find(&self.from.tables).flatten();
+        from_key.is_some()
+            && from_key == find(&self.to.tables).flatten()
+            && !self.rebuilt_tables.contains(&key)
+            && !has_default(&self.from.tables)
+            && !has_default(&self.to.tables)
+    }
+
+    /// Production form of a partition detach / attach of an altered table:
+    /// both run after commit, the detach concurrently where possible and the
+    /// attach behind a pre-validated CHECK. Without `use_drop` the detach is
+    /// a commented-out in-transaction statement, as in `build_alter_script`.
+    fn partition_membership_change_prod(
+        &self,
+        table: &Table,
+        change: PartitionMembershipChange,
+    ) -> production::ProdSplit {
+        let mut split = production::ProdSplit::default();
+        let post = &mut split.post_commit;
+        if let Some(old_parent) = change.detach_from {
+            if !self.use_drop {
+                split.in_txn.push_str(&format!(
+                    "-- alter table {} detach partition {}.{};\n",
+                    old_parent, table.schema, table.name
+                ));
+            } else if self.can_detach_concurrently(old_parent) {
+                post.push_str(&production::partition_detach_concurrently(
+                    old_parent, table,
+                ));
+            } else {
+                post.append_block(&format!(
+                    "alter table {} detach partition {}.{};",
+                    old_parent, table.schema, table.name
+                ));
+            }
+        }
+        if let Some((new_parent, bound)) = change.attach_to {
+            let key = Self::normalise_partition_of(new_parent);
+            let parent_key = self
+                .to
+                .tables
+                .iter()
+                .find(|t| Self::table_key(&t.schema, &t.name) == key)
+                .and_then(|t| t.partition_key.as_deref());
+            post.push_str(&production::partition_attach_split(
+                table, new_parent, bound, parent_key,
+            ));
+        }
+        split
+    }
+
+    /// True when a foreign key of any FROM table references `schema.name`.
+    fn is_referenced_by_foreign_key(&self, schema: &str, name: &str) -> bool {
+        let target = (schema.replace('"', ""), name.replace('"', ""));
+        self.from.tables.iter().any(|t| {
+            t.constraints.iter().any(|c| {
+                c.constraint_type.eq_ignore_ascii_case("foreign key")
+                    && c.definition.as_deref().is_some_and(|def| {
+                        Self::parse_fk_referenced_table(def, &c.schema).is_some_and(|(s, n)| {
+                            (s.replace('"', ""), n.replace('"', "")) == target
+                        })
+                    })
+            })
+        })
+    }
+
+    /// Emit a foreign key for production: added `NOT VALID` and validated
+    /// after commit. A key referencing a table whose UNIQUE / PRIMARY KEY
+    /// constraints are attached after commit cannot be added before them, so
+    /// it moves to the post-commit constraints phase as a whole.
+    fn emit_foreign_key_prod(
+        script: &mut String,
+        post_commit: &mut PostCommitScript,
+        deferred_unique_tables: &HashSet<(String, String)>,
+        constraint: &TableConstraint,
+    ) {
+        let split = production::foreign_key_split(constraint);
+        let deferred = constraint.definition.as_deref().is_some_and(|def| {
+            Self::parse_fk_referenced_table(def, &constraint.schema).is_some_and(
+                |(schema, name)| {
+                    deferred_unique_tables
+                        .contains(&(schema.replace('"', ""), name.replace('"', "")))
+                },
+            )
+        });
+        if deferred {
+            post_commit.push(PostCommitPhase::Constraints, &split.in_txn);
+        } else {
+            script.push_str(&split.in_txn);
         }
+        post_commit.push(PostCommitPhase::Validate, &split.post_commit);
     }
 
     fn get_script(&self) -> String {
+        self.render_output(&self.script)
+    }
+
+    /// The generated script split into the main section and the post-commit
+    /// section (empty unless production output moved statements there), each
+    /// rendered like [`Self::get_script`].
+    pub fn get_sections(&self) -> (String, String) {
+        let (main, post_commit) = self
+            .script
+            .split_at(self.post_commit_start.unwrap_or(self.script.len()));
+        (self.render_output(main), self.render_output(post_commit))
+    }
+
+    fn render_output(&self, script: &str) -> String {
+        let (script, _) = self.secrets.inject(script);
+        let script = script.as_str();
+        // Lock annotations are added after comments are stripped, so they are
+        // kept with --use-comments false.
+        let annotate = |script: String| {
+            if self.lock_annotations {
+                lock_levels::annotate_locks(&script)
+            } else {
+                script
+            }
+        };
         if self.use_comments {
-            return self.script.clone();
+            return annotate(script.to_string());
         }
-        let result = super::scanner::strip_comments_and_collapse(&self.script);
+        let result = super::scanner::strip_comments_and_collapse(script);
         let trimmed = result.trim();
         if trimmed.is_empty() {
             return String::new();
         }
         let mut out = trimmed.to_string();
         out.push('\n');
-        out
+        annotate(out)
     }
 
     fn normalized_view_reference(reference: &str) -> String {
@@ -778,6 +1368,17 @@ impl Comparer {
         sccs
     }
 
+    /// True when `table` has to be rebuilt to reproduce the column order of
+    /// `to_table` under [`ColumnOrderMode::Rebuild`]. Only plain tables are
+    /// rebuilt; a partitioned parent would drag its partitions along.
+    fn column_order_rebuild(&self, table: &Table, to_table: &Table) -> bool {
+        self.column_order_mode == ColumnOrderMode::Rebuild
+            && table.partition_key.is_none()
+            && to_table.partition_key.is_none()
+            && !table.will_be_dropped_and_recreated(to_table)
+            && table.column_order_mismatch(to_table).is_some()
+    }
+
     /// Returns the fully-quoted key used to match a table's `partition_of` reference.
     fn table_key(schema: &str, name: &str) -> String {
         format!("\"{}\".\"{}\"", schema, name)
@@ -949,6 +1550,145 @@ impl Comparer {
         Ok(())
     }
 
+    // Saves the generated script as versioned migration files for `format`
+    // in the `output` directory. The version is the current UTC time, moved
+    // past the golang-migrate versions already in the directory. The
+    // down / revert scripts and Liquibase rollbacks come from `reverse`, the
+    // comparer of the same dumps the other way round.
+    pub async fn save_migration(
+        &self,
+        output: &str,
+        format: MigrationFormat,
+        name: &str,
+        reverse: Option<&Comparer>,
+    ) -> Result<Vec<String>, Error> {
+        let now = chrono::Utc::now();
+        let mut version: u64 = now
+            .format("%Y%m%d%H%M%S")
+            .to_string()
+            .parse()
+            .map_err(|e| Error::other(format!("Invalid migration version: {e}")))?;
+        let (main, post_commit) = self.get_sections();
+        let reverse_sections = reverse.map(|reverse| reverse.get_sections());
+        let reverse_sections = reverse_sections
+            .as_ref()
+            .map(|(main, post_commit)| (main.as_str(), post_commit.as_str()));
+        let dir = Path::new(output);
+        if format == MigrationFormat::GolangMigrate
+            && let Ok(entries) = std::fs::read_dir(dir)
+        {
+            let existing: Vec<String> = entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .collect();
+            version = migration::golang_migrate_version(version, &existing);
+        }
+        let mut written = Vec::new();
+        for migration_file in
+            migration::migration_files(format, version, name, &main, &post_commit, reverse_sections)
+        {
+            let path = dir.join(&migration_file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&path)?;
+            file.write_all(migration_file.contents.as_bytes())?;
+            written.push(path.display().to_string());
+        }
+        if matches!(
+            format,
+            MigrationFormat::LiquibaseXml | MigrationFormat::LiquibaseYaml
+        ) {
+            let hashes = self.object_hashes();
+            let object_hash =
+                |kind: &str, key: &str| hashes.get(&(kind.to_string(), key.to_string())).cloned();
+            let sets = liquibase::change_sets(&main, &post_commit, reverse_sections, &object_hash);
+            if let Some(changelog) = liquibase::changelog(format, &sets) {
+                let extension = if format == MigrationFormat::LiquibaseXml {
+                    "xml"
+                } else {
+                    "yaml"
+                };
+                std::fs::create_dir_all(dir)?;
+                let path = dir.join(format!(
+                    "{version}_{}.changelog.{extension}",
+                    migration::migration_name(name)
+                ));
+                std::fs::write(&path, changelog)?;
+                written.push(path.display().to_string());
+            }
+        }
+        if format == MigrationFormat::Sqitch {
+            let path = dir.join("sqitch.plan");
+            let existing = std::fs::read_to_string(&path).ok();
+            let plan = migration::sqitch_plan(
+                existing.as_deref(),
+                version,
+                name,
+                !post_commit.trim().is_empty(),
+                &now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            );
+            std::fs::write(&path, plan)?;
+            written.push(path.display().to_string());
+        }
+        Ok(written)
+    }
+
+    // Dump hashes of the objects a Liquibase changeSet can be about, keyed
+    // by kind and name; objects of the "to" dump win over the "from" dump.
+    fn object_hashes(&self) -> HashMap<(String, String), String> {
+        let mut hashes = HashMap::new();
+        for dump in [&self.from, &self.to] {
+            let mut add = |kind: &str, schema: Option<&str>, name: &str, hash: &Option<String>| {
+                if let Some(hash) = hash {
+                    let key = match schema {
+                        Some(schema) => format!("{schema}.{name}"),
+                        None => name.to_string(),
+                    };
+                    hashes.insert((kind.to_string(), key), hash.clone());
+                }
+            };
+            for schema in &dump.schemas {
+                add("schema", None, &schema.name, &schema.hash);
+            }
+            for table in &dump.tables {
+                add("table", Some(&table.schema), &table.name, &table.hash);
+            }
+            for view in &dump.views {
+                let kind = if view.is_materialized {
+                    "materialized view"
+                } else {
+                    "view"
+                };
+                add(kind, Some(&view.schema), &view.name, &view.hash);
+            }
+            for sequence in &dump.sequences {
+                add(
+                    "sequence",
+                    Some(&sequence.schema),
+                    &sequence.name,
+                    &sequence.hash,
+                );
+            }
+            for routine in &dump.routines {
+                let kind = if routine.kind.eq_ignore_ascii_case("procedure") {
+                    "procedure"
+                } else {
+                    "function"
+                };
+                add(kind, Some(&routine.schema), &routine.name, &routine.hash);
+            }
+            for pg_type in &dump.types {
+                let kind = if pg_type.typtype == b'd' as i8 {
+                    "domain"
+                } else {
+                    "type"
+                };
+                add(kind, Some(&pg_type.schema), &pg_type.typname, &pg_type.hash);
+            }
+        }
+        hashes
+    }
+
     async fn compare_schemas(&mut self) -> Result<(), Error> {
         self.script
             .append_block("\n/* ---> Schemas: Start section --------------- */");
@@ -1124,7 +1864,19 @@ impl Comparer {
                     self.script.push_str(
                         format!("/* Type: {}.{} */\n", to_type.schema, to_type.typname).as_str(),
                     );
-                    let alter_script = from_type.get_alter_script(to_type, self.use_drop);
+                    let mut alter_script = if self.output_for_production {
+                        from_type.get_alter_script_for_production(to_type, self.use_drop)
+                    } else {
+                        from_type.get_alter_script(to_type, self.use_drop)
+                    };
+                    if self.output_for_production {
+                        for constraint in from_type.domain_check_additions(to_type) {
+                            let split = production::domain_check_split(to_type, constraint);
+                            alter_script.push_str(&split.in_txn);
+                            self.production_post_script
+                                .push(PostCommitPhase::Validate, &split.post_commit);
+                        }
+                    }
                     if alter_script.trim().is_empty() {
                         self.script.push_str(
                             "-- No supported alterations for this type; manual review required.\n",
@@ -1833,6 +2585,37 @@ impl Comparer {
             .map(|(i, t)| ((t.schema.as_str(), t.name.as_str()), i))
             .collect();
 
+        // Plain ↔ partitioned conversions (and, on request, tables whose
+        // column order differs) are rebuilt with a data copy instead of a
+        // data-losing drop+recreate. FROM partitions of a converted parent
+        // go away together with it, so passes 1 and 2 leave them alone; TO
+        // partitions are created by the conversion itself.
+        self.rebuilt_tables.clear();
+        self.deferred_unique_tables.clear();
+        self.repointed_foreign_keys.clear();
+        for table in ordered_from.iter() {
+            if let Some(&tidx) = to_table_map.get(&(table.schema.as_str(), table.name.as_str()))
+                && table.hash.is_some()
+                && self.to.tables[tidx].hash.is_some()
+                && Self::hashes_differ(&table.hash, &self.to.tables[tidx].hash)
+                && (table.partitioning_change(&self.to.tables[tidx]).is_some()
+                    || self.column_order_rebuild(table, &self.to.tables[tidx]))
+            {
+                self.rebuilt_tables
+                    .insert(Self::table_key(&table.schema, &table.name));
+            }
+        }
+        let mut rebuilt_descendants: HashSet<String> = HashSet::new();
+        for table in ordered_from.iter() {
+            if table.partition_of.as_ref().is_some_and(|pref| {
+                let parent_key = Self::normalise_partition_of(pref);
+                self.rebuilt_tables.contains(&parent_key)
+                    || rebuilt_descendants.contains(&parent_key)
+            }) {
+                rebuilt_descendants.insert(Self::table_key(&table.schema, &table.name));
+            }
+        }
+
         // Pass 1: collect FK drops for all tables that will be removed.
         //
         // Precompute every foreign-key constraint in the FROM dump once,
@@ -1875,7 +2658,9 @@ impl Comparer {
         let mut fk_pre_drop = String::new();
         let mut dropped_fk_keys: HashSet<String> = HashSet::new();
         for table in ordered_from_drop.iter() {
-            if to_table_map.contains_key(&(table.schema.as_str(), table.name.as_str())) {
+            if to_table_map.contains_key(&(table.schema.as_str(), table.name.as_str()))
+                || rebuilt_descendants.contains(&Self::table_key(&table.schema, &table.name))
+            {
                 continue;
             }
 
@@ -1939,11 +2724,68 @@ impl Comparer {
             self.script.push_str(&fk_pre_drop);
         }
 
+        // Foreign keys that reference a rebuilt table would keep pointing at
+        // the old one (or block its drop). Drop the ones that stay unchanged
+        // here; compare_foreign_keys re-adds them once the replacement exists.
+        if !self.rebuilt_tables.is_empty() {
+            let mut fk_repoint_drop = String::new();
+            for fk in &from_fk_refs {
+                let owner_key = Self::table_key(fk.owner_schema, fk.owner_name);
+                if self.rebuilt_tables.contains(&owner_key)
+                    || rebuilt_descendants.contains(&owner_key)
+                {
+                    continue;
+                }
+                let references_rebuilt = self.from.tables.iter().any(|t| {
+                    self.rebuilt_tables
+                        .contains(&Self::table_key(&t.schema, &t.name))
+                        && (fk.def_lower.contains(&format!(
+                            "references {}.{}(",
+                            t.schema.to_lowercase(),
+                            t.name.to_lowercase()
+                        )) || (fk.owner_schema == t.schema
+                            && fk
+                                .def_lower
+                                .contains(&format!("references {}(", t.name.to_lowercase()))))
+                });
+                if !references_rebuilt {
+                    continue;
+                }
+                let unchanged_in_to = to_table_map
+                    .get(&(fk.owner_schema, fk.owner_name))
+                    .is_some_and(|&i| {
+                        self.to.tables[i]
+                            .constraints
+                            .iter()
+                            .any(|c| c.name == fk.constraint.name && c == fk.constraint)
+                    });
+                if !unchanged_in_to {
+                    continue;
+                }
+                let key = format!(
+                    "{}.{}.{}",
+                    fk.constraint.schema, fk.constraint.table_name, fk.constraint.name
+                );
+                if !dropped_fk_keys.contains(&key) && self.repointed_foreign_keys.insert(key) {
+                    fk_repoint_drop.push_str(&fk.constraint.get_drop_script());
+                }
+            }
+            if !fk_repoint_drop.is_empty() {
+                self.script.push_str(
+                    "/* Foreign keys referencing rebuilt tables are re-added after the rebuild. */\n",
+                );
+                self.script.push_str(&fk_repoint_drop);
+            }
+        }
+
         // Pass 2: drop tables (and their triggers) now that FK dependencies are removed.
         for table in ordered_from_drop.iter() {
             if to_table_map.contains_key(&(table.schema.as_str(), table.name.as_str())) {
                 continue; // Table is present in both dumps, we already processed it
             }
+            if rebuilt_descendants.contains(&Self::table_key(&table.schema, &table.name)) {
+                continue; // Removed together with its converted parent
+            }
 
             let mut pre_drop = String::new();
 
@@ -1969,6 +2811,20 @@ impl Comparer {
                 self.script.push_str(&pre_drop);
             }
 
+            // Dropping a partition locks its parent; production mode detaches
+            // it concurrently after commit and drops it once detached.
+            if self.output_for_production
+                && self.use_drop
+                && let Some(parent) = table.partition_of.as_deref()
+                && self.can_detach_concurrently(parent)
+            {
+                let mut post = production::partition_detach_concurrently(parent, table);
+                post.push_str(&table.get_drop_script());
+                self.production_post_script
+                    .push(PostCommitPhase::Partitions, &post);
+                continue;
+            }
+
             let drop_script = table.get_drop_script();
             if self.use_drop {
                 self.script.push_str(drop_script.as_str());
@@ -1995,7 +2851,10 @@ impl Comparer {
             if let Some(&tidx) = to_table_map.get(&(table.schema.as_str(), table.name.as_str())) {
                 let to_table = &self.to.tables[tidx];
                 if table.partition_key != to_table.partition_key {
-                    recreated_parents.insert(Self::table_key(&table.schema, &table.name));
+                    let key = Self::table_key(&table.schema, &table.name);
+                    if !self.rebuilt_tables.contains(&key) {
+                        recreated_parents.insert(key);
+                    }
                     // Mark for grants: recreated tables auto-inherit default
                     // privileges when the new row is created, so compare_grants
                     // must treat the from-side ACL as "default", not the old
@@ -2042,6 +2901,16 @@ impl Comparer {
                     // Will be added in next comparision (below)
                 }
             } else {
+                // Partitions of a converted table are created by the
+                // conversion; their own sub-partitions wait until then.
+                if table.partition_of.as_ref().is_some_and(|pref| {
+                    self.rebuilt_tables
+                        .contains(&Self::normalise_partition_of(pref))
+                }) {
+                    deferred_new_keys.insert(Self::table_key(&table.schema, &table.name));
+                    continue;
+                }
+
                 // Table creation must be deferred if it belongs to a parent that is being recreated
                 let parent_being_recreated_or_deferred =
                     table.partition_of.as_ref().is_some_and(|pref| {
@@ -2114,7 +2983,98 @@ impl Comparer {
                         format!("/* Table: {}.{}*/\n", table.schema, table.name).as_str(),
                     );
 
-                    if parent_recreated {
+                    if self
+                        .rebuilt_tables
+                        .contains(&Self::table_key(&table.schema, &table.name))
+                    {
+                        let table_key = Self::table_key(&table.schema, &table.name);
+                        let partitions: Vec<&Table> = partition_children
+                            .iter()
+                            .filter(|(parent, _)| Self::normalise_partition_of(parent) == table_key)
+                            .flat_map(|(_, children)| children.iter())
+                            .filter(|child| {
+                                !from_table_map
+                                    .contains_key(&(child.schema.as_str(), child.table.as_str()))
+                            })
+                            .filter_map(|child| {
+                                to_table_map
+                                    .get(&(child.schema.as_str(), child.table.as_str()))
+                                    .map(|&i| &self.to.tables[i])
+                            })
+                            .collect();
+                        let owned_sequences: Vec<&Sequence> = self
+                            .from
+                            .sequences
+                            .iter()
+                            .filter(|s| {
+                                !s.is_identity
+                                    && s.owned_by_schema.as_deref() == Some(table.schema.as_str())
+                                    && s.owned_by_table.as_deref() == Some(table.name.as_str())
+                            })
+                            .collect();
+                        if table.partitioning_change(to_table).is_some() {
+                            self.script.push_str(&table.get_partitioning_change_script(
+                                to_table,
+                                &partitions,
+                                &owned_sequences,
+                                self.use_drop,
+                            ));
+                        } else {
+                            if let Some(mismatch) = table.column_order_mismatch(to_table) {
+                                self.script.push_str(&format!(
+                                    "/* Table {}.{} is rebuilt to reproduce the column order ({}). */\n",
+                                    table.schema,
+                                    table.name,
+                                    mismatch.expected.join(", ")
+                                ));
+                            }
+                            self.script.push_str(&table.get_rebuild_script(
+                                to_table,
+                                &[],
+                                None,
+                                &owned_sequences,
+                                self.use_drop,
+                            ));
+                        }
+
+                        // Complete the bare replacement: constraints, indexes,
+                        // policies, comments and triggers of the TO table.
+                        let shell = to_table.rebuild_shell();
+                        if self.output_for_production {
+                            self.script.push_str(
+                                &shell.get_alter_script_for_production(to_table, self.use_drop),
+                            );
+                            // The replacement is not visible before commit,
+                            // so its keys and checks are added in the
+                            // transaction.
+                            for constraint in shell
+                                .unique_constraint_additions(to_table)
+                                .into_iter()
+                                .chain(shell.check_constraint_additions(to_table))
+                            {
+                                self.script.push_str(&constraint.get_script());
+                            }
+                            let plan = shell.index_alter_plan(to_table);
+                            Self::emit_index_alter_plan_prod(
+                                &mut self.script,
+                                &mut self.production_post_script,
+                                &plan,
+                                self.use_drop,
+                                &partition_ctx,
+                            );
+                        } else {
+                            self.script.push_str(
+                                &shell.get_alter_script_without_triggers(to_table, self.use_drop),
+                            );
+                        }
+                        self.trigger_post_script
+                            .push_str(&shell.get_trigger_alter_script(to_table, self.use_drop));
+                        for partition in &partitions {
+                            self.trigger_post_script
+                                .push_str(&partition.get_trigger_script());
+                        }
+                        self.recreated_tables.insert(table_key);
+                    } else if parent_recreated {
                         // Mark for grants: this partition child is being
                         // re-created (its parent was dropped+recreated), so it
                         // inherits default privileges. compare_grants keys off
@@ -2147,6 +3107,23 @@ impl Comparer {
                         // drift apart.
                         let table_recreated = table.will_be_dropped_and_recreated(to_table);
 
+                        if !table_recreated
+                            && self.column_order_mode != ColumnOrderMode::Ignore
+                            && let Some(mismatch) = table.column_order_mismatch(to_table)
+                        {
+                            let hint = if self.column_order_mode == ColumnOrderMode::Rebuild {
+                                "partitioned tables are not rebuilt automatically"
+                            } else {
+                                "use column order mode 'rebuild' to reproduce it"
+                            };
+                            self.script.push_str(&format!(
+                                "/* Column order differs from the target: expected ({}), after this migration ({}); {}. */\n",
+                                mismatch.expected.join(", "),
+                                mismatch.actual.join(", "),
+                                hint
+                            ));
+                        }
+
                         if table_recreated {
                             // Mark for grants: same reasoning as the branch
                             // above — the dropped+recreated table inherits
@@ -2166,11 +3143,84 @@ impl Comparer {
                         // create/drop and re-emit them concurrently / partition-
                         // aware via the index alter plan.
                         if self.output_for_production && !table_recreated {
-                            let alter_script = table.get_alter_script_without_triggers_no_indexes(
-                                to_table,
-                                self.use_drop,
-                            );
+                            let alter_script =
+                                table.get_alter_script_for_production(to_table, self.use_drop);
                             self.script.push_str(alter_script.as_str());
+                            for column in table.not_null_additions(to_table) {
+                                let split = production::not_null_split(column);
+                                self.script.push_str(&split.in_txn);
+                                self.production_post_script
+                                    .push(PostCommitPhase::NotNull, &split.post_commit);
+                            }
+                            for constraint in table.unique_constraint_additions(to_table) {
+                                match production::unique_constraint_split(constraint) {
+                                    Some(split) => {
+                                        self.production_post_script
+                                            .push(PostCommitPhase::Indexes, &split.index_build);
+                                        self.production_post_script.push(
+                                            PostCommitPhase::Constraints,
+                                            &split.add_constraint,
+                                        );
+                                        self.deferred_unique_tables.insert((
+                                            table.schema.replace('"', ""),
+                                            table.name.replace('"', ""),
+                                        ));
+                                    }
+                                    None => self.script.push_str(&constraint.get_script()),
+                                }
+                            }
+                            for constraint in table.check_constraint_additions(to_table) {
+                                let split = production::check_constraint_split(constraint);
+                                self.script.push_str(&split.in_txn);
+                                self.production_post_script
+                                    .push(PostCommitPhase::Validate, &split.post_commit);
+                            }
+                            if let Some(change) = table.partition_membership_change(to_table) {
+                                let split = self.partition_membership_change_prod(table, change);
+                                self.script.push_str(&split.in_txn);
+                                self.production_post_script
+                                    .push(PostCommitPhase::Partitions, &split.post_commit);
+                            }
+                            // A key referenced by foreign keys cannot be
+                            // dropped from under them, so it is swapped only
+                            // when nothing references the table.
+                            let referenced =
+                                self.is_referenced_by_foreign_key(&table.schema, &table.name);
+                            for (old, new) in table.unique_constraint_replacements(to_table) {
+                                let swap = if self.use_drop && !referenced {
+                                    production::unique_constraint_swap(old, new)
+                                } else {
+                                    None
+                                };
+                                match swap {
+                                    Some(split) => {
+                                        self.production_post_script
+                                            .push(PostCommitPhase::Indexes, &split.index_build);
+                                        self.production_post_script.push(
+                                            PostCommitPhase::Constraints,
+                                            &split.add_constraint,
+                                        );
+                                        self.deferred_unique_tables.insert((
+                                            table.schema.replace('"', ""),
+                                            table.name.replace('"', ""),
+                                        ));
+                                    }
+                                    None => {
+                                        let drop_cmd = old.get_drop_script();
+                                        if self.use_drop {
+                                            self.script.push_str(&drop_cmd);
+                                        } else {
+                                            self.script.push_str(
+                                                &drop_cmd
+                                                    .lines()
+                                                    .map(|l| format!("-- {}\n", l))
+                                                    .collect::<String>(),
+                                            );
+                                        }
+                                        self.script.push_str(&new.get_script());
+                                    }
+                                }
+                            }
                             let plan = table.index_alter_plan(to_table);
                             Self::emit_index_alter_plan_prod(
                                 &mut self.script,
@@ -2855,7 +3905,11 @@ impl Comparer {
                 continue;
             }
 
-            if let Some(&fidx) = from_table_map.get(&(table.schema.as_str(), table.name.as_str())) {
+            if let Some(&fidx) = from_table_map.get(&(table.schema.as_str(), table.name.as_str()))
+                && !self
+                    .rebuilt_tables
+                    .contains(&Self::table_key(&table.schema, &table.name))
+            {
                 let from_table = &self.from.tables[fidx];
                 if from_table.hash.is_none() {
                     continue;
@@ -2868,22 +3922,46 @@ impl Comparer {
                     let (alters, new_fks) = from_table.foreign_key_alter_split(table);
                     self.script.push_str(&alters);
                     for constraint in new_fks {
-                        let split = production::foreign_key_split(constraint);
-                        self.script.push_str(&split.in_txn);
-                        self.production_post_script.push_str(&split.post_commit);
+                        Self::emit_foreign_key_prod(
+                            &mut self.script,
+                            &mut self.production_post_script,
+                            &self.deferred_unique_tables,
+                            constraint,
+                        );
                     }
                 } else {
                     self.script
                         .push_str(&from_table.get_foreign_key_alter_script(table));
                 }
+
+                // Unchanged foreign keys dropped because they reference a
+                // rebuilt table.
+                for constraint in table.constraints.iter().filter(|c| {
+                    self.repointed_foreign_keys
+                        .contains(&format!("{}.{}.{}", c.schema, c.table_name, c.name))
+                }) {
+                    if self.output_for_production {
+                        Self::emit_foreign_key_prod(
+                            &mut self.script,
+                            &mut self.production_post_script,
+                            &self.deferred_unique_tables,
+                            constraint,
+                        );
+                    } else {
+                        self.script.push_str(&constraint.get_script());
+                    }
+                }
             } else {
-                // New table. Add its FKs.
+                // New (or converted) table. Add its FKs.
                 if self.output_for_production {
                     for constraint in &table.constraints {
                         if constraint.constraint_type.to_lowercase() == "foreign key" {
-                            let split = production::foreign_key_split(constraint);
-                            self.script.push_str(&split.in_txn);
-                            self.production_post_script.push_str(&split.post_commit);
+                            Self::emit_foreign_key_prod(
+                                &mut self.script,
+                                &mut self.production_post_script,
+                                &self.deferred_unique_tables,
+                                constraint,
+                            );
                         }
                     }
                 } else {
@@ -5453,14 +6531,19 @@ impl Comparer {
 
         // --- Routines ---
         for routine in &self.to.routines {
-            let (from_acl, from_owner) = from_routine_map
-                .get(&(
-                    routine.schema.as_str(),
-                    routine.name.as_str(),
-                    routine.arguments.as_str(),
-                ))
-                .copied()
-                .unwrap_or((&[], ""));
+            // A new routine gets PostgreSQL's built-in default, EXECUTE for
+            // PUBLIC. An empty TO ACL is that default; an explicit one without
+            // PUBLIC needs the REVOKE in full mode.
+            let public_default = [format!("=X/{}", routine.owner)];
+            let (from_acl, from_owner): (&[String], &str) = match from_routine_map.get(&(
+                routine.schema.as_str(),
+                routine.name.as_str(),
+                routine.arguments.as_str(),
+            )) {
+                Some(&(acl, owner)) => (acl, owner),
+                None if full && !routine.acl.is_empty() => (&public_default, ""),
+                None => (&[], ""),
+            };
             let from_owners: Vec<&str> =
                 [from_owner].into_iter().filter(|o| !o.is_empty()).collect();
             let to_owners: Vec<&str> = [routine.owner.as_str()]
@@ -5505,10 +6588,14 @@ impl Comparer {
             .collect();
 
         for pg_type in &self.to.types {
-            let (from_acl, from_owner) = from_type_map
-                .get(&(pg_type.schema.as_str(), pg_type.typname.as_str()))
-                .copied()
-                .unwrap_or((&[], ""));
+            // Same as routines: a new type is USAGE for PUBLIC by default.
+            let public_default = [format!("=U/{}", pg_type.owner)];
+            let (from_acl, from_owner): (&[String], &str) =
+                match from_type_map.get(&(pg_type.schema.as_str(), pg_type.typname.as_str())) {
+                    Some(&(acl, owner)) => (acl, owner),
+                    None if full && !pg_type.acl.is_empty() => (&public_default, ""),
+                    None => (&[], ""),
+                };
             let from_owners: Vec<&str> =
                 [from_owner].into_iter().filter(|o| !o.is_empty()).collect();
             let to_owners: Vec<&str> = [pg_type.owner.as_str()]
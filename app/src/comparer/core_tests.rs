@@ -1,10 +1,12 @@
 use super::*;
 use crate::config::dump_config::DumpConfig;
 use crate::config::grants_mode::GrantsMode;
+use crate::config::lock_settings::LockSettings;
+use crate::config::migration_format::MigrationFormat;
 use crate::dump::default_privilege::DefaultPrivilege;
 use crate::dump::extension::Extension;
 use crate::dump::foreign_table::ForeignTable;
-use crate::dump::pg_type::{CompositeAttribute, PgType};
+use crate::dump::pg_type::{CompositeAttribute, DomainConstraint, PgType};
 use crate::dump::routine::Routine;
 use crate::dump::schema::Schema;
 use sqlx::postgres::types::Oid;
@@ -11313,3 +11315,1595 @@ async fn production_header_mentions_commit_only_with_single_transaction() {
         "header must describe the trailing section accurately:\n{no_txn_script}"
     );
 }
+
+fn partitioning_events_table(partition_key: Option<&str>, with_fk: bool) -> Table {
+    let constraints = if with_fk {
+        vec![TableConstraint {
+            catalog: "postgres".to_string(),
+            schema: "public".to_string(),
+            name: "events_part_fk".to_string(),
+            table_name: "events".to_string(),
+            constraint_type: "FOREIGN KEY".to_string(),
+            is_deferrable: false,
+            initially_deferred: false,
+            definition: Some("FOREIGN KEY (part_id) REFERENCES public.parts(id)".to_string()),
+            coninhcount: 0,
+            is_enforced: true,
+            no_inherit: false,
+            nulls_not_distinct: false,
+            comment: None,
+        }]
+    } else {
+        vec![]
+    };
+    let mut table = Table::new(
+        "public".to_string(),
+        "events".to_string(),
+        "public".to_string(),
+        "events".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![
+            int_column("public", "events", "id", 1),
+            int_column("public", "events", "part_id", 2),
+        ],
+        constraints,
+        vec![],
+        vec![],
+        None,
+    );
+    table.partition_key = partition_key.map(str::to_string);
+    table.hash();
+    table
+}
+
+fn partitioning_events_child(name: &str, bound: &str) -> Table {
+    let mut table = Table::new(
+        "public".to_string(),
+        name.to_string(),
+        "public".to_string(),
+        name.to_string(),
+        "postgres".to_string(),
+        None,
+        vec![
+            int_column("public", name, "id", 1),
+            int_column("public", name, "part_id", 2),
+        ],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.partition_of = Some("public.events".to_string());
+    table.partition_bound = Some(bound.to_string());
+    table.hash();
+    table
+}
+
+#[tokio::test]
+async fn plain_table_is_converted_to_partitioned_without_drop() {
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+
+    from_dump.tables.push(partitioning_events_table(None, true));
+    to_dump
+        .tables
+        .push(partitioning_events_table(Some("LIST (id)"), true));
+    to_dump
+        .tables
+        .push(partitioning_events_child("events_1", "FOR VALUES IN (1)"));
+    to_dump
+        .tables
+        .push(partitioning_events_child("events_other", "DEFAULT"));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, false, true, GrantsMode::Ignore);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        !script.contains("drop table"),
+        "conversion must not drop the table: {script}"
+    );
+    assert_eq!(
+        script.matches("create table public.events_1 ").count(),
+        1,
+        "partition created exactly once: {script}"
+    );
+    let create = script
+        .find("create table public.\"events__pgc_new\"")
+        .expect("staged partitioned table");
+    let copy = script
+        .find("insert into public.\"events__pgc_new\" (id, part_id)")
+        .expect("rows copied");
+    let rename = script
+        .find("alter table public.\"events__pgc_new\" rename to events;")
+        .expect("staged table renamed");
+    let fk = script
+        .find("alter table public.events add constraint events_part_fk")
+        .expect("foreign key re-added on the converted table");
+    assert!(create < copy && copy < rename && rename < fk);
+}
+
+#[tokio::test]
+async fn partitioned_table_is_converted_to_plain_without_dropping_partitions_separately() {
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+
+    from_dump
+        .tables
+        .push(partitioning_events_table(Some("LIST (id)"), false));
+    from_dump
+        .tables
+        .push(partitioning_events_child("events_1", "FOR VALUES IN (1)"));
+    to_dump.tables.push(partitioning_events_table(None, false));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, true, false, true, GrantsMode::Ignore);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        !script.contains("drop table if exists public.events_1"),
+        "partitions go away with the converted parent: {script}"
+    );
+    let copy = script
+        .find("insert into public.\"events__pgc_new\" (id, part_id)\nselect id, part_id from public.events;")
+        .expect("rows copied");
+    let drop = script
+        .find("drop table if exists public.events;")
+        .expect("old partitioned table dropped");
+    let rename = script
+        .find("alter table public.\"events__pgc_new\" rename to events;")
+        .expect("staged table renamed");
+    assert!(copy < drop && drop < rename);
+    assert!(!script.contains("partition by"));
+}
+
+fn column_order_accounts_table(columns: &[(&str, i32)]) -> Table {
+    let mut table = Table::new(
+        "public".to_string(),
+        "accounts".to_string(),
+        "public".to_string(),
+        "accounts".to_string(),
+        "postgres".to_string(),
+        None,
+        columns
+            .iter()
+            .map(|(name, ordinal)| int_column("public", "accounts", name, *ordinal))
+            .collect(),
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.hash();
+    table
+}
+
+fn column_order_orders_table() -> Table {
+    let mut table = Table::new(
+        "public".to_string(),
+        "orders".to_string(),
+        "public".to_string(),
+        "orders".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![
+            int_column("public", "orders", "id", 1),
+            int_column("public", "orders", "account_id", 2),
+        ],
+        vec![TableConstraint {
+            catalog: "postgres".to_string(),
+            schema: "public".to_string(),
+            name: "orders_account_fk".to_string(),
+            table_name: "orders".to_string(),
+            constraint_type: "FOREIGN KEY".to_string(),
+            is_deferrable: false,
+            initially_deferred: false,
+            definition: Some("FOREIGN KEY (account_id) REFERENCES public.accounts(id)".to_string()),
+            coninhcount: 0,
+            is_enforced: true,
+            no_inherit: false,
+            nulls_not_distinct: false,
+            comment: None,
+        }],
+        vec![],
+        vec![],
+        None,
+    );
+    table.hash();
+    table
+}
+
+async fn column_order_script(mode: ColumnOrderMode) -> String {
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump
+        .tables
+        .push(column_order_accounts_table(&[("id", 1), ("balance", 2)]));
+    from_dump.tables.push(column_order_orders_table());
+    to_dump.tables.push(column_order_accounts_table(&[
+        ("id", 1),
+        ("owner_id", 2),
+        ("balance", 3),
+    ]));
+    to_dump.tables.push(column_order_orders_table());
+
+    let mut comparer = Comparer::new(from_dump, to_dump, true, false, true, GrantsMode::Ignore);
+    comparer.set_column_order_mode(mode);
+    comparer.compare().await.unwrap();
+    comparer.get_script()
+}
+
+#[tokio::test]
+async fn column_order_ignore_mode_appends_silently() {
+    let script = column_order_script(ColumnOrderMode::Ignore).await;
+
+    assert!(script.contains("alter table public.accounts add column owner_id integer"));
+    assert!(!script.contains("Column order differs"));
+    assert!(!script.contains("__pgc_new"));
+}
+
+#[tokio::test]
+async fn column_order_report_mode_describes_mismatch() {
+    let script = column_order_script(ColumnOrderMode::Report).await;
+
+    assert!(script.contains("alter table public.accounts add column owner_id integer"));
+    assert!(
+        script.contains(
+            "Column order differs from the target: expected (id, owner_id, balance), after this migration (id, balance, owner_id)"
+        ),
+        "{script}"
+    );
+    assert!(!script.contains("__pgc_new"));
+}
+
+#[tokio::test]
+async fn column_order_rebuild_mode_copies_rows_and_repoints_foreign_keys() {
+    let script = column_order_script(ColumnOrderMode::Rebuild).await;
+
+    assert!(!script.contains("add column owner_id"), "{script}");
+    let fk_drop = script
+        .find("alter table public.orders drop constraint orders_account_fk;")
+        .expect("referencing foreign key dropped before the rebuild");
+    let create = script
+        .find("create table public.\"accounts__pgc_new\" (\n    id integer,\n    owner_id integer,\n    balance integer")
+        .expect("replacement created in target order");
+    let copy = script
+        .find("insert into public.\"accounts__pgc_new\" (id, balance)\nselect id, balance from public.accounts;")
+        .expect("rows copied");
+    let drop = script
+        .find("drop table if exists public.accounts;")
+        .expect("old table dropped");
+    let rename = script
+        .find("alter table public.\"accounts__pgc_new\" rename to accounts;")
+        .expect("replacement renamed");
+    let fk_add = script
+        .find("alter table public.orders add constraint orders_account_fk")
+        .expect("referencing foreign key re-added");
+    assert!(fk_drop < create && create < copy && copy < drop && drop < rename && rename < fk_add);
+}
+
+#[tokio::test]
+async fn output_for_production_sets_not_null_through_validated_check() {
+    let build = |nullable: bool| {
+        let mut customer_id = int_column("public", "orders", "customer_id", 2);
+        customer_id.is_nullable = nullable;
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "id", 1), customer_id],
+            vec![],
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(true));
+    to_dump.tables.push(build(false));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let commit_pos = script.find("commit;").expect("commit");
+    let check_pos = script
+        .find("add constraint orders_customer_id_not_null_check check (customer_id is not null) not valid;")
+        .expect("helper check added NOT VALID");
+    let validate_pos = script
+        .find("validate constraint orders_customer_id_not_null_check;")
+        .expect("helper check validated");
+    let set_pos = script
+        .find("alter table public.orders alter column customer_id set not null;")
+        .expect("set not null");
+    let drop_pos = script
+        .find("drop constraint if exists orders_customer_id_not_null_check;")
+        .expect("helper check dropped");
+    assert!(check_pos < commit_pos, "{script}");
+    assert!(commit_pos < validate_pos && validate_pos < set_pos && set_pos < drop_pos);
+    assert_eq!(
+        script.matches("set not null").count(),
+        1,
+        "SET NOT NULL must only run post-commit:\n{script}"
+    );
+}
+
+fn production_key(
+    table: &str,
+    name: &str,
+    constraint_type: &str,
+    definition: &str,
+) -> TableConstraint {
+    TableConstraint {
+        catalog: "postgres".to_string(),
+        schema: "public".to_string(),
+        name: name.to_string(),
+        table_name: table.to_string(),
+        constraint_type: constraint_type.to_string(),
+        is_deferrable: false,
+        initially_deferred: false,
+        definition: Some(definition.to_string()),
+        coninhcount: 0,
+        is_enforced: true,
+        no_inherit: false,
+        nulls_not_distinct: false,
+        comment: None,
+    }
+}
+
+#[tokio::test]
+async fn output_for_production_builds_primary_key_from_concurrent_index() {
+    let build = |name: &str, constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            name.to_string(),
+            "public".to_string(),
+            name.to_string(),
+            "postgres".to_string(),
+            None,
+            vec![
+                int_column("public", name, "id", 1),
+                int_column("public", name, "order_id", 2),
+            ],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build("orders", vec![]));
+    to_dump.tables.push(build(
+        "orders",
+        vec![production_key(
+            "orders",
+            "orders_pkey",
+            "PRIMARY KEY",
+            "PRIMARY KEY (id)",
+        )],
+    ));
+    to_dump.tables.push(build(
+        "items",
+        vec![production_key(
+            "items",
+            "items_order_fk",
+            "FOREIGN KEY",
+            "FOREIGN KEY (order_id) REFERENCES public.orders(id)",
+        )],
+    ));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let commit_pos = script.find("commit;").expect("commit");
+    let index_pos = script
+        .find("CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS orders_pkey ON public.orders USING btree (id);")
+        .expect("concurrent index build");
+    let attach_pos = script
+        .find("alter table public.orders add constraint orders_pkey primary key using index orders_pkey;")
+        .expect("primary key attached to the index");
+    let fk_pos = script
+        .find("add constraint items_order_fk foreign key (order_id) references public.orders(id) not valid;")
+        .expect("foreign key added");
+    let validate_pos = script
+        .find("validate constraint items_order_fk;")
+        .expect("foreign key validated");
+    assert!(commit_pos < index_pos && index_pos < attach_pos, "{script}");
+    assert!(
+        attach_pos < fk_pos && fk_pos < validate_pos,
+        "a foreign key referencing the new key must follow it:\n{script}"
+    );
+    assert_eq!(script.matches("primary key").count(), 1, "{script}");
+}
+
+#[tokio::test]
+async fn output_for_production_validates_new_checks_after_commit() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let domain = |constraints: Vec<DomainConstraint>| {
+        let mut domain = make_domain_type("public", "amount", 9001);
+        domain.domain_constraints = constraints;
+        domain.hash();
+        domain
+    };
+    let positive = DomainConstraint {
+        name: "amount_positive".to_string(),
+        definition: "CHECK (VALUE > 0)".to_string(),
+    };
+
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+    from_dump.types.push(domain(vec![]));
+    to_dump.types.push(domain(vec![positive]));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let commit_pos = script.find("commit;").expect("commit");
+    let check_pos = script
+        .find("alter table public.orders add constraint orders_total_check check ((total > 0)) not valid;")
+        .expect("check added NOT VALID");
+    let check_validate_pos = script
+        .find("alter table public.orders validate constraint orders_total_check;")
+        .expect("check validated");
+    let domain_pos = script
+        .find("alter domain public.amount add constraint \"amount_positive\" CHECK (VALUE > 0) not valid;")
+        .expect("domain check added NOT VALID");
+    let domain_validate_pos = script
+        .find("alter domain public.amount validate constraint \"amount_positive\";")
+        .expect("domain check validated");
+    assert!(
+        check_pos < commit_pos && commit_pos < check_validate_pos,
+        "{script}"
+    );
+    assert!(
+        domain_pos < commit_pos && commit_pos < domain_validate_pos,
+        "{script}"
+    );
+    assert_eq!(
+        script.matches("orders_total_check check").count(),
+        1,
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn output_for_production_sets_lock_timeouts_and_retries_ddl() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.set_lock_settings(LockSettings {
+        lock_timeout: Some("5s".to_string()),
+        statement_timeout: Some("1min".to_string()),
+        retries: 2,
+    });
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        script.contains(
+            "begin;\n\nset local lock_timeout = '5s';\nset local statement_timeout = '1min';\n"
+        ),
+        "{script}"
+    );
+    assert!(
+        script.contains("execute $pgc_ddl$alter table public.orders add constraint orders_total_check check ((total > 0)) not valid$pgc_ddl$;"),
+        "{script}"
+    );
+    let commit_pos = script.find("commit;").expect("commit");
+    let validate_pos = script
+        .find("set lock_timeout = '5s';\nset statement_timeout = '1min';\ndo $pgc_retry$")
+        .expect("post-commit statement prefixed with timeouts");
+    assert!(commit_pos < validate_pos, "{script}");
+    assert!(
+        script.contains(
+            "execute $pgc_ddl$alter table public.orders validate constraint orders_total_check$pgc_ddl$;"
+        ),
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn progress_ledger_guards_every_statement() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let compare = || async {
+        let mut from_dump = Dump::new(DumpConfig::default());
+        let mut to_dump = Dump::new(DumpConfig::default());
+        from_dump.tables.push(build(vec![]));
+        to_dump.tables.push(build(vec![production_key(
+            "orders",
+            "orders_total_check",
+            "CHECK",
+            "CHECK ((total > 0))",
+        )]));
+        let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+        comparer.set_output_for_production(true);
+        comparer.set_progress_ledger(true);
+        comparer.compare().await.unwrap();
+        comparer.get_script()
+    };
+    let script = compare().await;
+
+    let ledger_pos = script
+        .find("create table if not exists public.pgc_migration_progress (")
+        .expect("ledger table");
+    assert!(ledger_pos < script.find("begin;").unwrap(), "{script}");
+    assert!(
+        script.contains("execute $pgc_step_sql$alter table public.orders add constraint orders_total_check check ((total > 0)) not valid$pgc_step_sql$;"),
+        "{script}"
+    );
+    // The post-commit validation commits on its own, so its step is recorded
+    // and skipped on a rerun.
+    let validate = script
+        .find("execute $pgc_step_sql$alter table public.orders validate constraint orders_total_check$pgc_step_sql$;")
+        .expect("guarded validation");
+    assert!(script.find("commit;").unwrap() < validate, "{script}");
+    assert_eq!(script.matches("do $pgc_step$").count(), 2, "{script}");
+    // Step ids are stable across regenerations of the same delta.
+    assert_eq!(compare().await, script);
+}
+
+#[tokio::test]
+async fn fingerprint_guard_precedes_the_migration() {
+    let build = |columns: usize| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            (1..=columns)
+                .map(|i| int_column("public", "orders", &format!("c{i}"), i as i32))
+                .collect(),
+            vec![],
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let compare = |progress_ledger: bool| async move {
+        let mut from_dump = Dump::new(DumpConfig::default());
+        let mut to_dump = Dump::new(DumpConfig::default());
+        from_dump.tables.push(build(1));
+        to_dump.tables.push(build(2));
+        let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+        comparer.set_fingerprint_guard(true);
+        comparer.set_progress_ledger(progress_ledger);
+        comparer.compare().await.unwrap();
+        comparer.get_script()
+    };
+
+    let script = compare(false).await;
+    let guard_pos = script
+        .find("do $pgc_fingerprint$")
+        .expect("fingerprint guard");
+    assert!(guard_pos < script.find("begin;").unwrap(), "{script}");
+    // The guard describes the FROM table, not the TO one.
+    let signature = format!("{:x}", md5::compute("c1 integer"));
+    assert!(
+        script.contains(&format!("('table public.orders', '{signature}')")),
+        "{script}"
+    );
+
+    // With the ledger the guard is a recorded step, skipped on a rerun.
+    let script = compare(true).await;
+    let step_pos = script.find("do $pgc_step$").expect("ledger step");
+    assert!(step_pos < script.find("do $pgc_fingerprint$").unwrap());
+    assert!(script.find("do $pgc_fingerprint$").unwrap() < script.find("begin;").unwrap());
+    // The ledger the script has just created does not count as drift.
+    assert!(
+        script.contains("<> 'public.pgc_migration_progress'"),
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn data_checks_run_before_any_ddl() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.set_data_checks(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let check_pos = script
+        .find("/* Data check: check orders_total_check on public.orders. */")
+        .expect("data check");
+    assert!(check_pos < script.find("begin;").unwrap(), "{script}");
+    assert!(
+        script.contains("from public.orders where ((total > 0)) is false"),
+        "{script}"
+    );
+    assert_eq!(script.matches("do $pgc_data_check$").count(), 1, "{script}");
+}
+
+#[tokio::test]
+async fn lock_settings_are_ignored_without_production_output() {
+    let mut comparer = Comparer::new(
+        Dump::new(DumpConfig::default()),
+        Dump::new(DumpConfig::default()),
+        false,
+        true,
+        true,
+        GrantsMode::Ignore,
+    );
+    comparer.set_lock_settings(LockSettings {
+        lock_timeout: Some("5s".to_string()),
+        statement_timeout: None,
+        retries: 2,
+    });
+    comparer.compare().await.unwrap();
+    assert!(!comparer.get_script().contains("lock_timeout"));
+}
+
+#[tokio::test]
+async fn lock_annotations_and_grouping_by_table() {
+    let build = |name: &str, columns: usize| {
+        let mut table = Table::new(
+            "public".to_string(),
+            name.to_string(),
+            "public".to_string(),
+            name.to_string(),
+            "postgres".to_string(),
+            None,
+            (1..=columns)
+                .map(|i| int_column("public", name, &format!("c{i}"), i as i32))
+                .collect(),
+            vec![],
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build("alpha", 1));
+    from_dump.tables.push(build("beta", 1));
+    to_dump.tables.push(build("alpha", 2));
+    to_dump.tables.push(build("beta", 2));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, false, GrantsMode::Ignore);
+    comparer.set_lock_annotations(true);
+    comparer.set_group_by_table(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        script.contains("-- lock: ACCESS EXCLUSIVE on public.alpha\nalter table public.alpha add column c2 integer;"),
+        "{script}"
+    );
+    assert!(
+        script.contains("-- lock: ACCESS EXCLUSIVE on public.beta\nalter table public.beta add column c2 integer;"),
+        "{script}"
+    );
+    assert!(!script.contains("-- lock: ACCESS EXCLUSIVE on public.alpha\nbegin;"));
+}
+
+#[tokio::test]
+async fn output_for_production_replaces_changed_index_and_key_without_gap() {
+    let build = |index_def: &str, key_def: &str| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![
+                int_column("public", "orders", "code", 1),
+                int_column("public", "orders", "region", 2),
+            ],
+            vec![production_key(
+                "orders",
+                "orders_code_key",
+                "UNIQUE",
+                key_def,
+            )],
+            vec![TableIndex {
+                schema: "public".to_string(),
+                table: "orders".to_string(),
+                name: "orders_region_idx".to_string(),
+                catalog: None,
+                indexdef: index_def.to_string(),
+                is_partition_index: false,
+                comment: None,
+            }],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(
+        "CREATE INDEX orders_region_idx ON public.orders USING btree (region)",
+        "UNIQUE (code)",
+    ));
+    to_dump.tables.push(build(
+        "CREATE INDEX orders_region_idx ON public.orders USING btree (region, code)",
+        "UNIQUE (code, region)",
+    ));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, true, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let commit_pos = script.find("commit;").expect("commit");
+    let build_pos = script
+        .find("CREATE INDEX CONCURRENTLY IF NOT EXISTS orders_region_idx__pgc_new ON public.orders USING btree (region, code);")
+        .expect("replacement index built");
+    let drop_pos = script
+        .find("drop index concurrently if exists public.orders_region_idx;")
+        .expect("old index dropped");
+    let rename_pos = script
+        .find(
+            "alter index if exists public.orders_region_idx__pgc_new rename to orders_region_idx;",
+        )
+        .expect("replacement renamed");
+    let key_build_pos = script
+        .find("CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS orders_code_key__pgc_new ON public.orders USING btree (code, region);")
+        .expect("replacement key index built");
+    let swap_pos = script
+        .find("alter table public.orders drop constraint if exists orders_code_key, add constraint orders_code_key unique using index orders_code_key__pgc_new;")
+        .expect("constraint swapped");
+    assert!(
+        commit_pos < build_pos && build_pos < drop_pos && drop_pos < rename_pos,
+        "{script}"
+    );
+    assert!(
+        commit_pos < key_build_pos && key_build_pos < swap_pos,
+        "{script}"
+    );
+    assert_eq!(
+        script.matches("orders_code_key").count(),
+        4,
+        "the key must not also be dropped and re-added in place:\n{script}"
+    );
+}
+
+#[tokio::test]
+async fn output_for_production_detaches_concurrently_and_attaches_prevalidated() {
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    for dump in [&mut from_dump, &mut to_dump] {
+        dump.tables
+            .push(partitioning_events_table(Some("RANGE (id)"), false));
+    }
+    from_dump.tables.push(partitioning_events_child(
+        "events_1",
+        "FOR VALUES FROM (0) TO (100)",
+    ));
+    from_dump.tables.push(partitioning_events_child(
+        "events_old",
+        "FOR VALUES FROM (100) TO (200)",
+    ));
+    to_dump.tables.push(partitioning_events_child(
+        "events_1",
+        "FOR VALUES FROM (0) TO (200)",
+    ));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, true, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    let commit_pos = script.find("commit;").expect("commit");
+    let old_detach_pos = script
+        .find("alter table if exists events detach partition public.events_old concurrently;")
+        .expect("removed partition detached concurrently");
+    let old_drop_pos = script
+        .find("drop table if exists public.events_old;")
+        .expect("removed partition dropped");
+    let detach_pos = script
+        .find("alter table if exists events detach partition public.events_1 concurrently;")
+        .expect("re-bounded partition detached concurrently");
+    let check_pos = script
+        .find("alter table public.events_1 add constraint events_1_partition_check check (id IS NOT NULL AND id >= 0 AND id < 200) not valid;")
+        .expect("bound check added");
+    let validate_pos = script
+        .find("alter table public.events_1 validate constraint events_1_partition_check;")
+        .expect("bound check validated");
+    let attach_pos = script
+        .find("alter table public.events attach partition public.events_1 FOR VALUES FROM (0) TO (200);")
+        .expect("partition attached");
+    let drop_check_pos = script
+        .find("alter table public.events_1 drop constraint if exists events_1_partition_check;")
+        .expect("bound check dropped");
+    assert!(
+        commit_pos < old_detach_pos && old_detach_pos < old_drop_pos,
+        "{script}"
+    );
+    assert!(
+        commit_pos < detach_pos
+            && detach_pos < check_pos
+            && check_pos < validate_pos
+            && validate_pos < attach_pos
+            && attach_pos < drop_check_pos,
+        "{script}"
+    );
+    assert!(
+        script.contains(
+            "execute 'alter table public.events detach partition public.events_1 finalize';"
+        ),
+        "pending detach: {script}"
+    );
+    assert!(
+        !script.contains("detach partition public.events_1;"),
+        "{script}"
+    );
+    assert!(
+        !script.contains("detach partition public.events_old;"),
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn output_for_production_leaves_the_index_of_a_new_partition_to_its_attach() {
+    let parent = |indexes: Vec<TableIndex>| {
+        let mut table = partitioning_events_table(Some("RANGE (id)"), false);
+        table.indexes = indexes;
+        table.hash();
+        table
+    };
+    let mut standalone = partitioning_events_child("events_2", "FOR VALUES FROM (100) TO (200)");
+    standalone.partition_of = None;
+    standalone.partition_bound = None;
+    standalone.hash();
+    let index = TableIndex {
+        schema: "public".to_string(),
+        table: "events".to_string(),
+        name: "events_part_idx".to_string(),
+        catalog: None,
+        indexdef: "CREATE INDEX events_part_idx ON public.events USING btree (part_id)".to_string(),
+        is_partition_index: false,
+        comment: None,
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(parent(vec![]));
+    from_dump.tables.push(partitioning_events_child(
+        "events_1",
+        "FOR VALUES FROM (0) TO (100)",
+    ));
+    from_dump.tables.push(standalone);
+    to_dump.tables.push(parent(vec![index]));
+    to_dump.tables.push(partitioning_events_child(
+        "events_1",
+        "FOR VALUES FROM (0) TO (100)",
+    ));
+    to_dump.tables.push(partitioning_events_child(
+        "events_2",
+        "FOR VALUES FROM (100) TO (200)",
+    ));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, true, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        script
+            .contains("CREATE INDEX IF NOT EXISTS events_part_idx ON ONLY public.events USING btree (part_id);"),
+        "{script}"
+    );
+    assert!(
+        script.contains(
+            "alter index public.events_part_idx attach partition public.events_1_events_part_idx;"
+        ),
+        "{script}"
+    );
+    // events_2 only becomes a partition in the Partitions phase: its index is
+    // built while it is a plain table and adopted by the attach.
+    let build_pos = script
+        .find("CREATE INDEX CONCURRENTLY IF NOT EXISTS events_2_events_part_idx ON public.events_2 USING btree (part_id);")
+        .expect("index built ahead of the attach");
+    let attach_pos = script
+        .find("alter table public.events attach partition public.events_2 FOR VALUES FROM (100) TO (200);")
+        .expect("partition attached");
+    assert!(build_pos < attach_pos, "{script}");
+    assert!(
+        !script.contains("attach partition public.events_2_events_part_idx"),
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn save_migration_splits_post_commit_into_its_own_migration() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+
+    let (main, post_commit) = comparer.get_sections();
+    assert!(main.contains("not valid;") && main.trim_end().ends_with("commit;"));
+    assert!(post_commit.contains("validate constraint orders_total_check;"));
+    assert!(!post_commit.contains("commit;"));
+    assert_eq!(format!("{main}{post_commit}"), comparer.get_script());
+
+    let dir = std::env::temp_dir().join(format!("pgc_migration_{}", std::process::id()));
+    let output = dir.to_str().unwrap();
+    let files = comparer
+        .save_migration(output, MigrationFormat::Flyway, "orders check", None)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 3);
+    let main_file = files
+        .iter()
+        .find(|f| f.ends_with("__orders_check.sql"))
+        .expect("transactional migration");
+    let contents = std::fs::read_to_string(main_file).unwrap();
+    assert!(contents.contains("not valid;"));
+    assert!(!contents.contains("begin;") && !contents.contains("commit;"));
+    let post_file = files
+        .iter()
+        .find(|f| f.ends_with("__orders_check_post_commit.sql"))
+        .expect("post-commit migration");
+    assert!(
+        std::fs::read_to_string(post_file)
+            .unwrap()
+            .contains("validate constraint orders_total_check;")
+    );
+    assert_eq!(
+        std::fs::read_to_string(format!("{post_file}.conf")).unwrap(),
+        "executeInTransaction=false\n"
+    );
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn save_migration_never_reuses_a_golang_migrate_version() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("pgc_golang_migrate_{}", std::process::id()));
+    let output = dir.to_str().unwrap();
+    let mut versions = Vec::new();
+    for _ in 0..2 {
+        for file in comparer
+            .save_migration(output, MigrationFormat::GolangMigrate, "orders", None)
+            .await
+            .unwrap()
+        {
+            if file.ends_with(".up.sql") {
+                let name = Path::new(&file).file_name().unwrap().to_str().unwrap();
+                versions.push(name.split('_').next().unwrap().parse::<u64>().unwrap());
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(dir);
+    // Each run writes the main migration and a post-commit one.
+    assert_eq!(versions.len(), 4, "{versions:?}");
+    assert!(
+        versions.windows(2).all(|pair| pair[0] < pair[1]),
+        "{versions:?}"
+    );
+}
+
+#[tokio::test]
+async fn save_migration_writes_liquibase_changelog() {
+    let build = |constraints: Vec<TableConstraint>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            vec![int_column("public", "orders", "total", 1)],
+            constraints,
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![]));
+    to_dump.tables.push(build(vec![production_key(
+        "orders",
+        "orders_total_check",
+        "CHECK",
+        "CHECK ((total > 0))",
+    )]));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, true, true, GrantsMode::Ignore);
+    comparer.set_output_for_production(true);
+    comparer.compare().await.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("pgc_liquibase_{}", std::process::id()));
+    let files = comparer
+        .save_migration(
+            dir.to_str().unwrap(),
+            MigrationFormat::LiquibaseYaml,
+            "orders check",
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].ends_with("_orders_check.changelog.yaml"));
+    let changelog = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(changelog.starts_with("databaseChangeLog:\n"));
+    assert!(!changelog.contains("begin;") && !changelog.contains("commit;"));
+    // The transactional and the post-commit step on the table are separate
+    // changeSets with ids derived from the table's hash.
+    assert_eq!(changelog.matches("- changeSet:").count(), 2);
+    assert_eq!(changelog.matches("runInTransaction: false").count(), 1);
+    assert!(changelog.contains("validate constraint orders_total_check;"));
+
+    let regenerated = comparer
+        .save_migration(
+            dir.to_str().unwrap(),
+            MigrationFormat::LiquibaseYaml,
+            "orders check",
+            None,
+        )
+        .await
+        .unwrap();
+    let second = std::fs::read_to_string(&regenerated[0]).unwrap();
+    let ids = |text: &str| -> Vec<String> {
+        text.lines()
+            .filter(|line| line.trim_start().starts_with("id: "))
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(ids(&changelog), ids(&second));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn rollback_header_lists_steps_that_lose_data() {
+    let code = |length: i32| {
+        let mut column = int_column("public", "orders", "code", 2);
+        column.data_type = "character varying".to_string();
+        column.character_maximum_length = Some(length);
+        column.numeric_precision = None;
+        column.numeric_precision_radix = None;
+        column.numeric_scale = None;
+        column
+    };
+    let build = |name: &str, columns: Vec<TableColumn>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            name.to_string(),
+            "public".to_string(),
+            name.to_string(),
+            "postgres".to_string(),
+            None,
+            columns,
+            vec![],
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    // Forward migration: widen code, add a column and a table.
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(
+        "orders",
+        vec![int_column("public", "orders", "id", 1), code(20)],
+    ));
+    to_dump.tables.push(build(
+        "orders",
+        vec![
+            int_column("public", "orders", "id", 1),
+            code(100),
+            int_column("public", "orders", "note", 3),
+        ],
+    ));
+    to_dump
+        .tables
+        .push(build("audit", vec![int_column("public", "audit", "id", 1)]));
+
+    let mut forward = Comparer::new(
+        from_dump.clone(),
+        to_dump.clone(),
+        true,
+        true,
+        true,
+        GrantsMode::Ignore,
+    );
+    assert!(forward.data_loss_warnings().is_empty());
+    forward.compare().await.unwrap();
+    assert!(!forward.get_script().contains("Rollback script"));
+
+    let mut rollback = Comparer::new(to_dump, from_dump, true, true, true, GrantsMode::Ignore);
+    rollback.set_rollback(true);
+    rollback.compare().await.unwrap();
+    let script = rollback.get_script();
+
+    assert_eq!(
+        rollback.data_loss_warnings(),
+        vec![
+            "column public.orders.code changes type from character varying(100) to character varying(20); values that do not fit are lost or fail the conversion".to_string(),
+            "column public.orders.note is dropped with its values".to_string(),
+            "table public.audit is dropped with all its rows".to_string(),
+        ]
+    );
+    let header = script
+        .find("WARNING: these steps cannot be reversed without data loss")
+        .expect("header");
+    assert!(header < script.find("begin;").unwrap());
+    assert!(script.contains("     - table public.audit is dropped with all its rows\n"));
+    assert!(
+        script.contains("alter table public.orders alter column code type character varying(20);")
+    );
+}
+
+#[tokio::test]
+async fn rollback_header_lists_what_the_migration_lost() {
+    let code = |length: i32| {
+        let mut column = int_column("public", "orders", "code", 2);
+        column.data_type = "character varying".to_string();
+        column.character_maximum_length = Some(length);
+        column.numeric_precision = None;
+        column.numeric_precision_radix = None;
+        column.numeric_scale = None;
+        column
+    };
+    let build = |columns: Vec<TableColumn>| {
+        let mut table = Table::new(
+            "public".to_string(),
+            "orders".to_string(),
+            "public".to_string(),
+            "orders".to_string(),
+            "postgres".to_string(),
+            None,
+            columns,
+            vec![],
+            vec![],
+            vec![],
+            None,
+        );
+        table.hash();
+        table
+    };
+    // Forward migration: narrow code and drop legacy.
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables.push(build(vec![
+        int_column("public", "orders", "id", 1),
+        code(100),
+        int_column("public", "orders", "legacy", 3),
+    ]));
+    to_dump.tables.push(build(vec![
+        int_column("public", "orders", "id", 1),
+        code(20),
+    ]));
+
+    let mut rollback = Comparer::new(to_dump, from_dump, true, true, true, GrantsMode::Ignore);
+    rollback.set_rollback(true);
+    rollback.compare().await.unwrap();
+    let script = rollback.get_script();
+
+    // The rollback itself only widens and adds; the losses are the
+    // migration's.
+    assert_eq!(
+        rollback.data_loss_warnings(),
+        vec![
+            "column public.orders.code was narrowed from character varying(100) to character varying(20) by the migration; restoring the type does not restore the values it cut off".to_string(),
+            "column public.orders.legacy was dropped by the migration; it is recreated without its values".to_string(),
+        ]
+    );
+    assert!(
+        script.contains("WARNING: these steps cannot be reversed without data loss:\n"),
+        "{script}"
+    );
+    assert!(script.contains(
+        "     - column public.orders.legacy was dropped by the migration; it is recreated without its values\n"
+    ));
+}
+
+#[tokio::test]
+async fn compare_grants_new_routine_without_public_revokes_default_execute() {
+    let from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    let mut routine = Routine::new(
+        "public".to_string(),
+        Oid(1),
+        "secret".to_string(),
+        "sql".to_string(),
+        "function".to_string(),
+        "integer".to_string(),
+        "".to_string(),
+        None,
+        None,
+        "select 1".to_string(),
+    );
+    routine.owner = "owner".to_string();
+    routine.acl = vec!["owner=X/owner".to_string(), "app=X/owner".to_string()];
+    to_dump.routines.push(routine);
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, false, true, GrantsMode::Full);
+    comparer.compare_grants().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(
+        script.contains("REVOKE EXECUTE ON FUNCTION public.secret() FROM PUBLIC;"),
+        "Must revoke the default PUBLIC execute, got: {script}"
+    );
+    assert!(script.contains("GRANT EXECUTE ON FUNCTION public.secret() TO app;"));
+}
+
+#[tokio::test]
+async fn compare_grants_new_routine_with_default_acl_emits_nothing() {
+    let from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    to_dump.routines.push(Routine::new(
+        "public".to_string(),
+        Oid(1),
+        "open".to_string(),
+        "sql".to_string(),
+        "function".to_string(),
+        "integer".to_string(),
+        "".to_string(),
+        None,
+        None,
+        "select 1".to_string(),
+    ));
+
+    let mut comparer = Comparer::new(from_dump, to_dump, false, false, true, GrantsMode::Full);
+    comparer.compare_grants().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(!script.contains("REVOKE"), "got: {script}");
+    assert!(!script.contains("GRANT EXECUTE"), "got: {script}");
+}
+
+#[tokio::test]
+async fn creation_script_has_no_from_side_header_or_empty_sections() {
+    let mut to_dump = Dump::new(DumpConfig::default());
+    let mut table = Table::new(
+        "public".to_string(),
+        "items".to_string(),
+        "public".to_string(),
+        "items".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![int_column("public", "items", "id", 1)],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.acl = vec![
+        "postgres=arwdDxt/postgres".to_string(),
+        "reader=r/postgres".to_string(),
+    ];
+    table.hash();
+    to_dump.tables.push(table);
+
+    let mut comparer = Comparer::new_creation_script(to_dump, false, true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(script.starts_with("/*\nCreation script generated by PostgreSQL Comparer"));
+    assert!(!script.contains("From dump"), "got: {script}");
+    assert!(!script.contains("Views DROP"), "got: {script}");
+    assert!(!script.contains("Compare Rules"), "got: {script}");
+    assert!(script.contains("/* ---> Tables: Start section"));
+    assert!(script.contains("create table public.items ("));
+    assert!(script.contains("GRANT SELECT ON TABLE public.items TO reader;"));
+}
+
+#[tokio::test]
+async fn creation_script_idempotent_guards_every_create() {
+    let mut to_dump = Dump::new(DumpConfig::default());
+    to_dump
+        .types
+        .push(make_enum_type("public", "mood", 16400, vec!["ok"]));
+    let mut table = Table::new(
+        "public".to_string(),
+        "items".to_string(),
+        "public".to_string(),
+        "items".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![int_column("public", "items", "id", 1)],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.hash();
+    to_dump.tables.push(table);
+
+    let mut comparer = Comparer::new_creation_script(to_dump, false, true);
+    comparer.set_idempotent(true);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+
+    assert!(script.contains("create table if not exists public.items ("));
+    assert!(
+        script.contains("execute $pgc_create_sql$create type public.mood as enum ('ok')"),
+        "got: {script}"
+    );
+    assert!(!script.contains("Output generated for production"));
+}
+
+#[test]
+fn header_warns_about_different_major_versions() {
+    let dump = |num: i32| {
+        let mut dump = Dump::new(DumpConfig::default());
+        dump.metadata.server_version_num = Some(num);
+        dump
+    };
+
+    let comparer = Comparer::new(
+        dump(150004),
+        dump(160002),
+        true,
+        false,
+        true,
+        GrantsMode::Ignore,
+    );
+    assert!(comparer.major_version_warning().is_some());
+    let script = comparer.get_script();
+    assert!(
+        script.contains(
+            " WARNING: the dumps come from different PostgreSQL major versions (from: 15, to: 16)"
+        ),
+        "{script}"
+    );
+
+    let comparer = Comparer::new(
+        dump(150004),
+        dump(150010),
+        true,
+        false,
+        true,
+        GrantsMode::Ignore,
+    );
+    assert_eq!(comparer.major_version_warning(), None);
+    assert!(!comparer.get_script().contains("WARNING"));
+}
+
+#[tokio::test]
+async fn header_lists_objects_excluded_by_filters() {
+    let mut comparer = Comparer::new(
+        Dump::new(DumpConfig::default()),
+        Dump::new(DumpConfig::default()),
+        true,
+        true,
+        true,
+        GrantsMode::Ignore,
+    );
+    comparer.compare().await.unwrap();
+    assert!(!comparer.get_script().contains("Excluded by filters"));
+
+    let mut comparer = Comparer::new(
+        Dump::new(DumpConfig::default()),
+        Dump::new(DumpConfig::default()),
+        true,
+        true,
+        true,
+        GrantsMode::Ignore,
+    );
+    comparer.set_excluded_objects(vec![
+        "table public.events_tmp".to_string(),
+        "trigger public.users.audit".to_string(),
+    ]);
+    comparer.compare().await.unwrap();
+    let script = comparer.get_script();
+    let header = script
+        .find("/* Excluded by filters (2), not compared:\n")
+        .expect("header");
+    assert!(header < script.find("begin;").unwrap(), "{script}");
+    assert!(
+        script.contains("     - trigger public.users.audit\n"),
+        "{script}"
+    );
+}
+
+#[tokio::test]
+async fn injects_redacted_secrets_into_the_script() {
+    use crate::dump::fdw::UserMapping;
+    use crate::dump::secrets::redact_secrets;
+
+    let mut to_dump = Dump::new(DumpConfig::default());
+    let mut mapping = UserMapping {
+        server_name: "remote".to_string(),
+        username: "app".to_string(),
+        options: vec!["user=app".to_string(), "password=hunter2".to_string()],
+        hash: None,
+    };
+    mapping.hash();
+    to_dump.user_mappings.push(mapping);
+    redact_secrets(&mut to_dump);
+
+    let mut comparer = Comparer::new_creation_script(to_dump.clone(), false, true);
+    comparer.compare().await.unwrap();
+    assert_eq!(
+        comparer.missing_secrets(),
+        vec!["PGC_SECRET_USER_MAPPING_REMOTE_APP_PASSWORD"]
+    );
+    assert!(!comparer.get_script().contains("hunter2"));
+
+    let path = std::env::temp_dir().join(format!("pgc_comparer_secrets_{}", std::process::id()));
+    std::fs::write(
+        &path,
+        "PGC_SECRET_USER_MAPPING_REMOTE_APP_PASSWORD=s3cret\n",
+    )
+    .unwrap();
+    let mut comparer = Comparer::new_creation_script(to_dump, false, true);
+    comparer.set_secrets(Secrets::load(path.to_str().unwrap()).unwrap());
+    comparer.compare().await.unwrap();
+    let _ = std::fs::remove_file(path);
+    assert!(comparer.missing_secrets().is_empty());
+    let script = comparer.get_script();
+    assert!(
+        script.contains(
+            "CREATE USER MAPPING FOR app SERVER remote OPTIONS (user 'app', password 's3cret');"
+        ),
+        "{script}"
+    );
+}
@@ -0,0 +1,170 @@
+//! Full creation scripts for the `script` command.
+//!
+//! The creation script of a dump is the comparison of an empty dump with
+//! it, so objects come out in the comparer's dependency order with their
+//! owners, comments, grants and default privileges. Nothing exists on the
+//! FROM side, so the script gets its own header and the comparer's section
+//! markers are kept only around sections that have statements.
+//!
+//! With the idempotent option the script can be rerun against a database it
+//! has already (partly) built. The `if [not] exists` / `or replace` guards
+//! of production output cover most statements; the `CREATE` forms without
+//! such a clause (types, domains, policies, triggers, ...) and the added
+//! constraints are wrapped in a DO block that ignores the "already exists"
+//! errors. An object of the same name but another definition is left as it
+//! is.
+
+use crate::comparer::scanner::{Statement, split_statements};
+use crate::dump::core::Dump;
+use crate::utils::string_extensions::StringExt;
+
+const DUPLICATE_BLOCK_TAG: &str = "$pgc_create$";
+const DUPLICATE_STATEMENT_TAG: &str = "$pgc_create_sql$";
+
+/// The comment at the top of the creation script of `dump`.
+pub(crate) fn header(dump: &Dump) -> String {
+    let mut header = String::from("/*\n");
+    header.append_block("Creation script generated by PostgreSQL Comparer");
+    header.push_str(" Dump:\n");
+    header.push_str(&dump.get_info());
+    header.push_str("\n\n Creates every object of the dump in an empty database.\n");
+    header.append_block("*/");
+    header
+}
+
+fn is_marker(line: &str) -> bool {
+    line.trim_start().starts_with("/* ---> ")
+}
+
+/// The title of a `/* ---> Title: Start ... */` or `/* ---> Title: End ...
+/// */` marker, with whether it starts the section.
+fn section_bound(line: &str) -> Option<(&str, bool)> {
+    let rest = line.trim_start().strip_prefix("/* ---> ")?;
+    let (title, bound) = rest.split_once(": ")?;
+    if bound.starts_with("Start") {
+        Some((title, true))
+    } else if bound.starts_with("End") {
+        Some((title, false))
+    } else {
+        None
+    }
+}
+
+/// `script` without the section markers that enclose (or, for single
+/// `/* ---> Compare ... */` markers, precede) no statement. The blank lines
+/// after a removed marker go with it.
+pub(crate) fn strip_empty_sections(script: &str) -> String {
+    let lines: Vec<&str> = script.split('\n').collect();
+    let mut keep = vec![true; lines.len()];
+    let next_line = |keep: &[bool], from: usize| {
+        (from + 1..lines.len()).find(|&j| keep[j] && !lines[j].trim().is_empty())
+    };
+    for i in 0..lines.len() {
+        if !keep[i] || !is_marker(lines[i]) {
+            continue;
+        }
+        let next = next_line(&keep, i);
+        match section_bound(lines[i]) {
+            Some((title, true)) => {
+                if let Some(j) = next
+                    && section_bound(lines[j]) == Some((title, false))
+                {
+                    keep[i] = false;
+                    keep[j] = false;
+                }
+            }
+            Some((_, false)) => {}
+            None => {
+                if next.is_none_or(|j| is_marker(lines[j])) {
+                    keep[i] = false;
+                }
+            }
+        }
+    }
+    let mut out: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut after_removed = false;
+    for (line, kept) in lines.iter().zip(&keep) {
+        if !kept {
+            after_removed = true;
+            continue;
+        }
+        if after_removed && line.trim().is_empty() {
+            continue;
+        }
+        after_removed = false;
+        out.push(line);
+    }
+    out.join("\n")
+}
+
+/// Whether `statement` creates an object without an `IF NOT EXISTS` or `OR
+/// REPLACE` clause, or adds a constraint, and can run in a DO block.
+fn needs_duplicate_guard(statement: &str) -> bool {
+    let st = Statement::parse(statement);
+    let len = st.tokens.len();
+    if (0..len).any(|i| st.is_word(i, "concurrently")) {
+        return false;
+    }
+    if st.is_word(0, "create") {
+        let unguarded = !st.is_word(1, "or")
+            && !(0..len).any(|i| {
+                st.is_word(i, "if") && st.is_word(i + 1, "not") && st.is_word(i + 2, "exists")
+            });
+        // These may not run inside a transaction block.
+        return unguarded
+            && !["subscription", "database", "tablespace"]
+                .iter()
+                .any(|w| st.is_word(1, w));
+    }
+    st.is_word(0, "alter")
+        && (st.is_word(1, "table") || st.is_word(1, "domain"))
+        && st
+            .find_word(2, "add")
+            .is_some_and(|i| st.is_word(i + 1, "constraint"))
+}
+
+/// Wraps every statement of `script` that [`needs_duplicate_guard`] in a DO
+/// block that ignores the error raised when the object already exists.
+pub(crate) fn guard_duplicate_objects(script: &str) -> String {
+    let mut out = String::with_capacity(script.len() * 2);
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        if !needs_duplicate_guard(statement) {
+            out.push_str(statement);
+            continue;
+        }
+        let body = statement.trim_end().trim_end_matches(';');
+        // A second primary key is refused with invalid_table_definition.
+        let primary_key = Statement::parse(statement)
+            .find_word(0, "primary")
+            .is_some();
+        let errors = if primary_key {
+            "duplicate_object or duplicate_table or invalid_table_definition"
+        } else {
+            "duplicate_object or duplicate_table or duplicate_function"
+        };
+        out.push_str(&format!(
+            "do {DUPLICATE_BLOCK_TAG}\n\
+             begin\n    \
+             execute {DUPLICATE_STATEMENT_TAG}{body}{DUPLICATE_STATEMENT_TAG};\n\
+             exception when {errors} then\n    \
+             null;\n\
+             end\n{DUPLICATE_BLOCK_TAG};"
+        ));
+    }
+    out
+}
+
+/// The statement inside a DO block emitted by [`guard_duplicate_objects`],
+/// or `None` when `statement` is not one.
+pub(crate) fn duplicate_guarded_statement(statement: &str) -> Option<&str> {
+    let rest = statement.strip_prefix(&format!("do {DUPLICATE_BLOCK_TAG}"))?;
+    let open = format!("execute {DUPLICATE_STATEMENT_TAG}");
+    let start = rest.find(&open)? + open.len();
+    let len = rest[start..].find(DUPLICATE_STATEMENT_TAG)?;
+    Some(&rest[start..start + len])
+}
+
+#[cfg(test)]
+#[path = "creation_script_tests.rs"]
+mod tests;
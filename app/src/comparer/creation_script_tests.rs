@@ -0,0 +1,87 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+
+#[test]
+fn header_describes_the_dump_only() {
+    let header = header(&Dump::new(DumpConfig::default()));
+    assert!(header.starts_with("/*\nCreation script generated by PostgreSQL Comparer\n"));
+    assert!(header.contains("- Tables: 0"));
+    assert!(!header.contains("From dump"));
+    assert!(header.trim_end().ends_with("*/"));
+}
+
+#[test]
+fn strip_empty_sections_drops_markers_without_statements() {
+    let script = "\n/* ---> Schemas: Start section --------------- */\n\n\n\
+                  /* ---> Schemas: End section --------------- */\n\n\
+                  /* ---> Compare Rules --------------- */\n\n\
+                  /* ---> Tables: Start section --------------- */\n\n\
+                  create table t (id int);\n\n\
+                  /* ---> Tables: End section --------------- */\n\n\
+                  /* ---> Compare Casts --------------- */\n";
+    assert_eq!(
+        strip_empty_sections(script),
+        "\n/* ---> Tables: Start section --------------- */\n\n\
+         create table t (id int);\n\n\
+         /* ---> Tables: End section --------------- */\n"
+    );
+}
+
+#[test]
+fn strip_empty_sections_keeps_marker_before_statements() {
+    let script = "/* ---> Compare Event Triggers --------------- */\n\n\
+                  create trigger trg before insert on t for each row execute function f();\n";
+    assert_eq!(strip_empty_sections(script), script);
+}
+
+#[test]
+fn guard_duplicate_objects_wraps_unguarded_creates() {
+    let script = "create type public.mood as enum ('ok');\n\n\
+                  create table if not exists public.t (id int);\n\n\
+                  create or replace view public.v as select 1;\n\n\
+                  alter table public.t add constraint t_check check (id > 0);\n\n\
+                  alter table public.t owner to postgres;\n";
+    let guarded = guard_duplicate_objects(script);
+    assert_eq!(
+        guarded,
+        "do $pgc_create$\n\
+         begin\n    \
+         execute $pgc_create_sql$create type public.mood as enum ('ok')$pgc_create_sql$;\n\
+         exception when duplicate_object or duplicate_table or duplicate_function then\n    \
+         null;\n\
+         end\n$pgc_create$;\n\n\
+         create table if not exists public.t (id int);\n\n\
+         create or replace view public.v as select 1;\n\n\
+         do $pgc_create$\n\
+         begin\n    \
+         execute $pgc_create_sql$alter table public.t add constraint t_check check (id > 0)$pgc_create_sql$;\n\
+         exception when duplicate_object or duplicate_table or duplicate_function then\n    \
+         null;\n\
+         end\n$pgc_create$;\n\n\
+         alter table public.t owner to postgres;\n"
+    );
+}
+
+#[test]
+fn guard_duplicate_objects_catches_second_primary_key() {
+    let guarded =
+        guard_duplicate_objects("alter table public.t add constraint t_pkey primary key (id);");
+    assert!(guarded.contains("invalid_table_definition"), "{guarded}");
+}
+
+#[test]
+fn guard_duplicate_objects_leaves_statements_outside_transactions() {
+    let script = "create index concurrently if not exists i on t (id);\n\
+                  create subscription s connection 'x' publication p;\n";
+    assert_eq!(guard_duplicate_objects(script), script);
+}
+
+#[test]
+fn duplicate_guarded_statement_unwraps_the_block() {
+    let guarded = guard_duplicate_objects("create policy p on public.t using (true);");
+    assert_eq!(
+        duplicate_guarded_statement(&guarded),
+        Some("create policy p on public.t using (true)")
+    );
+    assert_eq!(duplicate_guarded_statement("create policy p;"), None);
+}
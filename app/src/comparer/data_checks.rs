@@ -0,0 +1,378 @@
+//! Data precondition checks for the `data_checks` option.
+//!
+//! `SET NOT NULL` and new `UNIQUE`, `PRIMARY KEY`, `CHECK` and `FOREIGN KEY`
+//! constraints fail when the existing rows do not satisfy them — in the
+//! production path only at `VALIDATE CONSTRAINT`, after the main transaction
+//! has committed. With the option every such change of an altered table gets
+//! a DO block at the top of the script, before any DDL runs, that counts the
+//! violating rows (null values, duplicate keys, rows failing the check,
+//! orphans) and raises an exception with a sample of their keys.
+//!
+//! The queries are built from the constraint definitions of the TO dump and
+//! the columns of the FROM one, and run against the database before it is
+//! migrated: constraints that involve a column the script adds are skipped,
+//! and a foreign key to a table the script creates treats every non-null key
+//! as an orphan. `NOT VALID` constraints do not check existing rows and are
+//! skipped too.
+
+use crate::comparer::scanner::{Statement, TokenKind};
+use crate::dump::core::Dump;
+use crate::dump::table::Table;
+use crate::dump::table_constraint::TableConstraint;
+
+/// Number of offending keys quoted in the error.
+const SAMPLE_SIZE: usize = 5;
+
+/// One check: the violating rows of a table and how to report them.
+struct DataCheck {
+    /// What the script changes, for the block comment.
+    change: String,
+    /// Error message, after the number of violations.
+    message: String,
+    /// Label of the sample in the error detail.
+    sample_label: &'static str,
+    /// A query returning one text key per violation.
+    query: String,
+}
+
+/// The check blocks for every table altered between `from` and `to`, in
+/// table order.
+pub(crate) fn data_check_blocks(from: &Dump, to: &Dump) -> Vec<String> {
+    let mut tables: Vec<&Table> = from.tables.iter().collect();
+    tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+    let mut blocks = Vec::new();
+    for table in tables {
+        let Some(to_table) = to
+            .tables
+            .iter()
+            .find(|t| t.schema == table.schema && t.name == table.name)
+        else {
+            continue;
+        };
+        // Partitions are checked through their parent, and a recreated
+        // table loses its rows anyway.
+        if table.hash == to_table.hash
+            || to_table.partition_of.is_some()
+            || table.will_be_dropped_and_recreated(to_table)
+        {
+            continue;
+        }
+        blocks.extend(
+            table_checks(from, to, table, to_table)
+                .iter()
+                .map(check_block),
+        );
+    }
+    blocks
+}
+
+fn table_checks(from: &Dump, to: &Dump, table: &Table, to_table: &Table) -> Vec<DataCheck> {
+    let qualified = format!("{}.{}", table.schema, table.name);
+    let row_key = row_key(table);
+    let mut checks = Vec::new();
+
+    for column in &to_table.columns {
+        if column.is_nullable {
+            continue;
+        }
+        let Some(old) = table.columns.iter().find(|old| old.name == column.name) else {
+            continue;
+        };
+        if old.is_nullable && !column.would_drop_and_re_add(old) {
+            checks.push(DataCheck {
+                change: format!("{qualified}.{} becomes NOT NULL", column.name),
+                message: format!(
+                    "row(s) of {qualified} have a null {}, which the script makes NOT NULL",
+                    column.name
+                ),
+                sample_label: "Offending rows",
+                query: format!(
+                    "select {row_key} from {qualified} where {} is null",
+                    column.name
+                ),
+            });
+        }
+    }
+
+    for constraint in &to_table.constraints {
+        if constraint.coninhcount != 0 || !constraint.is_enforced {
+            continue;
+        }
+        let unchanged = table.constraints.iter().any(|old| {
+            old.name == constraint.name
+                && old.constraint_type == constraint.constraint_type
+                && old.definition == constraint.definition
+        });
+        let Some(definition) = constraint.definition.as_deref() else {
+            continue;
+        };
+        if unchanged {
+            continue;
+        }
+        let st = Statement::parse(definition);
+        if is_not_valid(&st) || mentions_new_column(&st, table, to_table) {
+            continue;
+        }
+        let check = match constraint.constraint_type.to_uppercase().as_str() {
+            "PRIMARY KEY" | "UNIQUE" => unique_check(&qualified, constraint, &st),
+            "CHECK" => check_check(&qualified, &row_key, constraint, &st),
+            "FOREIGN KEY" => foreign_key_check(from, to, &qualified, constraint, &st),
+            _ => None,
+        };
+        checks.extend(check);
+    }
+    checks
+}
+
+fn check_block(check: &DataCheck) -> String {
+    format!(
+        "/* Data check: {change}. */
+do $pgc_data_check$
+declare
+    violations bigint;
+    sample text;
+begin
+    select count(*) into violations from ({query}) v;
+    if violations > 0 then
+        select string_agg(coalesce(key, 'null'), ', ') into sample from ({query} limit {SAMPLE_SIZE}) v(key);
+        raise exception {message}, violations
+            using detail = '{label} (first {SAMPLE_SIZE}): ' || coalesce(sample, 'none'),
+                  hint = 'Fix the data before applying this script.';
+    end if;
+end
+$pgc_data_check$;",
+        change = check.change.replace("*/", "* /"),
+        query = check.query,
+        message = quote_literal(&format!("pgc: % {}", check.message.replace('%', "%%"))),
+        label = check.sample_label,
+    )
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// A text key of a row of `table` for the samples: its primary key when it
+/// has one, its `ctid` otherwise.
+fn row_key(table: &Table) -> String {
+    table
+        .constraints
+        .iter()
+        .filter(|c| c.constraint_type.eq_ignore_ascii_case("primary key"))
+        .find_map(|c| {
+            let definition = c.definition.as_deref()?;
+            let st = Statement::parse(definition);
+            let columns = column_list(&st, st.find_word(0, "key")? + 1)?.0;
+            Some(key_expression(&columns))
+        })
+        .unwrap_or_else(|| "ctid::text".to_string())
+}
+
+fn key_expression(columns: &[String]) -> String {
+    if columns.len() == 1 {
+        format!("{}::text", columns[0])
+    } else {
+        format!("row({})::text", columns.join(", "))
+    }
+}
+
+/// The parenthesised column list starting at token `i`, and the index of
+/// the token after it.
+fn column_list(st: &Statement, i: usize) -> Option<(Vec<String>, usize)> {
+    if !st.is_punct(i, "(") {
+        return None;
+    }
+    let mut columns = Vec::new();
+    let mut j = i + 1;
+    loop {
+        if !st.is_name_part(j) {
+            return None;
+        }
+        columns.push(st.text(j).to_string());
+        if st.is_punct(j + 1, ")") {
+            return Some((columns, j + 2));
+        }
+        if !st.is_punct(j + 1, ",") {
+            return None;
+        }
+        j += 2;
+    }
+}
+
+/// The index of the `)` closing the `(` at token `i`.
+fn closing_paren(st: &Statement, i: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for j in i..st.tokens.len() {
+        if st.is_punct(j, "(") {
+            depth += 1;
+        } else if st.is_punct(j, ")") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(j);
+            }
+        }
+    }
+    None
+}
+
+fn is_not_valid(st: &Statement) -> bool {
+    st.find_word(0, "not")
+        .is_some_and(|i| st.is_word(i + 1, "valid"))
+}
+
+/// Whether the definition names a column `to_table` has and `table` does
+/// not, i.e. one the script adds.
+fn mentions_new_column(st: &Statement, table: &Table, to_table: &Table) -> bool {
+    st.tokens.iter().enumerate().any(|(i, token)| {
+        let name = match token.kind {
+            TokenKind::Word => st.text(i).to_lowercase(),
+            TokenKind::Quoted => st.text(i).to_string(),
+            _ => return false,
+        };
+        to_table.columns.iter().any(|c| c.name == name)
+            && !table.columns.iter().any(|c| c.name == name)
+    })
+}
+
+fn unique_check(
+    qualified: &str,
+    constraint: &TableConstraint,
+    st: &Statement,
+) -> Option<DataCheck> {
+    // Temporal keys (`WITHOUT OVERLAPS`) compare ranges, not values.
+    if st.find_word(0, "overlaps").is_some() {
+        return None;
+    }
+    let is_primary_key = st.is_word(0, "primary");
+    let nulls_not_distinct = !is_primary_key && st.skip(1, &["nulls", "not", "distinct"]) > 1;
+    let start = if is_primary_key || nulls_not_distinct {
+        st.find_word(0, if is_primary_key { "key" } else { "distinct" })? + 1
+    } else {
+        1
+    };
+    let (columns, _) = column_list(st, start)?;
+    let list = columns.join(", ");
+    // Rows with a null key never collide unless the key is a primary key
+    // (checked by its NOT NULL columns) or NULLS NOT DISTINCT.
+    let filter = if is_primary_key || nulls_not_distinct {
+        String::new()
+    } else {
+        format!(
+            " where {}",
+            columns
+                .iter()
+                .map(|column| format!("{column} is not null"))
+                .collect::<Vec<_>>()
+                .join(" and ")
+        )
+    };
+    Some(DataCheck {
+        change: format!(
+            "{} {} on {qualified}",
+            constraint.constraint_type.to_lowercase(),
+            constraint.name
+        ),
+        message: format!(
+            "key value(s) of {qualified} are duplicated, which {} {} forbids",
+            constraint.constraint_type.to_lowercase(),
+            constraint.name
+        ),
+        sample_label: "Duplicated keys",
+        query: format!(
+            "select {} from {qualified}{filter} group by {list} having count(*) > 1",
+            key_expression(&columns)
+        ),
+    })
+}
+
+fn check_check(
+    qualified: &str,
+    row_key: &str,
+    constraint: &TableConstraint,
+    st: &Statement,
+) -> Option<DataCheck> {
+    if !st.is_word(0, "check") {
+        return None;
+    }
+    let end = closing_paren(st, 1)?;
+    let expression = &st.sql[st.tokens[1].start..st.tokens[end].end];
+    Some(DataCheck {
+        change: format!("check {} on {qualified}", constraint.name),
+        message: format!("row(s) of {qualified} fail check {}", constraint.name),
+        sample_label: "Offending rows",
+        query: format!("select {row_key} from {qualified} where {expression} is false"),
+    })
+}
+
+fn foreign_key_check<'a>(
+    from: &'a Dump,
+    to: &'a Dump,
+    qualified: &str,
+    constraint: &TableConstraint,
+    st: &Statement,
+) -> Option<DataCheck> {
+    if !st.is_word(0, "foreign") || st.find_word(0, "period").is_some() {
+        return None;
+    }
+    let (columns, next) = column_list(st, 2)?;
+    if !st.is_word(next, "references") {
+        return None;
+    }
+    let parts = st.name_parts(next + 1);
+    let referenced = &st.sql[st.tokens[*parts.first()?].start..st.tokens[*parts.last()?].end];
+    let (referenced_columns, _) = column_list(st, parts.last()? + 1)?;
+    if referenced_columns.len() != columns.len() {
+        return None;
+    }
+    let names: Vec<&str> = parts.iter().map(|&p| st.text(p)).collect();
+    let find_table = |dump: &'a Dump| {
+        dump.tables.iter().find(|t| match names.as_slice() {
+            [schema, name] => t.schema == *schema && t.name == *name,
+            [name] => t.name == *name,
+            _ => false,
+        })
+    };
+    let from_table = find_table(from);
+    // The referenced key must exist before the script runs.
+    if let Some(from_table) = from_table
+        && referenced_columns
+            .iter()
+            .any(|column| !from_table.columns.iter().any(|c| &c.name == column))
+    {
+        return None;
+    }
+    let mut conditions: Vec<String> = columns
+        .iter()
+        .map(|column| format!("t.{column} is not null"))
+        .collect();
+    // A table the script creates is empty: every non-null key is an orphan.
+    if from_table.is_some() || find_table(to).is_none() {
+        let matches: Vec<String> = columns
+            .iter()
+            .zip(&referenced_columns)
+            .map(|(column, referenced_column)| format!("r.{referenced_column} = t.{column}"))
+            .collect();
+        conditions.push(format!(
+            "not exists (select 1 from {referenced} r where {})",
+            matches.join(" and ")
+        ));
+    }
+    let keys: Vec<String> = columns.iter().map(|column| format!("t.{column}")).collect();
+    Some(DataCheck {
+        change: format!("foreign key {} on {qualified}", constraint.name),
+        message: format!(
+            "row(s) of {qualified} reference missing rows of {referenced} through foreign key {}",
+            constraint.name
+        ),
+        sample_label: "Orphaned keys",
+        query: format!(
+            "select {} from {qualified} t where {}",
+            key_expression(&keys),
+            conditions.join(" and ")
+        ),
+    })
+}
+
+#[cfg(test)]
+#[path = "data_checks_tests.rs"]
+mod tests;
@@ -0,0 +1,281 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::dump::table_column::TableColumn;
+
+fn column(table: &str, name: &str, ordinal: i32, is_nullable: bool) -> TableColumn {
+    TableColumn {
+        catalog: "postgres".to_string(),
+        schema: "public".to_string(),
+        table: table.to_string(),
+        name: name.to_string(),
+        ordinal_position: ordinal,
+        column_default: None,
+        is_nullable,
+        data_type: "integer".to_string(),
+        character_maximum_length: None,
+        character_octet_length: None,
+        numeric_precision: Some(32),
+        numeric_precision_radix: Some(2),
+        numeric_scale: Some(0),
+        datetime_precision: None,
+        interval_type: None,
+        interval_precision: None,
+        character_set_catalog: None,
+        character_set_schema: None,
+        character_set_name: None,
+        collation_catalog: None,
+        collation_schema: None,
+        collation_name: None,
+        domain_catalog: None,
+        domain_schema: None,
+        domain_name: None,
+        udt_catalog: None,
+        udt_schema: None,
+        udt_name: None,
+        scope_catalog: None,
+        scope_schema: None,
+        scope_name: None,
+        maximum_cardinality: None,
+        dtd_identifier: None,
+        is_self_referencing: false,
+        is_identity: false,
+        identity_generation: None,
+        identity_start: None,
+        identity_increment: None,
+        identity_maximum: None,
+        identity_minimum: None,
+        identity_cycle: false,
+        is_generated: "NEVER".to_string(),
+        generation_expression: None,
+        generation_type: None,
+        is_updatable: true,
+        related_views: None,
+        comment: None,
+        storage: None,
+        compression: None,
+        statistics_target: None,
+        acl: vec![],
+        serial_type: None,
+    }
+}
+
+fn constraint(table: &str, name: &str, constraint_type: &str, definition: &str) -> TableConstraint {
+    TableConstraint {
+        catalog: "postgres".to_string(),
+        schema: "public".to_string(),
+        name: name.to_string(),
+        table_name: table.to_string(),
+        constraint_type: constraint_type.to_string(),
+        is_deferrable: false,
+        initially_deferred: false,
+        definition: Some(definition.to_string()),
+        coninhcount: 0,
+        is_enforced: true,
+        no_inherit: false,
+        nulls_not_distinct: false,
+        comment: None,
+    }
+}
+
+fn table(name: &str, columns: Vec<TableColumn>, constraints: Vec<TableConstraint>) -> Table {
+    let mut table = Table::new(
+        "public".to_string(),
+        name.to_string(),
+        "public".to_string(),
+        name.to_string(),
+        "postgres".to_string(),
+        None,
+        columns,
+        constraints,
+        vec![],
+        vec![],
+        None,
+    );
+    table.hash();
+    table
+}
+
+fn dumps(from: Vec<Table>, to: Vec<Table>) -> (Dump, Dump) {
+    let mut from_dump = Dump::new(DumpConfig::default());
+    let mut to_dump = Dump::new(DumpConfig::default());
+    from_dump.tables = from;
+    to_dump.tables = to;
+    (from_dump, to_dump)
+}
+
+fn orders(columns: Vec<TableColumn>, mut constraints: Vec<TableConstraint>) -> Table {
+    constraints.insert(
+        0,
+        constraint("orders", "orders_pkey", "PRIMARY KEY", "PRIMARY KEY (id)"),
+    );
+    table("orders", columns, constraints)
+}
+
+#[test]
+fn not_null_changes_count_null_rows() {
+    let (from, to) = dumps(
+        vec![orders(
+            vec![
+                column("orders", "id", 1, false),
+                column("orders", "total", 2, true),
+            ],
+            vec![],
+        )],
+        vec![orders(
+            vec![
+                column("orders", "id", 1, false),
+                column("orders", "total", 2, false),
+            ],
+            vec![],
+        )],
+    );
+    let blocks = data_check_blocks(&from, &to);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(
+        blocks[0],
+        "/* Data check: public.orders.total becomes NOT NULL. */
+do $pgc_data_check$
+declare
+    violations bigint;
+    sample text;
+begin
+    select count(*) into violations from (select id::text from public.orders where total is null) v;
+    if violations > 0 then
+        select string_agg(coalesce(key, 'null'), ', ') into sample from (select id::text from public.orders where total is null limit 5) v(key);
+        raise exception 'pgc: % row(s) of public.orders have a null total, which the script makes NOT NULL', violations
+            using detail = 'Offending rows (first 5): ' || coalesce(sample, 'none'),
+                  hint = 'Fix the data before applying this script.';
+    end if;
+end
+$pgc_data_check$;"
+    );
+}
+
+#[test]
+fn unique_constraints_count_duplicate_keys() {
+    let columns = || vec![column("t", "a", 1, true), column("t", "b", 2, true)];
+    let (from, to) = dumps(
+        vec![table("t", columns(), vec![])],
+        vec![table(
+            "t",
+            columns(),
+            vec![
+                constraint("t", "t_a_b_key", "UNIQUE", "UNIQUE (a, b)"),
+                constraint("t", "t_b_key", "UNIQUE", "UNIQUE NULLS NOT DISTINCT (b)"),
+            ],
+        )],
+    );
+    let blocks = data_check_blocks(&from, &to);
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks[0].contains(
+        "(select row(a, b)::text from public.t where a is not null and b is not null group by a, b having count(*) > 1)"
+    ));
+    assert!(blocks[0].contains("'Duplicated keys (first 5): '"));
+    assert!(blocks[1].contains("(select b::text from public.t group by b having count(*) > 1)"));
+}
+
+#[test]
+fn check_constraints_count_failing_rows() {
+    let columns = || vec![column("t", "total", 1, true)];
+    let (from, to) = dumps(
+        vec![table("t", columns(), vec![])],
+        vec![table(
+            "t",
+            columns(),
+            vec![constraint(
+                "t",
+                "t_total_check",
+                "CHECK",
+                "CHECK ((total > 0))",
+            )],
+        )],
+    );
+    let blocks = data_check_blocks(&from, &to);
+    assert_eq!(blocks.len(), 1);
+    assert!(
+        blocks[0].contains("(select ctid::text from public.t where ((total > 0)) is false)"),
+        "{}",
+        blocks[0]
+    );
+}
+
+#[test]
+fn foreign_keys_count_orphans() {
+    let line_columns = || vec![column("lines", "order_id", 1, true)];
+    let order_columns = || vec![column("orders", "id", 1, false)];
+    let fk = || {
+        constraint(
+            "lines",
+            "lines_order_fkey",
+            "FOREIGN KEY",
+            "FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE",
+        )
+    };
+    let (from, to) = dumps(
+        vec![
+            table("lines", line_columns(), vec![]),
+            orders(order_columns(), vec![]),
+        ],
+        vec![
+            table("lines", line_columns(), vec![fk()]),
+            orders(order_columns(), vec![]),
+        ],
+    );
+    let blocks = data_check_blocks(&from, &to);
+    assert_eq!(blocks.len(), 1);
+    assert!(blocks[0].contains(
+        "(select t.order_id::text from public.lines t where t.order_id is not null and not exists (select 1 from public.orders r where r.id = t.order_id))"
+    ));
+
+    // The script creates the referenced table: every key is an orphan.
+    let (from, to) = dumps(
+        vec![table("lines", line_columns(), vec![])],
+        vec![
+            table("lines", line_columns(), vec![fk()]),
+            orders(order_columns(), vec![]),
+        ],
+    );
+    let blocks = data_check_blocks(&from, &to);
+    assert!(
+        blocks[0]
+            .contains("(select t.order_id::text from public.lines t where t.order_id is not null)")
+    );
+}
+
+#[test]
+fn changes_that_cannot_fail_on_existing_rows_are_skipped() {
+    let (from, to) = dumps(
+        vec![table(
+            "t",
+            vec![column("t", "a", 1, true)],
+            vec![constraint("t", "t_a_check", "CHECK", "CHECK ((a > 0))")],
+        )],
+        vec![table(
+            "t",
+            vec![column("t", "a", 1, true), column("t", "b", 2, true)],
+            vec![
+                // Unchanged.
+                constraint("t", "t_a_check", "CHECK", "CHECK ((a > 0))"),
+                // On a column the script adds.
+                constraint("t", "t_b_key", "UNIQUE", "UNIQUE (b)"),
+                // Existing rows are not checked.
+                constraint("t", "t_a_max", "CHECK", "CHECK ((a < 10)) NOT VALID"),
+            ],
+        )],
+    );
+    assert!(data_check_blocks(&from, &to).is_empty());
+}
+
+#[test]
+fn messages_escape_quotes_and_percent_signs() {
+    let block = check_block(&DataCheck {
+        change: "x */ y".to_string(),
+        message: "row(s) of public.\"100%\" fail check it's".to_string(),
+        sample_label: "Offending rows",
+        query: "select 1".to_string(),
+    });
+    assert!(block.starts_with("/* Data check: x * / y. */\n"));
+    assert!(block.contains(
+        "raise exception 'pgc: % row(s) of public.\"100%%\" fail check it''s', violations"
+    ));
+}
@@ -0,0 +1,272 @@
+//! Target-fingerprint guard for the `fingerprint_guard` option.
+//!
+//! A delta is only valid for a database in exactly the state of the FROM
+//! dump. The guard is a DO block at the top of the script that recomputes a
+//! fingerprint of the relevant objects from the catalog and aborts with the
+//! list of drifted objects when it differs from the one taken from the dump.
+//!
+//! The dump's own object hashes cover attributes the catalog cannot cheaply
+//! reproduce in SQL, so the fingerprint is built from a structural signature
+//! both sides compute identically: for every table of the dumped schemas its
+//! columns in order, each with its (base) type name and nullability, and for
+//! every sequence its data type. Each object contributes `md5(signature)`;
+//! the fingerprint is the md5 of the sorted `object hash` lines. Objects
+//! missing from the target, extra objects in the dumped schemas and changed
+//! objects are all reported.
+//!
+//! Objects the include/exclude filters leave out of the dumps are left out
+//! of the catalog side too: the filter rules are translated into `LIKE`
+//! conditions on the qualified names, and a sequence owned by an excluded
+//! table goes with it, as in [`crate::dump::filter::apply_filter`].
+//!
+//! The progress ledger ([`LEDGER_TABLE`]) is never part of the schema: the
+//! script creates it ahead of the guard when `progress_ledger` is on, so
+//! neither side counts it.
+
+use crate::comparer::progress_ledger::LEDGER_TABLE;
+use crate::config::object_filter::{FilterKind, ObjectFilter};
+use crate::dump::core::Dump;
+
+/// The object names (`table schema.name`, `sequence schema.name`) and
+/// signature hashes of `dump`, sorted by object.
+pub(crate) fn object_signatures(dump: &Dump) -> Vec<(String, String)> {
+    let mut objects: Vec<(String, String)> = Vec::new();
+    for table in &dump.tables {
+        if format!("{}.{}", table.schema, table.name) == LEDGER_TABLE {
+            continue;
+        }
+        let mut columns: Vec<_> = table.columns.iter().collect();
+        columns.sort_by_key(|column| column.ordinal_position);
+        let signature = columns
+            .iter()
+            .map(|column| {
+                let type_name = column.udt_name.as_deref().unwrap_or(&column.data_type);
+                let not_null = if column.is_nullable { "" } else { " not null" };
+                format!("{} {type_name}{not_null}", column.name)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        objects.push((
+            format!("table {}.{}", table.schema, table.name),
+            format!("{:x}", md5::compute(signature)),
+        ));
+    }
+    for sequence in &dump.sequences {
+        objects.push((
+            format!("sequence {}.{}", sequence.schema, sequence.name),
+            format!("{:x}", md5::compute(&sequence.data_type)),
+        ));
+    }
+    objects.sort();
+    objects
+}
+
+/// The fingerprint of `signatures`, as [`object_signatures`] returns them.
+pub(crate) fn fingerprint(signatures: &[(String, String)]) -> String {
+    let lines: Vec<String> = signatures
+        .iter()
+        .map(|(object, hash)| format!("{object} {hash}"))
+        .collect();
+    format!("{:x}", md5::compute(lines.join("\n")))
+}
+
+/// The schemas the guard looks at: those of the dump and of its tables and
+/// sequences.
+fn dumped_schemas(dump: &Dump) -> Vec<String> {
+    let mut schemas: Vec<String> = dump
+        .schemas
+        .iter()
+        .map(|schema| schema.name.clone())
+        .chain(dump.tables.iter().map(|table| table.schema.clone()))
+        .chain(
+            dump.sequences
+                .iter()
+                .map(|sequence| sequence.schema.clone()),
+        )
+        .collect();
+    schemas.sort();
+    schemas.dedup();
+    schemas
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// A shell-style filter pattern as a `LIKE` pattern.
+fn like_pattern(pattern: &str) -> String {
+    let mut like = String::new();
+    for c in pattern.chars() {
+        match c {
+            '*' => like.push('%'),
+            '?' => like.push('_'),
+            '%' | '_' | '\\' => {
+                like.push('\\');
+                like.push(c);
+            }
+            _ => like.push(c),
+        }
+    }
+    like
+}
+
+/// SQL condition that holds when `filter` keeps the object of `kind` whose
+/// qualified name is `name`, mirroring [`ObjectFilter::keeps`] with the
+/// pattern matching of the `show` command (quotes in the name and the
+/// pattern are optional). `None` when no rule covers `kind`.
+pub(crate) fn filter_condition(
+    filter: &ObjectFilter,
+    kind: FilterKind,
+    name: &str,
+) -> Option<String> {
+    let rules: Vec<_> = filter
+        .rules
+        .iter()
+        .filter(|rule| rule.kind == kind || rule.kind == FilterKind::All)
+        .collect();
+    if rules.is_empty() {
+        return None;
+    }
+    let matches = |include: bool| -> Vec<String> {
+        rules
+            .iter()
+            .filter(|rule| rule.include == include)
+            .map(|rule| {
+                format!(
+                    "({name} like {} or replace({name}, '\"', '') like {})",
+                    quote_literal(&like_pattern(&rule.pattern)),
+                    quote_literal(&like_pattern(&rule.pattern.replace('"', "")))
+                )
+            })
+            .collect()
+    };
+    let mut conditions = Vec::new();
+    let includes = matches(true);
+    if !includes.is_empty() {
+        conditions.push(format!("({})", includes.join(" or ")));
+    }
+    let excludes = matches(false);
+    if !excludes.is_empty() {
+        conditions.push(format!("not ({})", excludes.join(" or ")));
+    }
+    Some(conditions.join(" and "))
+}
+
+/// The DO block that aborts the script when the target does not match
+/// `dump`, ignoring the target's objects that `filter` excludes.
+pub(crate) fn guard_block(dump: &Dump, filter: &ObjectFilter) -> String {
+    let signatures = object_signatures(dump);
+    let expected_fingerprint = fingerprint(&signatures);
+    let expected = if signatures.is_empty() {
+        "select null::text, null::text where false".to_string()
+    } else {
+        let rows: Vec<String> = signatures
+            .iter()
+            .map(|(object, hash)| format!("({}, '{hash}')", quote_literal(object)))
+            .collect();
+        format!("values\n            {}", rows.join(",\n            "))
+    };
+    let schemas: Vec<String> = dumped_schemas(dump)
+        .iter()
+        .map(|schema| quote_literal(schema))
+        .collect();
+    let schemas = format!("array[{}]::text[]", schemas.join(", "));
+    let table_name = |schema: &str, table: &str| {
+        format!("quote_ident({schema}.nspname) || '.' || quote_ident({table}.relname)")
+    };
+    let table_filter = filter_condition(filter, FilterKind::Tables, &table_name("n", "c"))
+        .map(|condition| format!("\n            and {condition}"))
+        .unwrap_or_default();
+    let mut sequence_filter =
+        filter_condition(filter, FilterKind::Sequences, &table_name("n", "c"))
+            .map(|condition| format!("\n            and {condition}"))
+            .unwrap_or_default();
+    if let Some(owner_kept) = filter_condition(
+        filter,
+        FilterKind::Tables,
+        &table_name("owner_n", "owner_c"),
+    ) {
+        sequence_filter.push_str(&format!(
+            "\n            and not exists (
+                select 1 from pg_catalog.pg_depend own_dep
+                join pg_catalog.pg_class owner_c on owner_c.oid = own_dep.refobjid
+                join pg_catalog.pg_namespace owner_n on owner_n.oid = owner_c.relnamespace
+                where own_dep.classid = 'pg_catalog.pg_class'::regclass
+                and own_dep.objid = c.oid
+                and own_dep.refclassid = 'pg_catalog.pg_class'::regclass
+                and own_dep.deptype in ('a', 'i')
+                and not ({owner_kept})
+            )"
+        ));
+    }
+    let ledger = table_name("n", "c");
+    let ledger_table = quote_literal(LEDGER_TABLE);
+    let not_extension_owned = "not exists (\n                select 1 from pg_catalog.pg_depend ext_dep\n                where ext_dep.classid = 'pg_catalog.pg_class'::regclass\n                and ext_dep.objid = c.oid\n                and ext_dep.objsubid = 0\n                and ext_dep.deptype = 'e'\n            )";
+    format!(
+        "/* Fingerprint guard: this script applies to a database matching the FROM dump (fingerprint {expected_fingerprint}). */
+do $pgc_fingerprint$
+declare
+    found text;
+    drift text;
+begin
+    with expected(object, hash) as (
+        {expected}
+    ),
+    actual(object, hash) as (
+        select
+            'table ' || quote_ident(n.nspname) || '.' || quote_ident(c.relname),
+            md5(coalesce(string_agg(
+                quote_ident(a.attname) || ' ' || coalesce(bt.typname, t.typname)
+                    || case when a.attnotnull or (t.typtype = 'd' and t.typnotnull) then ' not null' else '' end,
+                ', ' order by a.attnum
+            ), ''))
+        from pg_catalog.pg_class c
+        join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+        left join pg_catalog.pg_attribute a on a.attrelid = c.oid and a.attnum > 0 and not a.attisdropped
+        left join pg_catalog.pg_type t on t.oid = a.atttypid
+        left join pg_catalog.pg_type bt on t.typtype = 'd' and bt.oid = t.typbasetype
+        where c.relkind in ('r', 'p')
+            and quote_ident(n.nspname) = any({schemas})
+            and c.relname not like 'pg_%'
+            and {ledger} <> {ledger_table}
+            and {not_extension_owned}{table_filter}
+        group by n.nspname, c.relname
+        union all
+        select
+            'sequence ' || quote_ident(n.nspname) || '.' || quote_ident(c.relname),
+            md5(pg_catalog.format_type(s.seqtypid, null))
+        from pg_catalog.pg_sequence s
+        join pg_catalog.pg_class c on c.oid = s.seqrelid
+        join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+        where quote_ident(n.nspname) = any({schemas})
+            and {not_extension_owned}{sequence_filter}
+    )
+    select
+        (select md5(coalesce(string_agg(object || ' ' || hash, E'\\n' order by object collate \"C\"), '')) from actual),
+        (
+            select string_agg(
+                case
+                    when a.object is null then 'missing ' || e.object
+                    when e.object is null then 'unexpected ' || a.object
+                    else 'changed ' || e.object
+                end,
+                ', ' order by coalesce(e.object, a.object) collate \"C\"
+            )
+            from expected e
+            full join actual a on a.object = e.object
+            where e.hash is distinct from a.hash
+        )
+    into found, drift;
+    if found is distinct from '{expected_fingerprint}' then
+        raise exception 'pgc: the target database does not match the FROM dump this script was generated from (fingerprint %, expected %)', found, '{expected_fingerprint}'
+            using detail = 'Drifted objects: ' || coalesce(drift, 'none'),
+                  hint = 'Check that the script is applied to the right database, or regenerate it from a fresh dump of this one.';
+    end if;
+end
+$pgc_fingerprint$;"
+    )
+}
+
+#[cfg(test)]
+#[path = "fingerprint_tests.rs"]
+mod tests;
@@ -0,0 +1,188 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::config::object_filter::FilterRule;
+use crate::dump::sequence::Sequence;
+use crate::dump::table::Table;
+
+fn sequence(schema: &str, name: &str, data_type: &str) -> Sequence {
+    Sequence::new(
+        schema.to_string(),
+        name.to_string(),
+        "postgres".to_string(),
+        data_type.to_string(),
+        Some(1),
+        Some(1),
+        Some(1000),
+        Some(1),
+        false,
+        Some(1),
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+fn empty_table(schema: &str, name: &str) -> Table {
+    Table::new(
+        schema.to_string(),
+        name.to_string(),
+        schema.to_string(),
+        name.to_string(),
+        "postgres".to_string(),
+        None,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    )
+}
+
+#[test]
+fn signatures_are_sorted_and_hashed() {
+    let mut dump = Dump::new(DumpConfig::default());
+    dump.sequences.push(sequence("public", "b_seq", "bigint"));
+    dump.tables.push(empty_table("public", "z"));
+    dump.sequences.push(sequence("app", "a_seq", "integer"));
+    assert_eq!(
+        object_signatures(&dump),
+        vec![
+            (
+                "sequence app.a_seq".to_string(),
+                format!("{:x}", md5::compute("integer"))
+            ),
+            (
+                "sequence public.b_seq".to_string(),
+                format!("{:x}", md5::compute("bigint"))
+            ),
+            (
+                "table public.z".to_string(),
+                format!("{:x}", md5::compute(""))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fingerprint_hashes_the_object_lines() {
+    let signatures = vec![
+        ("sequence public.s".to_string(), "aa".to_string()),
+        ("table public.t".to_string(), "bb".to_string()),
+    ];
+    assert_eq!(
+        fingerprint(&signatures),
+        format!(
+            "{:x}",
+            md5::compute("sequence public.s aa\ntable public.t bb")
+        )
+    );
+    assert_eq!(fingerprint(&[]), format!("{:x}", md5::compute("")));
+}
+
+#[test]
+fn guard_block_embeds_expected_objects_and_schemas() {
+    let mut dump = Dump::new(DumpConfig::default());
+    dump.tables.push(empty_table("public", "it's"));
+    let block = guard_block(&dump, &ObjectFilter::default());
+    let expected = fingerprint(&object_signatures(&dump));
+    assert!(block.starts_with(&format!(
+        "/* Fingerprint guard: this script applies to a database matching the FROM dump (fingerprint {expected}). */\ndo $pgc_fingerprint$\n"
+    )));
+    assert!(block.contains(&format!(
+        "values\n            ('table public.it''s', '{:x}')",
+        md5::compute("")
+    )));
+    assert!(block.contains("quote_ident(n.nspname) = any(array['public']::text[])"));
+    assert!(block.contains(&format!("if found is distinct from '{expected}' then")));
+    assert!(block.ends_with("end\n$pgc_fingerprint$;"));
+}
+
+#[test]
+fn guard_block_for_an_empty_dump_expects_no_objects() {
+    let block = guard_block(&Dump::new(DumpConfig::default()), &ObjectFilter::default());
+    assert!(block.contains("select null::text, null::text where false"));
+    assert!(block.contains("any(array[]::text[])"));
+}
+
+#[test]
+fn guard_block_without_filters_looks_at_every_object() {
+    let block = guard_block(&Dump::new(DumpConfig::default()), &ObjectFilter::default());
+    assert!(!block.contains("replace("));
+    assert!(!block.contains("own_dep"));
+}
+
+#[test]
+fn filter_condition_translates_the_rules() {
+    let filter = ObjectFilter {
+        rules: vec![
+            FilterRule::parse("tables:app.*", true).unwrap(),
+            FilterRule::parse("tables:app.t_tmp?", false).unwrap(),
+            FilterRule::parse("all:app.my_%", false).unwrap(),
+        ],
+    };
+    assert_eq!(
+        filter_condition(&filter, FilterKind::Tables, "name").unwrap(),
+        "((name like 'app.%' or replace(name, '\"', '') like 'app.%')) \
+         and not ((name like 'app.t\\_tmp_' or replace(name, '\"', '') like 'app.t\\_tmp_') \
+         or (name like 'app.my\\_\\%' or replace(name, '\"', '') like 'app.my\\_\\%'))"
+    );
+    assert_eq!(
+        filter_condition(&filter, FilterKind::Sequences, "name").unwrap(),
+        "not ((name like 'app.my\\_\\%' or replace(name, '\"', '') like 'app.my\\_\\%'))"
+    );
+    assert_eq!(
+        filter_condition(&ObjectFilter::default(), FilterKind::Tables, "name"),
+        None
+    );
+}
+
+#[test]
+fn guard_block_leaves_excluded_tables_and_their_sequences_out() {
+    let filter = ObjectFilter {
+        rules: vec![FilterRule::parse("tables:app.t_tmp", false).unwrap()],
+    };
+    let block = guard_block(&Dump::new(DumpConfig::default()), &filter);
+    let condition =
+        "not ((quote_ident(n.nspname) || '.' || quote_ident(c.relname) like 'app.t\\_tmp'";
+    assert!(block.contains(&format!("and {condition}")), "{block}");
+    assert!(
+        block.contains("and own_dep.deptype in ('a', 'i')"),
+        "{block}"
+    );
+    assert!(
+        block.contains(
+            "and not (not ((quote_ident(owner_n.nspname) || '.' || quote_ident(owner_c.relname) like 'app.t\\_tmp'"
+        ),
+        "{block}"
+    );
+}
+
+#[test]
+fn guard_block_leaves_the_progress_ledger_out_of_both_sides() {
+    let mut dump = Dump::new(DumpConfig::default());
+    dump.tables.push(empty_table("public", "orders"));
+    dump.tables
+        .push(empty_table("public", "pgc_migration_progress"));
+    assert_eq!(
+        object_signatures(&dump),
+        vec![(
+            "table public.orders".to_string(),
+            format!("{:x}", md5::compute(""))
+        )]
+    );
+    let block = guard_block(&dump, &ObjectFilter::default());
+    assert!(
+        block.contains(&format!(
+            "values\n            ('table public.orders', '{:x}')\n    ),",
+            md5::compute("")
+        )),
+        "{block}"
+    );
+    assert!(
+        block.contains(
+            "and quote_ident(n.nspname) || '.' || quote_ident(c.relname) <> 'public.pgc_migration_progress'"
+        ),
+        "{block}"
+    );
+}
@@ -0,0 +1,747 @@
+//! Liquibase changelogs for `migration_format = liquibase-xml | liquibase-yaml`.
+//!
+//! The generated script is cut into one changeSet per changed object:
+//! consecutive statements on the same table, view, sequence, index, routine,
+//! type or schema form one changeSet (triggers, policies, rules, comments
+//! and grants belong to the object they are defined on). Each changeSet gets
+//!
+//!   * a deterministic id, `pgc-{kind}-{name}-{hash}`, where the hash is
+//!     derived from the object's hash in the dump (or from the changeSet's
+//!     SQL for objects the dump does not hash), so regenerating the same
+//!     diff yields the same ids;
+//!   * `runInTransaction: false` for the statements of the post-commit
+//!     section (see [`crate::comparer::production`]);
+//!   * `preConditions` checking the object's existence: a create is marked
+//!     as ran when the object already exists, a drop when it is already gone,
+//!     and any other change halts when the object is missing;
+//!   * a rollback taken from the reverse script (the comparison of the same
+//!     dumps the other way round): a changeSet of the main section rolls
+//!     back with the reverse main-section statements on its object, and the
+//!     first one, which Liquibase rolls back last of them, also with those
+//!     on objects no changeSet touches. Without a reverse script (the
+//!     `script` command) the rollback is derived from the changeSet itself
+//!     when it can be — dropping a created object, or dropping added
+//!     columns.
+//!
+//! The reverse post-commit section must run after the reverse main section
+//! and outside a transaction, so it is the rollback of a leading no-op
+//! changeSet with `runInTransaction: false`, which Liquibase rolls back
+//! last; the post-commit changeSets then have nothing to undo themselves.
+//!
+//! The script's own `begin;` / `commit;` are removed, as Liquibase runs every
+//! changeSet in its own transaction. Leading `SET` statements of a section
+//! (lock and statement timeouts) are repeated in every changeSet of that
+//! section.
+
+use sha2::{Digest, Sha256};
+
+use crate::comparer::progress_ledger::unwrap_statement;
+use crate::comparer::scanner::{Relation, Statement, split_statements};
+use crate::config::migration_format::MigrationFormat;
+
+/// What a statement does to its object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Create,
+    Replace,
+    Alter,
+    Drop,
+}
+
+/// The object a statement changes. `schema` is set for qualified names (and
+/// for indexes, from the table they are created on).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Target {
+    action: Action,
+    kind: &'static str,
+    key: String,
+    display: String,
+    schema: Option<String>,
+    name: String,
+}
+
+/// An existence check of a changeSet's object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Check {
+    /// A built-in Liquibase precondition such as `tableExists`.
+    Native {
+        tag: &'static str,
+        name_attribute: &'static str,
+        schema: Option<String>,
+        name: String,
+    },
+    /// A `sqlCheck` query returning 1 when the object exists, 0 otherwise.
+    Sql(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Precondition {
+    on_fail: &'static str,
+    exists: bool,
+    check: Check,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChangeSet {
+    pub id: String,
+    pub run_in_transaction: bool,
+    pub precondition: Option<Precondition>,
+    pub statements: Vec<String>,
+    pub rollback: Vec<String>,
+    /// Whether `rollback` comes from the reverse script, where an empty one
+    /// means there is nothing to undo rather than an unknown rollback.
+    pub reversed: bool,
+}
+
+/// Object kinds written as two words.
+const COMPOUND_KINDS: [(&str, &str, &str); 2] = [
+    ("materialized", "view", "materialized view"),
+    ("foreign", "table", "foreign table"),
+];
+
+const KINDS: [&str; 10] = [
+    "table",
+    "view",
+    "sequence",
+    "index",
+    "function",
+    "procedure",
+    "type",
+    "domain",
+    "schema",
+    "extension",
+];
+
+/// Words that may stand between `CREATE [OR REPLACE]` and the object kind.
+const CREATE_MODIFIERS: [&str; 7] = [
+    "unique",
+    "temp",
+    "temporary",
+    "unlogged",
+    "global",
+    "local",
+    "recursive",
+];
+
+/// The kind named at `i` and the index of the token after it.
+fn kind_at(st: &Statement, i: usize) -> Option<(&'static str, usize)> {
+    for (first, second, kind) in COMPOUND_KINDS {
+        if st.is_word(i, first) && st.is_word(i + 1, second) {
+            return Some((kind, i + 2));
+        }
+    }
+    KINDS
+        .iter()
+        .find(|kind| st.is_word(i, kind))
+        .map(|kind| (*kind, i + 1))
+}
+
+fn target(action: Action, kind: &'static str, relation: Relation) -> Target {
+    let mut parts = relation.parts;
+    let name = parts.pop().unwrap_or_default();
+    Target {
+        action,
+        kind,
+        key: relation.key,
+        display: relation.display,
+        schema: parts.pop(),
+        name,
+    }
+}
+
+/// The table (or domain) a trigger, policy, rule or constraint is on.
+fn owner_at(st: &Statement, on: usize, action: Action) -> Option<Target> {
+    let (kind, i) = if st.is_word(on + 1, "domain") {
+        ("domain", on + 2)
+    } else {
+        ("table", on + 1)
+    };
+    let (relation, _) = st.name_at(st.skip(i, &["only"]))?;
+    Some(target(action, kind, relation))
+}
+
+/// The object `sql` changes, if it names one.
+fn statement_target(sql: &str) -> Option<Target> {
+    let st = Statement::parse(unwrap_statement(sql));
+    if st.is_word(0, "create") {
+        let replace = st.is_word(1, "or") && st.is_word(2, "replace");
+        let mut i = if replace { 3 } else { 1 };
+        while CREATE_MODIFIERS.iter().any(|word| st.is_word(i, word)) {
+            i += 1;
+        }
+        if st.is_word(i, "trigger")
+            || (st.is_word(i, "constraint") && st.is_word(i + 1, "trigger"))
+            || st.is_word(i, "policy")
+        {
+            return owner_at(&st, st.find_word(i, "on")?, Action::Alter);
+        }
+        if st.is_word(i, "rule") {
+            return owner_at(&st, st.find_word(i, "to")?, Action::Alter);
+        }
+        let (kind, next) = kind_at(&st, i)?;
+        let action = if replace {
+            Action::Replace
+        } else {
+            Action::Create
+        };
+        let next = st.skip(st.skip(next, &["concurrently"]), &["if", "not", "exists"]);
+        let (relation, after) = st.name_at(next)?;
+        let mut created = target(action, kind, relation);
+        if kind == "index" {
+            let on = st.find_word(after, "on")?;
+            let (table, _) = st.name_at(st.skip(on + 1, &["only"]))?;
+            created.schema = target(action, kind, table).schema;
+            if let Some(schema) = &created.schema {
+                created.key = format!("{schema}.{}", created.name);
+                created.display = format!("{}.{}", quote_ident(schema), created.display);
+            }
+        }
+        return Some(created);
+    }
+    if st.is_word(0, "alter") || st.is_word(0, "drop") {
+        let action = if st.is_word(0, "drop") {
+            Action::Drop
+        } else {
+            Action::Alter
+        };
+        if st.is_word(1, "trigger") || st.is_word(1, "policy") || st.is_word(1, "rule") {
+            return owner_at(&st, st.find_word(2, "on")?, Action::Alter);
+        }
+        let (kind, next) = kind_at(&st, 1)?;
+        let next = st.skip(
+            st.skip(st.skip(next, &["concurrently"]), &["if", "exists"]),
+            &["only"],
+        );
+        let (relation, _) = st.name_at(next)?;
+        return Some(target(action, kind, relation));
+    }
+    if st.is_word(0, "comment") && st.is_word(1, "on") {
+        if st.is_word(2, "column") {
+            let parts = st.name_parts(3);
+            let relation = st.relation(&parts[..parts.len().saturating_sub(1)])?;
+            return Some(target(Action::Alter, "table", relation));
+        }
+        if st.is_word(2, "constraint")
+            || st.is_word(2, "trigger")
+            || st.is_word(2, "policy")
+            || st.is_word(2, "rule")
+        {
+            return owner_at(&st, st.find_word(3, "on")?, Action::Alter);
+        }
+        let (kind, next) = kind_at(&st, 2)?;
+        let (relation, _) = st.name_at(next)?;
+        return Some(target(Action::Alter, kind, relation));
+    }
+    if st.is_word(0, "grant") || st.is_word(0, "revoke") {
+        let on = st.find_word(1, "on")?;
+        if st.is_word(on + 1, "all") {
+            return None;
+        }
+        let (kind, next) = kind_at(&st, on + 1).unwrap_or(("table", on + 1));
+        let (relation, _) = st.name_at(next)?;
+        return Some(target(Action::Alter, kind, relation));
+    }
+    if st.is_word(0, "refresh") && st.is_word(1, "materialized") && st.is_word(2, "view") {
+        let (relation, _) = st.name_at(st.skip(3, &["concurrently"]))?;
+        return Some(target(Action::Alter, "materialized view", relation));
+    }
+    None
+}
+
+fn is_set_statement(sql: &str) -> bool {
+    Statement::parse(sql).is_word(0, "set")
+}
+
+fn is_transaction_statement(sql: &str) -> bool {
+    let keyword = sql.trim_end_matches(';').trim();
+    keyword.eq_ignore_ascii_case("begin") || keyword.eq_ignore_ascii_case("commit")
+}
+
+fn quote_ident(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$');
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// The existence check for `target`, if Liquibase or the catalog offers one.
+fn existence_check(target: &Target) -> Option<Check> {
+    let native = |tag, name_attribute| Check::Native {
+        tag,
+        name_attribute,
+        schema: target.schema.clone(),
+        name: target.name.clone(),
+    };
+    let schema = quote_literal(target.schema.as_deref().unwrap_or("public"));
+    let name = quote_literal(&target.name);
+    let exists = |query: String| {
+        Check::Sql(format!(
+            "select case when exists ({query}) then 1 else 0 end"
+        ))
+    };
+    match target.kind {
+        "table" | "foreign table" => Some(native("tableExists", "tableName")),
+        "view" => Some(native("viewExists", "viewName")),
+        "sequence" => Some(native("sequenceExists", "sequenceName")),
+        "index" => Some(native("indexExists", "indexName")),
+        "materialized view" => Some(exists(format!(
+            "select 1 from pg_catalog.pg_matviews where schemaname = {schema} and matviewname = {name}"
+        ))),
+        "function" | "procedure" => Some(exists(format!(
+            "select 1 from pg_catalog.pg_proc p join pg_catalog.pg_namespace n on n.oid = p.pronamespace where n.nspname = {schema} and p.proname = {name}"
+        ))),
+        "type" | "domain" => Some(exists(format!(
+            "select 1 from pg_catalog.pg_type t join pg_catalog.pg_namespace n on n.oid = t.typnamespace where n.nspname = {schema} and t.typname = {name}"
+        ))),
+        "schema" => Some(exists(format!(
+            "select 1 from pg_catalog.pg_namespace where nspname = {name}"
+        ))),
+        "extension" => Some(exists(format!(
+            "select 1 from pg_catalog.pg_extension where extname = {name}"
+        ))),
+        _ => None,
+    }
+}
+
+fn precondition(target: &Target) -> Option<Precondition> {
+    let (on_fail, exists) = match target.action {
+        Action::Create => ("MARK_RAN", false),
+        Action::Drop => ("MARK_RAN", true),
+        Action::Alter => ("HALT", true),
+        Action::Replace => return None,
+    };
+    Some(Precondition {
+        on_fail,
+        exists,
+        check: existence_check(target)?,
+    })
+}
+
+/// The column an `ALTER TABLE … ADD [COLUMN] [IF NOT EXISTS] c …` with a
+/// single subcommand adds.
+fn added_column(sql: &str) -> Option<String> {
+    let st = Statement::parse(sql);
+    if !(st.is_word(0, "alter") && st.is_word(1, "table")) {
+        return None;
+    }
+    let i = st.skip(st.skip(2, &["if", "exists"]), &["only"]);
+    let (_, next) = st.name_at(i)?;
+    if !st.is_word(next, "add") || st.subcommands(next).len() != 1 {
+        return None;
+    }
+    let column = st.skip(next + 1, &["column"]);
+    let table_constraint = [
+        "constraint",
+        "primary",
+        "unique",
+        "check",
+        "foreign",
+        "exclude",
+    ];
+    if column == next + 1 && table_constraint.iter().any(|word| st.is_word(column, word)) {
+        return None;
+    }
+    let column = st.skip(column, &["if", "not", "exists"]);
+    st.is_name_part(column).then(|| st.text(column).to_string())
+}
+
+/// The statements undoing `statements`, whose object is `target`, when they
+/// can be derived: dropping the object they create, or the columns they add.
+fn rollback(target: &Target, statements: &[String]) -> Vec<String> {
+    let own: Vec<&str> = statements
+        .iter()
+        .map(String::as_str)
+        .filter(|sql| !is_set_statement(sql))
+        .collect();
+    if target.action == Action::Create {
+        return vec![format!("drop {} {};", target.kind, target.display)];
+    }
+    if target.kind == "table" && !own.is_empty() {
+        let columns: Option<Vec<String>> = own
+            .iter()
+            .map(|sql| added_column(unwrap_statement(sql)))
+            .collect();
+        if let Some(columns) = columns {
+            return columns
+                .iter()
+                .rev()
+                .map(|column| format!("alter table {} drop column {column};", target.display))
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+/// Groups the statements of one section into changeSets.
+fn section_change_sets(script: &str, run_in_transaction: bool) -> Vec<(Target, ChangeSet)> {
+    let mut preamble: Vec<String> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut sets: Vec<(Target, ChangeSet)> = Vec::new();
+    for (_, statement) in split_statements(script) {
+        let statement = statement.trim();
+        if statement.is_empty() || is_transaction_statement(statement) {
+            continue;
+        }
+        if is_set_statement(statement) {
+            if sets.is_empty() && pending.is_empty() {
+                preamble.push(statement.to_string());
+            } else {
+                pending.push(statement.to_string());
+            }
+            continue;
+        }
+        match statement_target(statement) {
+            Some(target) => {
+                let mut statements = std::mem::take(&mut pending);
+                statements.push(statement.to_string());
+                match sets.last_mut() {
+                    Some((last, set)) if last.kind == target.kind && last.key == target.key => {
+                        set.statements.extend(statements);
+                    }
+                    _ => sets.push((
+                        target,
+                        ChangeSet {
+                            id: String::new(),
+                            run_in_transaction,
+                            precondition: None,
+                            statements,
+                            rollback: Vec::new(),
+                            reversed: false,
+                        },
+                    )),
+                }
+            }
+            // Statements that name no object (DO blocks, setval, …) go with
+            // the changeSet before them.
+            None => match sets.last_mut() {
+                Some((_, set)) => {
+                    set.statements.append(&mut pending);
+                    set.statements.push(statement.to_string());
+                }
+                None => pending.push(statement.to_string()),
+            },
+        }
+    }
+    if let Some((_, set)) = sets.last_mut() {
+        set.statements.append(&mut pending);
+    }
+    for (target, set) in &mut sets {
+        set.precondition = precondition(target);
+        set.rollback = rollback(target, &set.statements);
+        let missing: Vec<String> = preamble
+            .iter()
+            .filter(|sql| !set.statements.contains(sql))
+            .cloned()
+            .collect();
+        set.statements.splice(0..0, missing);
+    }
+    sets
+}
+
+/// Replaces the rollbacks of `sets` with the statements of the reverse
+/// main section `reverse` (see the module documentation). Returns the
+/// statements no changeSet of the main section can take.
+fn reverse_rollbacks(sets: &mut [(Target, ChangeSet)], reverse: &str) -> Vec<String> {
+    for (_, set) in sets.iter_mut() {
+        set.rollback.clear();
+        set.reversed = true;
+    }
+    let main: Vec<usize> = (0..sets.len())
+        .filter(|&i| sets[i].1.run_in_transaction)
+        .collect();
+    let mut unmatched: Vec<String> = Vec::new();
+    for (target, reverse_set) in section_change_sets(reverse, true) {
+        let owner = main.iter().copied().find(|&i| {
+            let (own, _) = &sets[i];
+            own.kind == target.kind && own.key == target.key
+        });
+        let rollback = match owner {
+            Some(i) => &mut sets[i].1.rollback,
+            None => &mut unmatched,
+        };
+        add_rollback(rollback, reverse_set.statements);
+    }
+    match main.first() {
+        Some(&first) => {
+            add_rollback(&mut sets[first].1.rollback, unmatched);
+            Vec::new()
+        }
+        None => unmatched,
+    }
+}
+
+/// The leading changeSet whose rollback is `rollback`, the reverse
+/// post-commit section (see the module documentation).
+fn reverse_post_commit_change_set(rollback: Vec<String>) -> ChangeSet {
+    let digest = format!("{:x}", Sha256::digest(rollback.join("\n").as_bytes()));
+    ChangeSet {
+        id: format!("pgc-reverse-post-commit-{}", &digest[..12]),
+        run_in_transaction: false,
+        precondition: None,
+        statements: vec![
+            "-- Rolls back the post-commit statements after every other changeSet.\nselect 1;"
+                .to_string(),
+        ],
+        rollback,
+        reversed: true,
+    }
+}
+
+/// Appends `statements` to `rollback`, without repeating the `SET`
+/// statements every reverse changeSet starts with.
+fn add_rollback(rollback: &mut Vec<String>, statements: Vec<String>) {
+    for statement in statements {
+        if !(is_set_statement(&statement) && rollback.contains(&statement)) {
+            rollback.push(statement);
+        }
+    }
+}
+
+/// The changeSets for the two sections of a generated script, with the
+/// rollbacks from the two sections of the reverse script, if any.
+/// `object_hash` returns the dump hash of an object by kind and normalised
+/// name.
+pub(crate) fn change_sets(
+    main: &str,
+    post_commit: &str,
+    reverse: Option<(&str, &str)>,
+    object_hash: &dyn Fn(&str, &str) -> Option<String>,
+) -> Vec<ChangeSet> {
+    let mut sets = section_change_sets(main, true);
+    sets.extend(section_change_sets(post_commit, false));
+    let mut leading = None;
+    if let Some((reverse_main, reverse_post_commit)) = reverse {
+        let mut rollback = reverse_rollbacks(&mut sets, reverse_main);
+        for (_, reverse_set) in section_change_sets(reverse_post_commit, false) {
+            add_rollback(&mut rollback, reverse_set.statements);
+        }
+        if !rollback.is_empty() {
+            leading = Some(reverse_post_commit_change_set(rollback));
+        }
+    }
+    let mut ids: Vec<String> = Vec::new();
+    let sets = sets.into_iter().map(|(target, mut set)| {
+        let mut hasher = Sha256::new();
+        hasher.update(target.kind.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(target.key.as_bytes());
+        hasher.update(b"\0");
+        match object_hash(target.kind, &target.key) {
+            Some(hash) => hasher.update(hash.as_bytes()),
+            None => hasher.update(set.statements.join("\n").as_bytes()),
+        }
+        let digest = format!("{:x}", hasher.finalize());
+        let base = format!(
+            "pgc-{}-{}-{}",
+            target.kind.replace(' ', "-"),
+            target.key,
+            &digest[..12]
+        );
+        let mut id = base.clone();
+        let mut n = 1;
+        while ids.contains(&id) {
+            n += 1;
+            id = format!("{base}-{n}");
+        }
+        ids.push(id.clone());
+        set.id = id;
+        set
+    });
+    leading.into_iter().chain(sets).collect()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn cdata(value: &str) -> String {
+    format!("<![CDATA[{}]]>", value.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn yaml_block(value: &str, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let lines: Vec<String> = value
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{pad}{line}")
+            }
+        })
+        .collect();
+    format!("|-\n{}", lines.join("\n"))
+}
+
+fn render_xml(sets: &[ChangeSet]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <databaseChangeLog\n\
+         \x20       xmlns=\"http://www.liquibase.org/xml/ns/dbchangelog\"\n\
+         \x20       xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\"\n\
+         \x20       xsi:schemaLocation=\"http://www.liquibase.org/xml/ns/dbchangelog http://www.liquibase.org/xml/ns/dbchangelog/dbchangelog-latest.xsd\">\n",
+    );
+    for set in sets {
+        out.push_str(&format!(
+            "    <changeSet id=\"{}\" author=\"pgc\"{}>\n",
+            xml_escape(&set.id),
+            if set.run_in_transaction {
+                ""
+            } else {
+                " runInTransaction=\"false\""
+            }
+        ));
+        if let Some(precondition) = &set.precondition {
+            out.push_str(&format!(
+                "        <preConditions onFail=\"{}\">\n",
+                precondition.on_fail
+            ));
+            let (indent, check_indent) = if precondition.exists {
+                ("", "            ")
+            } else {
+                out.push_str("            <not>\n");
+                ("            ", "                ")
+            };
+            match &precondition.check {
+                Check::Native {
+                    tag,
+                    name_attribute,
+                    schema,
+                    name,
+                } => {
+                    let schema = schema
+                        .as_ref()
+                        .map(|s| format!(" schemaName=\"{}\"", xml_escape(s)))
+                        .unwrap_or_default();
+                    out.push_str(&format!(
+                        "{check_indent}<{tag}{schema} {name_attribute}=\"{}\"/>\n",
+                        xml_escape(name)
+                    ));
+                }
+                Check::Sql(query) => {
+                    out.push_str(&format!(
+                        "{check_indent}<sqlCheck expectedResult=\"1\">{}</sqlCheck>\n",
+                        cdata(query)
+                    ));
+                }
+            }
+            if !precondition.exists {
+                out.push_str(&format!("{indent}</not>\n"));
+            }
+            out.push_str("        </preConditions>\n");
+        }
+        for statement in &set.statements {
+            out.push_str(&format!(
+                "        <sql splitStatements=\"false\" stripComments=\"false\">{}</sql>\n",
+                cdata(statement)
+            ));
+        }
+        if set.rollback.is_empty() && set.reversed {
+            out.push_str("        <rollback/>\n");
+        } else if !set.rollback.is_empty() {
+            out.push_str("        <rollback>\n");
+            for statement in &set.rollback {
+                out.push_str(&format!(
+                    "            <sql splitStatements=\"false\">{}</sql>\n",
+                    cdata(statement)
+                ));
+            }
+            out.push_str("        </rollback>\n");
+        }
+        out.push_str("    </changeSet>\n");
+    }
+    out.push_str("</databaseChangeLog>\n");
+    out
+}
+
+fn render_yaml(sets: &[ChangeSet]) -> String {
+    let mut out = String::from("databaseChangeLog:\n");
+    for set in sets {
+        out.push_str("  - changeSet:\n");
+        out.push_str(&format!("      id: {}\n", yaml_string(&set.id)));
+        out.push_str("      author: pgc\n");
+        if !set.run_in_transaction {
+            out.push_str("      runInTransaction: false\n");
+        }
+        if let Some(precondition) = &set.precondition {
+            out.push_str("      preConditions:\n");
+            out.push_str(&format!("        - onFail: {}\n", precondition.on_fail));
+            let pad = if precondition.exists {
+                "        "
+            } else {
+                out.push_str("        - not:\n");
+                "            "
+            };
+            match &precondition.check {
+                Check::Native {
+                    tag,
+                    name_attribute,
+                    schema,
+                    name,
+                } => {
+                    out.push_str(&format!("{pad}- {tag}:\n"));
+                    if let Some(schema) = schema {
+                        out.push_str(&format!("{pad}    schemaName: {}\n", yaml_string(schema)));
+                    }
+                    out.push_str(&format!(
+                        "{pad}    {name_attribute}: {}\n",
+                        yaml_string(name)
+                    ));
+                }
+                Check::Sql(query) => {
+                    out.push_str(&format!("{pad}- sqlCheck:\n"));
+                    out.push_str(&format!("{pad}    expectedResult: 1\n"));
+                    out.push_str(&format!("{pad}    sql: {}\n", yaml_string(query)));
+                }
+            }
+        }
+        out.push_str("      changes:\n");
+        for statement in &set.statements {
+            out.push_str("        - sql:\n");
+            out.push_str("            splitStatements: false\n");
+            out.push_str("            stripComments: false\n");
+            out.push_str(&format!("            sql: {}\n", yaml_block(statement, 14)));
+        }
+        if set.rollback.is_empty() && set.reversed {
+            out.push_str("      rollback:\n        - empty: {}\n");
+        } else if !set.rollback.is_empty() {
+            out.push_str("      rollback:\n");
+            for statement in &set.rollback {
+                out.push_str("        - sql:\n");
+                out.push_str("            splitStatements: false\n");
+                out.push_str(&format!("            sql: {}\n", yaml_block(statement, 14)));
+            }
+        }
+    }
+    out
+}
+
+/// The changelog for `format`; `None` for the non-Liquibase formats.
+pub(crate) fn changelog(format: MigrationFormat, sets: &[ChangeSet]) -> Option<String> {
+    match format {
+        MigrationFormat::LiquibaseXml => Some(render_xml(sets)),
+        MigrationFormat::LiquibaseYaml => Some(render_yaml(sets)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[path = "liquibase_tests.rs"]
+mod tests;
@@ -0,0 +1,329 @@
+use super::*;
+
+fn no_hash(_: &str, _: &str) -> Option<String> {
+    None
+}
+
+fn statements(set: &ChangeSet) -> Vec<&str> {
+    set.statements.iter().map(String::as_str).collect()
+}
+
+#[test]
+fn statement_targets() {
+    let t = statement_target("create table public.orders (id int);").unwrap();
+    assert_eq!(
+        (t.action, t.kind, t.key.as_str()),
+        (Action::Create, "table", "public.orders")
+    );
+    assert_eq!(
+        (t.schema.as_deref(), t.name.as_str()),
+        (Some("public"), "orders")
+    );
+
+    let t = statement_target(
+        "create unique index concurrently if not exists orders_idx on only public.orders (id);",
+    )
+    .unwrap();
+    assert_eq!(
+        (t.kind, t.key.as_str(), t.display.as_str()),
+        ("index", "public.orders_idx", "public.orders_idx")
+    );
+
+    let t = statement_target("comment on column public.orders.total is 'x';").unwrap();
+    assert_eq!(
+        (t.action, t.kind, t.key.as_str()),
+        (Action::Alter, "table", "public.orders")
+    );
+
+    let t = statement_target(
+        "create trigger trg before insert on public.orders for each row execute function f();",
+    )
+    .unwrap();
+    assert_eq!((t.kind, t.key.as_str()), ("table", "public.orders"));
+
+    let t = statement_target("drop materialized view if exists public.mv;").unwrap();
+    assert_eq!(
+        (t.action, t.kind, t.key.as_str()),
+        (Action::Drop, "materialized view", "public.mv")
+    );
+
+    let t = statement_target(
+        "create or replace function public.f(a int) returns int as $$ select 1 $$ language sql;",
+    )
+    .unwrap();
+    assert_eq!(
+        (t.action, t.kind, t.key.as_str()),
+        (Action::Replace, "function", "public.f")
+    );
+
+    let t = statement_target("grant select on public.orders to reader;").unwrap();
+    assert_eq!((t.kind, t.key.as_str()), ("table", "public.orders"));
+
+    assert!(statement_target("select pg_catalog.setval('public.s', 1, true);").is_none());
+    assert!(statement_target("grant select on all tables in schema public to reader;").is_none());
+}
+
+#[test]
+fn one_change_set_per_object() {
+    let main = concat!(
+        "/* header */\nbegin;\n",
+        "create table public.a (id int);\n",
+        "comment on table public.a is 'a';\n",
+        "alter table public.b add column x int;\n",
+        "alter table public.b add column y text;\n",
+        "create or replace view public.v as select 1;\n",
+        "commit;\n",
+    );
+    let sets = change_sets(main, "", None, &no_hash);
+    assert_eq!(sets.len(), 3);
+    assert_eq!(
+        statements(&sets[0]),
+        vec![
+            "create table public.a (id int);",
+            "comment on table public.a is 'a';"
+        ]
+    );
+    assert!(sets.iter().all(|set| set.run_in_transaction));
+    assert_eq!(sets[0].rollback, vec!["drop table public.a;"]);
+    assert_eq!(
+        sets[1].rollback,
+        vec![
+            "alter table public.b drop column y;",
+            "alter table public.b drop column x;",
+        ]
+    );
+    assert!(sets[2].rollback.is_empty());
+    assert!(sets[2].precondition.is_none());
+}
+
+#[test]
+fn preconditions_check_object_existence() {
+    let sets = change_sets(
+        "create table public.a (id int);\nalter table public.b add column x int;\ndrop sequence public.s;\ncreate type public.mood as enum ('sad');\n",
+        "",
+        None,
+        &no_hash,
+    );
+    let native = |schema: &str, tag, name_attribute, name: &str| Check::Native {
+        tag,
+        name_attribute,
+        schema: Some(schema.to_string()),
+        name: name.to_string(),
+    };
+    assert_eq!(
+        sets[0].precondition,
+        Some(Precondition {
+            on_fail: "MARK_RAN",
+            exists: false,
+            check: native("public", "tableExists", "tableName", "a"),
+        })
+    );
+    assert_eq!(
+        sets[1].precondition,
+        Some(Precondition {
+            on_fail: "HALT",
+            exists: true,
+            check: native("public", "tableExists", "tableName", "b"),
+        })
+    );
+    assert_eq!(
+        sets[2].precondition,
+        Some(Precondition {
+            on_fail: "MARK_RAN",
+            exists: true,
+            check: native("public", "sequenceExists", "sequenceName", "s"),
+        })
+    );
+    let Some(Precondition {
+        check: Check::Sql(query),
+        ..
+    }) = &sets[3].precondition
+    else {
+        panic!("expected a sqlCheck");
+    };
+    assert!(query.contains("t.typname = 'mood'"));
+}
+
+#[test]
+fn rollbacks_come_from_the_reverse_script() {
+    let sets = change_sets(
+        "begin;\nset local lock_timeout = '5s';\nalter table public.t add column c int;\ncreate view public.v as select 1;\ncommit;\n",
+        "create index concurrently if not exists t_c on public.t (c);\n",
+        Some((
+            "begin;\nset local lock_timeout = '5s';\ndrop view public.v;\nalter table public.t drop column c;\ncreate sequence public.s;\ncommit;\n",
+            "drop index concurrently if exists public.t_c;\ncreate index concurrently if not exists s_i on public.u (s);\n",
+        )),
+        &no_hash,
+    );
+    assert_eq!(sets.len(), 4);
+    // The reverse post-commit section is rolled back last, outside a
+    // transaction.
+    assert!(sets[0].id.starts_with("pgc-reverse-post-commit-"));
+    assert!(!sets[0].run_in_transaction);
+    assert_eq!(
+        sets[0].rollback,
+        vec![
+            "drop index concurrently if exists public.t_c;",
+            "create index concurrently if not exists s_i on public.u (s);",
+        ]
+    );
+    assert_eq!(
+        sets[1].rollback,
+        vec![
+            "set local lock_timeout = '5s';",
+            "alter table public.t drop column c;",
+            "create sequence public.s;",
+        ]
+    );
+    assert_eq!(
+        sets[2].rollback,
+        vec!["set local lock_timeout = '5s';", "drop view public.v;"]
+    );
+    assert!(sets[3].rollback.is_empty() && sets[3].reversed);
+    let xml = changelog(MigrationFormat::LiquibaseXml, &sets).unwrap();
+    assert!(xml.contains("        <rollback/>\n    </changeSet>\n"));
+    let yaml = changelog(MigrationFormat::LiquibaseYaml, &sets).unwrap();
+    assert!(yaml.ends_with("      rollback:\n        - empty: {}\n"));
+
+    // Objects the reverse script leaves alone have nothing to roll back.
+    let sets = change_sets(
+        "create table public.a (id int);\n",
+        "",
+        Some(("", "")),
+        &no_hash,
+    );
+    assert_eq!(sets.len(), 1);
+    assert!(sets[0].rollback.is_empty());
+}
+
+#[test]
+fn post_commit_change_sets_run_outside_a_transaction() {
+    let sets = change_sets(
+        "begin;\nalter table public.t add column c int;\ncommit;\n",
+        "create index concurrently if not exists t_c on public.t (c);\n",
+        None,
+        &no_hash,
+    );
+    assert_eq!(sets.len(), 2);
+    assert!(sets[0].run_in_transaction);
+    assert!(!sets[1].run_in_transaction);
+    assert_eq!(sets[1].rollback, vec!["drop index public.t_c;"]);
+}
+
+#[test]
+fn leading_set_statements_are_repeated_in_every_change_set() {
+    let sets = change_sets(
+        "begin;\nset local lock_timeout = '5s';\nalter table public.a add column x int;\nalter table public.b add column y int;\ncommit;\n",
+        "",
+        None,
+        &no_hash,
+    );
+    assert_eq!(sets.len(), 2);
+    for set in &sets {
+        assert_eq!(set.statements[0], "set local lock_timeout = '5s';");
+        assert_eq!(set.statements.len(), 2);
+    }
+}
+
+#[test]
+fn untargeted_statements_join_the_previous_change_set() {
+    let sets = change_sets(
+        "create sequence public.s;\nselect pg_catalog.setval('public.s', 10, true);\n",
+        "",
+        None,
+        &no_hash,
+    );
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].statements.len(), 2);
+}
+
+#[test]
+fn ids_are_deterministic_and_unique() {
+    let main = "alter table public.a add column x int;\ncreate table public.b (id int);\nalter table public.a add column y int;\n";
+    let hash =
+        |kind: &str, key: &str| (kind == "table" && key == "public.a").then(|| "h1".to_string());
+    let first = change_sets(main, "", None, &hash);
+    let second = change_sets(main, "", None, &hash);
+    assert_eq!(first, second);
+    assert!(first[0].id.starts_with("pgc-table-public.a-"));
+    assert_eq!(first[2].id, format!("{}-2", first[0].id));
+    let other = change_sets(main, "", None, &|_: &str, _: &str| Some("h2".to_string()));
+    assert_ne!(first[0].id, other[0].id);
+}
+
+#[test]
+fn renders_xml_changelog() {
+    let sets = change_sets(
+        "create table public.a (id int);\n",
+        "create index concurrently a_id on public.a (id);\n",
+        None,
+        &no_hash,
+    );
+    let xml = changelog(MigrationFormat::LiquibaseXml, &sets).unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<databaseChangeLog\n"));
+    assert!(xml.contains(&format!(
+        "    <changeSet id=\"{}\" author=\"pgc\">\n",
+        sets[0].id
+    )));
+    assert!(xml.contains(concat!(
+        "        <preConditions onFail=\"MARK_RAN\">\n",
+        "            <not>\n",
+        "                <tableExists schemaName=\"public\" tableName=\"a\"/>\n",
+        "            </not>\n",
+        "        </preConditions>\n",
+        "        <sql splitStatements=\"false\" stripComments=\"false\"><![CDATA[create table public.a (id int);]]></sql>\n",
+        "        <rollback>\n",
+        "            <sql splitStatements=\"false\"><![CDATA[drop table public.a;]]></sql>\n",
+        "        </rollback>\n",
+    )));
+    assert!(xml.contains(&format!(
+        "    <changeSet id=\"{}\" author=\"pgc\" runInTransaction=\"false\">\n",
+        sets[1].id
+    )));
+    assert!(xml.ends_with("</databaseChangeLog>\n"));
+}
+
+#[test]
+fn renders_yaml_changelog() {
+    let sets = change_sets(
+        "alter table public.a add column x int;\n",
+        "",
+        None,
+        &no_hash,
+    );
+    let yaml = changelog(MigrationFormat::LiquibaseYaml, &sets).unwrap();
+    assert_eq!(
+        yaml,
+        format!(
+            concat!(
+                "databaseChangeLog:\n",
+                "  - changeSet:\n",
+                "      id: \"{}\"\n",
+                "      author: pgc\n",
+                "      preConditions:\n",
+                "        - onFail: HALT\n",
+                "        - tableExists:\n",
+                "            schemaName: \"public\"\n",
+                "            tableName: \"a\"\n",
+                "      changes:\n",
+                "        - sql:\n",
+                "            splitStatements: false\n",
+                "            stripComments: false\n",
+                "            sql: |-\n",
+                "              alter table public.a add column x int;\n",
+                "      rollback:\n",
+                "        - sql:\n",
+                "            splitStatements: false\n",
+                "            sql: |-\n",
+                "              alter table public.a drop column x;\n",
+            ),
+            sets[0].id
+        )
+    );
+}
+
+#[test]
+fn cdata_sections_cannot_be_closed_early() {
+    assert_eq!(cdata("a]]>b"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    assert!(changelog(MigrationFormat::Flyway, &[]).is_none());
+}
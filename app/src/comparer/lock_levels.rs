@@ -0,0 +1,431 @@
+//! Lock levels taken by the DDL in a generated script.
+//!
+//! Used by `lock_annotations` to put a `-- lock: …` comment in front of every
+//! statement that locks an existing relation, and by `group_by_table` to keep
+//! the statements for one table together. The modes follow the PostgreSQL
+//! documentation for each command, notably the per-subcommand lock levels of
+//! `ALTER TABLE`; anything not listed there is assumed to take
+//! `ACCESS EXCLUSIVE`. Statements that only create new objects, or that touch
+//! no relation (types, functions, grants, …), are not annotated.
+
+use std::fmt;
+
+use crate::comparer::progress_ledger::unwrap_statement;
+use crate::comparer::scanner::{Relation, Statement, split_statements};
+
+/// Table-level lock modes, weakest first. Only the modes DDL can take are
+/// listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LockMode {
+    ShareUpdateExclusive,
+    Share,
+    ShareRowExclusive,
+    Exclusive,
+    AccessExclusive,
+}
+
+impl fmt::Display for LockMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShareUpdateExclusive => write!(f, "SHARE UPDATE EXCLUSIVE"),
+            Self::Share => write!(f, "SHARE"),
+            Self::ShareRowExclusive => write!(f, "SHARE ROW EXCLUSIVE"),
+            Self::Exclusive => write!(f, "EXCLUSIVE"),
+            Self::AccessExclusive => write!(f, "ACCESS EXCLUSIVE"),
+        }
+    }
+}
+
+/// Relations and the strongest mode taken on each, in order of appearance.
+#[derive(Default)]
+struct Locks(Vec<(LockMode, Relation)>);
+
+impl Locks {
+    fn add(&mut self, mode: LockMode, relation: Relation) {
+        match self.0.iter_mut().find(|(_, r)| r.key == relation.key) {
+            Some(existing) => existing.0 = existing.0.max(mode),
+            None => self.0.push((mode, relation)),
+        }
+    }
+
+    fn add_at(&mut self, st: &Statement, i: usize, mode: LockMode) {
+        if let Some((relation, _)) = st.name_at(st.skip(i, &["only"])) {
+            self.add(mode, relation);
+        }
+    }
+}
+
+/// The locks `sql` takes on existing relations, in order of appearance.
+pub(crate) fn statement_locks(sql: &str) -> Vec<(LockMode, Relation)> {
+    let st = Statement::parse(sql);
+    let mut locks = Locks::default();
+    if st.is_word(0, "alter") {
+        alter_locks(&st, &mut locks);
+    } else if st.is_word(0, "create") {
+        create_locks(&st, &mut locks);
+    } else if st.is_word(0, "drop") {
+        drop_locks(&st, &mut locks);
+    } else if st.is_word(0, "comment") && st.is_word(1, "on") {
+        comment_locks(&st, &mut locks);
+    } else if st.is_word(0, "truncate") {
+        for relation in st.names_at(st.skip(1, &["table"])) {
+            locks.add(LockMode::AccessExclusive, relation);
+        }
+    } else if st.is_word(0, "refresh") && st.is_word(1, "materialized") && st.is_word(2, "view") {
+        let i = st.skip(3, &["concurrently"]);
+        let mode = if i > 3 {
+            LockMode::Exclusive
+        } else {
+            LockMode::AccessExclusive
+        };
+        locks.add_at(&st, i, mode);
+    }
+    locks.0
+}
+
+fn alter_locks(st: &Statement, locks: &mut Locks) {
+    if st.is_word(1, "table") {
+        let i = st.skip(st.skip(2, &["if", "exists"]), &["only"]);
+        if let Some((table, next)) = st.name_at(i) {
+            alter_table_locks(st, locks, table, next);
+        }
+    } else if st.is_word(1, "index") {
+        let i = st.skip(2, &["if", "exists"]);
+        let Some((index, next)) = st.name_at(i) else {
+            return;
+        };
+        if st.is_word(next, "attach") && st.is_word(next + 1, "partition") {
+            locks.add(LockMode::ShareUpdateExclusive, index);
+            locks.add_at(st, next + 2, LockMode::ShareUpdateExclusive);
+        } else if st.is_word(next, "rename")
+            || ((st.is_word(next, "set") || st.is_word(next, "reset"))
+                && st.is_punct(next + 1, "("))
+        {
+            locks.add(LockMode::ShareUpdateExclusive, index);
+        } else {
+            locks.add(LockMode::AccessExclusive, index);
+        }
+    } else if st.is_word(1, "sequence") {
+        locks.add_at(
+            st,
+            st.skip(2, &["if", "exists"]),
+            LockMode::ShareRowExclusive,
+        );
+    } else if st.is_word(1, "view") {
+        locks.add_at(st, st.skip(2, &["if", "exists"]), LockMode::AccessExclusive);
+    } else if (st.is_word(1, "materialized") && st.is_word(2, "view"))
+        || (st.is_word(1, "foreign") && st.is_word(2, "table"))
+    {
+        locks.add_at(st, st.skip(3, &["if", "exists"]), LockMode::AccessExclusive);
+    }
+}
+
+/// Words that start a table constraint in `ALTER TABLE ... ADD`; anything
+/// else adds a column.
+const TABLE_CONSTRAINT_WORDS: &[&str] = &[
+    "constraint",
+    "primary",
+    "unique",
+    "check",
+    "foreign",
+    "exclude",
+];
+
+/// The lock levels of the `ALTER TABLE` subcommands; the statement takes the
+/// strongest of them.
+fn alter_table_locks(st: &Statement, locks: &mut Locks, table: Relation, from: usize) {
+    let mut mode = None;
+    let mut others: Vec<(LockMode, usize)> = Vec::new();
+    for (start, end) in st.subcommands(from) {
+        let sub = if st.is_word(start, "add") {
+            // An inline `REFERENCES` on a new column still locks the owning
+            // table for the column; only a table constraint gets away with
+            // SHARE ROW EXCLUSIVE.
+            let column = !TABLE_CONSTRAINT_WORDS
+                .iter()
+                .any(|word| st.is_word(start + 1, word));
+            match (start..end).find(|&i| st.is_word(i, "references")) {
+                Some(r) => {
+                    others.push((LockMode::ShareRowExclusive, r + 1));
+                    if column {
+                        LockMode::AccessExclusive
+                    } else {
+                        LockMode::ShareRowExclusive
+                    }
+                }
+                None => LockMode::AccessExclusive,
+            }
+        } else if st.is_word(start, "validate")
+            || st.is_word(start, "reset")
+            || st.is_word(start, "cluster")
+        {
+            LockMode::ShareUpdateExclusive
+        } else if st.is_word(start, "set") {
+            if st.is_punct(start + 1, "(")
+                || (st.is_word(start + 1, "without") && st.is_word(start + 2, "cluster"))
+            {
+                LockMode::ShareUpdateExclusive
+            } else {
+                LockMode::AccessExclusive
+            }
+        } else if st.is_word(start, "attach") && st.is_word(start + 1, "partition") {
+            others.push((LockMode::AccessExclusive, start + 2));
+            LockMode::ShareUpdateExclusive
+        } else if st.is_word(start, "detach") && st.is_word(start + 1, "partition") {
+            // The concurrent form only spares the parent: its second
+            // transaction still takes ACCESS EXCLUSIVE on the partition.
+            let online =
+                (start..end).any(|i| st.is_word(i, "concurrently") || st.is_word(i, "finalize"));
+            others.push((LockMode::AccessExclusive, start + 2));
+            if online {
+                LockMode::ShareUpdateExclusive
+            } else {
+                LockMode::AccessExclusive
+            }
+        } else if st.is_word(start, "alter") {
+            let column = st.skip(start + 1, &["column"]);
+            let op = column + 1;
+            if (st.is_word(op, "set") && st.is_word(op + 1, "statistics"))
+                || ((st.is_word(op, "set") || st.is_word(op, "reset")) && st.is_punct(op + 1, "("))
+            {
+                LockMode::ShareUpdateExclusive
+            } else {
+                LockMode::AccessExclusive
+            }
+        } else if st.is_word(start, "enable") || st.is_word(start, "disable") {
+            let target = st.skip(st.skip(start + 1, &["replica"]), &["always"]);
+            if st.is_word(target, "trigger") {
+                LockMode::ShareRowExclusive
+            } else {
+                LockMode::AccessExclusive
+            }
+        } else if st.is_word(start, "inherit")
+            || (st.is_word(start, "no") && st.is_word(start + 1, "inherit"))
+        {
+            others.push((LockMode::ShareUpdateExclusive, st.skip(start, &["no"]) + 1));
+            LockMode::AccessExclusive
+        } else {
+            LockMode::AccessExclusive
+        };
+        mode = mode.max(Some(sub));
+    }
+    locks.add(mode.unwrap_or(LockMode::AccessExclusive), table);
+    for (mode, at) in others {
+        locks.add_at(st, at, mode);
+    }
+}
+
+fn create_locks(st: &Statement, locks: &mut Locks) {
+    let or_replace = st.is_word(1, "or") && st.is_word(2, "replace");
+    let i = if or_replace { 3 } else { 1 };
+    let index = st.skip(i, &["unique"]);
+    if st.is_word(index, "index") {
+        let concurrently = st.is_word(index + 1, "concurrently");
+        let mode = if concurrently {
+            LockMode::ShareUpdateExclusive
+        } else {
+            LockMode::Share
+        };
+        if let Some(on) = st.find_word(index + 1, "on") {
+            locks.add_at(st, on + 1, mode);
+        }
+    } else if st.is_word(i, "trigger")
+        || (st.is_word(i, "constraint") && st.is_word(i + 1, "trigger"))
+    {
+        if let Some(on) = st.find_word(i, "on") {
+            locks.add_at(st, on + 1, LockMode::ShareRowExclusive);
+        }
+    } else if st.is_word(i, "policy") {
+        if let Some(on) = st.find_word(i, "on") {
+            locks.add_at(st, on + 1, LockMode::AccessExclusive);
+        }
+    } else if st.is_word(i, "rule") {
+        if let Some(to) = st.find_word(i, "to") {
+            locks.add_at(st, to + 1, LockMode::AccessExclusive);
+        }
+    } else if st.is_word(i, "statistics") {
+        if let Some(from) = st.find_word(i, "from") {
+            locks.add_at(st, from + 1, LockMode::ShareUpdateExclusive);
+        }
+    } else if or_replace && (st.is_word(i, "view") || st.is_word(i, "materialized")) {
+        locks.add_at(
+            st,
+            st.skip(i, &["materialized"]) + 1,
+            LockMode::AccessExclusive,
+        );
+    } else if let Some(table) = (i..i + 4).find(|&t| st.is_word(t, "table")) {
+        if let Some(partition) = st.find_word(table, "partition")
+            && st.is_word(partition + 1, "of")
+        {
+            locks.add_at(st, partition + 2, LockMode::AccessExclusive);
+        }
+        for r in (table..st.tokens.len()).filter(|&r| st.is_word(r, "references")) {
+            locks.add_at(st, r + 1, LockMode::ShareRowExclusive);
+        }
+    }
+}
+
+fn drop_locks(st: &Statement, locks: &mut Locks) {
+    if st.is_word(1, "index") {
+        let concurrently = st.is_word(2, "concurrently");
+        let mode = if concurrently {
+            LockMode::ShareUpdateExclusive
+        } else {
+            LockMode::AccessExclusive
+        };
+        let i = st.skip(if concurrently { 3 } else { 2 }, &["if", "exists"]);
+        for relation in st.names_at(i) {
+            locks.add(mode, relation);
+        }
+    } else if st.is_word(1, "trigger") || st.is_word(1, "policy") || st.is_word(1, "rule") {
+        if let Some(on) = st.find_word(2, "on") {
+            locks.add_at(st, on + 1, LockMode::AccessExclusive);
+        }
+    } else {
+        let i = if st.is_word(1, "table") || st.is_word(1, "view") || st.is_word(1, "sequence") {
+            2
+        } else if (st.is_word(1, "materialized") && st.is_word(2, "view"))
+            || (st.is_word(1, "foreign") && st.is_word(2, "table"))
+        {
+            3
+        } else {
+            return;
+        };
+        for relation in st.names_at(st.skip(i, &["if", "exists"])) {
+            locks.add(LockMode::AccessExclusive, relation);
+        }
+    }
+}
+
+fn comment_locks(st: &Statement, locks: &mut Locks) {
+    let mode = LockMode::ShareUpdateExclusive;
+    let i = if st.is_word(2, "materialized") || st.is_word(2, "foreign") {
+        4
+    } else {
+        3
+    };
+    if st.is_word(2, "table")
+        || st.is_word(2, "index")
+        || st.is_word(2, "view")
+        || st.is_word(2, "sequence")
+        || st.is_word(2, "materialized")
+        || st.is_word(2, "foreign")
+    {
+        locks.add_at(st, i, mode);
+    } else if st.is_word(2, "column") {
+        let parts = st.name_parts(3);
+        if parts.len() > 1
+            && let Some(relation) = st.relation(&parts[..parts.len() - 1])
+        {
+            locks.add(mode, relation);
+        }
+    } else if (st.is_word(2, "constraint")
+        || st.is_word(2, "trigger")
+        || st.is_word(2, "policy")
+        || st.is_word(2, "rule"))
+        && let Some(on) = st.find_word(3, "on")
+        && !st.is_word(on + 1, "domain")
+    {
+        locks.add_at(st, on + 1, mode);
+    }
+}
+
+/// The `-- lock: …` comment for `locks`: relations grouped by mode,
+/// strongest first.
+fn annotation(locks: &[(LockMode, Relation)]) -> String {
+    let mut modes: Vec<LockMode> = locks.iter().map(|(mode, _)| *mode).collect();
+    modes.sort_unstable_by(|a, b| b.cmp(a));
+    modes.dedup();
+    let groups: Vec<String> = modes
+        .iter()
+        .map(|mode| {
+            let relations: Vec<&str> = locks
+                .iter()
+                .filter(|(m, _)| m == mode)
+                .map(|(_, r)| r.display.as_str())
+                .collect();
+            format!("{mode} on {}", relations.join(", "))
+        })
+        .collect();
+    format!("-- lock: {}\n", groups.join("; "))
+}
+
+/// Put a `-- lock: MODE on relation[, …]` comment before every statement of
+/// `script` that locks an existing relation. Statements wrapped in a lock
+/// retry or progress ledger block are annotated with the locks of the wrapped
+/// statement.
+pub(crate) fn annotate_locks(script: &str) -> String {
+    let mut out = String::with_capacity(script.len() + script.len() / 4);
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        let target = unwrap_statement(statement);
+        let locks = statement_locks(target);
+        if !locks.is_empty() {
+            if !trivia.is_empty() && !trivia.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&annotation(&locks));
+        }
+        out.push_str(statement);
+    }
+    out
+}
+
+/// The table a statement can be grouped under: an `ALTER TABLE`,
+/// `CREATE INDEX` or `COMMENT ON TABLE / COLUMN / CONSTRAINT` that locks
+/// exactly one relation and renames nothing.
+fn group_key(statement: &str) -> Option<String> {
+    let st = Statement::parse(statement);
+    let movable = (st.is_word(0, "alter") && st.is_word(1, "table"))
+        || (st.is_word(0, "create") && st.is_word(st.skip(1, &["unique"]), "index"))
+        || (st.is_word(0, "comment")
+            && st.is_word(1, "on")
+            && (st.is_word(2, "table") || st.is_word(2, "column") || st.is_word(2, "constraint")));
+    if !movable || (0..st.tokens.len()).any(|i| st.is_word(i, "rename")) {
+        return None;
+    }
+    match statement_locks(statement).as_slice() {
+        [(_, relation)] => Some(relation.key.clone()),
+        _ => None,
+    }
+}
+
+/// Reorder each run of consecutive single-table statements (see
+/// [`group_key`]) so the statements for one table are adjacent, tables in
+/// order of first appearance and statements in their original order. Any
+/// other statement ends a run and is never moved, so statements only trade
+/// places with statements on other tables that cannot depend on them.
+pub(crate) fn group_by_table(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    let mut run: Vec<(String, String)> = Vec::new();
+    let flush = |run: &mut Vec<(String, String)>, out: &mut String| {
+        let mut order: Vec<&str> = Vec::new();
+        for (key, _) in run.iter() {
+            if !order.contains(&key.as_str()) {
+                order.push(key);
+            }
+        }
+        for key in order {
+            for (_, text) in run.iter().filter(|(k, _)| k == key) {
+                out.push_str(text);
+            }
+        }
+        run.clear();
+    };
+    for (trivia, statement) in split_statements(script) {
+        match group_key(statement) {
+            Some(key) => run.push((key, format!("{trivia}{statement}"))),
+            None => {
+                flush(&mut run, &mut out);
+                out.push_str(trivia);
+                out.push_str(statement);
+            }
+        }
+    }
+    flush(&mut run, &mut out);
+    out
+}
+
+#[cfg(test)]
+#[path = "lock_levels_tests.rs"]
+mod tests;
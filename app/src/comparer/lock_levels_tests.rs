@@ -0,0 +1,251 @@
+use super::*;
+
+fn locks(sql: &str) -> Vec<(LockMode, String)> {
+    statement_locks(sql)
+        .into_iter()
+        .map(|(mode, relation)| (mode, relation.display))
+        .collect()
+}
+
+#[test]
+fn alter_table_takes_strongest_subcommand_lock() {
+    assert_eq!(
+        locks("alter table public.t add column c int;"),
+        vec![(LockMode::AccessExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("alter table public.t validate constraint c;"),
+        vec![(LockMode::ShareUpdateExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks(
+            "alter table only public.t alter column c set statistics 500, set (fillfactor = 70);"
+        ),
+        vec![(LockMode::ShareUpdateExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks(
+            "alter table public.t alter column c set statistics 500, alter column d drop not null;"
+        ),
+        vec![(LockMode::AccessExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("alter table public.t disable trigger trg;"),
+        vec![(LockMode::ShareRowExclusive, "public.t".to_string())]
+    );
+}
+
+#[test]
+fn foreign_key_locks_both_tables() {
+    assert_eq!(
+        locks(
+            "alter table public.orders add constraint orders_customer_fkey foreign key (customer_id) references public.customers (id) not valid;"
+        ),
+        vec![
+            (LockMode::ShareRowExclusive, "public.orders".to_string()),
+            (LockMode::ShareRowExclusive, "public.customers".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn add_column_with_references_locks_owner_exclusively() {
+    assert_eq!(
+        locks(
+            "alter table public.orders add column customer_id bigint references public.customers (id);"
+        ),
+        vec![
+            (LockMode::AccessExclusive, "public.orders".to_string()),
+            (LockMode::ShareRowExclusive, "public.customers".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("alter table public.orders add customer_id bigint references public.customers;"),
+        vec![
+            (LockMode::AccessExclusive, "public.orders".to_string()),
+            (LockMode::ShareRowExclusive, "public.customers".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn partition_attach_and_detach() {
+    assert_eq!(
+        locks("alter table public.p attach partition public.c for values in (1);"),
+        vec![
+            (LockMode::ShareUpdateExclusive, "public.p".to_string()),
+            (LockMode::AccessExclusive, "public.c".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("alter table public.p detach partition public.c concurrently;"),
+        vec![
+            (LockMode::ShareUpdateExclusive, "public.p".to_string()),
+            (LockMode::AccessExclusive, "public.c".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("alter table public.p detach partition public.c finalize;"),
+        vec![
+            (LockMode::ShareUpdateExclusive, "public.p".to_string()),
+            (LockMode::AccessExclusive, "public.c".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("alter table public.p detach partition public.c;"),
+        vec![
+            (LockMode::AccessExclusive, "public.p".to_string()),
+            (LockMode::AccessExclusive, "public.c".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn index_statements() {
+    assert_eq!(
+        locks("create unique index concurrently if not exists i on only public.t using btree (a);"),
+        vec![(LockMode::ShareUpdateExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("create index i on public.t (a);"),
+        vec![(LockMode::Share, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("drop index concurrently if exists public.i;"),
+        vec![(LockMode::ShareUpdateExclusive, "public.i".to_string())]
+    );
+    assert_eq!(
+        locks("drop index public.i, public.j;"),
+        vec![
+            (LockMode::AccessExclusive, "public.i".to_string()),
+            (LockMode::AccessExclusive, "public.j".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("alter index if exists public.i__pgc_new rename to i;"),
+        vec![(
+            LockMode::ShareUpdateExclusive,
+            "public.i__pgc_new".to_string()
+        )]
+    );
+}
+
+#[test]
+fn other_relation_statements() {
+    assert_eq!(
+        locks("drop table if exists public.a, \"Public\".\"B\";"),
+        vec![
+            (LockMode::AccessExclusive, "public.a".to_string()),
+            (LockMode::AccessExclusive, "\"Public\".\"B\"".to_string()),
+        ]
+    );
+    assert_eq!(
+        locks("create trigger trg before insert on public.t for each row execute function f();"),
+        vec![(LockMode::ShareRowExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("comment on column public.t.c is 'drop table x;';"),
+        vec![(LockMode::ShareUpdateExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("comment on constraint c on public.t is null;"),
+        vec![(LockMode::ShareUpdateExclusive, "public.t".to_string())]
+    );
+    assert_eq!(
+        locks("create table public.c partition of public.p for values in (1);"),
+        vec![(LockMode::AccessExclusive, "public.p".to_string())]
+    );
+    assert_eq!(
+        locks("alter sequence public.s restart with 10;"),
+        vec![(LockMode::ShareRowExclusive, "public.s".to_string())]
+    );
+}
+
+#[test]
+fn statements_without_existing_relations_are_not_locked() {
+    assert!(locks("create table public.t (id int primary key);").is_empty());
+    assert!(locks("create type public.mood as enum ('sad');").is_empty());
+    assert!(locks("comment on constraint c on domain public.d is 'x';").is_empty());
+    assert!(locks("grant select on table public.t to reader;").is_empty());
+    assert!(locks("begin;").is_empty());
+}
+
+#[test]
+fn quoted_names_are_normalised() {
+    let relations = statement_locks("alter table \"public\".Orders add column c int;");
+    assert_eq!(relations[0].1.key, "public.orders");
+    assert_eq!(relations[0].1.display, "\"public\".Orders");
+}
+
+#[test]
+fn annotate_locks_comments_each_locking_statement() {
+    let script = "begin;\n\n/* Table: public.t */\nalter table public.t add column c int;\ncreate table public.n (id int);\ncommit;\n";
+    assert_eq!(
+        annotate_locks(script),
+        "begin;\n\n/* Table: public.t */\n-- lock: ACCESS EXCLUSIVE on public.t\nalter table public.t add column c int;\ncreate table public.n (id int);\ncommit;\n"
+    );
+}
+
+#[test]
+fn annotation_groups_relations_by_mode() {
+    assert_eq!(
+        annotate_locks("alter table public.p attach partition public.c default;"),
+        "-- lock: ACCESS EXCLUSIVE on public.c; SHARE UPDATE EXCLUSIVE on public.p\nalter table public.p attach partition public.c default;"
+    );
+}
+
+#[test]
+fn annotate_locks_looks_inside_retry_blocks() {
+    let wrapped = crate::comparer::production::wrap_lock_retries(
+        "alter table public.t validate constraint c;",
+        2,
+    );
+    let annotated = annotate_locks(&wrapped);
+    assert!(annotated.starts_with("-- lock: SHARE UPDATE EXCLUSIVE on public.t\ndo $pgc_retry$"));
+}
+
+#[test]
+fn group_by_table_groups_runs_of_single_table_statements() {
+    let script = concat!(
+        "begin;\n",
+        "alter table public.a add column x int;\n",
+        "alter table public.b add column y int;\n",
+        "create index a_x on public.a (x);\n",
+        "comment on column public.b.y is 'y';\n",
+        "create table public.c (id int);\n",
+        "alter table public.b drop column z;\n",
+        "alter table public.a drop column w;\n",
+        "alter table public.b drop column v;\n",
+        "commit;",
+    );
+    assert_eq!(
+        group_by_table(script),
+        concat!(
+            "begin;\n",
+            "alter table public.a add column x int;\n",
+            "create index a_x on public.a (x);\n",
+            "alter table public.b add column y int;\n",
+            "comment on column public.b.y is 'y';\n",
+            "create table public.c (id int);\n",
+            "alter table public.b drop column z;\n",
+            "alter table public.b drop column v;\n",
+            "alter table public.a drop column w;\n",
+            "commit;",
+        )
+    );
+}
+
+#[test]
+fn group_by_table_keeps_multi_table_and_rename_statements_in_place() {
+    let script = concat!(
+        "alter table public.a add column x int;\n",
+        "alter table public.b add constraint fk foreign key (x) references public.a (x);\n",
+        "alter table public.a add column y int;\n",
+        "alter table public.b rename column q to r;\n",
+        "alter table public.a add column z int;\n",
+        "drop index public.i;\n",
+        "create index i on public.b (r);\n",
+        "alter table public.a drop column x;\n",
+    );
+    assert_eq!(group_by_table(script), script);
+}
@@ -0,0 +1,334 @@
+//! Versioned migration files for the `migration_format` option.
+//!
+//! Migration frameworks run each migration either inside a transaction or
+//! outside one, so the main section of the generated script and its
+//! post-commit section (`CONCURRENTLY`, `VALIDATE CONSTRAINT`, … — see
+//! [`crate::comparer::production`]) become two consecutive migrations:
+//!
+//!   * Flyway — `V{version}__{name}.sql` runs in Flyway's transaction, so the
+//!     script's own `begin;` / `commit;` are removed; the post-commit part is
+//!     `V{version}.1__{name}_post_commit.sql` with a `.sql.conf` setting
+//!     `executeInTransaction=false`;
+//!   * golang-migrate — `{version}_{name}.up.sql` / `.down.sql`, then one
+//!     `{version + i}_{name}_post_commit_{i}` migration per post-commit step
+//!     (see [`golang_migrate_steps`]);
+//!   * Sqitch — `deploy/`, `revert/` and `verify/` scripts for a
+//!     `{name}_{version}` change (deploy wrapped in `begin;` / `commit;` as
+//!     Sqitch expects) and a `{name}_{version}_post_commit` change that
+//!     requires it, plus the matching `sqitch.plan` entries.
+//!
+//! The down / revert scripts hold the reverse script — the comparison of the
+//! same dumps the other way round (see [`crate::comparer::core::Comparer::set_rollback`]).
+//! Reverse migrations run in the opposite order, so the post-commit
+//! migration's down script holds its main section and the first migration's
+//! its post-commit section, which keeps the reverse script's own order;
+//! without a post-commit migration the first one holds both. golang-migrate
+//! spreads the reverse script over the down files the same way, one step per
+//! file, adding versions that only undo when the reverse has more steps than
+//! the forward migration. Without a reverse script (the `script` command)
+//! they only carry a note. Liquibase changelogs are written by
+//! [`crate::comparer::liquibase`].
+
+use crate::comparer::progress_ledger::runs_outside_transaction;
+use crate::comparer::scanner::{Statement, split_statements};
+use crate::config::migration_format::MigrationFormat;
+
+/// A file of a migration set; `path` is relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationFile {
+    pub path: String,
+    pub contents: String,
+}
+
+const IRREVERSIBLE_NOTE: &str =
+    "-- No reverse migration was generated; write this step by hand if it must be undone.\n";
+
+const SQITCH_PLAN_HEADER: &str = "%syntax-version=1.0.0\n%project=pgc\n\n";
+
+/// `name` reduced to the characters every framework accepts in a file name.
+pub(crate) fn migration_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() {
+        "pgc_delta".to_string()
+    } else {
+        name
+    }
+}
+
+/// `script` without its top-level `begin;` / `commit;` statements.
+fn without_transaction(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        let keyword = statement.trim_end_matches(';').trim();
+        if !(keyword.eq_ignore_ascii_case("begin") || keyword.eq_ignore_ascii_case("commit")) {
+            out.push_str(statement);
+        }
+    }
+    out
+}
+
+/// The down / revert contents of the two migrations, in migration order,
+/// from the sections of the reverse script (see the module documentation);
+/// `transactional` renders its main section. The second is `None` when
+/// there is no post-commit migration.
+fn reverse_contents(
+    reverse: Option<(&str, &str)>,
+    has_post_commit: bool,
+    post_name: &str,
+    transactional: impl Fn(&str) -> String,
+) -> (String, Option<String>) {
+    let Some((main, post_commit)) = reverse else {
+        return (
+            IRREVERSIBLE_NOTE.to_string(),
+            has_post_commit.then(|| IRREVERSIBLE_NOTE.to_string()),
+        );
+    };
+    let main = transactional(main);
+    let post_commit = (!post_commit.trim().is_empty()).then_some(post_commit);
+    if !has_post_commit {
+        let mut contents = main;
+        if let Some(post_commit) = post_commit {
+            contents.push_str(&format!("\n-- Runs outside a transaction.\n{post_commit}"));
+        }
+        return (contents, None);
+    }
+    let first = match post_commit {
+        Some(post_commit) => format!("-- Runs outside a transaction.\n{post_commit}"),
+        None => format!("-- Nothing to undo here: {post_name} reverts the whole migration.\n"),
+    };
+    (first, Some(main))
+}
+
+/// `script` wrapped in a single `begin;` / `commit;`, as Sqitch expects.
+fn in_transaction(script: &str) -> String {
+    format!("begin;\n{}\ncommit;\n", without_transaction(script).trim())
+}
+
+/// The files for `format`. `main` and `post_commit` are the two sections of
+/// the generated script, `reverse` those of the reverse script; no
+/// post-commit migration is written when `post_commit` is empty. Returns
+/// nothing for [`MigrationFormat::Single`] and the Liquibase formats.
+pub fn migration_files(
+    format: MigrationFormat,
+    version: u64,
+    name: &str,
+    main: &str,
+    post_commit: &str,
+    reverse: Option<(&str, &str)>,
+) -> Vec<MigrationFile> {
+    let name = migration_name(name);
+    let has_post_commit = !post_commit.trim().is_empty();
+    let file = |path: String, contents: String| MigrationFile { path, contents };
+    let mut files = Vec::new();
+    match format {
+        MigrationFormat::Single
+        | MigrationFormat::LiquibaseXml
+        | MigrationFormat::LiquibaseYaml => {}
+        MigrationFormat::Flyway => {
+            files.push(file(
+                format!("V{version}__{name}.sql"),
+                without_transaction(main),
+            ));
+            if has_post_commit {
+                let post_name = format!("V{version}.1__{name}_post_commit.sql");
+                files.push(file(post_name.clone(), post_commit.to_string()));
+                files.push(file(
+                    format!("{post_name}.conf"),
+                    "executeInTransaction=false\n".to_string(),
+                ));
+            }
+        }
+        MigrationFormat::GolangMigrate => {
+            files.extend(golang_migrate_files(
+                version,
+                &name,
+                main,
+                post_commit,
+                reverse,
+            ));
+        }
+        MigrationFormat::Sqitch => {
+            let change = format!("{name}_{version}");
+            let post_change = format!("{change}_post_commit");
+            let (revert, post_revert) = reverse_contents(
+                reverse,
+                has_post_commit,
+                &format!("revert/{post_change}.sql"),
+                in_transaction,
+            );
+            let deploy = format!("-- Deploy {change} to pg\n\n{}", in_transaction(main));
+            files.extend(sqitch_change(&change, deploy, revert));
+            if let Some(post_revert) = post_revert {
+                let deploy = format!(
+                    "-- Deploy {post_change} to pg\n-- Runs outside a transaction.\n{post_commit}"
+                );
+                files.extend(sqitch_change(&post_change, deploy, post_revert));
+            }
+        }
+    }
+    files
+}
+
+/// `section` cut into the steps golang-migrate can run one file at a time.
+/// It sends a file as one query, which PostgreSQL runs as a single
+/// transaction (and `x-multi-statement` splits at every `;`, DO blocks
+/// included), so every statement becomes a step of its own, together with
+/// the `SET` / `RESET` statements before it, unless it refuses to run inside
+/// a transaction (`CONCURRENTLY`): those settings are then a step of their
+/// own.
+fn golang_migrate_steps(section: &str) -> Vec<String> {
+    let mut steps = Vec::new();
+    let mut pending = String::new();
+    let mut pending_settings = false;
+    for (trivia, statement) in split_statements(section) {
+        let st = Statement::parse(statement);
+        if st.tokens.is_empty() {
+            pending.push_str(trivia);
+            pending.push_str(statement);
+            continue;
+        }
+        if st.is_word(0, "set") || st.is_word(0, "reset") {
+            pending.push_str(trivia);
+            pending.push_str(statement);
+            pending_settings = true;
+            continue;
+        }
+        if pending_settings && runs_outside_transaction(statement) {
+            steps.push(std::mem::take(&mut pending));
+        }
+        pending.push_str(trivia);
+        pending.push_str(statement);
+        steps.push(std::mem::take(&mut pending));
+        pending_settings = false;
+    }
+    if pending_settings {
+        steps.push(pending);
+    } else if let Some(last) = steps.last_mut() {
+        last.push_str(&pending);
+    }
+    steps
+        .iter()
+        .map(|step| format!("{}\n", step.trim()))
+        .collect()
+}
+
+/// The golang-migrate files: the main section under `version`, then one
+/// migration per post-commit step under the versions after it. The down
+/// files undo in the opposite order, the last version's holding the reverse
+/// main section and the ones before it a reverse post-commit step each, so
+/// there are more versions than forward steps when the reverse has more
+/// steps; their up files only carry a note.
+fn golang_migrate_files(
+    version: u64,
+    name: &str,
+    main: &str,
+    post_commit: &str,
+    reverse: Option<(&str, &str)>,
+) -> Vec<MigrationFile> {
+    let steps = golang_migrate_steps(post_commit);
+    let reverse_steps = reverse.map(|(main, post_commit)| {
+        let mut steps = vec![main.to_string()];
+        steps.extend(golang_migrate_steps(post_commit));
+        steps
+    });
+    let count = 1 + steps
+        .len()
+        .max(reverse_steps.as_ref().map_or(1, Vec::len) - 1);
+    let base_name = |i: usize| {
+        if i == 0 {
+            format!("{version}_{name}")
+        } else {
+            format!("{}_{name}_post_commit_{i}", version + i as u64)
+        }
+    };
+    let mut files = Vec::new();
+    for i in 0..count {
+        let up = match i {
+            0 => main.to_string(),
+            _ => steps.get(i - 1).cloned().unwrap_or_else(|| {
+                "-- Nothing to apply here: this version only holds a step of the reverse migration.\n"
+                    .to_string()
+            }),
+        };
+        let down = match &reverse_steps {
+            None => IRREVERSIBLE_NOTE.to_string(),
+            Some(reverse_steps) => reverse_steps.get(count - 1 - i).cloned().unwrap_or_else(|| {
+                "-- Nothing to undo here: the down migrations of the later versions revert the whole migration.\n"
+                    .to_string()
+            }),
+        };
+        files.push(MigrationFile {
+            path: format!("{}.up.sql", base_name(i)),
+            contents: up,
+        });
+        files.push(MigrationFile {
+            path: format!("{}.down.sql", base_name(i)),
+            contents: down,
+        });
+    }
+    files
+}
+
+/// The first version of a golang-migrate set generated at `version` in a
+/// directory holding the files `existing`: past every version already there,
+/// since a set takes the versions after its first one too.
+pub fn golang_migrate_version(version: u64, existing: &[String]) -> u64 {
+    existing
+        .iter()
+        .filter(|file| file.ends_with(".up.sql") || file.ends_with(".down.sql"))
+        .filter_map(|file| file.split('_').next()?.parse::<u64>().ok())
+        .map(|taken| taken + 1)
+        .fold(version, u64::max)
+}
+
+fn sqitch_change(change: &str, deploy: String, revert: String) -> [MigrationFile; 3] {
+    [
+        MigrationFile {
+            path: format!("deploy/{change}.sql"),
+            contents: deploy,
+        },
+        MigrationFile {
+            path: format!("revert/{change}.sql"),
+            contents: format!("-- Revert {change} from pg\n\n{revert}"),
+        },
+        MigrationFile {
+            path: format!("verify/{change}.sql"),
+            contents: format!("-- Verify {change} on pg\n"),
+        },
+    ]
+}
+
+/// `sqitch.plan` with the changes written by [`migration_files`] appended;
+/// `existing` is the current plan, if there is one. `planned_at` is the
+/// plan timestamp (`YYYY-MM-DDTHH:MM:SSZ`).
+pub fn sqitch_plan(
+    existing: Option<&str>,
+    version: u64,
+    name: &str,
+    has_post_commit: bool,
+    planned_at: &str,
+) -> String {
+    let change = format!("{}_{version}", migration_name(name));
+    let mut plan = existing.unwrap_or(SQITCH_PLAN_HEADER).to_string();
+    if !plan.is_empty() && !plan.ends_with('\n') {
+        plan.push('\n');
+    }
+    plan.push_str(&format!(
+        "{change} {planned_at} pgc <pgc@localhost> # Generated by pgc\n"
+    ));
+    if has_post_commit {
+        plan.push_str(&format!(
+            "{change}_post_commit [{change}] {planned_at} pgc <pgc@localhost> # Post-commit steps of {change}\n"
+        ));
+    }
+    plan
+}
+
+#[cfg(test)]
+#[path = "migration_tests.rs"]
+mod tests;
@@ -0,0 +1,269 @@
+use super::*;
+
+const MAIN: &str = "/* header */\nbegin;\n\nalter table public.t add column c int;\n\ncommit;";
+const POST: &str = "create index concurrently if not exists i on public.t (c);\n";
+const REVERSE_MAIN: &str = "begin;\nalter table public.t drop column c;\ncommit;\n";
+const REVERSE_POST: &str = "drop index concurrently if exists public.j;\n";
+
+fn paths(files: &[MigrationFile]) -> Vec<&str> {
+    files.iter().map(|f| f.path.as_str()).collect()
+}
+
+#[test]
+fn single_format_writes_no_migration_files() {
+    assert!(migration_files(MigrationFormat::Single, 1, "x", MAIN, POST, None).is_empty());
+}
+
+#[test]
+fn flyway_splits_transactional_and_post_commit_migrations() {
+    let files = migration_files(
+        MigrationFormat::Flyway,
+        20261018120000,
+        "add c",
+        MAIN,
+        POST,
+        None,
+    );
+    assert_eq!(
+        paths(&files),
+        vec![
+            "V20261018120000__add_c.sql",
+            "V20261018120000.1__add_c_post_commit.sql",
+            "V20261018120000.1__add_c_post_commit.sql.conf",
+        ]
+    );
+    assert_eq!(
+        files[0].contents,
+        "/* header */\n\n\nalter table public.t add column c int;\n\n"
+    );
+    assert_eq!(files[1].contents, POST);
+    assert_eq!(files[2].contents, "executeInTransaction=false\n");
+}
+
+#[test]
+fn post_commit_migration_is_skipped_when_empty() {
+    let files = migration_files(MigrationFormat::Flyway, 7, "delta", MAIN, "\n", None);
+    assert_eq!(paths(&files), vec!["V7__delta.sql"]);
+}
+
+#[test]
+fn golang_migrate_writes_up_down_pairs() {
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        POST,
+        None,
+    );
+    assert_eq!(
+        paths(&files),
+        vec![
+            "100_delta.up.sql",
+            "100_delta.down.sql",
+            "101_delta_post_commit_1.up.sql",
+            "101_delta_post_commit_1.down.sql",
+        ]
+    );
+    assert_eq!(files[0].contents, MAIN);
+    assert_eq!(files[1].contents, IRREVERSIBLE_NOTE);
+    assert_eq!(files[2].contents, POST);
+    assert_eq!(files[3].contents, IRREVERSIBLE_NOTE);
+}
+
+#[test]
+fn golang_migrate_runs_one_post_commit_step_per_migration() {
+    let post = "/* Production post-commit */\nset lock_timeout = '5s';\n\
+                create index concurrently if not exists i on public.t (c);\n\
+                set lock_timeout = '5s';\n\
+                do $pgc_retry$ begin perform 1; end $pgc_retry$;\n\n";
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        post,
+        None,
+    );
+    let ups: Vec<(&str, &str)> = files
+        .iter()
+        .filter(|f| f.path.ends_with(".up.sql"))
+        .map(|f| (f.path.as_str(), f.contents.as_str()))
+        .collect();
+    // A setting cannot share the query of a CONCURRENTLY statement, but
+    // goes with any other statement.
+    assert_eq!(
+        ups,
+        vec![
+            ("100_delta.up.sql", MAIN),
+            (
+                "101_delta_post_commit_1.up.sql",
+                "/* Production post-commit */\nset lock_timeout = '5s';\n"
+            ),
+            (
+                "102_delta_post_commit_2.up.sql",
+                "create index concurrently if not exists i on public.t (c);\n"
+            ),
+            (
+                "103_delta_post_commit_3.up.sql",
+                "set lock_timeout = '5s';\ndo $pgc_retry$ begin perform 1; end $pgc_retry$;\n"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn golang_migrate_down_files_hold_the_reverse_script() {
+    let reverse = Some((REVERSE_MAIN, REVERSE_POST));
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        POST,
+        reverse,
+    );
+    // The post-commit migration is undone first, by the reverse main section.
+    assert_eq!(files[1].contents, REVERSE_POST);
+    assert_eq!(files[3].contents, REVERSE_MAIN);
+
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        POST,
+        Some((REVERSE_MAIN, "")),
+    );
+    assert_eq!(
+        files[1].contents,
+        "-- Nothing to undo here: the down migrations of the later versions revert the whole migration.\n"
+    );
+    assert_eq!(files[3].contents, REVERSE_MAIN);
+
+    // A reverse with more steps than the forward migration gets versions
+    // that only undo.
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        "",
+        reverse,
+    );
+    assert_eq!(
+        paths(&files),
+        vec![
+            "100_delta.up.sql",
+            "100_delta.down.sql",
+            "101_delta_post_commit_1.up.sql",
+            "101_delta_post_commit_1.down.sql",
+        ]
+    );
+    assert_eq!(files[1].contents, REVERSE_POST);
+    assert_eq!(
+        files[2].contents,
+        "-- Nothing to apply here: this version only holds a step of the reverse migration.\n"
+    );
+    assert_eq!(files[3].contents, REVERSE_MAIN);
+
+    let files = migration_files(
+        MigrationFormat::GolangMigrate,
+        100,
+        "delta",
+        MAIN,
+        "",
+        Some((REVERSE_MAIN, "")),
+    );
+    assert_eq!(
+        paths(&files),
+        vec!["100_delta.up.sql", "100_delta.down.sql"]
+    );
+    assert_eq!(files[1].contents, REVERSE_MAIN);
+}
+
+#[test]
+fn golang_migrate_version_skips_the_versions_already_taken() {
+    let existing = [
+        "20261018120000_a.up.sql".to_string(),
+        "20261018120000_a.down.sql".to_string(),
+        "20261018120001_a_post_commit_1.up.sql".to_string(),
+        "README.md".to_string(),
+    ];
+    assert_eq!(
+        golang_migrate_version(20261018120001, &existing),
+        20261018120002
+    );
+    assert_eq!(
+        golang_migrate_version(20261018130000, &existing),
+        20261018130000
+    );
+    assert_eq!(golang_migrate_version(5, &[]), 5);
+}
+
+#[test]
+fn sqitch_writes_deploy_revert_verify() {
+    let files = migration_files(MigrationFormat::Sqitch, 5, "delta", MAIN, POST, None);
+    assert_eq!(
+        paths(&files),
+        vec![
+            "deploy/delta_5.sql",
+            "revert/delta_5.sql",
+            "verify/delta_5.sql",
+            "deploy/delta_5_post_commit.sql",
+            "revert/delta_5_post_commit.sql",
+            "verify/delta_5_post_commit.sql",
+        ]
+    );
+    assert_eq!(
+        files[0].contents,
+        "-- Deploy delta_5 to pg\n\nbegin;\n/* header */\n\n\nalter table public.t add column c int;\ncommit;\n"
+    );
+    assert!(!files[3].contents.contains("begin;"));
+    assert!(files[3].contents.ends_with(POST));
+}
+
+#[test]
+fn sqitch_revert_scripts_hold_the_reverse_script() {
+    let files = migration_files(
+        MigrationFormat::Sqitch,
+        5,
+        "delta",
+        MAIN,
+        POST,
+        Some((REVERSE_MAIN, REVERSE_POST)),
+    );
+    assert_eq!(
+        files[1].contents,
+        format!("-- Revert delta_5 from pg\n\n-- Runs outside a transaction.\n{REVERSE_POST}")
+    );
+    assert_eq!(
+        files[4].contents,
+        "-- Revert delta_5_post_commit from pg\n\nbegin;\nalter table public.t drop column c;\ncommit;\n"
+    );
+    assert!(!files[1].contents.contains("No reverse migration"));
+}
+
+#[test]
+fn sqitch_plan_appends_changes() {
+    let plan = sqitch_plan(None, 5, "delta", true, "2026-10-18T12:00:00Z");
+    assert_eq!(
+        plan,
+        "%syntax-version=1.0.0\n%project=pgc\n\n\
+         delta_5 2026-10-18T12:00:00Z pgc <pgc@localhost> # Generated by pgc\n\
+         delta_5_post_commit [delta_5] 2026-10-18T12:00:00Z pgc <pgc@localhost> # Post-commit steps of delta_5\n"
+    );
+    let existing = "%project=app\n\nfirst 2026-01-01T00:00:00Z me <me@x> # first";
+    let plan = sqitch_plan(Some(existing), 6, "delta", false, "2026-10-18T12:00:00Z");
+    assert_eq!(
+        plan,
+        "%project=app\n\nfirst 2026-01-01T00:00:00Z me <me@x> # first\n\
+         delta_6 2026-10-18T12:00:00Z pgc <pgc@localhost> # Generated by pgc\n"
+    );
+}
+
+#[test]
+fn migration_name_is_sanitised() {
+    assert_eq!(migration_name(" add-orders.total "), "add_orders_total");
+    assert_eq!(migration_name(""), "pgc_delta");
+}
@@ -1,3 +1,10 @@
 pub mod core;
+mod creation_script;
+mod data_checks;
+mod fingerprint;
+mod liquibase;
+mod lock_levels;
+mod migration;
 pub mod production;
-mod scanner;
+pub(crate) mod progress_ledger;
+pub(crate) mod scanner;
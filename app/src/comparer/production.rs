@@ -12,16 +12,41 @@
 //!   * foreign keys are added `NOT VALID` and validated in a separate step
 //!     (`VALIDATE CONSTRAINT`) so the long validation scan does not hold a
 //!     table lock for the whole migration;
-//!   * indexes are dropped with `DROP INDEX CONCURRENTLY`.
+//!   * new CHECK constraints, on tables and on domains, get the same
+//!     `NOT VALID` + `VALIDATE CONSTRAINT` treatment;
+//!   * `SET NOT NULL` on an existing column is proven by a validated
+//!     `CHECK (col IS NOT NULL)` helper constraint instead of a full scan
+//!     under an ACCESS EXCLUSIVE lock;
+//!   * new `UNIQUE` / `PRIMARY KEY` constraints on existing tables are backed
+//!     by a `CREATE UNIQUE INDEX CONCURRENTLY` and then attached with
+//!     `ADD CONSTRAINT ... USING INDEX`, which only needs a brief lock;
+//!   * indexes are dropped with `DROP INDEX CONCURRENTLY`;
+//!   * partitions are detached with `DETACH PARTITION ... CONCURRENTLY`, and
+//!     an existing table is attached as a partition only after a validated
+//!     CHECK matching its bound, so `ATTACH PARTITION` skips the scan;
+//!   * an index (or unique / primary key constraint) whose definition changed
+//!     is replaced without a gap: the new index is built concurrently under a
+//!     temporary name and takes over once the old one is gone.
 //!
 //! `CONCURRENTLY`, `VALIDATE CONSTRAINT` and `ATTACH PARTITION` cannot run
 //! inside a transaction block, so every statement that must run after the main
 //! transaction commits is collected in the `post_commit` half of a
-//! [`ProdSplit`]. The comparer appends that buffer after `commit;`.
+//! [`ProdSplit`]. The comparer files those fragments into a
+//! [`PostCommitScript`] by [`PostCommitPhase`] and appends it after `commit;`.
+//!
+//! Configured [`LockSettings`] put `lock_timeout` / `statement_timeout` at the
+//! top of the script and in front of every post-commit statement, and can wrap
+//! each DDL statement in a DO block that retries it on `lock_not_available`.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::comparer::scanner::{copy_quoted_literal, dollar_tag_at};
+use crate::comparer::scanner::{
+    copy_quoted_literal, dollar_tag_at, split_statements, strip_comments_and_collapse,
+};
+use crate::config::lock_settings::LockSettings;
+use crate::dump::pg_type::{DomainConstraint, PgType};
+use crate::dump::table::Table;
+use crate::dump::table_column::TableColumn;
 use crate::dump::table_constraint::TableConstraint;
 use crate::dump::table_index::TableIndex;
 use crate::utils::string_extensions::StringExt;
@@ -35,12 +60,65 @@ pub struct ProdSplit {
     pub post_commit: String,
 }
 
+/// Ordering bucket of a post-commit statement. Phases are emitted in
+/// declaration order, so a statement can rely on everything filed under an
+/// earlier phase having run: `ADD CONSTRAINT ... USING INDEX` needs its index
+/// built, and a foreign key referencing that constraint needs it in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PostCommitPhase {
+    /// Concurrent index builds and drops, partition-index attaches.
+    Indexes,
+    /// `SET NOT NULL` proven through a validated helper check.
+    NotNull,
+    /// Constraints attached to concurrently built indexes, and foreign keys
+    /// that depend on them.
+    Constraints,
+    /// `VALIDATE CONSTRAINT` of constraints added `NOT VALID`.
+    Validate,
+    /// Concurrent partition detaches and pre-validated attaches.
+    Partitions,
+}
+
+/// The post-commit section of a production migration, kept per
+/// [`PostCommitPhase`] so that statements collected in any order across the
+/// comparison are rendered in dependency order. Within a phase statements keep
+/// the order they were pushed in.
+#[derive(Debug, Default)]
+pub struct PostCommitScript {
+    phases: BTreeMap<PostCommitPhase, String>,
+}
+
+impl PostCommitScript {
+    /// Buffer of `phase`, for helpers that append to a plain `&mut String`.
+    pub fn phase_mut(&mut self, phase: PostCommitPhase) -> &mut String {
+        self.phases.entry(phase).or_default()
+    }
+
+    /// Append a script fragment to `phase`.
+    pub fn push(&mut self, phase: PostCommitPhase, fragment: &str) {
+        self.phase_mut(phase).push_str(fragment);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phases.values().all(|s| s.trim().is_empty())
+    }
+
+    /// Concatenate every phase in order.
+    pub fn render(&self) -> String {
+        self.phases.values().map(String::as_str).collect()
+    }
+}
+
 /// Direct child of a partitioned parent table. Identifiers are stored in the
 /// same `quote_ident`-applied form as [`TableIndex::schema`] / [`TableIndex::table`].
 #[derive(Debug, Clone)]
 pub struct ChildRef {
     pub schema: String,
     pub table: String,
+    /// An existing table attached to the parent after commit in this same
+    /// migration. Its index is built beforehand but not attached: the
+    /// `ATTACH PARTITION` adopts it.
+    pub attached_later: bool,
 }
 
 /// Partition topology the index rewriters need. All qualified names use the
@@ -112,6 +190,10 @@ fn unquote_ident(s: &str) -> String {
     }
 }
 
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
 fn quote_ident(s: &str) -> String {
     let needs_quote = s.is_empty()
         || s.chars()
@@ -250,12 +332,18 @@ pub fn index_create_split(
 
     // 2. Build each partition's index concurrently, then attach it. Once every
     //    partition is attached the parent index becomes valid automatically.
+    //    A table that only becomes a partition later in the post-commit
+    //    section cannot have its index attached yet; its ATTACH PARTITION
+    //    adopts the matching index instead.
     for child in children {
         let child_idx = child_index_name(index, child);
         split.post_commit.append_block(&format!(
             "{create_kw} CONCURRENTLY {child_idx} ON {}.{} {tail};",
             child.schema, child.table
         ));
+        if child.attached_later {
+            continue;
+        }
         split.post_commit.append_block(&format!(
             "alter index {}.{} attach partition {}.{};",
             index.schema, index.name, child.schema, child_idx
@@ -286,47 +374,602 @@ pub fn index_drop_statement(index: &TableIndex, ctx: &PartitionContext) -> (Stri
     }
 }
 
+/// Split an `ADD CONSTRAINT` into a `NOT VALID` add (in the transaction, with
+/// the constraint comment, if any) and a post-commit `VALIDATE CONSTRAINT`.
+fn not_valid_split(constraint: &TableConstraint) -> ProdSplit {
+    let full = constraint.get_script();
+    // The comment block is appended after the ADD CONSTRAINT statement, so
+    // rendering without it isolates the statement even when a CHECK
+    // expression contains a ';' inside a literal.
+    let add_only = TableConstraint {
+        comment: None,
+        ..constraint.clone()
+    }
+    .get_script();
+    let remainder = full
+        .strip_prefix(add_only.as_str())
+        .unwrap_or_default()
+        .trim_start_matches('\n');
+    let add_stmt = add_only.trim_end();
+    let add_stmt = add_stmt.strip_suffix(';').unwrap_or(add_stmt).trim_end();
+
+    let mut in_txn = format!("{add_stmt} not valid;").with_empty_lines();
+    if !remainder.trim().is_empty() {
+        in_txn.push_str(remainder);
+    }
+    ProdSplit {
+        in_txn,
+        post_commit: format!(
+            "alter table {}.{} validate constraint {};",
+            constraint.schema, constraint.table_name, constraint.name
+        )
+        .with_empty_lines(),
+    }
+}
+
 /// Production rewrite of a foreign-key constraint creation: add it `NOT VALID`
 /// inside the transaction, then `VALIDATE CONSTRAINT` afterwards. Only enforced
 /// FOREIGN KEY constraints are split; anything else is emitted unchanged
 /// in-transaction (a `NOT ENFORCED` constraint is never validated, and other
-/// constraint kinds do not support `NOT VALID` in this path).
+/// constraint kinds go through their own rewrites).
 pub fn foreign_key_split(constraint: &TableConstraint) -> ProdSplit {
-    let mut split = ProdSplit::default();
-    let full = constraint.get_script();
-
     if !constraint
         .constraint_type
         .eq_ignore_ascii_case("foreign key")
         || !constraint.is_enforced
     {
-        split.in_txn.push_str(&full);
-        return split;
+        return ProdSplit {
+            in_txn: constraint.get_script(),
+            post_commit: String::new(),
+        };
+    }
+    not_valid_split(constraint)
+}
+
+/// Production rewrite of a new table CHECK constraint: the same `NOT VALID`
+/// add plus post-commit `VALIDATE CONSTRAINT` as [`foreign_key_split`], so the
+/// scan that proves existing rows runs under SHARE UPDATE EXCLUSIVE instead of
+/// ACCESS EXCLUSIVE. A `NOT ENFORCED` check is emitted unchanged.
+pub fn check_constraint_split(constraint: &TableConstraint) -> ProdSplit {
+    if !constraint.constraint_type.eq_ignore_ascii_case("check") || !constraint.is_enforced {
+        return ProdSplit {
+            in_txn: constraint.get_script(),
+            post_commit: String::new(),
+        };
     }
+    not_valid_split(constraint)
+}
 
-    // The ADD CONSTRAINT statement ends at the first ';' (a FOREIGN KEY clause
-    // never contains one); any trailing `comment on constraint` block follows.
-    match full.find(';') {
-        Some(semi) => {
-            let add_stmt = full[..semi].trim_end();
-            let remainder = full[semi + 1..].trim_start_matches('\n');
+/// Production rewrite of a new CHECK constraint on an existing domain:
+/// `ALTER DOMAIN ... ADD CONSTRAINT ... NOT VALID` in the transaction and
+/// `ALTER DOMAIN ... VALIDATE CONSTRAINT` after commit, which checks the
+/// columns using the domain without blocking writes to their tables.
+pub fn domain_check_split(domain: &PgType, constraint: &DomainConstraint) -> ProdSplit {
+    // Domain constraint names are always quoted by `PgType`'s alter script.
+    let name = format!("\"{}\"", constraint.name.replace('"', "\"\""));
+    let target = format!("{}.{}", domain.schema, domain.typname);
+    ProdSplit {
+        in_txn: format!(
+            "alter domain {target} add constraint {name} {} not valid;",
+            constraint.definition
+        )
+        .with_empty_lines(),
+        post_commit: format!("alter domain {target} validate constraint {name};")
+            .with_empty_lines(),
+    }
+}
 
-            let mut in_txn = add_stmt.to_string();
-            in_txn.push_str(" not valid;");
-            in_txn = in_txn.with_empty_lines();
-            if !remainder.trim().is_empty() {
-                in_txn.push_str(remainder);
+/// Name of the helper CHECK constraint used by [`not_null_split`], e.g.
+/// `orders_customer_id_not_null_check` for `orders.customer_id`.
+fn not_null_check_name(column: &TableColumn) -> String {
+    quote_ident(&format!(
+        "{}_{}_not_null_check",
+        unquote_ident(&column.table),
+        unquote_ident(&column.name)
+    ))
+}
+
+/// Production rewrite of `ALTER COLUMN ... SET NOT NULL` on an existing
+/// column. A plain `SET NOT NULL` scans the whole table under an ACCESS
+/// EXCLUSIVE lock; instead a `CHECK (col IS NOT NULL)` is added `NOT VALID`
+/// inside the transaction, validated after commit (SHARE UPDATE EXCLUSIVE, so
+/// reads and writes continue), and then `SET NOT NULL` — which PostgreSQL 12+
+/// proves from the validated check without a scan — replaces it.
+pub fn not_null_split(column: &TableColumn) -> ProdSplit {
+    let check = not_null_check_name(column);
+    let table = format!("{}.{}", column.schema, column.table);
+    let mut split = ProdSplit::default();
+    split.in_txn.append_block(&format!(
+        "alter table {table} add constraint {check} check ({} is not null) not valid;",
+        column.name
+    ));
+    split
+        .post_commit
+        .append_block(&format!("alter table {table} validate constraint {check};"));
+    split.post_commit.append_block(&format!(
+        "alter table {table} alter column {} set not null;",
+        column.name
+    ));
+    split
+        .post_commit
+        .append_block(&format!("alter table {table} drop constraint {check};"));
+    split
+}
+
+/// Post-commit halves of a production `UNIQUE` / `PRIMARY KEY` addition, see
+/// [`unique_constraint_split`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct UniqueConstraintSplit {
+    /// `CREATE UNIQUE INDEX CONCURRENTLY` named after the constraint.
+    pub index_build: String,
+    /// `ADD CONSTRAINT ... USING INDEX` (plus the constraint comment, if any).
+    pub add_constraint: String,
+}
+
+/// Split a leading parenthesised group off `s` (which must start with `(`),
+/// returning its contents and the text after the closing paren. Parentheses
+/// inside double-quoted identifiers are ignored.
+fn take_parens(s: &str) -> Option<(&str, &str)> {
+    if !s.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' if !in_quotes => depth += 1,
+            ')' if !in_quotes => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&s[1..i], s[i + 1..].trim_start()));
+                }
             }
-            split.in_txn = in_txn;
-            split.post_commit = format!(
-                "alter table {}.{} validate constraint {};",
-                constraint.schema, constraint.table_name, constraint.name
-            )
-            .with_empty_lines();
+            _ => {}
         }
-        None => split.in_txn.push_str(&full),
     }
-    split
+    None
+}
+
+/// `primary key` / `unique` for a constraint of either kind, `None` otherwise.
+fn unique_constraint_kind(constraint: &TableConstraint) -> Option<&'static str> {
+    if constraint
+        .constraint_type
+        .eq_ignore_ascii_case("primary key")
+    {
+        Some("primary key")
+    } else if constraint.constraint_type.eq_ignore_ascii_case("unique") {
+        Some("unique")
+    } else {
+        None
+    }
+}
+
+/// `CREATE UNIQUE INDEX CONCURRENTLY <index_name>` equivalent to the index a
+/// `UNIQUE` / `PRIMARY KEY` constraint builds, parsed from its definition.
+/// `None` when the definition is not a plain column list with the options
+/// `ADD CONSTRAINT ... USING INDEX` accepts (e.g. `WITHOUT OVERLAPS`).
+fn unique_index_build(constraint: &TableConstraint, index_name: &str) -> Option<String> {
+    let kind = unique_constraint_kind(constraint)?;
+    let definition = constraint.definition.as_deref()?.trim();
+    let upper = definition.to_ascii_uppercase();
+    let prefix = if kind == "unique" {
+        "UNIQUE"
+    } else {
+        "PRIMARY KEY"
+    };
+    if !upper.starts_with(prefix) {
+        return None;
+    }
+    let mut rest = definition[prefix.len()..].trim_start();
+    let mut nulls_not_distinct = false;
+    if rest.to_ascii_uppercase().starts_with("NULLS NOT DISTINCT") {
+        nulls_not_distinct = true;
+        rest = rest["NULLS NOT DISTINCT".len()..].trim_start();
+    }
+    let (columns, mut rest) = take_parens(rest)?;
+    if columns.to_ascii_uppercase().contains(" WITHOUT OVERLAPS") {
+        return None;
+    }
+
+    let mut include = None;
+    let mut with = None;
+    let mut tablespace = None;
+    while !rest.is_empty() {
+        let upper = rest.to_ascii_uppercase();
+        if upper.starts_with("INCLUDE") {
+            let (cols, tail) = take_parens(rest["INCLUDE".len()..].trim_start())?;
+            include = Some(cols);
+            rest = tail;
+        } else if upper.starts_with("WITH") {
+            let (params, tail) = take_parens(rest["WITH".len()..].trim_start())?;
+            with = Some(params);
+            rest = tail;
+        } else if upper.starts_with("USING INDEX TABLESPACE ") {
+            let tail = rest["USING INDEX TABLESPACE ".len()..].trim_start();
+            let end = tail.find(char::is_whitespace).unwrap_or(tail.len());
+            tablespace = Some(&tail[..end]);
+            rest = tail[end..].trim_start();
+        } else if let Some(kw) = [
+            "NOT DEFERRABLE",
+            "DEFERRABLE",
+            "INITIALLY DEFERRED",
+            "INITIALLY IMMEDIATE",
+        ]
+        .iter()
+        .find(|kw| upper.starts_with(*kw))
+        {
+            // Carried by the constraint flags, see `using_index_clause`.
+            rest = rest[kw.len()..].trim_start();
+        } else {
+            return None;
+        }
+    }
+
+    let mut index_build = format!(
+        "CREATE UNIQUE INDEX CONCURRENTLY {index_name} ON {}.{} USING btree ({columns})",
+        constraint.schema, constraint.table_name
+    );
+    if let Some(include) = include {
+        index_build.push_str(&format!(" INCLUDE ({include})"));
+    }
+    if nulls_not_distinct {
+        index_build.push_str(" NULLS NOT DISTINCT");
+    }
+    if let Some(with) = with {
+        index_build.push_str(&format!(" WITH ({with})"));
+    }
+    if let Some(tablespace) = tablespace {
+        index_build.push_str(&format!(" TABLESPACE {tablespace}"));
+    }
+    index_build.push(';');
+    Some(index_build.with_empty_lines())
+}
+
+/// `add constraint <name> {primary key|unique} using index <index_name>` with
+/// the constraint's deferrability, without the `alter table` prefix.
+fn using_index_clause(constraint: &TableConstraint, kind: &str, index_name: &str) -> String {
+    let mut clause = format!(
+        "add constraint {} {kind} using index {index_name}",
+        constraint.name
+    );
+    if constraint.is_deferrable {
+        clause.push_str(" deferrable");
+    }
+    if constraint.initially_deferred {
+        clause.push_str(" initially deferred");
+    }
+    clause
+}
+
+fn comment_on_constraint(constraint: &TableConstraint, comment: &str) -> String {
+    format!(
+        "comment on constraint {} on {}.{} is '{}';",
+        constraint.name,
+        constraint.schema,
+        constraint.table_name,
+        comment.replace('\'', "''")
+    )
+}
+
+/// Production rewrite of a new `UNIQUE` / `PRIMARY KEY` constraint on an
+/// existing table. Adding the constraint directly builds its index under an
+/// ACCESS EXCLUSIVE lock; instead the index is built with
+/// `CREATE UNIQUE INDEX CONCURRENTLY` under the constraint's name and then
+/// promoted with `ADD CONSTRAINT ... USING INDEX`, which only validates the
+/// catalog entry (a primary key additionally needs its columns `NOT NULL`).
+///
+/// Returns `None` when the constraint definition is not a plain column list
+/// this form can express (e.g. `WITHOUT OVERLAPS`), in which case the caller
+/// keeps the regular in-transaction `ADD CONSTRAINT`.
+pub fn unique_constraint_split(constraint: &TableConstraint) -> Option<UniqueConstraintSplit> {
+    let kind = unique_constraint_kind(constraint)?;
+    let index_build = unique_index_build(constraint, &constraint.name)?;
+    let mut add_constraint = format!(
+        "alter table {}.{} {};",
+        constraint.schema,
+        constraint.table_name,
+        using_index_clause(constraint, kind, &constraint.name)
+    )
+    .with_empty_lines();
+    if let Some(comment) = &constraint.comment {
+        add_constraint.append_block(&comment_on_constraint(constraint, comment));
+    }
+    Some(UniqueConstraintSplit {
+        index_build,
+        add_constraint,
+    })
+}
+
+/// Name the replacement of `name` is built under before it takes over the
+/// final name, e.g. `orders_total_idx__pgc_new`. The base is shortened so the
+/// result stays within PostgreSQL's 63-byte identifier limit.
+fn replacement_name(name: &str) -> String {
+    const SUFFIX: &str = "__pgc_new";
+    let mut base = unquote_ident(name);
+    while base.len() + SUFFIX.len() > 63 {
+        base.pop();
+    }
+    quote_ident(&format!("{base}{SUFFIX}"))
+}
+
+/// Production rewrite of an index whose definition changed. Dropping the old
+/// index and building the new one leaves the table without it in between, so
+/// instead — all after commit — the replacement is built concurrently under a
+/// temporary name, the old index is dropped concurrently, and the replacement
+/// is renamed to the final name.
+///
+/// Returns `None` for indexes of partitioned tables (no `CONCURRENTLY`) and
+/// unrecognised definitions; the caller then drops and re-creates as usual.
+pub fn index_replace_split(
+    old: &TableIndex,
+    new: &TableIndex,
+    ctx: &PartitionContext,
+) -> Option<ProdSplit> {
+    let parent_qualified = format!("{}.{}", new.schema, new.table);
+    if ctx.partitioned_parents.contains(&parent_qualified)
+        || ctx
+            .partitioned_indexes
+            .contains(&format!("{}.{}", old.schema, old.name))
+    {
+        return None;
+    }
+    let (create_kw, tail) = create_kw_and_tail(&new.indexdef)?;
+    let temp = replacement_name(&new.name);
+    let mut post_commit =
+        format!("{create_kw} CONCURRENTLY {temp} ON {parent_qualified} {tail};").with_empty_lines();
+    post_commit.append_block(&format!(
+        "drop index concurrently if exists {}.{};",
+        old.schema, old.name
+    ));
+    post_commit.append_block(&format!(
+        "alter index if exists {}.{temp} rename to {};",
+        new.schema, new.name
+    ));
+    if let Some(comment) = &new.comment {
+        post_commit.append_block(&comment_on_index(new, comment));
+    }
+    Some(ProdSplit {
+        in_txn: String::new(),
+        post_commit,
+    })
+}
+
+/// Production rewrite of a `UNIQUE` / `PRIMARY KEY` constraint whose
+/// definition changed. The new index is built concurrently under a temporary
+/// name, then a single `ALTER TABLE` drops the old constraint and attaches
+/// the new one with `ADD CONSTRAINT ... USING INDEX` (which renames the index
+/// to the constraint name), so the table is never without the key.
+///
+/// Returns `None` when either side is not a unique / primary key constraint
+/// or the new definition cannot be expressed as a plain unique index.
+pub fn unique_constraint_swap(
+    old: &TableConstraint,
+    new: &TableConstraint,
+) -> Option<UniqueConstraintSplit> {
+    unique_constraint_kind(old)?;
+    let kind = unique_constraint_kind(new)?;
+    let temp = replacement_name(&new.name);
+    let index_build = unique_index_build(new, &temp)?;
+    let mut add_constraint = format!(
+        "alter table {}.{} drop constraint {}, {};",
+        new.schema,
+        new.table_name,
+        old.name,
+        using_index_clause(new, kind, &temp)
+    )
+    .with_empty_lines();
+    if let Some(comment) = &new.comment {
+        add_constraint.append_block(&comment_on_constraint(new, comment));
+    }
+    Some(UniqueConstraintSplit {
+        index_build,
+        add_constraint,
+    })
+}
+
+/// Split `s` at top-level commas, ignoring commas inside quotes and parens.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '(' if !in_single && !in_double => depth += 1,
+            ')' if !in_single && !in_double => depth = depth.saturating_sub(1),
+            ',' if !in_single && !in_double && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts
+}
+
+/// CHECK expression equivalent to the partition constraint PostgreSQL derives
+/// from `bound` under the parent's `partition_key` (`pg_get_partkeydef` form,
+/// e.g. `RANGE (created_at)`). Only single-column RANGE and LIST keys on a
+/// plain column are supported; HASH, DEFAULT, multi-column and expression
+/// keys return `None`.
+pub fn partition_check_expression(partition_key: &str, bound: &str) -> Option<String> {
+    let key = partition_key.trim();
+    let upper_key = key.to_ascii_uppercase();
+    let strategy = ["RANGE", "LIST"]
+        .into_iter()
+        .find(|s| upper_key.starts_with(s))?;
+    let (column, rest) = take_parens(key[strategy.len()..].trim_start())?;
+    let column = column.trim();
+    if !rest.is_empty() || column.is_empty() || column.contains([',', '(', ' ']) {
+        return None;
+    }
+
+    let bound = bound.trim();
+    if !bound.to_ascii_uppercase().starts_with("FOR VALUES ") {
+        return None;
+    }
+    let values = bound["FOR VALUES ".len()..].trim_start();
+    let upper_values = values.to_ascii_uppercase();
+
+    if strategy == "RANGE" && upper_values.starts_with("FROM") {
+        let (lower, rest) = take_parens(values["FROM".len()..].trim_start())?;
+        if !rest.to_ascii_uppercase().starts_with("TO") {
+            return None;
+        }
+        let (upper, rest) = take_parens(rest["TO".len()..].trim_start())?;
+        if !rest.is_empty()
+            || split_top_level(lower).len() != 1
+            || split_top_level(upper).len() != 1
+        {
+            return None;
+        }
+        let mut terms = vec![format!("{column} IS NOT NULL")];
+        if !lower.trim().eq_ignore_ascii_case("MINVALUE") {
+            terms.push(format!("{column} >= {}", lower.trim()));
+        }
+        if !upper.trim().eq_ignore_ascii_case("MAXVALUE") {
+            terms.push(format!("{column} < {}", upper.trim()));
+        }
+        return Some(terms.join(" AND "));
+    }
+
+    if strategy == "LIST" && upper_values.starts_with("IN") {
+        let (list, rest) = take_parens(values["IN".len()..].trim_start())?;
+        if !rest.is_empty() {
+            return None;
+        }
+        let (nulls, items): (Vec<&str>, Vec<&str>) = split_top_level(list)
+            .into_iter()
+            .partition(|v| v.eq_ignore_ascii_case("NULL"));
+        return Some(match (nulls.is_empty(), items.is_empty()) {
+            (true, false) => format!(
+                "{column} IS NOT NULL AND {column} IN ({})",
+                items.join(", ")
+            ),
+            (false, false) => format!("{column} IS NULL OR {column} IN ({})", items.join(", ")),
+            (false, true) => format!("{column} IS NULL"),
+            (true, true) => return None,
+        });
+    }
+    None
+}
+
+const DETACH_GUARD_TAG: &str = "$pgc_detach$";
+
+/// `qualified` split at its first dot outside double quotes, each part
+/// kept as written.
+fn split_qualified(qualified: &str) -> Option<(&str, &str)> {
+    let mut in_quotes = false;
+    for (i, c) in qualified.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '.' if !in_quotes => return Some((&qualified[..i], &qualified[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Production form of detaching `partition` from `parent`:
+/// `DETACH PARTITION ... CONCURRENTLY` (PostgreSQL 14+), which only takes
+/// SHARE UPDATE EXCLUSIVE on the parent.
+///
+/// An interrupted concurrent detach leaves the partition pending
+/// (`pg_inherits.inhdetachpending`), and repeating the detach then fails;
+/// it must be completed with `FINALIZE`. The concurrent detach cannot run
+/// inside a DO block, so it names the parent unqualified with `IF EXISTS`,
+/// and the DO block ahead of it sets `search_path` to the parent's schema
+/// only while the partition is still attached. A pending detach is
+/// finalized there instead, and it and an already detached partition get an
+/// empty `search_path` that makes the concurrent detach a no-op. `RESET
+/// search_path` follows.
+pub fn partition_detach_concurrently(parent: &str, partition: &Table) -> String {
+    let table = format!("{}.{}", partition.schema, partition.name);
+    let mut script = String::new();
+    let Some((parent_schema, parent_name)) = split_qualified(parent) else {
+        script.append_block(&format!(
+            "alter table {parent} detach partition {table} concurrently;"
+        ));
+        return script;
+    };
+    let attached = format!(
+        "select 1 from pg_catalog.pg_inherits\n        \
+         where inhrelid = pg_catalog.to_regclass({})\n        \
+         and inhparent = pg_catalog.to_regclass({})",
+        quote_literal(&table),
+        quote_literal(parent)
+    );
+    script.append_block(&format!(
+        "do {DETACH_GUARD_TAG}\n\
+         begin\n    \
+         if exists (\n        \
+         {attached}\n        \
+         and not inhdetachpending\n    \
+         ) then\n        \
+         perform pg_catalog.set_config('search_path', {}, false);\n    \
+         else\n        \
+         if exists (\n            \
+         {}\n            \
+         and inhdetachpending\n        \
+         ) then\n            \
+         execute {};\n        \
+         end if;\n        \
+         perform pg_catalog.set_config('search_path', '', false);\n    \
+         end if;\n\
+         end\n{DETACH_GUARD_TAG};",
+        quote_literal(parent_schema),
+        attached.replace("\n        ", "\n            "),
+        quote_literal(&format!(
+            "alter table {parent} detach partition {table} finalize"
+        ))
+    ));
+    script.append_block(&format!(
+        "alter table if exists {parent_name} detach partition {table} concurrently;"
+    ));
+    script.append_block("reset search_path;");
+    script
+}
+
+/// Whether `statement` is the DO block [`partition_detach_concurrently`]
+/// puts ahead of the concurrent detach, which has to run on every attempt.
+pub fn is_detach_guard(statement: &str) -> bool {
+    statement.starts_with(&format!("do {DETACH_GUARD_TAG}"))
+}
+
+/// Production form of attaching the existing table `partition` to `parent`.
+/// `ATTACH PARTITION` scans the table to prove every row fits `bound` unless
+/// a valid CHECK constraint already implies it, so a matching CHECK is added
+/// `NOT VALID`, validated (SHARE UPDATE EXCLUSIVE, writes continue), used by
+/// the attach, and dropped again. Without a derivable expression (see
+/// [`partition_check_expression`]) the plain attach is returned.
+pub fn partition_attach_split(
+    partition: &Table,
+    parent: &str,
+    bound: &str,
+    parent_key: Option<&str>,
+) -> String {
+    let table = format!("{}.{}", partition.schema, partition.name);
+    let attach =
+        format!("alter table {parent} attach partition {table} {bound};").with_empty_lines();
+    let Some(expression) = parent_key.and_then(|key| partition_check_expression(key, bound)) else {
+        return attach;
+    };
+    let check = quote_ident(&format!(
+        "{}_partition_check",
+        unquote_ident(&partition.name)
+    ));
+    let mut script =
+        format!("alter table {table} add constraint {check} check ({expression}) not valid;")
+            .with_empty_lines();
+    script.append_block(&format!("alter table {table} validate constraint {check};"));
+    script.push_str(&attach);
+    script.append_block(&format!("alter table {table} drop constraint {check};"));
+    script
 }
 
 /// Case-insensitive ASCII check that `src[pos..]` begins with `pat`.
@@ -418,7 +1061,8 @@ const ALTER_GUARDS: &[AlterGuard] = &[
 
 /// Make a production migration script re-runnable by injecting idempotency
 /// guards into the DDL forms PostgreSQL supports them for. Applied once, at the
-/// end of [`Comparer::compare`], only when `output_for_production` is set.
+/// end of [`Comparer::compare`], when `output_for_production` or `idempotent`
+/// is set.
 ///
 /// The scan is literal-, comment- and dollar-quote-aware (mirroring
 /// [`crate::comparer::scanner::strip_comments_and_collapse`]) so a keyword that
@@ -614,6 +1258,123 @@ pub fn make_idempotent(script: &str) -> String {
     String::from_utf8(out).expect("output must be valid UTF-8")
 }
 
+/// `set lock_timeout` / `set statement_timeout` for the configured timeouts,
+/// one statement per line, or an empty string when neither is set. `local`
+/// scopes them to the enclosing transaction.
+pub fn lock_settings_statements(settings: &LockSettings, local: bool) -> String {
+    let scope = if local { "local " } else { "" };
+    let mut out = String::new();
+    for (name, value) in [
+        ("lock_timeout", &settings.lock_timeout),
+        ("statement_timeout", &settings.statement_timeout),
+    ] {
+        if let Some(value) = value {
+            out.push_str(&format!("set {scope}{name} = '{value}';\n"));
+        }
+    }
+    out
+}
+
+/// Prefix every statement of `script` (the rendered post-commit section) with
+/// the configured timeouts. Each post-commit statement runs in its own
+/// implicit transaction, so session-level `set` is used.
+pub fn with_lock_settings(script: &str, settings: &LockSettings) -> String {
+    let settings = lock_settings_statements(settings, false);
+    if settings.is_empty() {
+        return script.to_string();
+    }
+    let mut out = String::with_capacity(script.len());
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        if !statement.is_empty() {
+            out.push_str(&settings);
+            out.push_str(statement);
+        }
+    }
+    out
+}
+
+const RETRY_BLOCK_TAG: &str = "$pgc_retry$";
+const RETRY_STATEMENT_TAG: &str = "$pgc_ddl$";
+
+/// True for DDL that may queue behind a lock and can run inside a DO block.
+/// `CONCURRENTLY` statements and the commands PostgreSQL refuses inside a
+/// transaction block (or that take no table lock) are left alone.
+fn is_retryable_ddl(statement: &str) -> bool {
+    if statement.contains(RETRY_BLOCK_TAG) || statement.contains(RETRY_STATEMENT_TAG) {
+        return false;
+    }
+    let code = strip_comments_and_collapse(statement).to_ascii_lowercase();
+    let words: Vec<&str> = code
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let Some(first) = words.first() else {
+        return false;
+    };
+    if !matches!(
+        *first,
+        "alter" | "create" | "drop" | "comment" | "grant" | "revoke"
+    ) {
+        return false;
+    }
+    if words
+        .get(1)
+        .is_some_and(|w| matches!(*w, "database" | "tablespace" | "system" | "subscription"))
+    {
+        return false;
+    }
+    !words.contains(&"concurrently")
+}
+
+/// Wrap every retryable DDL statement of `script` in a DO block that retries
+/// it up to `retries` more times when it fails with `lock_not_available`
+/// (i.e. hits `lock_timeout`), sleeping with exponential backoff — 0.2s,
+/// 0.4s, … capped at 5s — between attempts. Each attempt runs in its own
+/// subtransaction, so a timed-out attempt leaves nothing behind.
+///
+/// Applied after [`make_idempotent`] so the guards it injects are inside the
+/// wrapped statements.
+pub fn wrap_lock_retries(script: &str, retries: u32) -> String {
+    if retries == 0 {
+        return script.to_string();
+    }
+    let mut out = String::with_capacity(script.len() * 2);
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        if !is_retryable_ddl(statement) {
+            out.push_str(statement);
+            continue;
+        }
+        let body = statement.trim_end().trim_end_matches(';');
+        out.push_str(&format!(
+            "do {RETRY_BLOCK_TAG}\n\
+             declare\n    attempt int := 0;\n\
+             begin\n    loop\n        begin\n\
+             \x20           execute {RETRY_STATEMENT_TAG}{body}{RETRY_STATEMENT_TAG};\n\
+             \x20           exit;\n\
+             \x20       exception when lock_not_available then\n\
+             \x20           attempt := attempt + 1;\n\
+             \x20           if attempt > {retries} then\n\
+             \x20               raise;\n\
+             \x20           end if;\n\
+             \x20           perform pg_sleep(least(0.1 * power(2, attempt), 5));\n\
+             \x20       end;\n    end loop;\nend\n{RETRY_BLOCK_TAG};"
+        ));
+    }
+    out
+}
+
+/// The statement inside a DO block emitted by [`wrap_lock_retries`], or
+/// `None` when `statement` is not one.
+pub fn retry_wrapped_statement(statement: &str) -> Option<&str> {
+    let rest = statement.strip_prefix(&format!("do {RETRY_BLOCK_TAG}"))?;
+    let open = format!("execute {RETRY_STATEMENT_TAG}");
+    let start = rest.find(&open)? + open.len();
+    let len = rest[start..].find(RETRY_STATEMENT_TAG)?;
+    Some(&rest[start..start + len])
+}
+
 #[cfg(test)]
 #[path = "production_tests.rs"]
 mod tests;
@@ -30,6 +30,63 @@ fn fk(schema: &str, table: &str, name: &str, definition: &str) -> TableConstrain
     }
 }
 
+fn column(schema: &str, table: &str, name: &str) -> TableColumn {
+    TableColumn {
+        catalog: "postgres".to_string(),
+        schema: schema.to_string(),
+        table: table.to_string(),
+        name: name.to_string(),
+        ordinal_position: 1,
+        column_default: None,
+        is_nullable: false,
+        data_type: "integer".to_string(),
+        character_maximum_length: None,
+        character_octet_length: None,
+        numeric_precision: None,
+        numeric_precision_radix: None,
+        numeric_scale: None,
+        datetime_precision: None,
+        interval_type: None,
+        interval_precision: None,
+        character_set_catalog: None,
+        character_set_schema: None,
+        character_set_name: None,
+        collation_catalog: None,
+        collation_schema: None,
+        collation_name: None,
+        domain_catalog: None,
+        domain_schema: None,
+        domain_name: None,
+        udt_catalog: None,
+        udt_schema: None,
+        udt_name: None,
+        scope_catalog: None,
+        scope_schema: None,
+        scope_name: None,
+        maximum_cardinality: None,
+        dtd_identifier: None,
+        is_self_referencing: false,
+        is_identity: false,
+        identity_generation: None,
+        identity_start: None,
+        identity_increment: None,
+        identity_maximum: None,
+        identity_minimum: None,
+        identity_cycle: false,
+        is_generated: "NEVER".to_string(),
+        generation_expression: None,
+        generation_type: None,
+        is_updatable: true,
+        related_views: None,
+        comment: None,
+        storage: None,
+        compression: None,
+        statistics_target: None,
+        acl: vec![],
+        serial_type: None,
+    }
+}
+
 fn empty_ctx() -> (
     HashSet<String>,
     HashMap<String, Vec<ChildRef>>,
@@ -122,10 +179,12 @@ fn partitioned_parent_expands_to_only_plus_attach() {
             ChildRef {
                 schema: "public".to_string(),
                 table: "orders_2023".to_string(),
+                attached_later: false,
             },
             ChildRef {
                 schema: "public".to_string(),
                 table: "orders_2024".to_string(),
+                attached_later: false,
             },
         ],
     );
@@ -181,6 +240,7 @@ fn new_partitioned_parent_emits_plain_in_txn_no_attach() {
         vec![ChildRef {
             schema: "public".to_string(),
             table: "orders_2024".to_string(),
+            attached_later: false,
         }],
     );
     let part_idx = HashSet::new();
@@ -228,6 +288,7 @@ fn partitioned_parent_indexdef_already_on_only_is_not_doubled() {
         vec![ChildRef {
             schema: "data".to_string(),
             table: "tagged_items_p0".to_string(),
+            attached_later: false,
         }],
     );
     let part_idx = HashSet::new();
@@ -316,6 +377,84 @@ fn drop_partitioned_index_stays_in_txn_non_concurrent() {
     assert_eq!(stmt, "drop index if exists public.idx_total;");
 }
 
+#[test]
+fn changed_index_is_replaced_under_temporary_name() {
+    let (parents, children, part_idx) = empty_ctx();
+    let ctx = PartitionContext {
+        partitioned_parents: &parents,
+        children: &children,
+        partitioned_indexes: &part_idx,
+    };
+    let old = index(
+        "public",
+        "orders",
+        "idx_orders_total",
+        "CREATE INDEX idx_orders_total ON public.orders USING btree (total)",
+    );
+    let mut new = index(
+        "public",
+        "orders",
+        "idx_orders_total",
+        "CREATE INDEX idx_orders_total ON public.orders USING btree (total, created_at)",
+    );
+    new.comment = Some("totals".to_string());
+    let split = index_replace_split(&old, &new, &ctx).expect("replace");
+    assert!(split.in_txn.is_empty());
+    let build = split
+        .post_commit
+        .find("CREATE INDEX CONCURRENTLY idx_orders_total__pgc_new ON public.orders USING btree (total, created_at);")
+        .expect("replacement built");
+    let drop = split
+        .post_commit
+        .find("drop index concurrently if exists public.idx_orders_total;")
+        .expect("old dropped");
+    let rename = split
+        .post_commit
+        .find("alter index if exists public.idx_orders_total__pgc_new rename to idx_orders_total;")
+        .expect("replacement renamed");
+    let comment = split
+        .post_commit
+        .find("comment on index public.idx_orders_total is 'totals';")
+        .expect("comment");
+    assert!(build < drop && drop < rename && rename < comment);
+}
+
+#[test]
+fn partitioned_index_is_not_replaced_concurrently() {
+    let (mut parents, children, part_idx) = empty_ctx();
+    parents.insert("public.events".to_string());
+    let ctx = PartitionContext {
+        partitioned_parents: &parents,
+        children: &children,
+        partitioned_indexes: &part_idx,
+    };
+    let old = index(
+        "public",
+        "events",
+        "events_at_idx",
+        "CREATE INDEX events_at_idx ON ONLY public.events USING btree (at)",
+    );
+    let new = index(
+        "public",
+        "events",
+        "events_at_idx",
+        "CREATE INDEX events_at_idx ON ONLY public.events USING brin (at)",
+    );
+    assert_eq!(index_replace_split(&old, &new, &ctx), None);
+}
+
+#[test]
+fn replacement_name_stays_within_identifier_limit() {
+    let long = "a".repeat(63);
+    let name = replacement_name(&long);
+    assert_eq!(name.len(), 63);
+    assert!(name.ends_with("__pgc_new"));
+    assert_eq!(
+        replacement_name("\"Orders_Idx\""),
+        "\"Orders_Idx__pgc_new\""
+    );
+}
+
 #[test]
 fn foreign_key_split_into_not_valid_and_validate() {
     let c = fk(
@@ -377,6 +516,352 @@ fn fk_comment_preserved_in_txn() {
     );
 }
 
+#[test]
+fn set_not_null_split_into_check_validate_and_set() {
+    let column = column("public", "orders", "customer_id");
+    let split = not_null_split(&column);
+    assert_eq!(
+        split.in_txn.trim_end(),
+        "alter table public.orders add constraint orders_customer_id_not_null_check check (customer_id is not null) not valid;"
+    );
+    let validate = split
+        .post_commit
+        .find("alter table public.orders validate constraint orders_customer_id_not_null_check;")
+        .expect("validate");
+    let set = split
+        .post_commit
+        .find("alter table public.orders alter column customer_id set not null;")
+        .expect("set not null");
+    let drop = split
+        .post_commit
+        .find("alter table public.orders drop constraint orders_customer_id_not_null_check;")
+        .expect("drop helper");
+    assert!(validate < set && set < drop, "{}", split.post_commit);
+}
+
+#[test]
+fn set_not_null_split_quotes_helper_name() {
+    let column = column("public", "\"Orders\"", "\"CustomerId\"");
+    let split = not_null_split(&column);
+    assert!(
+        split.in_txn.contains(
+            "alter table public.\"Orders\" add constraint \"Orders_CustomerId_not_null_check\" check (\"CustomerId\" is not null) not valid;"
+        ),
+        "{}",
+        split.in_txn
+    );
+}
+
+#[test]
+fn check_split_into_not_valid_and_validate() {
+    let mut c = fk(
+        "public",
+        "orders",
+        "orders_note_check",
+        "CHECK ((note <> 'a;b'::text))",
+    );
+    c.constraint_type = "CHECK".to_string();
+    c.comment = Some("no separators".to_string());
+    let split = check_constraint_split(&c);
+    assert!(
+        split.in_txn.starts_with(
+            "alter table public.orders add constraint orders_note_check check ((note <> 'a;b'::text)) not valid;"
+        ),
+        "in_txn: {}",
+        split.in_txn
+    );
+    assert!(
+        split.in_txn.contains(
+            "comment on constraint orders_note_check on public.orders is 'no separators';"
+        )
+    );
+    assert_eq!(
+        split.post_commit.trim_end(),
+        "alter table public.orders validate constraint orders_note_check;"
+    );
+}
+
+#[test]
+fn non_enforced_check_is_not_split() {
+    let mut c = fk(
+        "public",
+        "orders",
+        "orders_total_check",
+        "CHECK ((total > 0)) NOT ENFORCED",
+    );
+    c.constraint_type = "CHECK".to_string();
+    c.is_enforced = false;
+    let split = check_constraint_split(&c);
+    assert!(split.post_commit.is_empty());
+    assert!(!split.in_txn.contains("not valid"));
+}
+
+#[test]
+fn domain_check_split_into_not_valid_and_validate() {
+    let domain = PgType::new(
+        sqlx::postgres::types::Oid(1),
+        "public".to_string(),
+        "amount".to_string(),
+        sqlx::postgres::types::Oid(2200),
+        sqlx::postgres::types::Oid(10),
+        "postgres".to_string(),
+        -1,
+        false,
+        'd' as i8,
+        'N' as i8,
+        false,
+        true,
+        ',' as i8,
+        None,
+        None,
+        None,
+        None,
+        "domain_in".to_string(),
+        "domain_out".to_string(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        'i' as i8,
+        'p' as i8,
+        false,
+        Some(sqlx::postgres::types::Oid(23)),
+        None,
+        0,
+        None,
+        None,
+        Some("integer".to_string()),
+        Vec::new(),
+        Vec::new(),
+        None,
+    );
+    let constraint = DomainConstraint {
+        name: "amount_positive".to_string(),
+        definition: "CHECK (VALUE > 0)".to_string(),
+    };
+    let split = domain_check_split(&domain, &constraint);
+    assert_eq!(
+        split.in_txn.trim_end(),
+        "alter domain public.amount add constraint \"amount_positive\" CHECK (VALUE > 0) not valid;"
+    );
+    assert_eq!(
+        split.post_commit.trim_end(),
+        "alter domain public.amount validate constraint \"amount_positive\";"
+    );
+}
+
+#[test]
+fn partition_check_expression_for_range_and_list_bounds() {
+    assert_eq!(
+        partition_check_expression(
+            "RANGE (created_at)",
+            "FOR VALUES FROM ('2024-01-01 00:00:00') TO ('2024-02-01 00:00:00')"
+        )
+        .as_deref(),
+        Some(
+            "created_at IS NOT NULL AND created_at >= '2024-01-01 00:00:00' AND created_at < '2024-02-01 00:00:00'"
+        )
+    );
+    assert_eq!(
+        partition_check_expression("RANGE (id)", "FOR VALUES FROM (MINVALUE) TO (10)").as_deref(),
+        Some("id IS NOT NULL AND id < 10")
+    );
+    assert_eq!(
+        partition_check_expression("LIST (region)", "FOR VALUES IN ('eu', 'a,b')").as_deref(),
+        Some("region IS NOT NULL AND region IN ('eu', 'a,b')")
+    );
+    assert_eq!(
+        partition_check_expression("LIST (region)", "FOR VALUES IN (NULL, 'us')").as_deref(),
+        Some("region IS NULL OR region IN ('us')")
+    );
+}
+
+#[test]
+fn partition_check_expression_rejects_unsupported_bounds() {
+    assert_eq!(partition_check_expression("LIST (region)", "DEFAULT"), None);
+    assert_eq!(
+        partition_check_expression("HASH (id)", "FOR VALUES WITH (modulus 4, remainder 0)"),
+        None
+    );
+    assert_eq!(
+        partition_check_expression("RANGE (a, b)", "FOR VALUES FROM (1, 1) TO (2, 2)"),
+        None
+    );
+    assert_eq!(
+        partition_check_expression("RANGE (lower(name))", "FOR VALUES FROM ('a') TO ('m')"),
+        None
+    );
+}
+
+#[test]
+fn partition_attach_without_expression_stays_plain() {
+    let mut table = Table::new(
+        "public".to_string(),
+        "events_other".to_string(),
+        "public".to_string(),
+        "events_other".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.partition_of = Some("public.events".to_string());
+    assert_eq!(
+        partition_attach_split(&table, "public.events", "DEFAULT", Some("LIST (id)")).trim_end(),
+        "alter table public.events attach partition public.events_other DEFAULT;"
+    );
+}
+
+#[test]
+fn partition_detach_finalizes_a_pending_detach_instead() {
+    let table = Table::new(
+        "public".to_string(),
+        "events_other".to_string(),
+        "public".to_string(),
+        "events_other".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    let detach = partition_detach_concurrently("public.events", &table);
+    let statements: Vec<&str> = split_statements(&detach)
+        .into_iter()
+        .map(|(_, statement)| statement)
+        .filter(|statement| !statement.is_empty())
+        .collect();
+    assert_eq!(statements.len(), 3, "{detach}");
+    assert!(is_detach_guard(statements[0]));
+    assert!(statements[0].contains(
+        "where inhrelid = pg_catalog.to_regclass('public.events_other')\n        \
+         and inhparent = pg_catalog.to_regclass('public.events')\n        \
+         and not inhdetachpending\n    \
+         ) then\n        \
+         perform pg_catalog.set_config('search_path', 'public', false);"
+    ));
+    assert!(statements[0].contains(
+        "and inhdetachpending\n        \
+         ) then\n            \
+         execute 'alter table public.events detach partition public.events_other finalize';\n        \
+         end if;\n        \
+         perform pg_catalog.set_config('search_path', '', false);"
+    ));
+    assert_eq!(
+        statements[1],
+        "alter table if exists events detach partition public.events_other concurrently;"
+    );
+    assert_eq!(statements[2], "reset search_path;");
+
+    // Quoted names keep their quotes, dots inside them included.
+    let detach = partition_detach_concurrently("\"my.app\".\"Events\"", &table);
+    assert!(detach.contains("set_config('search_path', '\"my.app\"', false)"));
+    assert!(detach.contains("alter table if exists \"Events\" detach partition"));
+}
+
+fn key(table: &str, name: &str, constraint_type: &str, definition: &str) -> TableConstraint {
+    TableConstraint {
+        constraint_type: constraint_type.to_string(),
+        ..fk("public", table, name, definition)
+    }
+}
+
+#[test]
+fn primary_key_split_into_concurrent_index_and_using_index() {
+    let constraint = key("orders", "orders_pkey", "PRIMARY KEY", "PRIMARY KEY (id)");
+    let split = unique_constraint_split(&constraint).expect("split");
+    assert_eq!(
+        split.index_build.trim_end(),
+        "CREATE UNIQUE INDEX CONCURRENTLY orders_pkey ON public.orders USING btree (id);"
+    );
+    assert_eq!(
+        split.add_constraint.trim_end(),
+        "alter table public.orders add constraint orders_pkey primary key using index orders_pkey;"
+    );
+}
+
+#[test]
+fn unique_split_keeps_index_options_and_deferrability() {
+    let mut constraint = key(
+        "orders",
+        "orders_code_key",
+        "UNIQUE",
+        "UNIQUE NULLS NOT DISTINCT (code, \"Region\") INCLUDE (note) WITH (fillfactor='70') USING INDEX TABLESPACE fast DEFERRABLE INITIALLY DEFERRED",
+    );
+    constraint.is_deferrable = true;
+    constraint.initially_deferred = true;
+    constraint.comment = Some("one per region".to_string());
+    let split = unique_constraint_split(&constraint).expect("split");
+    assert_eq!(
+        split.index_build.trim_end(),
+        "CREATE UNIQUE INDEX CONCURRENTLY orders_code_key ON public.orders USING btree (code, \"Region\") INCLUDE (note) NULLS NOT DISTINCT WITH (fillfactor='70') TABLESPACE fast;"
+    );
+    assert!(split.add_constraint.contains(
+        "add constraint orders_code_key unique using index orders_code_key deferrable initially deferred;"
+    ));
+    assert!(
+        split.add_constraint.contains(
+            "comment on constraint orders_code_key on public.orders is 'one per region';"
+        )
+    );
+}
+
+#[test]
+fn changed_unique_constraint_is_swapped_onto_new_index() {
+    let old = key("orders", "orders_code_key", "UNIQUE", "UNIQUE (code)");
+    let new = key(
+        "orders",
+        "orders_code_key",
+        "UNIQUE",
+        "UNIQUE (code, region)",
+    );
+    let split = unique_constraint_swap(&old, &new).expect("swap");
+    assert_eq!(
+        split.index_build.trim_end(),
+        "CREATE UNIQUE INDEX CONCURRENTLY orders_code_key__pgc_new ON public.orders USING btree (code, region);"
+    );
+    assert_eq!(
+        split.add_constraint.trim_end(),
+        "alter table public.orders drop constraint orders_code_key, add constraint orders_code_key unique using index orders_code_key__pgc_new;"
+    );
+}
+
+#[test]
+fn without_overlaps_key_is_not_split() {
+    let constraint = key(
+        "bookings",
+        "bookings_pkey",
+        "PRIMARY KEY",
+        "PRIMARY KEY (room_id, during WITHOUT OVERLAPS)",
+    );
+    assert_eq!(unique_constraint_split(&constraint), None);
+    let check = key("orders", "orders_check", "CHECK", "CHECK (id > 0)");
+    assert_eq!(unique_constraint_split(&check), None);
+}
+
+#[test]
+fn post_commit_script_renders_phases_in_order() {
+    let mut post = PostCommitScript::default();
+    assert!(post.is_empty());
+    post.push(PostCommitPhase::Validate, "validate;\n");
+    post.push(PostCommitPhase::Constraints, "using index;\n");
+    post.push(PostCommitPhase::Indexes, "index a;\n");
+    post.phase_mut(PostCommitPhase::Indexes)
+        .push_str("index b;\n");
+    post.push(PostCommitPhase::NotNull, "set not null;\n");
+    assert!(!post.is_empty());
+    assert_eq!(
+        post.render(),
+        "index a;\nindex b;\nset not null;\nusing index;\nvalidate;\n"
+    );
+}
+
 // ---- make_idempotent ----
 
 #[test]
@@ -597,3 +1082,88 @@ fn alter_column_set_default_is_not_mistaken_for_op() {
 fn empty_input_is_empty() {
     assert_eq!(make_idempotent(""), "");
 }
+
+fn lock_settings(retries: u32) -> LockSettings {
+    LockSettings {
+        lock_timeout: Some("5s".to_string()),
+        statement_timeout: Some("15min".to_string()),
+        retries,
+    }
+}
+
+#[test]
+fn lock_settings_statements_scope() {
+    assert_eq!(
+        lock_settings_statements(&lock_settings(0), true),
+        "set local lock_timeout = '5s';\nset local statement_timeout = '15min';\n"
+    );
+    let only_lock = LockSettings {
+        lock_timeout: Some("500ms".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        lock_settings_statements(&only_lock, false),
+        "set lock_timeout = '500ms';\n"
+    );
+    assert_eq!(
+        lock_settings_statements(&LockSettings::default(), false),
+        ""
+    );
+}
+
+#[test]
+fn with_lock_settings_prefixes_each_statement() {
+    let post = "/* Indexes */\ncreate index concurrently i on public.t (a);\n\nalter table public.t validate constraint c;\n";
+    assert_eq!(
+        with_lock_settings(post, &lock_settings(0)),
+        "/* Indexes */\nset lock_timeout = '5s';\nset statement_timeout = '15min';\ncreate index concurrently i on public.t (a);\n\n\
+         set lock_timeout = '5s';\nset statement_timeout = '15min';\nalter table public.t validate constraint c;\n"
+    );
+    assert_eq!(with_lock_settings(post, &LockSettings::default()), post);
+}
+
+#[test]
+fn wrap_lock_retries_wraps_ddl_only() {
+    let script = concat!(
+        "begin;\n",
+        "set local lock_timeout = '5s';\n",
+        "alter table public.t add column if not exists c int;\n",
+        "-- drop table public.old;\n",
+        "commit;\n",
+        "create index concurrently if not exists i on public.t (c);\n",
+    );
+    let out = wrap_lock_retries(script, 3);
+    assert!(out.starts_with("begin;\nset local lock_timeout = '5s';\ndo $pgc_retry$\n"));
+    assert!(out.contains(
+        "execute $pgc_ddl$alter table public.t add column if not exists c int$pgc_ddl$;"
+    ));
+    assert!(out.contains("exception when lock_not_available then"));
+    assert!(out.contains("if attempt > 3 then"));
+    assert!(out.contains("end\n$pgc_retry$;\n-- drop table public.old;\ncommit;\n"));
+    assert!(out.ends_with("\ncreate index concurrently if not exists i on public.t (c);\n"));
+    assert_eq!(out.matches("do $pgc_retry$").count(), 1);
+}
+
+#[test]
+fn wrap_lock_retries_skips_non_transactional_ddl() {
+    for statement in [
+        "drop index concurrently if exists public.i;",
+        "alter table public.p detach partition public.c concurrently;",
+        "create subscription s connection 'x' publication p;",
+        "alter system set work_mem = '4MB';",
+    ] {
+        assert_eq!(wrap_lock_retries(statement, 2), statement);
+    }
+    let script = "alter table public.t add column c int;";
+    assert_eq!(wrap_lock_retries(script, 0), script);
+}
+
+#[test]
+fn retry_wrapped_statement_unwraps_retry_block() {
+    let wrapped = wrap_lock_retries("alter table public.t drop column c;", 1);
+    assert_eq!(
+        retry_wrapped_statement(&wrapped),
+        Some("alter table public.t drop column c")
+    );
+    assert_eq!(retry_wrapped_statement("do $$ begin end $$;"), None);
+}
@@ -0,0 +1,258 @@
+//! Resumable scripts for the `progress_ledger` option.
+//!
+//! Every statement of the script gets a stable step id, `{migration}-{hash}`:
+//! the migration part is a hash of the whole generated script, the hash part
+//! one of the statement itself (with a `-2`, `-3`, … suffix for repeats), so
+//! regenerating the same delta yields the same ids while another delta never
+//! reuses them. Completed steps are recorded in the
+//! `public.pgc_migration_progress` ledger, which the script creates when it
+//! is missing, and each statement is wrapped in a DO block that skips it when
+//! its id is already recorded and records it in the same transaction
+//! otherwise. A migration that failed half-way — typically in the
+//! post-commit section, where every statement commits on its own — can then
+//! be rerun from the top and resumes at the step that failed.
+//!
+//! Statements that cannot run inside a transaction block (`CONCURRENTLY`)
+//! cannot be put in a DO block either; they are emitted as they are, rely on
+//! their `IF [NOT] EXISTS` guards, and are recorded by the statement after
+//! them. A failed concurrent index build leaves an INVALID index behind
+//! that `IF NOT EXISTS` would take for a finished one, so such a build is
+//! preceded by a DO block that renames an invalid index of the same name out
+//! of the way and a `DROP INDEX CONCURRENTLY` of it, and the step is only
+//! recorded once the index is valid. `BEGIN`, `COMMIT`, `SET` and `RESET`
+//! statements and the DO block ahead of a concurrent partition detach (see
+//! [`crate::comparer::production::partition_detach_concurrently`]) are never
+//! guarded.
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::comparer::creation_script::duplicate_guarded_statement;
+use crate::comparer::production::{is_detach_guard, retry_wrapped_statement};
+use crate::comparer::scanner::{Relation, Statement, split_statements};
+
+/// The ledger of completed steps.
+pub(crate) const LEDGER_TABLE: &str = "public.pgc_migration_progress";
+
+const STEP_BLOCK_TAG: &str = "$pgc_step$";
+const STEP_STATEMENT_TAG: &str = "$pgc_step_sql$";
+const INVALID_INDEX_TAG: &str = "$pgc_invalid_index$";
+
+fn short_hash(text: &str) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    format!("{digest:x}")[..12].to_string()
+}
+
+/// The statement creating the ledger when it does not exist yet.
+pub(crate) fn ledger_table_statement() -> String {
+    format!(
+        "create table if not exists {LEDGER_TABLE} (\n    \
+         step_id text primary key,\n    \
+         completed_at timestamptz not null default now()\n);"
+    )
+}
+
+/// Hands out the step ids of one migration.
+pub(crate) struct StepIds {
+    migration: String,
+    seen: HashMap<String, usize>,
+}
+
+impl StepIds {
+    /// Ids for the migration whose (unwrapped) script is `script`.
+    pub(crate) fn new(script: &str) -> Self {
+        Self {
+            migration: short_hash(script),
+            seen: HashMap::new(),
+        }
+    }
+
+    fn next(&mut self, statement: &str) -> String {
+        let hash = short_hash(statement.trim());
+        let count = self.seen.entry(hash.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            format!("{}-{hash}", self.migration)
+        } else {
+            format!("{}-{hash}-{count}", self.migration)
+        }
+    }
+}
+
+fn is_unguarded(statement: &str) -> bool {
+    let st = Statement::parse(statement);
+    if st.tokens.is_empty() {
+        return true;
+    }
+    let keyword = statement.trim_end_matches(';').trim();
+    keyword.eq_ignore_ascii_case("begin")
+        || keyword.eq_ignore_ascii_case("commit")
+        || st.is_word(0, "set")
+        || st.is_word(0, "reset")
+        || is_detach_guard(statement)
+        || statement.trim() == ledger_table_statement()
+}
+
+/// Whether `statement` refuses to run inside a transaction block.
+pub(crate) fn runs_outside_transaction(statement: &str) -> bool {
+    let st = Statement::parse(statement);
+    (0..st.tokens.len()).any(|i| st.is_word(i, "concurrently"))
+        && !(st.is_word(0, "refresh") && st.is_word(1, "materialized"))
+}
+
+fn record(id: &str) -> String {
+    format!("insert into {LEDGER_TABLE} (step_id) values ('{id}') on conflict do nothing;")
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// The schema and name of the index a `CREATE [UNIQUE] INDEX CONCURRENTLY`
+/// statement builds; the schema is the table's, `None` when unqualified.
+fn concurrent_index(statement: &str) -> Option<(Option<String>, String)> {
+    let st = Statement::parse(statement);
+    let index = st.skip(1, &["unique"]);
+    if !st.is_word(0, "create")
+        || !st.is_word(index, "index")
+        || !st.is_word(index + 1, "concurrently")
+    {
+        return None;
+    }
+    let (name, next) = st.name_at(st.skip(index + 2, &["if", "not", "exists"]))?;
+    if !st.is_word(next, "on") {
+        return None;
+    }
+    let (table, _) = st.name_at(st.skip(next + 1, &["only"]))?;
+    let schema = |relation: &Relation| {
+        (relation.parts.len() > 1).then(|| relation.parts[relation.parts.len() - 2].clone())
+    };
+    Some((schema(&table), name.parts.last()?.clone()))
+}
+
+/// The catalog condition selecting the index `name` in `schema` (the current
+/// schema when `None`).
+fn index_condition(schema: &Option<String>, name: &str) -> String {
+    let schema = schema
+        .as_deref()
+        .map_or_else(|| "current_schema()".to_string(), quote_literal);
+    format!(
+        "from pg_catalog.pg_index i\n        \
+         join pg_catalog.pg_class c on c.oid = i.indexrelid\n        \
+         join pg_catalog.pg_namespace n on n.oid = c.relnamespace\n        \
+         where n.nspname = {schema} and c.relname = {}",
+        quote_literal(name)
+    )
+}
+
+/// The statements that drop an INVALID index left behind by a failed
+/// concurrent build of the index `name`: a DO block renaming it to a name of
+/// its own, and a concurrent drop of that name, a no-op when the index is
+/// valid or missing.
+fn drop_invalid_index(schema: &Option<String>, name: &str) -> String {
+    let qualified = match schema {
+        Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(name)),
+        None => quote_ident(name),
+    };
+    let invalid = format!("pgc_invalid_{}", short_hash(&qualified));
+    let invalid_qualified = match schema {
+        Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(&invalid)),
+        None => quote_ident(&invalid),
+    };
+    format!(
+        "do {INVALID_INDEX_TAG}\n\
+         begin\n    \
+         if exists (\n        \
+         select 1 {}\n        \
+         and not i.indisvalid\n    \
+         ) then\n        \
+         execute {};\n    \
+         end if;\n\
+         end\n{INVALID_INDEX_TAG};\n\
+         drop index concurrently if exists {invalid_qualified};\n",
+        index_condition(schema, name),
+        quote_literal(&format!(
+            "alter index {qualified} rename to {}",
+            quote_ident(&invalid)
+        ))
+    )
+}
+
+/// Records `id` once the index `name` exists and is valid.
+fn record_valid_index(id: &str, schema: &Option<String>, name: &str) -> String {
+    format!(
+        "insert into {LEDGER_TABLE} (step_id)\n\
+         select '{id}' where exists (\n        \
+         select 1 {}\n        \
+         and i.indisvalid\n\
+         )\n\
+         on conflict do nothing;",
+        index_condition(schema, name)
+    )
+}
+
+/// Wrap every statement of `script` in a ledger check (see the module
+/// documentation). `ids` is shared by the sections of one script so repeated
+/// statements get distinct ids.
+pub(crate) fn with_progress_ledger(script: &str, ids: &mut StepIds) -> String {
+    let mut out = String::with_capacity(script.len() * 2);
+    for (trivia, statement) in split_statements(script) {
+        out.push_str(trivia);
+        if is_unguarded(statement) {
+            out.push_str(statement);
+            continue;
+        }
+        let id = ids.next(statement);
+        if runs_outside_transaction(statement) {
+            let index = concurrent_index(statement);
+            if let Some((schema, name)) = &index {
+                out.push_str(&drop_invalid_index(schema, name));
+            }
+            out.push_str(statement);
+            out.push('\n');
+            out.push_str(&match &index {
+                Some((schema, name)) => record_valid_index(&id, schema, name),
+                None => record(&id),
+            });
+            continue;
+        }
+        let body = statement.trim_end().trim_end_matches(';');
+        out.push_str(&format!(
+            "do {STEP_BLOCK_TAG}\n\
+             begin\n    \
+             if not exists (select 1 from {LEDGER_TABLE} where step_id = '{id}') then\n        \
+             execute {STEP_STATEMENT_TAG}{body}{STEP_STATEMENT_TAG};\n        \
+             insert into {LEDGER_TABLE} (step_id) values ('{id}');\n    \
+             end if;\n\
+             end\n{STEP_BLOCK_TAG};"
+        ));
+    }
+    out
+}
+
+/// The statement inside a DO block emitted by [`with_progress_ledger`], or
+/// `None` when `statement` is not one.
+pub(crate) fn ledger_wrapped_statement(statement: &str) -> Option<&str> {
+    let rest = statement.strip_prefix(&format!("do {STEP_BLOCK_TAG}"))?;
+    let open = format!("execute {STEP_STATEMENT_TAG}");
+    let start = rest.find(&open)? + open.len();
+    let len = rest[start..].find(STEP_STATEMENT_TAG)?;
+    Some(&rest[start..start + len])
+}
+
+/// `statement` without the ledger, duplicate-object and lock retry blocks
+/// pgc may have wrapped it in.
+pub(crate) fn unwrap_statement(statement: &str) -> &str {
+    let statement = ledger_wrapped_statement(statement).unwrap_or(statement);
+    let statement = duplicate_guarded_statement(statement).unwrap_or(statement);
+    retry_wrapped_statement(statement).unwrap_or(statement)
+}
+
+#[cfg(test)]
+#[path = "progress_ledger_tests.rs"]
+mod tests;
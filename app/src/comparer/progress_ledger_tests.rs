@@ -0,0 +1,156 @@
+use super::*;
+use crate::comparer::production::partition_detach_concurrently;
+use crate::dump::table::Table;
+
+#[test]
+fn statements_are_guarded_by_the_ledger() {
+    let mut ids = StepIds::new("script");
+    let guarded = with_progress_ledger(
+        "begin;\n/* Table: public.t */\nalter table public.t add column c int;\ncommit;\n",
+        &mut ids,
+    );
+    let id = format!(
+        "{}-{}",
+        short_hash("script"),
+        short_hash("alter table public.t add column c int;")
+    );
+    assert_eq!(
+        guarded,
+        format!(
+            "begin;\n/* Table: public.t */\n\
+             do $pgc_step$\n\
+             begin\n    \
+             if not exists (select 1 from public.pgc_migration_progress where step_id = '{id}') then\n        \
+             execute $pgc_step_sql$alter table public.t add column c int$pgc_step_sql$;\n        \
+             insert into public.pgc_migration_progress (step_id) values ('{id}');\n    \
+             end if;\n\
+             end\n$pgc_step$;\ncommit;\n"
+        )
+    );
+    assert_eq!(
+        ledger_wrapped_statement(guarded.split_once("*/\n").unwrap().1),
+        Some("alter table public.t add column c int")
+    );
+}
+
+#[test]
+fn repeated_statements_get_distinct_ids() {
+    let mut ids = StepIds::new("script");
+    let first = ids.next("alter table public.t validate constraint c;");
+    let second = ids.next("alter table public.t validate constraint c;");
+    assert_eq!(second, format!("{first}-2"));
+    assert_ne!(
+        first,
+        StepIds::new("other script").next("alter table public.t validate constraint c;")
+    );
+}
+
+#[test]
+fn concurrent_statements_are_recorded_after_running() {
+    let mut ids = StepIds::new("script");
+    let guarded = with_progress_ledger("drop index concurrently if exists public.i;\n", &mut ids);
+    let (statement, record) = guarded.split_once('\n').unwrap();
+    assert_eq!(statement, "drop index concurrently if exists public.i;");
+    assert!(record.starts_with("insert into public.pgc_migration_progress (step_id) values ('"));
+    assert!(record.contains("on conflict do nothing;"));
+}
+
+#[test]
+fn concurrent_index_builds_drop_invalid_leftovers_and_record_valid_indexes() {
+    let mut ids = StepIds::new("script");
+    let statement = "create unique index concurrently if not exists \"T_v_idx\" on only public.t using btree (v);";
+    let guarded = with_progress_ledger(&format!("{statement}\n"), &mut ids);
+    let invalid = format!("pgc_invalid_{}", short_hash("\"public\".\"T_v_idx\""));
+    let condition = "from pg_catalog.pg_index i\n        \
+                     join pg_catalog.pg_class c on c.oid = i.indexrelid\n        \
+                     join pg_catalog.pg_namespace n on n.oid = c.relnamespace\n        \
+                     where n.nspname = 'public' and c.relname = 'T_v_idx'";
+    let id = format!("{}-{}", short_hash("script"), short_hash(statement));
+    assert_eq!(
+        guarded,
+        format!(
+            "do $pgc_invalid_index$\n\
+             begin\n    \
+             if exists (\n        \
+             select 1 {condition}\n        \
+             and not i.indisvalid\n    \
+             ) then\n        \
+             execute 'alter index \"public\".\"T_v_idx\" rename to \"{invalid}\"';\n    \
+             end if;\n\
+             end\n$pgc_invalid_index$;\n\
+             drop index concurrently if exists \"public\".\"{invalid}\";\n\
+             {statement}\n\
+             insert into public.pgc_migration_progress (step_id)\n\
+             select '{id}' where exists (\n        \
+             select 1 {condition}\n        \
+             and i.indisvalid\n\
+             )\n\
+             on conflict do nothing;\n"
+        )
+    );
+}
+
+#[test]
+fn concurrent_index_on_an_unqualified_table_uses_the_current_schema() {
+    assert_eq!(
+        concurrent_index("create index concurrently i on t (c);"),
+        Some((None, "i".to_string()))
+    );
+    assert!(index_condition(&None, "i").contains("where n.nspname = current_schema() and"));
+    assert_eq!(concurrent_index("drop index concurrently i;"), None);
+}
+
+#[test]
+fn session_and_ledger_statements_are_not_guarded() {
+    let script = format!(
+        "{}\nbegin;\nset local lock_timeout = '5s';\ncommit;\n",
+        ledger_table_statement()
+    );
+    assert_eq!(
+        with_progress_ledger(&script, &mut StepIds::new(&script)),
+        script
+    );
+}
+
+#[test]
+fn concurrent_detach_runs_its_guard_on_every_attempt() {
+    let table = Table::new(
+        "public".to_string(),
+        "events_1".to_string(),
+        "public".to_string(),
+        "events_1".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    let script = partition_detach_concurrently("public.events", &table);
+    let mut ids = StepIds::new(&script);
+    let guarded = with_progress_ledger(&script, &mut ids);
+    let detach = "alter table if exists events detach partition public.events_1 concurrently;";
+    let id = StepIds::new(&script).next(detach);
+    assert!(guarded.starts_with("do $pgc_detach$\n"), "{guarded}");
+    assert!(
+        guarded.contains(&format!("{detach}\n{}", record(&id))),
+        "{guarded}"
+    );
+    assert!(guarded.ends_with("\nreset search_path;\n\n"), "{guarded}");
+    assert!(!guarded.contains(STEP_BLOCK_TAG), "{guarded}");
+}
+
+#[test]
+fn unwrap_statement_removes_ledger_and_retry_blocks() {
+    let retried = crate::comparer::production::wrap_lock_retries(
+        "alter table public.t validate constraint c;",
+        2,
+    );
+    let guarded = with_progress_ledger(&retried, &mut StepIds::new(&retried));
+    assert!(guarded.starts_with("do $pgc_step$"));
+    assert_eq!(
+        unwrap_statement(&guarded),
+        "alter table public.t validate constraint c"
+    );
+}
@@ -6,6 +6,9 @@
 //!   - single-quoted literals:     `'` … `'`   (`''` is the escape for a literal quote)
 //!   - E-string literals:          `E'` … `'`  (backslash sequences and `''` are handled)
 //!   - double-quoted identifiers:  `"` … `"`   (`""` is the escape for a literal quote)
+//!
+//! [`split_statements`] uses the same rules to cut a script into top-level
+//! statements, and [`Statement`] to cut a statement into tokens.
 
 /// Single-pass scanner: strip SQL comments (`--` line and `/* */` block),
 /// collapse runs of 3+ newlines into 2, and pass all quoted literals
@@ -118,6 +121,99 @@ pub(crate) fn strip_comments_and_collapse(script: &str) -> String {
     String::from_utf8(result).expect("output must be valid UTF-8")
 }
 
+/// Splits `script` into top-level statements. Each entry pairs the leading
+/// whitespace/comments with the statement that follows, terminating `;`
+/// included. Text after the last statement (or an unterminated statement) is
+/// returned as a final entry, so concatenating every pair reproduces `script`
+/// exactly. Semicolons inside literals, identifiers, dollar quotes and
+/// comments do not end a statement.
+pub(crate) fn split_statements(script: &str) -> Vec<(&str, &str)> {
+    let src = script.as_bytes();
+    let len = src.len();
+    let mut parts = Vec::new();
+    // Scratch buffer for copy_quoted_literal; only the cursor movement matters.
+    let mut skipped: Vec<u8> = Vec::new();
+    let mut start = 0;
+    let mut stmt_start: Option<usize> = None;
+    let mut i = 0;
+
+    while i < len {
+        let b = src[i];
+        if b == b'-' && i + 1 < len && src[i + 1] == b'-' {
+            while i < len && src[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if b == b'/' && i + 1 < len && src[i + 1] == b'*' {
+            i += 2;
+            let mut depth: usize = 1;
+            while i + 1 < len && depth > 0 {
+                if src[i] == b'/' && src[i + 1] == b'*' {
+                    depth += 1;
+                    i += 2;
+                } else if src[i] == b'*' && src[i + 1] == b'/' {
+                    depth -= 1;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            if depth > 0 {
+                i = len;
+            }
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        stmt_start.get_or_insert(i);
+        if b == b'$'
+            && let Some(tag_len) = dollar_tag_at(src, i)
+        {
+            let tag = &src[i..i + tag_len];
+            i += tag_len;
+            while i < len {
+                if src[i] == b'$'
+                    && let Some(close_len) = dollar_tag_at(src, i)
+                    && &src[i..i + close_len] == tag
+                {
+                    i += close_len;
+                    break;
+                }
+                i += 1;
+            }
+            continue;
+        }
+        if (b == b'E' || b == b'e') && i + 1 < len && src[i + 1] == b'\'' {
+            i += 2;
+            copy_quoted_literal(src, &mut skipped, &mut i, b'\'', true);
+            skipped.clear();
+            continue;
+        }
+        if b == b'\'' || b == b'"' {
+            i += 1;
+            copy_quoted_literal(src, &mut skipped, &mut i, b, false);
+            skipped.clear();
+            continue;
+        }
+        i += 1;
+        if b == b';' {
+            let body = stmt_start.take().unwrap_or(start);
+            parts.push((&script[start..body], &script[body..i]));
+            start = i;
+        }
+    }
+    if start < len {
+        match stmt_start {
+            Some(body) => parts.push((&script[start..body], &script[body..])),
+            None => parts.push((&script[start..], "")),
+        }
+    }
+    parts
+}
+
 /// Copies a quoted literal body (everything after the opening delimiter has
 /// already been pushed) into `result`, advancing `i` past the closing
 /// delimiter.  Two quoting conventions are supported:
@@ -184,6 +280,262 @@ pub(crate) fn dollar_tag_at(src: &[u8], pos: usize) -> Option<usize> {
     }
 }
 
+/// A relation named by a statement: `key` is the normalised name (quotes
+/// removed, unquoted parts lowercased) used for comparison, `display` the
+/// name as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Relation {
+    pub key: String,
+    pub display: String,
+    /// The normalised parts of the name, schema first.
+    pub parts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Word,
+    Quoted,
+    Literal,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A statement cut into tokens; comments and whitespace are dropped and
+/// literals are kept as opaque tokens.
+pub(crate) struct Statement<'a> {
+    pub sql: &'a str,
+    pub tokens: Vec<Token>,
+}
+
+impl<'a> Statement<'a> {
+    pub(crate) fn parse(sql: &'a str) -> Self {
+        let src = sql.as_bytes();
+        let len = src.len();
+        let mut tokens = Vec::new();
+        let mut skipped: Vec<u8> = Vec::new();
+        let mut i = 0;
+        while i < len {
+            let b = src[i];
+            let start = i;
+            if b.is_ascii_whitespace() {
+                i += 1;
+                continue;
+            }
+            if b == b'-' && i + 1 < len && src[i + 1] == b'-' {
+                while i < len && src[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            if b == b'/' && i + 1 < len && src[i + 1] == b'*' {
+                i += 2;
+                let mut depth: usize = 1;
+                while i + 1 < len && depth > 0 {
+                    if src[i] == b'/' && src[i + 1] == b'*' {
+                        depth += 1;
+                        i += 2;
+                    } else if src[i] == b'*' && src[i + 1] == b'/' {
+                        depth -= 1;
+                        i += 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+                if depth > 0 {
+                    i = len;
+                }
+                continue;
+            }
+            let kind = if b == b'$'
+                && let Some(tag_len) = dollar_tag_at(src, i)
+            {
+                let tag = &src[i..i + tag_len];
+                i += tag_len;
+                while i < len {
+                    if src[i] == b'$'
+                        && let Some(close_len) = dollar_tag_at(src, i)
+                        && &src[i..i + close_len] == tag
+                    {
+                        i += close_len;
+                        break;
+                    }
+                    i += 1;
+                }
+                TokenKind::Literal
+            } else if (b == b'E' || b == b'e') && i + 1 < len && src[i + 1] == b'\'' {
+                i += 2;
+                copy_quoted_literal(src, &mut skipped, &mut i, b'\'', true);
+                TokenKind::Literal
+            } else if b == b'\'' {
+                i += 1;
+                copy_quoted_literal(src, &mut skipped, &mut i, b'\'', false);
+                TokenKind::Literal
+            } else if b == b'"' {
+                i += 1;
+                copy_quoted_literal(src, &mut skipped, &mut i, b'"', false);
+                TokenKind::Quoted
+            } else if b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 {
+                while i < len
+                    && (src[i].is_ascii_alphanumeric()
+                        || src[i] == b'_'
+                        || src[i] == b'$'
+                        || src[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                TokenKind::Word
+            } else {
+                i += 1;
+                TokenKind::Punct
+            };
+            skipped.clear();
+            tokens.push(Token {
+                kind,
+                start,
+                end: i,
+            });
+        }
+        Self { sql, tokens }
+    }
+
+    pub(crate) fn text(&self, i: usize) -> &'a str {
+        self.tokens.get(i).map_or("", |t| &self.sql[t.start..t.end])
+    }
+
+    pub(crate) fn is_word(&self, i: usize, word: &str) -> bool {
+        self.tokens
+            .get(i)
+            .is_some_and(|t| t.kind == TokenKind::Word)
+            && self.text(i).eq_ignore_ascii_case(word)
+    }
+
+    pub(crate) fn is_punct(&self, i: usize, punct: &str) -> bool {
+        self.tokens
+            .get(i)
+            .is_some_and(|t| t.kind == TokenKind::Punct)
+            && self.text(i) == punct
+    }
+
+    pub(crate) fn is_name_part(&self, i: usize) -> bool {
+        self.tokens
+            .get(i)
+            .is_some_and(|t| matches!(t.kind, TokenKind::Word | TokenKind::Quoted))
+    }
+
+    /// Skips `words` at `i` when they all match, otherwise returns `i`.
+    pub(crate) fn skip(&self, i: usize, words: &[&str]) -> usize {
+        if words
+            .iter()
+            .enumerate()
+            .all(|(k, word)| self.is_word(i + k, word))
+        {
+            i + words.len()
+        } else {
+            i
+        }
+    }
+
+    /// The token indices of the (possibly qualified) name at `i`.
+    pub(crate) fn name_parts(&self, i: usize) -> Vec<usize> {
+        let mut parts = Vec::new();
+        if !self.is_name_part(i) {
+            return parts;
+        }
+        parts.push(i);
+        let mut j = i;
+        while self.is_punct(j + 1, ".") && self.is_name_part(j + 2) {
+            j += 2;
+            parts.push(j);
+        }
+        parts
+    }
+
+    pub(crate) fn relation(&self, parts: &[usize]) -> Option<Relation> {
+        let (first, last) = (*parts.first()?, *parts.last()?);
+        let normalised: Vec<String> = parts
+            .iter()
+            .map(|&p| {
+                let text = self.text(p);
+                if self.tokens[p].kind == TokenKind::Quoted {
+                    text[1..text.len().saturating_sub(1).max(1)].replace("\"\"", "\"")
+                } else {
+                    text.to_lowercase()
+                }
+            })
+            .collect();
+        Some(Relation {
+            key: normalised.join("."),
+            parts: normalised,
+            display: self.sql[self.tokens[first].start..self.tokens[last].end].to_string(),
+        })
+    }
+
+    /// The relation named at `i` and the index of the token after it.
+    pub(crate) fn name_at(&self, i: usize) -> Option<(Relation, usize)> {
+        let parts = self.name_parts(i);
+        let next = parts.last()? + 1;
+        Some((self.relation(&parts)?, next))
+    }
+
+    /// A comma-separated list of relations starting at `i`.
+    pub(crate) fn names_at(&self, mut i: usize) -> Vec<Relation> {
+        let mut names = Vec::new();
+        while let Some((relation, next)) = self.name_at(self.skip(i, &["only"])) {
+            names.push(relation);
+            if !self.is_punct(next, ",") {
+                break;
+            }
+            i = next + 1;
+        }
+        names
+    }
+
+    /// The first top-level occurrence of `word` at or after `from`.
+    pub(crate) fn find_word(&self, from: usize, word: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for i in from..self.tokens.len() {
+            if self.is_punct(i, "(") {
+                depth += 1;
+            } else if self.is_punct(i, ")") {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && self.is_word(i, word) {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Splits the tokens from `from` to the end into top-level,
+    /// comma-separated ranges (the subcommands of an `ALTER TABLE`).
+    pub(crate) fn subcommands(&self, from: usize) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut depth = 0usize;
+        let mut start = from;
+        for i in from..self.tokens.len() {
+            if self.is_punct(i, "(") {
+                depth += 1;
+            } else if self.is_punct(i, ")") {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && (self.is_punct(i, ",") || self.is_punct(i, ";")) {
+                if i > start {
+                    ranges.push((start, i));
+                }
+                start = i + 1;
+            }
+        }
+        if start < self.tokens.len() {
+            ranges.push((start, self.tokens.len()));
+        }
+        ranges
+    }
+}
+
 #[cfg(test)]
 #[path = "scanner_tests.rs"]
 mod tests;
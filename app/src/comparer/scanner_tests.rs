@@ -321,3 +321,36 @@ fn mixed_literal_types_with_newlines() {
     assert!(out.contains("\"id\n\n\n\ncol\""));
     assert!(out.contains("BEGIN\n\n\n\n  NULL;"));
 }
+
+#[test]
+fn split_statements_round_trips() {
+    let input = "/* header */\nbegin;\n-- a comment\ncreate table a (x int);\n\ncommit;\n";
+    let parts = split_statements(input);
+    let joined: String = parts.iter().map(|(t, s)| format!("{t}{s}")).collect();
+    assert_eq!(joined, input);
+    let stmts: Vec<&str> = parts.iter().map(|(_, s)| *s).collect();
+    assert_eq!(
+        stmts,
+        vec!["begin;", "create table a (x int);", "commit;", ""]
+    );
+    assert_eq!(parts[1].0, "\n-- a comment\n");
+}
+
+#[test]
+fn split_statements_ignores_quoted_semicolons() {
+    let input = concat!(
+        "comment on table a is 'x; y';\n",
+        "create function f() returns int as $body$ select 1; $body$ language sql;\n",
+        "alter table \"a;b\" add column c text default E'\\'; ';\n",
+        "/* drop table a; */ select 1",
+    );
+    let stmts: Vec<&str> = split_statements(input)
+        .into_iter()
+        .map(|(_, s)| s)
+        .collect();
+    assert_eq!(stmts.len(), 4);
+    assert_eq!(stmts[0], "comment on table a is 'x; y';");
+    assert!(stmts[1].ends_with("language sql;"));
+    assert!(stmts[2].starts_with("alter table \"a;b\""));
+    assert_eq!(stmts[3], "select 1");
+}
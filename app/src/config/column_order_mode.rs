@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Controls what happens when an in-place ALTER cannot reproduce the column
+/// order of the target table (PostgreSQL only appends new columns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ColumnOrderMode {
+    /// Ignore column order (default, current behaviour).
+    #[default]
+    Ignore,
+    /// Add a comment describing the mismatch to the script.
+    Report,
+    /// Rebuild the table with a data copy to reproduce the target layout.
+    Rebuild,
+}
+
+impl fmt::Display for ColumnOrderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ignore => write!(f, "ignore"),
+            Self::Report => write!(f, "report"),
+            Self::Rebuild => write!(f, "rebuild"),
+        }
+    }
+}
+
+impl FromStr for ColumnOrderMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ignore" => Ok(Self::Ignore),
+            "report" => Ok(Self::Report),
+            "rebuild" => Ok(Self::Rebuild),
+            _ => Err(format!(
+                "invalid column order mode '{}'; valid values: ignore, report, rebuild",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "column_order_mode_tests.rs"]
+mod tests;
@@ -0,0 +1,59 @@
+use super::*;
+
+#[test]
+fn from_str_ignore() {
+    assert_eq!(
+        "ignore".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Ignore
+    );
+    assert_eq!(
+        "IGNORE".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Ignore
+    );
+}
+
+#[test]
+fn from_str_report() {
+    assert_eq!(
+        "report".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Report
+    );
+    assert_eq!(
+        "Report".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Report
+    );
+}
+
+#[test]
+fn from_str_rebuild() {
+    assert_eq!(
+        "rebuild".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Rebuild
+    );
+    assert_eq!(
+        "REBUILD".parse::<ColumnOrderMode>().unwrap(),
+        ColumnOrderMode::Rebuild
+    );
+}
+
+#[test]
+fn from_str_invalid() {
+    assert!("strict".parse::<ColumnOrderMode>().is_err());
+    assert!("".parse::<ColumnOrderMode>().is_err());
+}
+
+#[test]
+fn display_round_trips() {
+    for mode in [
+        ColumnOrderMode::Ignore,
+        ColumnOrderMode::Report,
+        ColumnOrderMode::Rebuild,
+    ] {
+        assert_eq!(mode.to_string().parse::<ColumnOrderMode>().unwrap(), mode);
+    }
+}
+
+#[test]
+fn default_is_ignore() {
+    assert_eq!(ColumnOrderMode::default(), ColumnOrderMode::Ignore);
+}
@@ -1,5 +1,10 @@
+use crate::config::column_order_mode::ColumnOrderMode;
 use crate::config::dump_config::DumpConfig;
 use crate::config::grants_mode::GrantsMode;
+use crate::config::lock_settings::{LockSettings, parse_duration};
+use crate::config::migration_format::MigrationFormat;
+use crate::config::object_filter::{FilterRule, ObjectFilter};
+use crate::config::ssl_settings::{SslSettings, parse_ssl_mode};
 
 // Configuration file representation.
 #[derive(Debug, Clone)]
@@ -25,6 +30,33 @@ pub struct Config {
     // creation, NOT VALID + VALIDATE for foreign keys, concurrent index drops,
     // and a split transaction so the concurrent statements run outside it).
     pub output_for_production: bool,
+    // What to do when altering a table in place cannot reproduce the target
+    // column order
+    pub column_order_mode: ColumnOrderMode,
+    // Lock and statement timeouts, and lock retries, for production output
+    pub lock_settings: LockSettings,
+    // Whether statements are annotated with the locks they take
+    pub lock_annotations: bool,
+    // Whether consecutive single-table statements are grouped per table
+    pub group_by_table: bool,
+    // How the compare result is written (single file or migration files)
+    pub migration_format: MigrationFormat,
+    // Name used in migration file names
+    pub migration_name: String,
+    // Rollback script file (TO back to FROM), if one is wanted
+    pub rollback_output: Option<String>,
+    // Whether statements are guarded by the progress ledger
+    pub progress_ledger: bool,
+    // Whether the script starts with a check of the target's fingerprint
+    pub fingerprint_guard: bool,
+    // Whether the script starts with checks of the existing data
+    pub data_checks: bool,
+    // Include/exclude rules applied to both dumps and to the comparison
+    pub object_filter: ObjectFilter,
+    // Whether stored credentials are replaced with placeholders
+    pub redact_secrets: bool,
+    // File with the real values of redacted secrets
+    pub secrets_file: Option<String>,
 }
 
 impl Config {
@@ -39,20 +71,22 @@ impl Config {
             .collect::<Vec<&str>>();
 
         let mut from_host = "".to_string();
-        let mut from_port = "5432".to_string();
+        let mut from_port = "".to_string();
         let mut from_user = "".to_string();
         let mut from_password = "".to_string();
         let mut from_database = "".to_string();
         let mut from_scheme = "".to_string();
         let mut from_ssl = false;
+        let mut from_ssl_settings = SslSettings::default();
         let mut from_dump = "dump.from".to_string();
         let mut to_host = "".to_string();
-        let mut to_port = "5432".to_string();
+        let mut to_port = "".to_string();
         let mut to_user = "".to_string();
         let mut to_password = "".to_string();
         let mut to_database = "".to_string();
         let mut to_scheme = "".to_string();
         let mut to_ssl = false;
+        let mut to_ssl_settings = SslSettings::default();
         let mut to_dump = "dump.to".to_string();
         let mut output = "data.out".to_string();
         let mut use_drop = false;
@@ -61,6 +95,19 @@ impl Config {
         let mut grants_mode = GrantsMode::Ignore;
         let mut max_connections: u32 = 16;
         let mut output_for_production = false;
+        let mut column_order_mode = ColumnOrderMode::Ignore;
+        let mut lock_settings = LockSettings::default();
+        let mut lock_annotations = false;
+        let mut group_by_table = false;
+        let mut migration_format = MigrationFormat::Single;
+        let mut migration_name = "pgc_delta".to_string();
+        let mut rollback_output = None;
+        let mut progress_ledger = false;
+        let mut fingerprint_guard = false;
+        let mut data_checks = false;
+        let mut object_filter = ObjectFilter::default();
+        let mut redact_secrets = true;
+        let mut secrets_file = None;
 
         for line in &config_data {
             if line.trim().is_empty() || line.starts_with('#') {
@@ -80,6 +127,11 @@ impl Config {
                 && key != "FROM_DATABASE"
                 && key != "FROM_SCHEME"
                 && key != "FROM_SSL"
+                && key != "FROM_SSLMODE"
+                && key != "FROM_SSLROOTCERT"
+                && key != "FROM_SSLCERT"
+                && key != "FROM_SSLKEY"
+                && key != "FROM_SSLPASSWORD"
                 && key != "FROM_DUMP"
                 && key != "TO_HOST"
                 && key != "TO_PORT"
@@ -88,6 +140,11 @@ impl Config {
                 && key != "TO_DATABASE"
                 && key != "TO_SCHEME"
                 && key != "TO_SSL"
+                && key != "TO_SSLMODE"
+                && key != "TO_SSLROOTCERT"
+                && key != "TO_SSLCERT"
+                && key != "TO_SSLKEY"
+                && key != "TO_SSLPASSWORD"
                 && key != "TO_DUMP"
                 && key != "OUTPUT"
                 && key != "USE_DROP"
@@ -96,6 +153,22 @@ impl Config {
                 && key != "GRANTS_MODE"
                 && key != "MAX_CONNECTIONS"
                 && key != "OUTPUT_FOR_PRODUCTION"
+                && key != "COLUMN_ORDER"
+                && key != "LOCK_TIMEOUT"
+                && key != "STATEMENT_TIMEOUT"
+                && key != "LOCK_RETRIES"
+                && key != "LOCK_ANNOTATIONS"
+                && key != "GROUP_BY_TABLE"
+                && key != "MIGRATION_FORMAT"
+                && key != "MIGRATION_NAME"
+                && key != "ROLLBACK_OUTPUT"
+                && key != "PROGRESS_LEDGER"
+                && key != "FINGERPRINT_GUARD"
+                && key != "DATA_CHECKS"
+                && key != "INCLUDE"
+                && key != "EXCLUDE"
+                && key != "REDACT_SECRETS"
+                && key != "SECRETS_FILE"
             {
                 return Err(format!("Unknown configuration key: {}", parts[0]));
             }
@@ -114,6 +187,16 @@ impl Config {
                 "FROM_DATABASE" => from_database = raw_value.to_string(),
                 "FROM_SCHEME" => from_scheme = raw_value.to_string(),
                 "FROM_SSL" => from_ssl = value == "TRUE",
+                "FROM_SSLMODE" => {
+                    from_ssl_settings.mode = Some(
+                        parse_ssl_mode(raw_value)
+                            .map_err(|e| format!("Invalid value for FROM_SSLMODE: {e}"))?,
+                    );
+                }
+                "FROM_SSLROOTCERT" => from_ssl_settings.root_cert = Some(raw_value.to_string()),
+                "FROM_SSLCERT" => from_ssl_settings.cert = Some(raw_value.to_string()),
+                "FROM_SSLKEY" => from_ssl_settings.key = Some(raw_value.to_string()),
+                "FROM_SSLPASSWORD" => from_ssl_settings.password = Some(raw_value.to_string()),
                 "FROM_DUMP" => from_dump = raw_value.to_string(),
                 "TO_HOST" => to_host = raw_value.to_string(),
                 "TO_PORT" => to_port = raw_value.to_string(),
@@ -122,6 +205,16 @@ impl Config {
                 "TO_DATABASE" => to_database = raw_value.to_string(),
                 "TO_SCHEME" => to_scheme = raw_value.to_string(),
                 "TO_SSL" => to_ssl = value == "TRUE",
+                "TO_SSLMODE" => {
+                    to_ssl_settings.mode = Some(
+                        parse_ssl_mode(raw_value)
+                            .map_err(|e| format!("Invalid value for TO_SSLMODE: {e}"))?,
+                    );
+                }
+                "TO_SSLROOTCERT" => to_ssl_settings.root_cert = Some(raw_value.to_string()),
+                "TO_SSLCERT" => to_ssl_settings.cert = Some(raw_value.to_string()),
+                "TO_SSLKEY" => to_ssl_settings.key = Some(raw_value.to_string()),
+                "TO_SSLPASSWORD" => to_ssl_settings.password = Some(raw_value.to_string()),
                 "TO_DUMP" => to_dump = raw_value.to_string(),
                 "OUTPUT" => output = raw_value.to_string(),
                 "USE_DROP" => use_drop = value == "TRUE",
@@ -137,6 +230,47 @@ impl Config {
                         }
                     };
                 }
+                "LOCK_ANNOTATIONS" => {
+                    lock_annotations = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => {
+                            return Err(format!("Invalid value for LOCK_ANNOTATIONS: {raw_value}"));
+                        }
+                    };
+                }
+                "GROUP_BY_TABLE" => {
+                    group_by_table = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => return Err(format!("Invalid value for GROUP_BY_TABLE: {raw_value}")),
+                    };
+                }
+                "PROGRESS_LEDGER" => {
+                    progress_ledger = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => return Err(format!("Invalid value for PROGRESS_LEDGER: {raw_value}")),
+                    };
+                }
+                "FINGERPRINT_GUARD" => {
+                    fingerprint_guard = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => {
+                            return Err(format!(
+                                "Invalid value for FINGERPRINT_GUARD: {raw_value}"
+                            ));
+                        }
+                    };
+                }
+                "DATA_CHECKS" => {
+                    data_checks = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => return Err(format!("Invalid value for DATA_CHECKS: {raw_value}")),
+                    };
+                }
                 "USE_COMMENTS" => {
                     use_comments = match value.as_str() {
                         "TRUE" => true,
@@ -147,6 +281,47 @@ impl Config {
                 "GRANTS_MODE" => {
                     grants_mode = raw_value.parse::<GrantsMode>().map_err(|e| e.to_string())?;
                 }
+                "COLUMN_ORDER" => {
+                    column_order_mode = raw_value
+                        .parse::<ColumnOrderMode>()
+                        .map_err(|e| e.to_string())?;
+                }
+                "LOCK_TIMEOUT" => {
+                    lock_settings.lock_timeout = Some(
+                        parse_duration(raw_value)
+                            .map_err(|e| format!("Invalid value for LOCK_TIMEOUT: {e}"))?,
+                    );
+                }
+                "STATEMENT_TIMEOUT" => {
+                    lock_settings.statement_timeout = Some(
+                        parse_duration(raw_value)
+                            .map_err(|e| format!("Invalid value for STATEMENT_TIMEOUT: {e}"))?,
+                    );
+                }
+                "LOCK_RETRIES" => {
+                    lock_settings.retries = raw_value
+                        .parse::<u32>()
+                        .map_err(|e| format!("Invalid value for LOCK_RETRIES: {e}"))?;
+                }
+                "MIGRATION_FORMAT" => {
+                    migration_format = raw_value
+                        .parse::<MigrationFormat>()
+                        .map_err(|e| e.to_string())?;
+                }
+                "MIGRATION_NAME" => migration_name = raw_value.to_string(),
+                "ROLLBACK_OUTPUT" => rollback_output = Some(raw_value.to_string()),
+                "REDACT_SECRETS" => {
+                    redact_secrets = match value.as_str() {
+                        "TRUE" => true,
+                        "FALSE" => false,
+                        _ => return Err(format!("Invalid value for REDACT_SECRETS: {raw_value}")),
+                    };
+                }
+                "SECRETS_FILE" => secrets_file = Some(raw_value.to_string()),
+                "INCLUDE" | "EXCLUDE" => object_filter.rules.push(
+                    FilterRule::parse(raw_value, key == "INCLUDE")
+                        .map_err(|e| format!("Invalid value for {key}: {e}"))?,
+                ),
                 "MAX_CONNECTIONS" => {
                     let v = raw_value
                         .parse::<u32>()
@@ -159,6 +334,11 @@ impl Config {
                 _ => {}
             }
         }
+        // Without a lock timeout a statement waits for its lock indefinitely,
+        // so lock_not_available never fires and retries would be dead code.
+        if lock_settings.retries > 0 && lock_settings.lock_timeout.is_none() {
+            return Err("LOCK_RETRIES requires LOCK_TIMEOUT to be set".to_string());
+        }
         let from = DumpConfig {
             host: from_host,
             port: from_port,
@@ -167,6 +347,7 @@ impl Config {
             database: from_database,
             scheme: from_scheme,
             ssl: from_ssl,
+            ssl_settings: from_ssl_settings,
             file: from_dump,
         };
         let to = DumpConfig {
@@ -177,6 +358,7 @@ impl Config {
             database: to_database,
             scheme: to_scheme,
             ssl: to_ssl,
+            ssl_settings: to_ssl_settings,
             file: to_dump,
         };
 
@@ -200,17 +382,20 @@ impl Config {
         }
 
         // An empty FROM/TO host slips past the same-target check above
-        // (which gates on both hosts being non-empty). A blank host almost
+        // (which gates on both hosts being non-empty). It is resolved from
+        // PGHOST or a service, but with neither set a blank host almost
         // always means the user forgot a key, mistyped one, or fed in an
-        // empty config file by mistake; silently falling back to defaults
+        // empty config file by mistake; silently falling back to localhost
         // produces a baffling connection error later. Surface it now.
-        if from.host.is_empty() {
+        let host_from_environment =
+            std::env::var_os("PGHOST").is_some() || std::env::var_os("PGSERVICE").is_some();
+        if from.host.is_empty() && !host_from_environment {
             eprintln!(
                 "Warning: FROM_HOST is empty in the configuration file — \
                  connection will likely fail. Check that the FROM_* keys are set."
             );
         }
-        if to.host.is_empty() {
+        if to.host.is_empty() && !host_from_environment {
             eprintln!(
                 "Warning: TO_HOST is empty in the configuration file — \
                  connection will likely fail. Check that the TO_* keys are set."
@@ -227,6 +412,19 @@ impl Config {
             grants_mode,
             max_connections,
             output_for_production,
+            column_order_mode,
+            lock_settings,
+            lock_annotations,
+            group_by_table,
+            migration_format,
+            migration_name,
+            rollback_output,
+            progress_ledger,
+            fingerprint_guard,
+            data_checks,
+            object_filter,
+            redact_secrets,
+            secrets_file,
         })
     }
 
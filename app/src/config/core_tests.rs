@@ -69,6 +69,252 @@ fn test_invalid_output_for_production_value_panics() {
     let _ = std::fs::remove_file(file);
 }
 
+#[test]
+fn test_column_order_mode_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nCOLUMN_ORDER=Rebuild";
+    let file = write_temp_config(config_content, "test_column_order_mode.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(config.column_order_mode, ColumnOrderMode::Rebuild);
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_column_order_mode_defaults_ignore() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost";
+    let file = write_temp_config(config_content, "test_column_order_mode_default.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(config.column_order_mode, ColumnOrderMode::Ignore);
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_invalid_column_order_mode_is_error() {
+    let config_content = "FROM_HOST=localhost\nCOLUMN_ORDER=strict";
+    let file = write_temp_config(config_content, "test_invalid_column_order_mode.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("invalid column order mode"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_lock_settings_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nLOCK_TIMEOUT=5s\nSTATEMENT_TIMEOUT=15 min\nLOCK_RETRIES=3";
+    let file = write_temp_config(config_content, "test_lock_settings.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(config.lock_settings.lock_timeout.as_deref(), Some("5s"));
+    assert_eq!(
+        config.lock_settings.statement_timeout.as_deref(),
+        Some("15min")
+    );
+    assert_eq!(config.lock_settings.retries, 3);
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_lock_retries_without_lock_timeout_is_error() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nLOCK_RETRIES=3";
+    let file = write_temp_config(config_content, "test_lock_retries_no_timeout.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("LOCK_RETRIES requires LOCK_TIMEOUT"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_invalid_lock_timeout_is_error() {
+    let config_content = "FROM_HOST=localhost\nLOCK_TIMEOUT=soon";
+    let file = write_temp_config(config_content, "test_invalid_lock_timeout.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for LOCK_TIMEOUT"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_lock_annotations_and_group_by_table_parsing() {
+    let config_content =
+        "FROM_HOST=localhost\nTO_HOST=remotehost\nLOCK_ANNOTATIONS=true\nGROUP_BY_TABLE=TRUE";
+    let file = write_temp_config(config_content, "test_lock_annotations.cfg");
+    let config = Config::new(file.clone());
+    assert!(config.lock_annotations);
+    assert!(config.group_by_table);
+    let _ = std::fs::remove_file(file);
+
+    let file = write_temp_config(
+        "FROM_HOST=localhost\nTO_HOST=remotehost",
+        "test_lock_annotations_default.cfg",
+    );
+    let config = Config::new(file.clone());
+    assert!(!config.lock_annotations);
+    assert!(!config.group_by_table);
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_invalid_group_by_table_is_error() {
+    let config_content = "FROM_HOST=localhost\nGROUP_BY_TABLE=sometimes";
+    let file = write_temp_config(config_content, "test_invalid_group_by_table.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for GROUP_BY_TABLE"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_migration_format_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nMIGRATION_FORMAT=golang-migrate\nMIGRATION_NAME=add_orders";
+    let file = write_temp_config(config_content, "test_migration_format.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(config.migration_format, MigrationFormat::GolangMigrate);
+    assert_eq!(config.migration_name, "add_orders");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_invalid_migration_format_is_error() {
+    let config_content = "FROM_HOST=localhost\nMIGRATION_FORMAT=liquibase";
+    let file = write_temp_config(config_content, "test_invalid_migration_format.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("invalid migration format"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_rollback_output_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nROLLBACK_OUTPUT=rollback.sql";
+    let file = write_temp_config(config_content, "test_rollback_output.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(config.rollback_output.as_deref(), Some("rollback.sql"));
+    let _ = std::fs::remove_file(file);
+
+    let file = write_temp_config(
+        "FROM_HOST=localhost\nTO_HOST=remotehost",
+        "test_rollback_output_default.cfg",
+    );
+    assert_eq!(Config::new(file.clone()).rollback_output, None);
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_progress_ledger_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nPROGRESS_LEDGER=true";
+    let file = write_temp_config(config_content, "test_progress_ledger.cfg");
+    assert!(Config::new(file.clone()).progress_ledger);
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nPROGRESS_LEDGER=maybe";
+    let file = write_temp_config(config_content, "test_invalid_progress_ledger.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for PROGRESS_LEDGER"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_fingerprint_guard_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nFINGERPRINT_GUARD=TRUE";
+    let file = write_temp_config(config_content, "test_fingerprint_guard.cfg");
+    assert!(Config::new(file.clone()).fingerprint_guard);
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nFINGERPRINT_GUARD=1";
+    let file = write_temp_config(config_content, "test_invalid_fingerprint_guard.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for FINGERPRINT_GUARD"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_data_checks_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nDATA_CHECKS=TRUE";
+    let file = write_temp_config(config_content, "test_data_checks.cfg");
+    assert!(Config::new(file.clone()).data_checks);
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nDATA_CHECKS=maybe";
+    let file = write_temp_config(config_content, "test_invalid_data_checks.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for DATA_CHECKS"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_include_and_exclude_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost\nINCLUDE=tables:app.*\n\
+                          exclude=tables:app.*_tmp\nEXCLUDE=triggers:*.*.audit_*";
+    let file = write_temp_config(config_content, "test_include_exclude.cfg");
+    let rules: Vec<String> = Config::new(file.clone())
+        .object_filter
+        .rules
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    assert_eq!(
+        rules,
+        vec![
+            "include tables:app.*",
+            "exclude tables:app.*_tmp",
+            "exclude triggers:*.*.audit_*",
+        ]
+    );
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nEXCLUDE=columns:app.*";
+    let file = write_temp_config(config_content, "test_invalid_exclude.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for EXCLUDE"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_redact_secrets_and_secrets_file_parsing() {
+    let config_content = "FROM_HOST=localhost\nTO_HOST=remotehost";
+    let file = write_temp_config(config_content, "test_redact_secrets_default.cfg");
+    let config = Config::new(file.clone());
+    assert!(config.redact_secrets);
+    assert_eq!(config.secrets_file, None);
+    let _ = std::fs::remove_file(file);
+
+    let config_content =
+        "FROM_HOST=localhost\nTO_HOST=remotehost\nREDACT_SECRETS=false\nSECRETS_FILE=secrets.env";
+    let file = write_temp_config(config_content, "test_redact_secrets.cfg");
+    let config = Config::new(file.clone());
+    assert!(!config.redact_secrets);
+    assert_eq!(config.secrets_file.as_deref(), Some("secrets.env"));
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nREDACT_SECRETS=maybe";
+    let file = write_temp_config(config_content, "test_invalid_redact_secrets.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for REDACT_SECRETS"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
+#[test]
+fn test_ssl_settings_parsing() {
+    let config_content = "FROM_HOST=db1\nFROM_SSLMODE=Verify-Full\nFROM_SSLROOTCERT=/etc/pgc/ca.crt\n\
+                          FROM_SSLCERT=client.crt\nFROM_SSLKEY=client.key\nFROM_SSLPASSWORD=Pa=ss\n\
+                          TO_HOST=db2\nTO_SSL=true";
+    let file = write_temp_config(config_content, "test_ssl_settings.cfg");
+    let config = Config::new(file.clone());
+    assert_eq!(
+        config.from.ssl_settings.mode.as_deref(),
+        Some("verify-full")
+    );
+    assert_eq!(
+        config.from.ssl_settings.root_cert.as_deref(),
+        Some("/etc/pgc/ca.crt")
+    );
+    assert_eq!(config.from.ssl_settings.cert.as_deref(), Some("client.crt"));
+    assert_eq!(config.from.ssl_settings.key.as_deref(), Some("client.key"));
+    assert_eq!(config.from.ssl_settings.password.as_deref(), Some("Pa=ss"));
+    assert!(config.to.ssl);
+    assert_eq!(config.to.ssl_settings, SslSettings::default());
+    let _ = std::fs::remove_file(file);
+
+    let config_content = "FROM_HOST=localhost\nTO_SSLMODE=always";
+    let file = write_temp_config(config_content, "test_invalid_sslmode.cfg");
+    let err = Config::load(&file).unwrap_err();
+    assert!(err.contains("Invalid value for TO_SSLMODE"), "{err}");
+    let _ = std::fs::remove_file(file);
+}
+
 #[test]
 #[should_panic]
 fn test_invalid_config_line_panics() {
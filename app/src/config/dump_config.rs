@@ -1,3 +1,5 @@
+use crate::config::connection::{self, ConnectionParams};
+use crate::config::ssl_settings::SslSettings;
 use serde::{Deserialize, Serialize};
 
 // This is a database dump configuration structure.
@@ -15,35 +17,37 @@ pub struct DumpConfig {
     pub database: String,
     // Schema name. Mask allowed. For example: sche*
     pub scheme: String,
-    // Flag of SSL usage
+    // Flag of SSL usage (sslmode=require)
     pub ssl: bool,
+    // sslmode, certificates and key passphrase
+    #[serde(default)]
+    pub ssl_settings: SslSettings,
     // Dump file name
     pub file: String,
 }
 
 impl DumpConfig {
+    // Resolves the connection parameters from these settings, a URL given as
+    // the host, the service file, the PG* environment variables, the password
+    // file and the defaults (see config::connection).
+    pub fn resolve_connection(&self) -> Result<ConnectionParams, String> {
+        connection::resolve(self, &|name| std::env::var(name).ok())
+    }
+
     // Returns the connection string for the database.
     pub fn get_connection_string(&self) -> String {
-        format!(
-            "postgres://{}:{}@{}:{}/{}?sslmode={}",
-            self.user,
-            self.password,
-            self.host,
-            self.port,
-            self.database,
-            if self.ssl { "require" } else { "disable" }
-        )
+        match self.resolve_connection() {
+            Ok(params) => params.url(false),
+            Err(e) => format!("invalid connection settings: {e}"),
+        }
     }
 
     // Returns a masked connection string for the database.
     pub fn get_masked_connection_string(&self) -> String {
-        format!(
-            "postgres://*:*@{}:{}/{}?sslmode={}",
-            self.host,
-            self.port,
-            self.database,
-            if self.ssl { "require" } else { "disable" }
-        )
+        match self.resolve_connection() {
+            Ok(params) => params.url(true),
+            Err(e) => format!("invalid connection settings: {e}"),
+        }
     }
 }
 impl Default for DumpConfig {
@@ -56,6 +60,7 @@ impl Default for DumpConfig {
             database: "postgres".to_string(),
             scheme: "public".to_string(),
             ssl: false,
+            ssl_settings: SslSettings::default(),
             file: "dump.io".to_string(),
         }
     }
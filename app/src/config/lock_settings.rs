@@ -0,0 +1,42 @@
+/// Lock safety settings for production migrations: the `lock_timeout` and
+/// `statement_timeout` set at the top of the script and before each
+/// post-commit statement, and how many times a DDL statement that fails with
+/// `lock_not_available` is retried.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LockSettings {
+    /// Value for `SET lock_timeout` (e.g. `5s`), or `None` to leave it alone.
+    pub lock_timeout: Option<String>,
+    /// Value for `SET statement_timeout` (e.g. `15min`), or `None` to leave
+    /// it alone.
+    pub statement_timeout: Option<String>,
+    /// How many times a DDL statement is retried after `lock_not_available`.
+    /// Zero (default) emits the statements unwrapped.
+    pub retries: u32,
+}
+
+impl LockSettings {
+    /// True when neither timeout is set and retries are off.
+    pub fn is_empty(&self) -> bool {
+        self.lock_timeout.is_none() && self.statement_timeout.is_none() && self.retries == 0
+    }
+}
+
+/// Validates a PostgreSQL duration setting: a non-negative integer followed
+/// by an optional unit (`us`, `ms`, `s`, `min`, `h`, `d`; milliseconds when
+/// omitted). Returns the value normalised to lowercase without spaces.
+pub fn parse_duration(src: &str) -> Result<String, String> {
+    let value = src.trim().to_ascii_lowercase().replace(' ', "");
+    let digits = value.chars().take_while(|c| c.is_ascii_digit()).count();
+    let unit = &value[digits..];
+    if digits == 0 || !matches!(unit, "" | "us" | "ms" | "s" | "min" | "h" | "d") {
+        return Err(format!(
+            "invalid duration '{}'; expected a number with an optional unit (us, ms, s, min, h, d)",
+            src
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+#[path = "lock_settings_tests.rs"]
+mod tests;
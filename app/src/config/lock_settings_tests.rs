@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn parse_duration_accepts_units() {
+    assert_eq!(parse_duration("5s").unwrap(), "5s");
+    assert_eq!(parse_duration("500ms").unwrap(), "500ms");
+    assert_eq!(parse_duration("15 MIN").unwrap(), "15min");
+    assert_eq!(parse_duration("2h").unwrap(), "2h");
+    assert_eq!(parse_duration("1d").unwrap(), "1d");
+    assert_eq!(parse_duration("250us").unwrap(), "250us");
+}
+
+#[test]
+fn parse_duration_defaults_to_milliseconds() {
+    assert_eq!(parse_duration("3000").unwrap(), "3000");
+    assert_eq!(parse_duration("0").unwrap(), "0");
+}
+
+#[test]
+fn parse_duration_rejects_invalid() {
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("s").is_err());
+    assert!(parse_duration("5 seconds").is_err());
+    assert!(parse_duration("-5s").is_err());
+    assert!(parse_duration("1.5s").is_err());
+    assert!(parse_duration("5s'; drop table x; --").is_err());
+}
+
+#[test]
+fn default_is_empty() {
+    assert!(LockSettings::default().is_empty());
+    let settings = LockSettings {
+        retries: 3,
+        ..Default::default()
+    };
+    assert!(!settings.is_empty());
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How the compare result is written: one script file, or a directory of
+/// versioned migration files for a migration framework.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MigrationFormat {
+    /// A single script file (default, current behaviour).
+    #[default]
+    Single,
+    /// Flyway versioned migrations (`V{version}__{name}.sql`).
+    Flyway,
+    /// golang-migrate up/down pairs (`{version}_{name}.up.sql`).
+    GolangMigrate,
+    /// Sqitch deploy/revert/verify scripts plus `sqitch.plan` entries.
+    Sqitch,
+    /// A Liquibase XML changelog, one changeSet per changed object.
+    LiquibaseXml,
+    /// A Liquibase YAML changelog, one changeSet per changed object.
+    LiquibaseYaml,
+}
+
+impl MigrationFormat {
+    /// Whether the format has a place for reverse migrations: down and
+    /// revert scripts, or Liquibase rollbacks.
+    pub fn has_reverse(&self) -> bool {
+        matches!(
+            self,
+            Self::GolangMigrate | Self::Sqitch | Self::LiquibaseXml | Self::LiquibaseYaml
+        )
+    }
+}
+
+impl fmt::Display for MigrationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single => write!(f, "single"),
+            Self::Flyway => write!(f, "flyway"),
+            Self::GolangMigrate => write!(f, "golang-migrate"),
+            Self::Sqitch => write!(f, "sqitch"),
+            Self::LiquibaseXml => write!(f, "liquibase-xml"),
+            Self::LiquibaseYaml => write!(f, "liquibase-yaml"),
+        }
+    }
+}
+
+impl FromStr for MigrationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "single" => Ok(Self::Single),
+            "flyway" => Ok(Self::Flyway),
+            "golang-migrate" => Ok(Self::GolangMigrate),
+            "sqitch" => Ok(Self::Sqitch),
+            "liquibase-xml" => Ok(Self::LiquibaseXml),
+            "liquibase-yaml" => Ok(Self::LiquibaseYaml),
+            _ => Err(format!(
+                "invalid migration format '{}'; valid values: single, flyway, golang-migrate, sqitch, liquibase-xml, liquibase-yaml",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "migration_format_tests.rs"]
+mod tests;
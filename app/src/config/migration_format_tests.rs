@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn from_str_accepts_all_formats() {
+    assert_eq!(
+        "single".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::Single
+    );
+    assert_eq!(
+        "Flyway".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::Flyway
+    );
+    assert_eq!(
+        "golang-migrate".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::GolangMigrate
+    );
+    assert_eq!(
+        "SQITCH".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::Sqitch
+    );
+    assert_eq!(
+        "liquibase-xml".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::LiquibaseXml
+    );
+    assert_eq!(
+        "Liquibase-YAML".parse::<MigrationFormat>().unwrap(),
+        MigrationFormat::LiquibaseYaml
+    );
+}
+
+#[test]
+fn from_str_invalid() {
+    let err = "liquibase-json".parse::<MigrationFormat>().unwrap_err();
+    assert!(err.contains("invalid migration format"));
+}
+
+#[test]
+fn display_round_trips() {
+    for format in [
+        MigrationFormat::Single,
+        MigrationFormat::Flyway,
+        MigrationFormat::GolangMigrate,
+        MigrationFormat::Sqitch,
+        MigrationFormat::LiquibaseXml,
+        MigrationFormat::LiquibaseYaml,
+    ] {
+        assert_eq!(
+            format.to_string().parse::<MigrationFormat>().unwrap(),
+            format
+        );
+    }
+}
+
+#[test]
+fn default_is_single() {
+    assert_eq!(MigrationFormat::default(), MigrationFormat::Single);
+}
+
+#[test]
+fn only_formats_with_a_down_side_have_reverse_migrations() {
+    assert!(!MigrationFormat::Single.has_reverse());
+    assert!(!MigrationFormat::Flyway.has_reverse());
+    assert!(MigrationFormat::GolangMigrate.has_reverse());
+    assert!(MigrationFormat::Sqitch.has_reverse());
+    assert!(MigrationFormat::LiquibaseXml.has_reverse());
+}
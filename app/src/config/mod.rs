@@ -1,3 +1,9 @@
+pub mod column_order_mode;
+pub mod connection;
 pub mod core;
 pub mod dump_config;
 pub mod grants_mode;
+pub mod lock_settings;
+pub mod migration_format;
+pub mod object_filter;
+pub mod ssl_settings;
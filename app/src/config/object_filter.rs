@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Object kind an include/exclude rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterKind {
+    Tables,
+    Views,
+    Routines,
+    Types,
+    Sequences,
+    Triggers,
+    Indexes,
+    Policies,
+    /// The privileges of the matching objects, not the objects themselves.
+    Grants,
+    Extensions,
+    /// Every kind except grants.
+    All,
+}
+
+impl fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tables => write!(f, "tables"),
+            Self::Views => write!(f, "views"),
+            Self::Routines => write!(f, "routines"),
+            Self::Types => write!(f, "types"),
+            Self::Sequences => write!(f, "sequences"),
+            Self::Triggers => write!(f, "triggers"),
+            Self::Indexes => write!(f, "indexes"),
+            Self::Policies => write!(f, "policies"),
+            Self::Grants => write!(f, "grants"),
+            Self::Extensions => write!(f, "extensions"),
+            Self::All => write!(f, "all"),
+        }
+    }
+}
+
+impl FromStr for FilterKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "tables" | "table" => Ok(Self::Tables),
+            "views" | "view" => Ok(Self::Views),
+            "routines" | "routine" | "functions" | "function" | "procedures" | "procedure" => {
+                Ok(Self::Routines)
+            }
+            "types" | "type" => Ok(Self::Types),
+            "sequences" | "sequence" => Ok(Self::Sequences),
+            "triggers" | "trigger" => Ok(Self::Triggers),
+            "indexes" | "index" => Ok(Self::Indexes),
+            "policies" | "policy" => Ok(Self::Policies),
+            "grants" | "grant" => Ok(Self::Grants),
+            "extensions" | "extension" => Ok(Self::Extensions),
+            "all" | "*" => Ok(Self::All),
+            _ => Err(format!(
+                "invalid object kind '{}'; valid values: tables, views, routines, types, \
+                 sequences, triggers, indexes, policies, grants, extensions, all",
+                s
+            )),
+        }
+    }
+}
+
+/// One include or exclude rule: `kind:pattern`, e.g. `tables:app.*_tmp`.
+///
+/// The pattern is shell-style (`*`, `?`) and matches the qualified name the
+/// `show` command lists: `schema.name` for tables, views, types and
+/// sequences, `schema.table.name` for triggers and policies,
+/// `schema.index` for indexes, `schema.name(arguments)` for routines and
+/// the bare name for extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub include: bool,
+    pub kind: FilterKind,
+    pub pattern: String,
+}
+
+impl FilterRule {
+    /// Parses `kind:pattern`; a missing pattern means `*`.
+    pub fn parse(spec: &str, include: bool) -> Result<Self, String> {
+        let (kind, pattern) = spec.split_once(':').unwrap_or((spec, "*"));
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(format!("filter '{spec}' has an empty name pattern"));
+        }
+        Ok(FilterRule {
+            include,
+            kind: kind.parse()?,
+            pattern: pattern.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for FilterRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = if self.include { "include" } else { "exclude" };
+        write!(f, "{action} {}:{}", self.kind, self.pattern)
+    }
+}
+
+/// Include/exclude rules applied to a dump when it is taken and again when
+/// it is compared. An object is kept when no include rule covers its kind
+/// or one of them matches it, and no exclude rule matches it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectFilter {
+    pub rules: Vec<FilterRule>,
+}
+
+impl ObjectFilter {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether an object of `kind` named `name` passes the filter.
+    /// `matches(pattern, name)` decides whether a rule's pattern matches.
+    pub fn keeps(
+        &self,
+        kind: FilterKind,
+        name: &str,
+        matches: impl Fn(&str, &str) -> bool,
+    ) -> bool {
+        let applies = |rule: &&FilterRule| {
+            rule.kind == kind || (rule.kind == FilterKind::All && kind != FilterKind::Grants)
+        };
+        let mut includes = self
+            .rules
+            .iter()
+            .filter(|r| r.include)
+            .filter(applies)
+            .peekable();
+        let included = includes.peek().is_none() || includes.any(|r| matches(&r.pattern, name));
+        included
+            && !self
+                .rules
+                .iter()
+                .filter(|r| !r.include)
+                .filter(applies)
+                .any(|r| matches(&r.pattern, name))
+    }
+}
+
+#[cfg(test)]
+#[path = "object_filter_tests.rs"]
+mod tests;
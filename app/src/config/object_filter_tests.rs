@@ -0,0 +1,70 @@
+use super::*;
+
+fn equals(pattern: &str, name: &str) -> bool {
+    pattern == "*" || pattern == name
+}
+
+fn filter(includes: &[&str], excludes: &[&str]) -> ObjectFilter {
+    let rules = |specs: &[&str], include: bool| {
+        specs
+            .iter()
+            .map(|spec| FilterRule::parse(spec, include).unwrap())
+            .collect::<Vec<_>>()
+    };
+    ObjectFilter {
+        rules: [rules(includes, true), rules(excludes, false)].concat(),
+    }
+}
+
+#[test]
+fn parses_kinds_and_aliases() {
+    assert_eq!("tables".parse::<FilterKind>().unwrap(), FilterKind::Tables);
+    assert_eq!("Table".parse::<FilterKind>().unwrap(), FilterKind::Tables);
+    assert_eq!(
+        "functions".parse::<FilterKind>().unwrap(),
+        FilterKind::Routines
+    );
+    assert_eq!("*".parse::<FilterKind>().unwrap(), FilterKind::All);
+    assert!("columns".parse::<FilterKind>().is_err());
+}
+
+#[test]
+fn parses_rules() {
+    let rule = FilterRule::parse("tables:app.*_tmp", false).unwrap();
+    assert_eq!(rule.kind, FilterKind::Tables);
+    assert_eq!(rule.pattern, "app.*_tmp");
+    assert_eq!(rule.to_string(), "exclude tables:app.*_tmp");
+    assert_eq!(FilterRule::parse("grants", true).unwrap().pattern, "*");
+    assert!(FilterRule::parse("tables:", true).is_err());
+    assert!(FilterRule::parse("rows:x", true).is_err());
+}
+
+#[test]
+fn an_empty_filter_keeps_everything() {
+    let filter = ObjectFilter::default();
+    assert!(filter.is_empty());
+    assert!(filter.keeps(FilterKind::Tables, "app.users", equals));
+}
+
+#[test]
+fn includes_narrow_only_their_kind() {
+    let filter = filter(&["tables:app.users"], &[]);
+    assert!(filter.keeps(FilterKind::Tables, "app.users", equals));
+    assert!(!filter.keeps(FilterKind::Tables, "app.orders", equals));
+    assert!(filter.keeps(FilterKind::Views, "app.orders", equals));
+}
+
+#[test]
+fn excludes_win_over_includes() {
+    let filter = filter(&["tables:*"], &["tables:app.users"]);
+    assert!(!filter.keeps(FilterKind::Tables, "app.users", equals));
+    assert!(filter.keeps(FilterKind::Tables, "app.orders", equals));
+}
+
+#[test]
+fn all_covers_every_kind_but_grants() {
+    let filter = filter(&[], &["all:app.users"]);
+    assert!(!filter.keeps(FilterKind::Tables, "app.users", equals));
+    assert!(!filter.keeps(FilterKind::Views, "app.users", equals));
+    assert!(filter.keeps(FilterKind::Grants, "app.users", equals));
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// The values `sslmode` accepts, as in libpq.
+pub const SSL_MODES: &[&str] = &[
+    "disable",
+    "allow",
+    "prefer",
+    "require",
+    "verify-ca",
+    "verify-full",
+];
+
+/// TLS settings of one side, named after the libpq parameters. A value left
+/// unset is resolved like the other connection settings: from a URL given
+/// as the host, the service file or the `PGSSL*` environment variables.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SslSettings {
+    /// `sslmode`; wins over the `ssl` flag of the dump configuration.
+    pub mode: Option<String>,
+    /// `sslrootcert`: the CA certificates the server certificate is
+    /// verified against with `verify-ca` and `verify-full`.
+    pub root_cert: Option<String>,
+    /// `sslcert`: the client certificate.
+    pub cert: Option<String>,
+    /// `sslkey`: the private key of the client certificate.
+    pub key: Option<String>,
+    /// `sslpassword`: the passphrase of an encrypted (PKCS#8) client key.
+    pub password: Option<String>,
+}
+
+/// Validates an `sslmode` value. Returns it lowercased.
+pub fn parse_ssl_mode(src: &str) -> Result<String, String> {
+    let mode = src.trim().to_ascii_lowercase();
+    if !SSL_MODES.contains(&mode.as_str()) {
+        return Err(format!(
+            "invalid sslmode '{}'; valid values: {}",
+            src,
+            SSL_MODES.join(", ")
+        ));
+    }
+    Ok(mode)
+}
+
+#[cfg(test)]
+#[path = "ssl_settings_tests.rs"]
+mod tests;
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn accepts_every_libpq_ssl_mode() {
+    for mode in SSL_MODES {
+        assert_eq!(parse_ssl_mode(mode).unwrap(), *mode);
+    }
+    assert_eq!(parse_ssl_mode(" Verify-Full ").unwrap(), "verify-full");
+}
+
+#[test]
+fn rejects_unknown_ssl_modes() {
+    let error = parse_ssl_mode("always").unwrap_err();
+    assert!(error.contains("'always'"), "{error}");
+    assert!(error.contains("verify-full"), "{error}");
+}
+
+#[test]
+fn default_leaves_everything_unset() {
+    let settings = SslSettings::default();
+    assert!(settings.mode.is_none() && settings.root_cert.is_none());
+    assert!(settings.cert.is_none() && settings.key.is_none() && settings.password.is_none());
+}
@@ -1,10 +1,14 @@
+use crate::comparer::progress_ledger::LEDGER_TABLE;
+use crate::config::object_filter::ObjectFilter;
 use crate::dump::cast::Cast;
 use crate::dump::collation::Collation;
 use crate::dump::column_dependent::{ColumnDependent, ColumnDependentKind};
 use crate::dump::default_privilege::DefaultPrivilege;
 use crate::dump::event_trigger::EventTrigger;
 use crate::dump::fdw::{ForeignDataWrapper, ForeignServer, UserMapping};
+use crate::dump::filter::apply_filter;
 use crate::dump::foreign_table::{ForeignTable, ForeignTableColumn};
+use crate::dump::metadata::{self, DumpMetadata};
 use crate::dump::operator::Operator;
 use crate::dump::pg_enum::PgEnum;
 use crate::dump::pg_type::{CompositeAttribute, DomainConstraint, PgType};
@@ -12,6 +16,7 @@ use crate::dump::publication::{Publication, Subscription};
 use crate::dump::routine::Routine;
 use crate::dump::rule::Rule;
 use crate::dump::schema::Schema;
+use crate::dump::secrets::redact_secrets;
 use crate::dump::sequence::Sequence;
 use crate::dump::statistic::Statistic;
 use crate::dump::table::{PgCatalogCaps, Table};
@@ -21,8 +26,10 @@ use crate::{config::dump_config::DumpConfig, dump::extension::Extension};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use sqlx::Row;
+use sqlx::postgres::PgConnection;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::postgres::types::Oid;
+use sqlx::{Connection, Executor};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs::File;
@@ -76,6 +83,10 @@ pub struct Dump {
     #[serde(skip_serializing, skip_deserializing)]
     pub configuration: DumpConfig,
 
+    // Format version and provenance of the dump. Serialised first, as the
+    // header of the file.
+    pub metadata: DumpMetadata,
+
     // List of schemas in the dump.
     pub schemas: Vec<Schema>,
 
@@ -174,6 +185,7 @@ impl Dump {
     // Create a new Dump instance.
     pub fn new(config: DumpConfig) -> Self {
         Dump {
+            metadata: DumpMetadata::new(&config),
             configuration: config,
             schemas: Vec::new(),
             extensions: Vec::new(),
@@ -202,8 +214,15 @@ impl Dump {
         }
     }
 
-    // Retrieve the dump from the configuration.
-    pub async fn process(&mut self, max_connections: u32) -> Result<(), Error> {
+    // Retrieve the dump from the configuration, leave out what `filter`
+    // excludes, redact the stored credentials unless `redact` is off and
+    // write it. Returns the excluded objects.
+    pub async fn process(
+        &mut self,
+        max_connections: u32,
+        filter: &ObjectFilter,
+        redact: bool,
+    ) -> Result<Vec<String>, Error> {
         if max_connections < FILL_SIBLING_BRANCH_COUNT {
             eprintln!(
                 "Warning: max_connections ({}) is below the number of parallel \
@@ -214,24 +233,100 @@ impl Dump {
             );
         }
 
-        let pool = PgPoolOptions::new()
-            .max_connections(max_connections)
-            .connect(self.configuration.get_connection_string().as_str())
+        let connection = self
+            .configuration
+            .resolve_connection()
+            .map_err(|e| Error::other(format!("Invalid connection settings: {e}.")))?;
+        let connect_options = connection
+            .connect_options()
+            .map_err(|e| Error::other(format!("Invalid connection settings: {e}.")))?;
+        let connection_error = |e: sqlx::Error| {
+            Error::other(format!(
+                "Failed to connect to database ({}): {}.",
+                connection.url(true),
+                e
+            ))
+        };
+
+        // The fill branches run concurrently on separate connections. The
+        // leader transaction exports its snapshot and every pooled connection
+        // imports it, so all of them see the database at the same instant.
+        let mut leader = PgConnection::connect_with(&connect_options)
             .await
-            .map_err(|e| {
-                Error::other(format!(
-                    "Failed to connect to database ({}): {}.",
-                    self.configuration.get_masked_connection_string(),
-                    e
-                ))
-            })?;
+            .map_err(connection_error)?;
+        let snapshot = Self::export_snapshot(&mut leader).await;
+
+        let mut pool_options = PgPoolOptions::new().max_connections(max_connections);
+        if let Some(snapshot) = snapshot {
+            let import = Self::snapshot_import_statement(&snapshot);
+            pool_options = pool_options
+                .after_connect(move |conn, _| {
+                    let import = import.clone();
+                    Box::pin(async move {
+                        conn.execute(import.as_str()).await?;
+                        Ok(())
+                    })
+                })
+                // A failed query aborts the connection's transaction, and
+                // with it the imported snapshot: drop the connection so the
+                // pool opens a fresh one.
+                .after_release(|conn, _| {
+                    Box::pin(async move { Ok(conn.execute("select 1").await.is_ok()) })
+                });
+        }
+        let pool = pool_options
+            .connect_with(connect_options)
+            .await
+            .map_err(connection_error)?;
 
         // Fill the dump.
         self.fill(&pool).await?;
 
         pool.close().await;
+        leader.execute("commit").await.ok();
+        leader.close().await.ok();
 
-        self.write_to_file(&self.configuration.file)
+        let excluded = apply_filter(self, filter);
+        if redact {
+            redact_secrets(self);
+        }
+        self.write_to_file(&self.configuration.file)?;
+        Ok(excluded)
+    }
+
+    /// Open the leader transaction on `leader` and export its snapshot.
+    /// `None`, with a warning, when that fails (behind a pooler in
+    /// transaction mode, for instance): the dump then falls back to one
+    /// snapshot per query.
+    async fn export_snapshot(leader: &mut PgConnection) -> Option<String> {
+        let exported = async {
+            leader
+                .execute("begin isolation level repeatable read read only")
+                .await?;
+            sqlx::query_scalar::<_, String>("select pg_catalog.pg_export_snapshot()")
+                .fetch_one(&mut *leader)
+                .await
+        }
+        .await;
+        match exported {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                eprintln!(
+                    "Warning: could not export a snapshot ({e}); the dump is not taken at a \
+                     single point in time."
+                );
+                leader.execute("rollback").await.ok();
+                None
+            }
+        }
+    }
+
+    /// The statement a pooled connection runs to join the leader's snapshot.
+    fn snapshot_import_statement(snapshot: &str) -> String {
+        format!(
+            "begin isolation level repeatable read read only; set transaction snapshot '{}';",
+            snapshot.replace('\'', "''")
+        )
     }
 
     /// Persist this dump to `path` as a zip-compressed JSON archive.
@@ -290,11 +385,14 @@ impl Dump {
         // Detect PostgreSQL server version once and pass it down to the
         // per-object-kind futures. The value is a session constant, so an
         // extra round-trip per parallel branch is pure waste.
-        let pg_version: i32 =
-            sqlx::query_scalar("select current_setting('server_version_num')::int4;")
-                .fetch_one(pool)
-                .await
-                .map_err(|e| Error::other(format!("Failed to fetch server_version_num: {e}.")))?;
+        let (pg_version, server_version): (i32, String) = sqlx::query_as(
+            "select current_setting('server_version_num')::int4, current_setting('server_version');",
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| Error::other(format!("Failed to fetch server_version_num: {e}.")))?;
+        self.metadata.server_version = Some(server_version);
+        self.metadata.server_version_num = Some(pg_version);
 
         let types_enums_fut = async {
             let mut types = Vec::new();
@@ -876,6 +974,8 @@ impl Dump {
             if let Some(mut constraints) = constraints_by_type.remove(&pg_type.oid.0) {
                 constraints.sort_by(|a, b| a.name.cmp(&b.name));
                 pg_type.domain_constraints = constraints;
+                // The type was hashed before its constraints were known.
+                pg_type.hash();
             }
         }
 
@@ -1406,6 +1506,8 @@ impl Dump {
         Ok(shell_tables)
     }
 
+    /// The tables of `schema_filter`, without the progress ledger the
+    /// resumable scripts create (see [`crate::comparer::progress_ledger`]).
     fn build_tables_standalone_query(schema_filter: &str) -> String {
         format!(
             "
@@ -1451,6 +1553,7 @@ impl Dump {
                     t.schemaname not in ('pg_catalog', 'information_schema')
                     and t.schemaname in {}
                     and t.tablename not like 'pg_%'
+                    and quote_ident(t.schemaname) || '.' || quote_ident(t.tablename) <> '{LEDGER_TABLE}'
                     and not exists (
                         select 1 from pg_depend ext_dep
                         where ext_dep.classid = 'pg_class'::regclass
@@ -3194,15 +3297,23 @@ impl Dump {
         }
     }
 
-    // Read a dump from a file and deserialize it.
+    // Read a dump from a file and deserialize it. Dumps of an older format
+    // are upgraded first (see [`metadata::upgrade`]); a newer format is an
+    // error.
     pub async fn read_from_file(file: &str) -> Result<Self, Error> {
+        let path = file;
         let file = File::open(file)?;
         let mut zip = zip::ZipArchive::new(file)?;
         let mut dump_file = zip.by_name("dump.io")?;
         let mut serialized_data = String::new();
         dump_file.read_to_string(&mut serialized_data)?;
 
-        let mut dump: Dump = serde_json::from_str(&serialized_data)
+        let mut payload: serde_json::Value = serde_json::from_str(&serialized_data)
+            .map_err(|e| Error::other(format!("Failed to deserialize dump: {e}.")))?;
+        drop(serialized_data);
+        metadata::upgrade(&mut payload)
+            .map_err(|e| Error::other(format!("Failed to read dump {path}: {e}.")))?;
+        let mut dump: Dump = serde_json::from_value(payload)
             .map_err(|e| Error::other(format!("Failed to deserialize dump: {e}.")))?;
 
         // Normalize CRLF -> LF in routine source code so that hashes are
@@ -3234,14 +3345,21 @@ impl Dump {
 
     /// Connect to the database and fill the dump without saving to a file.
     pub async fn inspect(&mut self, max_connections: u32) -> Result<(), Error> {
+        let connection = self
+            .configuration
+            .resolve_connection()
+            .map_err(|e| Error::other(format!("Invalid connection settings: {e}.")))?;
+        let connect_options = connection
+            .connect_options()
+            .map_err(|e| Error::other(format!("Invalid connection settings: {e}.")))?;
         let pool = PgPoolOptions::new()
             .max_connections(max_connections)
-            .connect(self.configuration.get_connection_string().as_str())
+            .connect_with(connect_options)
             .await
             .map_err(|e| {
                 Error::other(format!(
                     "Failed to connect to database ({}): {}.",
-                    self.configuration.get_masked_connection_string(),
+                    connection.url(true),
                     e
                 ))
             })?;
@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::ssl_settings::SslSettings;
 use crate::dump::extension::Extension;
 use crate::dump::routine::Routine;
 use crate::dump::schema::Schema;
@@ -49,6 +50,7 @@ fn empty_dump() -> Dump {
         database: "testdb".to_string(),
         scheme: "public".to_string(),
         ssl: false,
+        ssl_settings: SslSettings::default(),
         file: String::new(),
     })
 }
@@ -642,6 +644,17 @@ fn build_tables_standalone_query_filters_by_pg_class() {
     );
 }
 
+#[test]
+fn build_tables_standalone_query_leaves_out_the_progress_ledger() {
+    let query = Dump::build_tables_standalone_query("('public')");
+    assert!(
+        query.contains(
+            "and quote_ident(t.schemaname) || '.' || quote_ident(t.tablename) <> 'public.pgc_migration_progress'"
+        ),
+        "{query}"
+    );
+}
+
 #[test]
 fn build_regular_views_query_filters_by_pg_class() {
     let query = Dump::build_regular_views_query("('public')");
@@ -1024,3 +1037,76 @@ fn build_sequences_standalone_query_owner_dep_join_is_precise() {
         "sequences: owner-dep join must filter dep.refclassid to 'pg_class'"
     );
 }
+
+#[test]
+fn snapshot_import_statement_joins_the_leader_snapshot() {
+    assert_eq!(
+        Dump::snapshot_import_statement("00000003-0000001B-1"),
+        "begin isolation level repeatable read read only; set transaction snapshot '00000003-0000001B-1';"
+    );
+    assert!(Dump::snapshot_import_statement("a'b").ends_with("snapshot 'a''b';"));
+}
+
+/// Writes `payload` as the `dump.io` entry of a zip, like a dump file.
+fn write_payload(path: &TempPath, payload: &str) {
+    let file = File::create(path.as_str().as_ref()).unwrap();
+    let mut zip = ZipWriter::new(file);
+    zip.start_file("dump.io", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(payload.as_bytes()).unwrap();
+    zip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn write_to_file_stores_the_header_first() {
+    let mut dump = empty_dump();
+    dump.metadata.server_version = Some("15.4".to_string());
+    dump.metadata.server_version_num = Some(150004);
+    let path = TempPath::new("pgc_dump_header", "zip");
+    dump.write_to_file(&path.as_str()).unwrap();
+
+    let mut zip = zip::ZipArchive::new(File::open(path.as_str().as_ref()).unwrap()).unwrap();
+    let mut payload = String::new();
+    zip.by_name("dump.io")
+        .unwrap()
+        .read_to_string(&mut payload)
+        .unwrap();
+    assert!(
+        payload.starts_with("{\"metadata\":{\"format_version\":"),
+        "{payload}"
+    );
+
+    let restored = Dump::read_from_file(&path.as_str()).await.unwrap();
+    assert_eq!(restored.metadata, dump.metadata);
+}
+
+#[tokio::test]
+async fn read_from_file_upgrades_dumps_without_a_header() {
+    let path = TempPath::new("pgc_dump_v1", "zip");
+    write_payload(
+        &path,
+        r#"{"schemas":[],"extensions":[],"types":[],"enums":[],"sequences":[],"routines":[],"tables":[],"views":[]}"#,
+    );
+    let dump = Dump::read_from_file(&path.as_str()).await.unwrap();
+    assert_eq!(dump.metadata.format_version, metadata::FORMAT_VERSION);
+    assert_eq!(dump.metadata.pgc_version, None);
+    assert_eq!(dump.metadata.major_version(), None);
+}
+
+#[tokio::test]
+async fn read_from_file_refuses_newer_formats() {
+    let path = TempPath::new("pgc_dump_future", "zip");
+    write_payload(
+        &path,
+        &format!(
+            r#"{{"metadata":{{"format_version":{}}},"schemas":[]}}"#,
+            metadata::FORMAT_VERSION + 1
+        ),
+    );
+    let error = Dump::read_from_file(&path.as_str())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("upgrade pgc"), "{error}");
+    assert!(error.contains(path.as_str().as_ref()), "{error}");
+}
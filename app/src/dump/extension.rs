@@ -39,10 +39,17 @@ impl Extension {
     }
 
     /// Returns a string to create the extension.
+    /// An empty version (a dump built from SQL files that name no `VERSION`)
+    /// omits the clause so the server installs its default version.
     pub fn get_script(&self) -> String {
+        let version = if self.version.is_empty() {
+            String::new()
+        } else {
+            format!(" version '{}'", self.version)
+        };
         format!(
-            "create extension if not exists {} with schema {} version '{}';",
-            self.name, self.schema, self.version
+            "create extension if not exists {} with schema {}{};",
+            self.name, self.schema, version
         )
         .with_empty_lines()
     }
@@ -53,9 +60,11 @@ impl Extension {
     }
 
     /// Returns a script to alter this extension to match the target.
+    /// An empty target version (a dump built from SQL files that name no
+    /// `VERSION`) leaves the installed version alone.
     pub fn get_alter_script(&self, target: &Extension) -> String {
         let mut script = String::new();
-        if self.version != target.version {
+        if self.version != target.version && !target.version.is_empty() {
             script.append_block(&format!(
                 "alter extension {} update to '{}';",
                 target.name, target.version
@@ -250,10 +250,7 @@ fn test_edge_cases_empty_strings() {
 
     // Scripts should work with empty strings
     let script = extension.get_script();
-    assert_eq!(
-        script,
-        "create extension if not exists  with schema  version '';\n\n"
-    );
+    assert_eq!(script, "create extension if not exists  with schema ;\n\n");
 
     let drop_script = extension.get_drop_script();
     assert_eq!(drop_script, "drop extension if exists ;\n\n");
@@ -371,3 +368,44 @@ fn test_extension_hash_case_sensitivity() {
 
     assert_ne!(lower_schema.hash(), upper_schema.hash());
 }
+
+#[test]
+fn test_get_alter_script_updates_version() {
+    let from = Extension::new(
+        "hstore".to_string(),
+        "1.7".to_string(),
+        "public".to_string(),
+    );
+    let to = Extension::new(
+        "hstore".to_string(),
+        "1.8".to_string(),
+        "public".to_string(),
+    );
+
+    assert_eq!(
+        from.get_alter_script(&to),
+        "alter extension hstore update to '1.8';\n\n"
+    );
+}
+
+#[test]
+fn test_get_script_omits_empty_version() {
+    let extension = Extension::new("hstore".to_string(), String::new(), "public".to_string());
+
+    assert_eq!(
+        extension.get_script(),
+        "create extension if not exists hstore with schema public;\n\n"
+    );
+}
+
+#[test]
+fn test_get_alter_script_keeps_version_when_target_has_none() {
+    let from = Extension::new(
+        "hstore".to_string(),
+        "1.8".to_string(),
+        "public".to_string(),
+    );
+    let to = Extension::new("hstore".to_string(), String::new(), "public".to_string());
+
+    assert_eq!(from.get_alter_script(&to), "");
+}
@@ -0,0 +1,215 @@
+//! Applies the include/exclude rules of an [`ObjectFilter`] to a dump.
+//!
+//! Objects that do not pass are removed with what cannot exist without
+//! them: a table takes its owned sequences, rules, extended statistics and
+//! column dependencies along. A `grants` rule keeps the object and clears
+//! its privileges instead, so the comparer sees none on either side.
+
+use crate::config::object_filter::{FilterKind, ObjectFilter};
+use crate::dump::core::Dump;
+use crate::dump::show::name_matches;
+
+/// Removes the objects of `dump` that `filter` does not keep and returns
+/// them as `kind name` lines, in dump order.
+pub fn apply_filter(dump: &mut Dump, filter: &ObjectFilter) -> Vec<String> {
+    let mut excluded = Vec::new();
+    if filter.is_empty() {
+        return excluded;
+    }
+    let keeps =
+        |kind: FilterKind, name: &str| filter.keeps(kind, name, |p, n| name_matches(p, n, false));
+    let keeps_routine =
+        |name: &str| filter.keeps(FilterKind::Routines, name, |p, n| name_matches(p, n, true));
+    let mut retain = |kind: &str, name: String, keep: bool| {
+        if !keep {
+            excluded.push(format!("{kind} {name}"));
+        }
+        keep
+    };
+
+    dump.extensions.retain(|e| {
+        retain(
+            "extension",
+            e.name.clone(),
+            keeps(FilterKind::Extensions, &e.name),
+        )
+    });
+
+    let mut dropped_tables = Vec::new();
+    dump.tables.retain(|t| {
+        let name = format!("{}.{}", t.schema, t.name);
+        let keep = retain("table", name.clone(), keeps(FilterKind::Tables, &name));
+        if !keep {
+            dropped_tables.push((t.schema.clone(), t.name.clone()));
+        }
+        keep
+    });
+    let dropped = |schema: &str, table: &str| {
+        dropped_tables
+            .iter()
+            .any(|(s, t)| s == schema && t == table)
+    };
+    dump.sequences.retain(|s| {
+        let owned_by_dropped = match (&s.owned_by_schema, &s.owned_by_table) {
+            (Some(schema), Some(table)) => dropped(schema, table),
+            _ => false,
+        };
+        let name = format!("{}.{}", s.schema, s.name);
+        // A sequence owned by an excluded table goes with it, silently.
+        !owned_by_dropped
+            && retain(
+                "sequence",
+                name.clone(),
+                keeps(FilterKind::Sequences, &name),
+            )
+    });
+    dump.rules.retain(|r| !dropped(&r.schema, &r.table_name));
+    dump.statistics
+        .retain(|s| !dropped(&s.table_schema, &s.table_name));
+    dump.column_dependents
+        .retain(|d| !dropped(&d.schema, &d.table));
+
+    for table in &mut dump.tables {
+        let before = (
+            table.indexes.len(),
+            table.triggers.len(),
+            table.policies.len(),
+        );
+        let (schema, name) = (table.schema.clone(), table.name.clone());
+        table.indexes.retain(|i| {
+            let index = format!("{}.{}", i.schema, i.name);
+            retain("index", index.clone(), keeps(FilterKind::Indexes, &index))
+        });
+        table.triggers.retain(|t| {
+            let trigger = format!("{schema}.{name}.{}", t.name);
+            retain(
+                "trigger",
+                trigger.clone(),
+                keeps(FilterKind::Triggers, &trigger),
+            )
+        });
+        table.policies.retain(|p| {
+            let policy = format!("{}.{}.{}", p.schema, p.table, p.name);
+            retain(
+                "policy",
+                policy.clone(),
+                keeps(FilterKind::Policies, &policy),
+            )
+        });
+        if before
+            != (
+                table.indexes.len(),
+                table.triggers.len(),
+                table.policies.len(),
+            )
+        {
+            table.hash();
+        }
+    }
+    dump.views.retain(|v| {
+        let name = format!("{}.{}", v.schema, v.name);
+        retain("view", name.clone(), keeps(FilterKind::Views, &name))
+    });
+    dump.routines.retain(|r| {
+        let name = format!("{}.{}({})", r.schema, r.name, r.arguments);
+        retain("routine", name.clone(), keeps_routine(&name))
+    });
+    let mut dropped_types = Vec::new();
+    dump.types.retain(|t| {
+        let name = format!("{}.{}", t.schema, t.typname);
+        let keep = retain("type", name.clone(), keeps(FilterKind::Types, &name));
+        if !keep {
+            dropped_types.push(t.oid);
+        }
+        keep
+    });
+    dump.enums.retain(|e| !dropped_types.contains(&e.enumtypid));
+
+    // The comparer recreates the dependents of a changed column; an
+    // excluded index is not one of them.
+    let dropped_indexes: Vec<String> = excluded
+        .iter()
+        .filter_map(|line| line.strip_prefix("index "))
+        .map(str::to_string)
+        .collect();
+    dump.column_dependents
+        .retain(|d| !dropped_indexes.contains(&format!("{}.{}", d.dep_schema, d.dep_name)));
+
+    clear_grants(dump, filter, &mut excluded);
+    excluded
+}
+
+/// Clears the privileges of the objects a `grants` rule does not keep.
+fn clear_grants(dump: &mut Dump, filter: &ObjectFilter, excluded: &mut Vec<String>) {
+    let mut clear = |kind: &str, name: String, acl: &mut Vec<String>, routine: bool| {
+        if acl.is_empty()
+            || filter.keeps(FilterKind::Grants, &name, |p, n| {
+                name_matches(p, n, routine)
+            })
+        {
+            return false;
+        }
+        acl.clear();
+        excluded.push(format!("grants on {kind} {name}"));
+        true
+    };
+    for schema in &mut dump.schemas {
+        if clear("schema", schema.name.clone(), &mut schema.acl, false) {
+            schema.hash();
+        }
+    }
+    for table in &mut dump.tables {
+        let name = format!("{}.{}", table.schema, table.name);
+        let mut changed = clear("table", name.clone(), &mut table.acl, false);
+        for column in &mut table.columns {
+            // Column privileges follow the table's rule.
+            if !column.acl.is_empty()
+                && !filter.keeps(FilterKind::Grants, &name, |p, n| name_matches(p, n, false))
+            {
+                column.acl.clear();
+                changed = true;
+            }
+        }
+        if changed {
+            table.hash();
+        }
+    }
+    for view in &mut dump.views {
+        if clear(
+            "view",
+            format!("{}.{}", view.schema, view.name),
+            &mut view.acl,
+            false,
+        ) {
+            view.hash();
+        }
+    }
+    for sequence in &mut dump.sequences {
+        let name = format!("{}.{}", sequence.schema, sequence.name);
+        if clear("sequence", name, &mut sequence.acl, false) {
+            sequence.hash();
+        }
+    }
+    for routine in &mut dump.routines {
+        let name = format!("{}.{}({})", routine.schema, routine.name, routine.arguments);
+        if clear("routine", name, &mut routine.acl, true) {
+            routine.hash();
+        }
+    }
+    for pg_type in &mut dump.types {
+        let name = format!("{}.{}", pg_type.schema, pg_type.typname);
+        if clear("type", name, &mut pg_type.acl, false) {
+            pg_type.hash();
+        }
+    }
+    for table in &mut dump.foreign_tables {
+        let name = format!("{}.{}", table.schema, table.name);
+        if clear("foreign table", name, &mut table.acl, false) {
+            table.hash();
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "filter_tests.rs"]
+mod tests;
@@ -0,0 +1,131 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::config::object_filter::FilterRule;
+use crate::dump::sql_import::import_sources;
+
+const SCHEMA: &str = "
+CREATE SCHEMA app;
+GRANT USAGE ON SCHEMA app TO reader;
+CREATE TYPE app.mood AS ENUM ('sad', 'ok');
+CREATE TABLE app.users (id serial PRIMARY KEY, mood app.mood, name text);
+CREATE INDEX users_name_idx ON app.users (name);
+CREATE TABLE app.events_tmp (id serial PRIMARY KEY);
+GRANT SELECT ON app.users TO reader;
+CREATE FUNCTION app.audit() RETURNS trigger LANGUAGE plpgsql AS $$ begin return new; end $$;
+CREATE FUNCTION app.add(a integer) RETURNS integer LANGUAGE sql AS $$ select a $$;
+CREATE FUNCTION app.add(a integer, b integer) RETURNS integer LANGUAGE sql AS $$ select a + b $$;
+CREATE TRIGGER users_audit AFTER INSERT ON app.users FOR EACH ROW EXECUTE FUNCTION app.audit();
+CREATE POLICY own ON app.users USING (true);
+CREATE VIEW app.names AS SELECT name FROM app.users;
+";
+
+fn dump() -> Dump {
+    let config = DumpConfig {
+        scheme: "app".to_string(),
+        ..DumpConfig::default()
+    };
+    let (dump, skipped) = import_sources(config, &[("a.sql".to_string(), SCHEMA.to_string())]);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    dump
+}
+
+fn filter(includes: &[&str], excludes: &[&str]) -> ObjectFilter {
+    let rules = |specs: &[&str], include: bool| {
+        specs
+            .iter()
+            .map(|spec| FilterRule::parse(spec, include).unwrap())
+            .collect::<Vec<_>>()
+    };
+    ObjectFilter {
+        rules: [rules(includes, true), rules(excludes, false)].concat(),
+    }
+}
+
+#[test]
+fn an_empty_filter_changes_nothing() {
+    let mut filtered = dump();
+    assert!(apply_filter(&mut filtered, &ObjectFilter::default()).is_empty());
+    assert_eq!(
+        serde_json::to_value(&filtered).unwrap(),
+        serde_json::to_value(dump()).unwrap()
+    );
+}
+
+#[test]
+fn an_excluded_table_takes_its_sequences_along() {
+    let mut dump = dump();
+    let excluded = apply_filter(&mut dump, &filter(&[], &["tables:app.*_tmp"]));
+    assert_eq!(excluded, vec!["table app.events_tmp"]);
+    assert_eq!(dump.tables.len(), 1);
+    assert_eq!(dump.sequences.len(), 1);
+    assert_eq!(dump.sequences[0].name, "users_id_seq");
+}
+
+#[test]
+fn removes_triggers_indexes_and_policies_from_their_table() {
+    let mut dump = dump();
+    let users = |dump: &Dump| {
+        dump.tables
+            .iter()
+            .find(|t| t.name == "users")
+            .cloned()
+            .unwrap()
+    };
+    let hash = users(&dump).hash;
+    let excluded = apply_filter(
+        &mut dump,
+        &filter(
+            &[],
+            &["triggers:*.*_audit", "indexes:app.*_name_idx", "policies:*"],
+        ),
+    );
+    assert_eq!(
+        excluded,
+        vec![
+            "index app.users_name_idx",
+            "trigger app.users.users_audit",
+            "policy app.users.own",
+        ]
+    );
+    let users = users(&dump);
+    assert!(users.triggers.is_empty() && users.policies.is_empty());
+    assert_eq!(users.indexes.len(), 0);
+    assert_ne!(users.hash, hash);
+}
+
+#[test]
+fn includes_keep_only_the_matching_objects_of_their_kind() {
+    let mut dump = dump();
+    let excluded = apply_filter(&mut dump, &filter(&["routines:app.add(integer)"], &[]));
+    assert_eq!(
+        excluded,
+        vec![
+            "routine app.audit()",
+            "routine app.add(a integer, b integer)"
+        ]
+    );
+    assert_eq!(dump.routines.len(), 1);
+    assert_eq!(dump.views.len(), 1);
+}
+
+#[test]
+fn an_excluded_type_takes_its_labels_along() {
+    let mut dump = dump();
+    assert_eq!(dump.enums.len(), 2);
+    apply_filter(&mut dump, &filter(&[], &["types:app.mood"]));
+    assert!(dump.types.is_empty());
+    assert!(dump.enums.is_empty());
+}
+
+#[test]
+fn grants_rules_clear_privileges_and_keep_the_objects() {
+    let mut dump = dump();
+    let excluded = apply_filter(&mut dump, &filter(&[], &["grants:*"]));
+    assert_eq!(
+        excluded,
+        vec!["grants on schema app", "grants on table app.users"]
+    );
+    assert_eq!(dump.tables.len(), 2);
+    assert!(dump.schemas[0].acl.is_empty());
+    assert!(dump.tables.iter().all(|t| t.acl.is_empty()));
+}
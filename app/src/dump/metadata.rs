@@ -0,0 +1,153 @@
+//! The header record of a dump file: its format version and where it came
+//! from.
+//!
+//! A dump is a JSON object whose `metadata` member is this header. Dumps
+//! written before the header existed are format 1. [`upgrade`] brings an
+//! older payload up to [`FORMAT_VERSION`] one explicit migration at a time
+//! before it is deserialised, and refuses a payload from a newer pgc.
+
+use crate::config::dump_config::DumpConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::io::Error;
+
+/// Format version written by this build.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// `MIGRATIONS[n - 1]` turns a format `n` payload into format `n + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v1_to_v2];
+
+const _: () = assert!(
+    MIGRATIONS.len() as u64 == FORMAT_VERSION - 1,
+    "every format version below FORMAT_VERSION needs a migration"
+);
+
+/// Format version and provenance of a dump. The provenance fields are
+/// `None` when unknown: a dump built from SQL files has no server, and a
+/// dump upgraded from format 1 recorded none of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpMetadata {
+    pub format_version: u64,
+    /// pgc version that wrote the dump.
+    pub pgc_version: Option<String>,
+    /// `server_version` of the source database, e.g. `15.4`.
+    pub server_version: Option<String>,
+    /// `server_version_num` of the source database, e.g. `150004`.
+    pub server_version_num: Option<i32>,
+    /// `host:port` of the source server, or the SQL files the dump was
+    /// built from.
+    pub source: Option<String>,
+    pub database: Option<String>,
+    /// Schema filter the dump was taken with.
+    pub scheme: Option<String>,
+    /// When the dump was taken (RFC 3339, UTC).
+    pub created_at: Option<String>,
+}
+
+impl DumpMetadata {
+    /// Header of a dump taken now with `config`. The source and database
+    /// come from the resolved connection, so a URL given as the host never
+    /// leaves its credentials in the dump.
+    pub fn new(config: &DumpConfig) -> Self {
+        let known = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let connection = config.resolve_connection().ok();
+        DumpMetadata {
+            format_version: FORMAT_VERSION,
+            pgc_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            server_version: None,
+            server_version_num: None,
+            source: connection.as_ref().map(|c| c.address()),
+            database: connection
+                .map(|c| c.database)
+                .or_else(|| known(&config.database)),
+            scheme: known(&config.scheme),
+            created_at: Some(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        }
+    }
+
+    /// Major version of the source server: `15` for 15.4, `9.6` for 9.6.24.
+    pub fn major_version(&self) -> Option<String> {
+        let num = self.server_version_num?;
+        Some(if num >= 100_000 {
+            (num / 10_000).to_string()
+        } else {
+            format!("{}.{}", num / 10_000, num / 100 % 100)
+        })
+    }
+
+    /// One line describing the dump, for console output.
+    pub fn summary(&self) -> String {
+        let unknown = |value: &Option<String>| value.as_deref().unwrap_or("unknown").to_string();
+        format!(
+            "format {}, pgc {}, PostgreSQL {}, source {}, database {}, scheme {}, taken {}",
+            self.format_version,
+            unknown(&self.pgc_version),
+            unknown(&self.server_version),
+            unknown(&self.source),
+            unknown(&self.database),
+            unknown(&self.scheme),
+            unknown(&self.created_at),
+        )
+    }
+}
+
+/// Warning for a comparison of dumps taken from different PostgreSQL major
+/// versions, whose catalogs can differ in ways that are not schema changes.
+pub fn major_version_warning(from: &DumpMetadata, to: &DumpMetadata) -> Option<String> {
+    let (from, to) = (from.major_version()?, to.major_version()?);
+    (from != to).then(|| {
+        format!(
+            "the dumps come from different PostgreSQL major versions (from: {from}, to: {to}); \
+             differences in catalog output may show up as changes"
+        )
+    })
+}
+
+/// Upgrades the JSON `payload` of a dump to [`FORMAT_VERSION`] in place and
+/// returns the format version it was written with.
+pub fn upgrade(payload: &mut Value) -> Result<u64, Error> {
+    let dump = payload
+        .as_object_mut()
+        .ok_or_else(|| Error::other("the dump is not a JSON object"))?;
+    let version = match dump.get("metadata") {
+        None => 1,
+        Some(metadata) => metadata
+            .get("format_version")
+            .and_then(Value::as_u64)
+            .filter(|&version| version >= 1)
+            .ok_or_else(|| Error::other("the dump header has no valid format_version"))?,
+    };
+    if version > FORMAT_VERSION {
+        return Err(Error::other(format!(
+            "the dump has format version {version}, but pgc {} reads format {FORMAT_VERSION} \
+             and older; upgrade pgc to read it",
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+    for from in version..FORMAT_VERSION {
+        MIGRATIONS[(from - 1) as usize](dump);
+        dump["metadata"]["format_version"] = json!(from + 1);
+    }
+    Ok(version)
+}
+
+/// Format 1 had no header. Its provenance is unknown.
+fn v1_to_v2(dump: &mut Map<String, Value>) {
+    dump.insert(
+        "metadata".to_string(),
+        json!({
+            "format_version": 2,
+            "pgc_version": null,
+            "server_version": null,
+            "server_version_num": null,
+            "source": null,
+            "database": null,
+            "scheme": null,
+            "created_at": null,
+        }),
+    );
+}
+
+#[cfg(test)]
+#[path = "metadata_tests.rs"]
+mod tests;
@@ -7,7 +7,9 @@ pub mod default_privilege;
 pub mod event_trigger;
 pub mod extension;
 pub mod fdw;
+pub mod filter;
 pub mod foreign_table;
+pub mod metadata;
 pub mod operator;
 pub mod pg_enum;
 pub mod pg_type;
@@ -15,7 +17,10 @@ pub mod publication;
 pub mod routine;
 pub mod rule;
 pub mod schema;
+pub mod secrets;
 pub mod sequence;
+pub mod show;
+pub mod sql_import;
 pub mod statistic;
 pub mod table;
 pub mod table_column;
@@ -24,4 +29,5 @@ pub mod table_index;
 pub mod table_policy;
 pub mod table_trigger;
 pub mod text_search;
+pub mod tree;
 pub mod view;
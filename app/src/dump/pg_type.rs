@@ -503,6 +503,38 @@ impl PgType {
 
     /// Returns a string to alter the existing user-defined type to match the target definition.
     pub fn get_alter_script(&self, target: &PgType, use_drop: bool) -> String {
+        self.build_alter_script(target, use_drop, false)
+    }
+
+    /// Like [`PgType::get_alter_script`] but leaves out the new domain CHECK
+    /// constraints the production output path adds `NOT VALID` itself (see
+    /// [`PgType::domain_check_additions`]).
+    pub fn get_alter_script_for_production(&self, target: &PgType, use_drop: bool) -> String {
+        self.build_alter_script(target, use_drop, true)
+    }
+
+    /// CHECK constraints of the `target` domain that do not exist on `self`.
+    /// Adding one validates every column of the domain type while holding
+    /// locks on their tables, so the production path adds it `NOT VALID` and
+    /// validates it after commit.
+    pub fn domain_check_additions<'a>(&self, target: &'a PgType) -> Vec<&'a DomainConstraint> {
+        if self.typtype as u8 != b'd' || target.typtype as u8 != b'd' {
+            return Vec::new();
+        }
+        target
+            .domain_constraints
+            .iter()
+            .filter(|c| {
+                c.definition
+                    .trim_start()
+                    .to_ascii_uppercase()
+                    .starts_with("CHECK")
+                    && !self.domain_constraints.iter().any(|old| old.name == c.name)
+            })
+            .collect()
+    }
+
+    fn build_alter_script(&self, target: &PgType, use_drop: bool, for_production: bool) -> String {
         if self.schema != target.schema || self.typname != target.typname {
             return format!(
                 "-- Cannot alter type {}.{} because target is {}.{}\n",
@@ -643,6 +675,11 @@ impl PgType {
                     .map(|constraint| (constraint.name.as_str(), constraint))
                     .collect();
                 let mut replaced_or_added = BTreeSet::new();
+                let deferred_checks = if for_production {
+                    self.domain_check_additions(target)
+                } else {
+                    Vec::new()
+                };
 
                 for (name, current_constraint) in &current_constraints {
                     match target_constraints.get(name) {
@@ -696,7 +733,9 @@ impl PgType {
                         continue;
                     }
 
-                    if !current_constraints.contains_key(name) {
+                    if !current_constraints.contains_key(name)
+                        && !deferred_checks.iter().any(|c| c.name == **name)
+                    {
                         statements.push(format!(
                             "alter domain {}.{} add constraint {} {};",
                             self.schema,
@@ -707,7 +746,9 @@ impl PgType {
                     }
                 }
 
-                if statements.is_empty() {
+                if statements.is_empty() && !deferred_checks.is_empty() {
+                    String::new()
+                } else if statements.is_empty() {
                     format!(
                         "-- Domain {}.{} requires no supported changes.\n",
                         self.schema, self.typname
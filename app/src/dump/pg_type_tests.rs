@@ -198,6 +198,31 @@ alter domain public.amount add constraint \"FreshConstraint\" check (value <> 0)
     assert_eq!(script, expected);
 }
 
+#[test]
+fn get_alter_script_for_production_leaves_out_new_domain_checks() {
+    let mut current = base_pg_type('d');
+    current.typname = "amount".to_string();
+    current.domain_constraints = vec![DomainConstraint {
+        name: "ValueCheck".to_string(),
+        definition: "CHECK (VALUE > 0)".to_string(),
+    }];
+    let mut target = current.clone();
+    target.domain_constraints.push(DomainConstraint {
+        name: "FreshConstraint".to_string(),
+        definition: "CHECK (VALUE <> 13)".to_string(),
+    });
+
+    let additions = current.domain_check_additions(&target);
+    assert_eq!(additions.len(), 1);
+    assert_eq!(additions[0].name, "FreshConstraint");
+    assert!(
+        current
+            .get_alter_script(&target, true)
+            .contains("add constraint \"FreshConstraint\"")
+    );
+    assert_eq!(current.get_alter_script_for_production(&target, true), "");
+}
+
 #[test]
 fn composite_get_script_generates_create_statement() {
     let mut pg_type = base_pg_type('c');
@@ -0,0 +1,266 @@
+//! Redaction of the credentials the catalog stores in plain text: the
+//! connection string of a subscription and the options of foreign servers
+//! and user mappings.
+//!
+//! A password-like value is replaced with a placeholder
+//! `pgc:redacted:<NAME>:<hash>`. `NAME` is the environment variable that
+//! holds the real value when a script is generated (for instance
+//! `PGC_SECRET_USER_MAPPING_REMOTE_APP_PASSWORD`), and the hash is a digest
+//! of the value, so a changed secret still shows up as a change when two
+//! redacted dumps are compared. [`Secrets::inject`] puts the real values
+//! back into a script, from the environment or a secrets file.
+
+use crate::dump::core::Dump;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Error;
+
+/// Start of every placeholder.
+pub const PLACEHOLDER_PREFIX: &str = "pgc:redacted:";
+
+/// Start of the environment variable name of every secret.
+const NAME_PREFIX: &str = "PGC_SECRET_";
+
+/// Whether the option or connection parameter `key` holds a credential.
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.trim().to_ascii_lowercase();
+    key == "pwd"
+        || ["password", "passwd", "secret", "token", "api_key", "apikey"]
+            .iter()
+            .any(|word| key.contains(word))
+}
+
+/// The environment variable name of the secret `key` of `object`, e.g.
+/// `PGC_SECRET_SERVER_REMOTE_PASSWORD` for `server remote`.
+pub fn secret_name(object: &str, key: &str) -> String {
+    let mut name = String::from(NAME_PREFIX);
+    for part in [object, key] {
+        if !name.ends_with('_') {
+            name.push('_');
+        }
+        for c in part.trim().chars() {
+            if c.is_ascii_alphanumeric() {
+                name.push(c.to_ascii_uppercase());
+            } else if !name.ends_with('_') && c != '"' {
+                name.push('_');
+            }
+        }
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+fn placeholder(object: &str, key: &str, value: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+    format!(
+        "{PLACEHOLDER_PREFIX}{}:{}",
+        secret_name(object, key),
+        &digest[..16]
+    )
+}
+
+/// Replaces the password-like values of catalog options (`key=value`).
+/// Returns whether anything was replaced.
+pub fn redact_options(options: &mut [String], object: &str) -> bool {
+    let mut changed = false;
+    for option in options.iter_mut() {
+        let Some((key, value)) = option.split_once('=') else {
+            continue;
+        };
+        if is_secret_key(key) && !value.starts_with(PLACEHOLDER_PREFIX) {
+            *option = format!("{key}={}", placeholder(object, key, value));
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Replaces the password-like parameters of a libpq connection string,
+/// written as `key=value` pairs or as a `postgresql://` URI.
+pub fn redact_conninfo(conninfo: &str, object: &str) -> String {
+    let redact = |key: &str, value: &str| {
+        if is_secret_key(key) && !value.starts_with(PLACEHOLDER_PREFIX) {
+            placeholder(object, key, value)
+        } else {
+            value.to_string()
+        }
+    };
+    if let Some((scheme, rest)) = conninfo.split_once("://") {
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let authority_end = rest.find('/').unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(authority_end);
+        let authority = match authority.rsplit_once('@') {
+            Some((userinfo, host)) => match userinfo.split_once(':') {
+                Some((user, password)) => format!("{user}:{}@{host}", redact("password", password)),
+                None => authority.to_string(),
+            },
+            None => authority.to_string(),
+        };
+        let mut result = format!("{scheme}://{authority}{path}");
+        if let Some(query) = query {
+            let parameters: Vec<String> = query
+                .split('&')
+                .map(|parameter| match parameter.split_once('=') {
+                    Some((key, value)) => format!("{key}={}", redact(key, value)),
+                    None => parameter.to_string(),
+                })
+                .collect();
+            result.push('?');
+            result.push_str(&parameters.join("&"));
+        }
+        return result;
+    }
+
+    // key = value pairs; a value may be single-quoted with backslash escapes.
+    let chars: Vec<char> = conninfo.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let key_start = i;
+        while i < chars.len() && chars[i] != '=' && !chars[i].is_whitespace() {
+            i += 1;
+        }
+        let key: String = chars[key_start..i].iter().collect();
+        while i < chars.len() && (chars[i].is_whitespace() || chars[i] == '=') {
+            i += 1;
+        }
+        let separator: String = chars[key_start + key.chars().count()..i].iter().collect();
+        let value_start = i;
+        let mut value = String::new();
+        if i < chars.len() && chars[i] == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                value.push(chars[i]);
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+        } else {
+            while i < chars.len() && !chars[i].is_whitespace() {
+                value.push(chars[i]);
+                i += 1;
+            }
+        }
+        result.push_str(&key);
+        result.push_str(&separator);
+        if is_secret_key(&key) && !value.starts_with(PLACEHOLDER_PREFIX) {
+            result.push_str(&placeholder(object, &key, &value));
+        } else {
+            result.extend(&chars[value_start..i]);
+        }
+    }
+    result
+}
+
+/// Redacts the secrets of every subscription, foreign server and user
+/// mapping of `dump`. Returns the number of objects changed.
+pub fn redact_secrets(dump: &mut Dump) -> usize {
+    let mut changed = 0;
+    for subscription in &mut dump.subscriptions {
+        let object = format!("subscription {}", subscription.name);
+        let connection = redact_conninfo(&subscription.connection, &object);
+        if connection != subscription.connection {
+            subscription.connection = connection;
+            subscription.hash();
+            changed += 1;
+        }
+    }
+    for server in &mut dump.foreign_servers {
+        if redact_options(&mut server.options, &format!("server {}", server.name)) {
+            server.hash();
+            changed += 1;
+        }
+    }
+    for mapping in &mut dump.user_mappings {
+        let object = format!("user mapping {} {}", mapping.server_name, mapping.username);
+        if redact_options(&mut mapping.options, &object) {
+            mapping.hash();
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// Values for the placeholders of a script: the environment variable named
+/// by the placeholder, else the secrets file.
+#[derive(Debug, Clone, Default)]
+pub struct Secrets {
+    values: HashMap<String, String>,
+}
+
+impl Secrets {
+    /// Reads a secrets file of `NAME=value` lines; empty lines and lines
+    /// starting with `#` are skipped.
+    pub fn load(file: &str) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(file)
+            .map_err(|e| Error::other(format!("Error reading secrets file {file}: {e}")))?;
+        let mut values = HashMap::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // The line is not echoed: it may hold a secret.
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(Error::other(format!(
+                    "Invalid line {} in secrets file {file}: expected NAME=value",
+                    number + 1
+                )));
+            };
+            values.insert(name.trim().to_string(), value.to_string());
+        }
+        Ok(Secrets { values })
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        std::env::var(name)
+            .ok()
+            .or_else(|| self.values.get(name).cloned())
+    }
+
+    /// Replaces the placeholders in `script` that have a value, escaped for
+    /// the SQL string literal they stand in. Returns the script and the
+    /// names of the placeholders left without a value.
+    pub fn inject(&self, script: &str) -> (String, Vec<String>) {
+        let mut result = String::with_capacity(script.len());
+        let mut missing = Vec::new();
+        let mut rest = script;
+        while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+            result.push_str(&rest[..start]);
+            let token = &rest[start..];
+            let end = PLACEHOLDER_PREFIX.len()
+                + token[PLACEHOLDER_PREFIX.len()..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+                    .unwrap_or(token.len() - PLACEHOLDER_PREFIX.len());
+            let name = token[PLACEHOLDER_PREFIX.len()..end]
+                .split(':')
+                .next()
+                .unwrap_or_default();
+            match self.value(name) {
+                Some(value) => result.push_str(&value.replace('\'', "''")),
+                None => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                    result.push_str(&token[..end]);
+                }
+            }
+            rest = &token[end..];
+        }
+        result.push_str(rest);
+        (result, missing)
+    }
+}
+
+#[cfg(test)]
+#[path = "secrets_tests.rs"]
+mod tests;
@@ -0,0 +1,191 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::dump::fdw::{ForeignServer, UserMapping};
+use crate::dump::publication::Subscription;
+
+fn secrets(values: &[(&str, &str)]) -> Secrets {
+    Secrets {
+        values: values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn recognises_password_like_keys() {
+    for key in [
+        "password",
+        "PASSWORD",
+        "sslpassword",
+        "pwd",
+        "api_key",
+        "client_secret",
+    ] {
+        assert!(is_secret_key(key), "{key}");
+    }
+    for key in ["host", "user", "dbname", "sslkey", "port"] {
+        assert!(!is_secret_key(key), "{key}");
+    }
+}
+
+#[test]
+fn names_secrets_after_their_object_and_key() {
+    assert_eq!(
+        secret_name("server remote", "password"),
+        "PGC_SECRET_SERVER_REMOTE_PASSWORD"
+    );
+    assert_eq!(
+        secret_name("user mapping \"Remote db\" app", "password"),
+        "PGC_SECRET_USER_MAPPING_REMOTE_DB_APP_PASSWORD"
+    );
+}
+
+#[test]
+fn redacts_keyword_conninfo() {
+    let redacted = redact_conninfo(
+        "host=db port=5432 password='hunter 2\\'s' dbname=shop user = app password = plain",
+        "subscription s",
+    );
+    assert!(
+        !redacted.contains("plain") && !redacted.contains("hunter"),
+        "{redacted}"
+    );
+    assert!(
+        redacted.starts_with(
+            "host=db port=5432 password=pgc:redacted:PGC_SECRET_SUBSCRIPTION_S_PASSWORD:"
+        ),
+        "{redacted}"
+    );
+    assert!(
+        redacted.contains(" dbname=shop user = app password = pgc:redacted:"),
+        "{redacted}"
+    );
+    // Redacting twice changes nothing.
+    assert_eq!(redact_conninfo(&redacted, "subscription s"), redacted);
+}
+
+#[test]
+fn redacts_uri_conninfo() {
+    let redacted = redact_conninfo(
+        "postgresql://app:hunter2@db:5432/shop?sslmode=require&password=other",
+        "subscription s",
+    );
+    assert!(
+        !redacted.contains("hunter2") && !redacted.contains("other"),
+        "{redacted}"
+    );
+    assert!(
+        redacted.starts_with("postgresql://app:pgc:redacted:"),
+        "{redacted}"
+    );
+    assert!(
+        redacted.contains("@db:5432/shop?sslmode=require&password=pgc:redacted:"),
+        "{redacted}"
+    );
+    assert_eq!(
+        redact_conninfo("postgresql://app@db/shop", "subscription s"),
+        "postgresql://app@db/shop"
+    );
+}
+
+#[test]
+fn placeholders_differ_when_the_secret_changes() {
+    let one = redact_conninfo("password=one", "subscription s");
+    let two = redact_conninfo("password=two", "subscription s");
+    assert_ne!(one, two);
+    assert_eq!(one, redact_conninfo("password=one", "subscription s"));
+}
+
+#[test]
+fn redacts_the_secrets_of_a_dump() {
+    let mut dump = Dump::new(DumpConfig::default());
+    let mut subscription = Subscription {
+        name: "s".to_string(),
+        owner: String::new(),
+        connection: "host=db password=hunter2".to_string(),
+        publications: vec!["p".to_string()],
+        enabled: true,
+        comment: None,
+        hash: None,
+    };
+    subscription.hash();
+    dump.subscriptions.push(subscription);
+    dump.foreign_servers.push(ForeignServer {
+        name: "remote".to_string(),
+        owner: String::new(),
+        fdw_name: "postgres_fdw".to_string(),
+        server_type: None,
+        server_version: None,
+        options: vec!["host=db".to_string()],
+        comment: None,
+        hash: None,
+    });
+    dump.user_mappings.push(UserMapping {
+        server_name: "remote".to_string(),
+        username: "app".to_string(),
+        options: vec!["user=app".to_string(), "password=hunter2".to_string()],
+        hash: None,
+    });
+    let hash = dump.subscriptions[0].hash.clone();
+
+    assert_eq!(redact_secrets(&mut dump), 2);
+    let json = serde_json::to_string(&dump).unwrap();
+    assert!(!json.contains("hunter2"), "{json}");
+    assert_ne!(dump.subscriptions[0].hash, hash);
+    assert_eq!(dump.user_mappings[0].options[0], "user=app");
+    assert!(
+        dump.user_mappings[0].options[1]
+            .starts_with("password=pgc:redacted:PGC_SECRET_USER_MAPPING_REMOTE_APP_PASSWORD:")
+    );
+    assert_eq!(redact_secrets(&mut dump), 0);
+}
+
+#[test]
+fn injects_known_values_and_reports_the_rest() {
+    let script = format!(
+        "CREATE USER MAPPING FOR app SERVER remote OPTIONS (password '{}');\n\
+         ALTER SUBSCRIPTION s CONNECTION 'host=db password={}';",
+        redact_conninfo("password=x", "user mapping remote app").trim_start_matches("password="),
+        redact_conninfo("password=y", "subscription s").trim_start_matches("password="),
+    );
+    let (injected, missing) =
+        secrets(&[("PGC_SECRET_USER_MAPPING_REMOTE_APP_PASSWORD", "it's")]).inject(&script);
+    assert!(
+        injected.contains("OPTIONS (password 'it''s');"),
+        "{injected}"
+    );
+    assert!(
+        injected.contains(
+            "CONNECTION 'host=db password=pgc:redacted:PGC_SECRET_SUBSCRIPTION_S_PASSWORD:"
+        ),
+        "{injected}"
+    );
+    assert_eq!(missing, vec!["PGC_SECRET_SUBSCRIPTION_S_PASSWORD"]);
+}
+
+#[test]
+fn loads_a_secrets_file() {
+    let path = std::env::temp_dir().join(format!("pgc_secrets_{}.env", std::process::id()));
+    std::fs::write(
+        &path,
+        "# secrets\n\nPGC_SECRET_SERVER_REMOTE_PASSWORD=a=b\n",
+    )
+    .unwrap();
+    let secrets = Secrets::load(path.to_str().unwrap()).unwrap();
+    assert_eq!(
+        secrets
+            .values
+            .get("PGC_SECRET_SERVER_REMOTE_PASSWORD")
+            .map(String::as_str),
+        Some("a=b")
+    );
+
+    std::fs::write(&path, "hunter2\n").unwrap();
+    let error = Secrets::load(path.to_str().unwrap())
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("Invalid line 1"), "{error}");
+    assert!(!error.contains("hunter2"), "{error}");
+    let _ = std::fs::remove_file(path);
+}
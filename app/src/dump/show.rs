@@ -0,0 +1,469 @@
+//! The `show` command: what a dump contains, without unpacking it.
+//!
+//! Without an object spec it prints the dump header and the number of
+//! objects of every kind. A spec names a kind and a pattern:
+//!
+//! - `tables` or `tables:app.*` lists the objects of a kind (the plural)
+//!   whose name matches, with their hashes;
+//! - `table:public.users` or `function:app.f(int)` prints the DDL of the
+//!   matching objects of a kind (the singular).
+//!
+//! Patterns are shell-style (`*`, `?`) and match the qualified name, with or
+//! without identifier quotes. A routine pattern may end in an argument list,
+//! compared by type (`int` matches `integer`); `(*)` or no list matches
+//! every overload.
+
+use crate::dump::core::Dump;
+use crate::dump::sql_import::argument_types;
+use crate::utils::string_extensions::glob_match;
+
+/// Object kinds, singular and plural.
+const KINDS: &[(&str, &str)] = &[
+    ("schema", "schemas"),
+    ("extension", "extensions"),
+    ("type", "types"),
+    ("sequence", "sequences"),
+    ("function", "functions"),
+    ("procedure", "procedures"),
+    ("table", "tables"),
+    ("column", "columns"),
+    ("constraint", "constraints"),
+    ("index", "indexes"),
+    ("trigger", "triggers"),
+    ("policy", "policies"),
+    ("view", "views"),
+    ("foreign_table", "foreign_tables"),
+    ("statistic", "statistics"),
+    ("rule", "rules"),
+    ("event_trigger", "event_triggers"),
+    ("collation", "collations"),
+    ("ts_config", "ts_configs"),
+    ("ts_dict", "ts_dicts"),
+    ("cast", "casts"),
+    ("operator", "operators"),
+    ("default_privilege", "default_privileges"),
+    ("publication", "publications"),
+    ("subscription", "subscriptions"),
+    ("foreign_data_wrapper", "foreign_data_wrappers"),
+    ("server", "servers"),
+    ("user_mapping", "user_mappings"),
+];
+
+/// One object of a dump: its kind (singular), qualified name, hash and the
+/// DDL that creates it, built on demand.
+pub struct DumpObject<'a> {
+    pub kind: &'static str,
+    pub name: String,
+    pub hash: Option<String>,
+    script: Box<dyn Fn() -> String + 'a>,
+}
+
+impl DumpObject<'_> {
+    pub fn script(&self) -> String {
+        (self.script)()
+    }
+}
+
+fn object<'a>(
+    kind: &'static str,
+    name: String,
+    hash: Option<String>,
+    script: impl Fn() -> String + 'a,
+) -> DumpObject<'a> {
+    DumpObject {
+        kind,
+        name,
+        hash,
+        script: Box::new(script),
+    }
+}
+
+/// Every object of `dump`, kind by kind in [`KINDS`] order.
+pub fn objects(dump: &Dump) -> Vec<DumpObject<'_>> {
+    let mut objects = Vec::new();
+    for schema in &dump.schemas {
+        objects.push(object(
+            "schema",
+            schema.name.clone(),
+            schema.hash.clone(),
+            || schema.get_script(),
+        ));
+    }
+    for extension in &dump.extensions {
+        objects.push(object(
+            "extension",
+            extension.name.clone(),
+            Some(extension.hash()),
+            || extension.get_script(),
+        ));
+    }
+    for pg_type in &dump.types {
+        objects.push(object(
+            "type",
+            format!("{}.{}", pg_type.schema, pg_type.typname),
+            pg_type.hash.clone(),
+            || pg_type.get_script(),
+        ));
+    }
+    for sequence in &dump.sequences {
+        objects.push(object(
+            "sequence",
+            format!("{}.{}", sequence.schema, sequence.name),
+            sequence.hash.clone(),
+            || sequence.get_script(),
+        ));
+    }
+    for routine in &dump.routines {
+        let kind = if routine.kind.eq_ignore_ascii_case("procedure") {
+            "procedure"
+        } else {
+            "function"
+        };
+        objects.push(object(
+            kind,
+            format!("{}.{}({})", routine.schema, routine.name, routine.arguments),
+            routine.hash.clone(),
+            || routine.get_script(),
+        ));
+    }
+    for table in &dump.tables {
+        objects.push(object(
+            "table",
+            format!("{}.{}", table.schema, table.name),
+            table.hash.clone(),
+            || {
+                let mut script = table.get_script();
+                script.push_str(&table.get_foreign_key_script());
+                script
+            },
+        ));
+    }
+    for table in &dump.tables {
+        for column in &table.columns {
+            objects.push(object(
+                "column",
+                format!("{}.{}.{}", table.schema, table.name, column.name),
+                None,
+                || column.get_script(),
+            ));
+        }
+    }
+    for table in &dump.tables {
+        for constraint in &table.constraints {
+            objects.push(object(
+                "constraint",
+                format!("{}.{}.{}", table.schema, table.name, constraint.name),
+                None,
+                || constraint.get_script(),
+            ));
+        }
+    }
+    for table in &dump.tables {
+        for index in &table.indexes {
+            objects.push(object(
+                "index",
+                format!("{}.{}", index.schema, index.name),
+                None,
+                || index.get_script(),
+            ));
+        }
+    }
+    for table in &dump.tables {
+        for trigger in &table.triggers {
+            objects.push(object(
+                "trigger",
+                format!("{}.{}.{}", table.schema, table.name, trigger.name),
+                None,
+                || trigger.get_script(&table.schema, &table.name),
+            ));
+        }
+    }
+    for table in &dump.tables {
+        for policy in &table.policies {
+            objects.push(object(
+                "policy",
+                format!("{}.{}.{}", policy.schema, policy.table, policy.name),
+                None,
+                || policy.get_script(),
+            ));
+        }
+    }
+    for view in &dump.views {
+        objects.push(object(
+            "view",
+            format!("{}.{}", view.schema, view.name),
+            view.hash.clone(),
+            || view.get_script(),
+        ));
+    }
+    for table in &dump.foreign_tables {
+        objects.push(object(
+            "foreign_table",
+            format!("{}.{}", table.schema, table.name),
+            table.hash.clone(),
+            || table.get_script(),
+        ));
+    }
+    for statistic in &dump.statistics {
+        objects.push(object(
+            "statistic",
+            format!("{}.{}", statistic.schema, statistic.name),
+            statistic.hash.clone(),
+            || statistic.get_script(),
+        ));
+    }
+    for rule in &dump.rules {
+        objects.push(object(
+            "rule",
+            format!("{}.{}.{}", rule.schema, rule.table_name, rule.rule_name),
+            rule.hash.clone(),
+            || rule.get_script(),
+        ));
+    }
+    for trigger in &dump.event_triggers {
+        objects.push(object(
+            "event_trigger",
+            trigger.name.clone(),
+            trigger.hash.clone(),
+            || trigger.get_script(),
+        ));
+    }
+    for collation in &dump.collations {
+        objects.push(object(
+            "collation",
+            format!("{}.{}", collation.schema, collation.name),
+            collation.hash.clone(),
+            || collation.get_script(),
+        ));
+    }
+    for config in &dump.ts_configs {
+        objects.push(object(
+            "ts_config",
+            format!("{}.{}", config.schema, config.name),
+            config.hash.clone(),
+            || config.get_script(),
+        ));
+    }
+    for dict in &dump.ts_dicts {
+        objects.push(object(
+            "ts_dict",
+            format!("{}.{}", dict.schema, dict.name),
+            dict.hash.clone(),
+            || dict.get_script(),
+        ));
+    }
+    for cast in &dump.casts {
+        objects.push(object(
+            "cast",
+            format!("({} as {})", cast.source_type, cast.target_type),
+            cast.hash.clone(),
+            || cast.get_script(),
+        ));
+    }
+    for operator in &dump.operators {
+        objects.push(object(
+            "operator",
+            format!(
+                "{}.{}({}, {})",
+                operator.schema,
+                operator.name,
+                operator.left_type.as_deref().unwrap_or("none"),
+                operator.right_type.as_deref().unwrap_or("none")
+            ),
+            operator.hash.clone(),
+            || operator.get_script(),
+        ));
+    }
+    for privilege in &dump.default_privileges {
+        let on = match privilege.object_type.as_str() {
+            "r" => "tables",
+            "S" => "sequences",
+            "f" => "functions",
+            "T" => "types",
+            "n" => "schemas",
+            other => other,
+        };
+        let name = if privilege.schema_name.is_empty() {
+            format!("{}/{on}", privilege.role_name)
+        } else {
+            format!("{}/{}/{on}", privilege.role_name, privilege.schema_name)
+        };
+        objects.push(object(
+            "default_privilege",
+            name,
+            privilege.hash.clone(),
+            || privilege.get_script(),
+        ));
+    }
+    for publication in &dump.publications {
+        objects.push(object(
+            "publication",
+            publication.name.clone(),
+            publication.hash.clone(),
+            || publication.get_script(),
+        ));
+    }
+    for subscription in &dump.subscriptions {
+        objects.push(object(
+            "subscription",
+            subscription.name.clone(),
+            subscription.hash.clone(),
+            || subscription.get_script(),
+        ));
+    }
+    for wrapper in &dump.foreign_data_wrappers {
+        objects.push(object(
+            "foreign_data_wrapper",
+            wrapper.name.clone(),
+            wrapper.hash.clone(),
+            || wrapper.get_script(),
+        ));
+    }
+    for server in &dump.foreign_servers {
+        objects.push(object(
+            "server",
+            server.name.clone(),
+            server.hash.clone(),
+            || server.get_script(),
+        ));
+    }
+    for mapping in &dump.user_mappings {
+        objects.push(object(
+            "user_mapping",
+            format!("{}@{}", mapping.username, mapping.server_name),
+            mapping.hash.clone(),
+            || mapping.get_script(),
+        ));
+    }
+    objects
+}
+
+/// The number of objects of every kind in `dump`, including the ones only
+/// kept for the comparer (column dependencies).
+pub fn counts(dump: &Dump) -> Vec<(&'static str, usize)> {
+    let objects = objects(dump);
+    let mut counts: Vec<(&'static str, usize)> = KINDS
+        .iter()
+        .map(|&(kind, plural)| (plural, objects.iter().filter(|o| o.kind == kind).count()))
+        .collect();
+    counts.push(("enum_labels", dump.enums.len()));
+    counts.push(("column_dependents", dump.column_dependents.len()));
+    counts
+}
+
+/// What `spec` asks for: the kind, whether to list (a plural kind) or print
+/// the DDL, and the name pattern.
+fn parse_spec(spec: &str) -> Result<(&'static str, bool, &str), String> {
+    let (kind, pattern) = spec.split_once(':').unwrap_or((spec, "*"));
+    let kind = kind.trim().to_lowercase();
+    for &(singular, plural) in KINDS {
+        if kind == plural {
+            return Ok((singular, true, pattern));
+        }
+        if kind == singular {
+            if pattern == "*" && !spec.contains(':') {
+                return Err(format!(
+                    "{singular} needs a name, e.g. {singular}:schema.name; use {plural} to list them"
+                ));
+            }
+            return Ok((singular, false, pattern));
+        }
+    }
+    let known: Vec<&str> = KINDS.iter().map(|&(_, plural)| plural).collect();
+    Err(format!(
+        "unknown object kind {kind}; expected one of {} (or the singular)",
+        known.join(", ")
+    ))
+}
+
+/// Whether `object` matches `pattern`.
+fn matches(object: &DumpObject<'_>, pattern: &str) -> bool {
+    name_matches(
+        pattern,
+        &object.name,
+        matches!(object.kind, "function" | "procedure"),
+    )
+}
+
+/// Whether the qualified `name` matches `pattern` (see the module
+/// documentation). A `routine` name ends in its argument list.
+pub(crate) fn name_matches(pattern: &str, name: &str, routine: bool) -> bool {
+    let glob = |pattern: &str, name: &str| {
+        glob_match(pattern, name) || glob_match(&pattern.replace('"', ""), &name.replace('"', ""))
+    };
+    if !routine {
+        return glob(pattern, name);
+    }
+    let (name, arguments) = name.split_once('(').unwrap_or((name, ")"));
+    let Some((name_pattern, wanted)) = pattern.split_once('(') else {
+        return glob(pattern, name);
+    };
+    if !glob(name_pattern, name) {
+        return false;
+    }
+    let wanted = wanted.trim_end().strip_suffix(')').unwrap_or(wanted);
+    if wanted.trim() == "*" {
+        return true;
+    }
+    let arguments = arguments.strip_suffix(')').unwrap_or(arguments);
+    match (argument_types(wanted), argument_types(arguments)) {
+        (Some(wanted), Some(actual)) => wanted == actual,
+        _ => false,
+    }
+}
+
+/// The text the `show` command prints for `spec` (see the module
+/// documentation); `None` prints the dump header and object counts.
+pub fn show(dump: &Dump, spec: Option<&str>) -> Result<String, String> {
+    let Some(spec) = spec else {
+        let mut out = format!("Dump: {}\n\nObjects:\n", dump.metadata.summary());
+        for (kind, count) in counts(dump) {
+            out.push_str(&format!("  {kind:<22} {count}\n"));
+        }
+        return Ok(out);
+    };
+    let (kind, list, pattern) = parse_spec(spec)?;
+    let found: Vec<DumpObject<'_>> = objects(dump)
+        .into_iter()
+        .filter(|o| o.kind == kind && matches(o, pattern))
+        .collect();
+    if list {
+        let width = found.iter().map(|o| o.name.len()).max().unwrap_or(0);
+        let plural = KINDS
+            .iter()
+            .find(|&&(singular, _)| singular == kind)
+            .map_or(kind, |&(_, plural)| plural);
+        let mut out = if pattern == "*" {
+            format!("{plural} ({}):\n", found.len())
+        } else {
+            format!("{plural} {pattern} ({}):\n", found.len())
+        };
+        for object in &found {
+            out.push_str(&format!(
+                "  {:<width$}  {}\n",
+                object.name,
+                object.hash.as_deref().unwrap_or("-")
+            ));
+        }
+        return Ok(out);
+    }
+    if found.is_empty() {
+        return Err(format!("no {kind} matches {pattern}"));
+    }
+    let mut out = String::new();
+    for object in &found {
+        out.push_str(&format!(
+            "-- {} {} (hash: {})\n",
+            object.kind,
+            object.name,
+            object.hash.as_deref().unwrap_or("-")
+        ));
+        out.push_str(object.script().trim_end());
+        out.push_str("\n\n");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+#[path = "show_tests.rs"]
+mod tests;
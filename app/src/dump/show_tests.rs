@@ -0,0 +1,112 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::config::ssl_settings::SslSettings;
+use crate::dump::sql_import::import_sources;
+
+const SCHEMA: &str = "
+CREATE SCHEMA app;
+CREATE TYPE app.mood AS ENUM ('sad', 'ok');
+CREATE TABLE app.users (
+    id serial PRIMARY KEY,
+    mood app.mood,
+    name text
+);
+CREATE TABLE app.users_tmp (id integer);
+CREATE TABLE app.\"Odd\" (id integer);
+CREATE INDEX users_name_idx ON app.users (name);
+CREATE FUNCTION app.f(a integer) RETURNS integer LANGUAGE sql AS $$ select a $$;
+CREATE FUNCTION app.f(a text) RETURNS text LANGUAGE sql AS $$ select a $$;
+CREATE PROCEDURE app.p() LANGUAGE sql AS $$ select 1 $$;
+";
+
+fn dump() -> Dump {
+    let config = DumpConfig {
+        host: String::new(),
+        port: String::new(),
+        user: "postgres".to_string(),
+        password: String::new(),
+        database: "offline".to_string(),
+        scheme: "app".to_string(),
+        ssl: false,
+        ssl_settings: SslSettings::default(),
+        file: String::new(),
+    };
+    let (dump, skipped) = import_sources(config, &[("a.sql".to_string(), SCHEMA.to_string())]);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    dump
+}
+
+#[test]
+fn counts_every_kind() {
+    let dump = dump();
+    let counts = counts(&dump);
+    assert_eq!(counts.len(), KINDS.len() + 2);
+    let count = |kind: &str| counts.iter().find(|(k, _)| *k == kind).unwrap().1;
+    assert_eq!(count("schemas"), 1);
+    assert_eq!(count("tables"), 3);
+    assert_eq!(count("columns"), 5);
+    assert_eq!(count("indexes"), 1);
+    assert_eq!(count("functions"), 2);
+    assert_eq!(count("procedures"), 1);
+    assert_eq!(count("enum_labels"), 2);
+    assert_eq!(count("casts"), 0);
+
+    let text = show(&dump, None).unwrap();
+    assert!(text.starts_with("Dump: format 2, pgc "), "{text}");
+    assert!(text.contains("  tables                 3\n"), "{text}");
+}
+
+#[test]
+fn lists_objects_by_pattern_with_hashes() {
+    let dump = dump();
+    let text = show(&dump, Some("tables:app.users*")).unwrap();
+    let users = dump.tables.iter().find(|t| t.name == "users").unwrap();
+    assert!(text.starts_with("tables app.users* (2):\n"), "{text}");
+    assert!(
+        text.contains(&format!(
+            "  app.users      {}\n",
+            users.hash.as_deref().unwrap()
+        )),
+        "{text}"
+    );
+    assert!(text.contains("  app.users_tmp  "), "{text}");
+
+    let text = show(&dump, Some("tables")).unwrap();
+    assert!(text.starts_with("tables (3):\n"), "{text}");
+    let text = show(&dump, Some("tables:app.Odd")).unwrap();
+    assert!(text.contains("app.\"Odd\""), "{text}");
+    let text = show(&dump, Some("indexes")).unwrap();
+    assert!(text.contains("app.users_name_idx  -\n"), "{text}");
+}
+
+#[test]
+fn prints_the_ddl_of_matching_objects() {
+    let dump = dump();
+    let text = show(&dump, Some("table:app.users")).unwrap();
+    assert!(text.starts_with("-- table app.users (hash: "), "{text}");
+    assert!(text.contains("create table app.users ("), "{text}");
+    assert!(!text.contains("users_tmp"), "{text}");
+
+    let text = show(&dump, Some("function:app.f(int4)")).unwrap();
+    assert!(text.contains("app.f(a integer)"), "{text}");
+    assert!(!text.contains("a text"), "{text}");
+    let text = show(&dump, Some("function:app.f")).unwrap();
+    assert_eq!(text.matches("-- function").count(), 2, "{text}");
+    let text = show(&dump, Some("function:app.f(*)")).unwrap();
+    assert_eq!(text.matches("-- function").count(), 2, "{text}");
+    let text = show(&dump, Some("procedure:app.p()")).unwrap();
+    assert!(text.contains("procedure app.p()"), "{text}");
+}
+
+#[test]
+fn rejects_unknown_kinds_and_missing_objects() {
+    let dump = dump();
+    let error = show(&dump, Some("tablez")).unwrap_err();
+    assert!(error.starts_with("unknown object kind tablez"), "{error}");
+    let error = show(&dump, Some("table")).unwrap_err();
+    assert!(error.contains("use tables to list them"), "{error}");
+    let error = show(&dump, Some("table:app.missing")).unwrap_err();
+    assert_eq!(error, "no table matches app.missing");
+    let error = show(&dump, Some("function:app.f(bigint)")).unwrap_err();
+    assert_eq!(error, "no function matches app.f(bigint)");
+}
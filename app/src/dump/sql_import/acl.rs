@@ -0,0 +1,560 @@
+//! `GRANT`, `REVOKE` and `ALTER DEFAULT PRIVILEGES`.
+
+use super::Importer;
+use super::statements::{list_items, quote_ident, unquote};
+use crate::comparer::scanner::Statement;
+use crate::dump::default_privilege::DefaultPrivilege;
+
+impl Importer {
+    /// Replaces `old` with `new` in an ACL, as `ALTER ... OWNER TO` does.
+    pub(super) fn reassign_acl(acl: &mut [String], old: &str, new: &str) {
+        let (old, new) = (acl_role(&unquote(old)), acl_role(&unquote(new)));
+        for item in acl.iter_mut() {
+            if let Some((grantee, rest)) = item.split_once('=')
+                && let Some((privileges, grantor)) = rest.rsplit_once('/')
+            {
+                let swap = |role: &str| {
+                    if role == old {
+                        new.clone()
+                    } else {
+                        role.to_string()
+                    }
+                };
+                *item = format!("{}={}/{}", swap(grantee), privileges, swap(grantor));
+            }
+        }
+    }
+
+    pub(super) fn alter_default_privileges(&mut self, st: &Statement) -> Result<(), String> {
+        let mut k = 3;
+        let mut roles = Vec::new();
+        let mut schemas = Vec::new();
+        loop {
+            if st.skip(k, &["for", "role"]) != k || st.skip(k, &["for", "user"]) != k {
+                k += 1;
+                loop {
+                    let role = self.role_at(st, k + 1).ok_or("missing role name")?;
+                    roles.push(quote_ident(&role));
+                    k += 2;
+                    if !st.is_punct(k, ",") {
+                        break;
+                    }
+                }
+            } else if st.skip(k, &["in", "schema"]) != k {
+                k += 1;
+                loop {
+                    let schema = st.relation(&[k + 1]).ok_or("missing schema name")?.key;
+                    schemas.push(quote_ident(&schema));
+                    k += 2;
+                    if !st.is_punct(k, ",") {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        if roles.is_empty() {
+            roles.push(quote_ident(&self.owner));
+        }
+        if schemas.is_empty() {
+            schemas.push(String::new());
+        }
+        let is_grant = st.is_word(k, "grant");
+        if !is_grant && !st.is_word(k, "revoke") {
+            return Err("GRANT or REVOKE expected".to_string());
+        }
+        let AclStatement {
+            all,
+            privileges,
+            change,
+            on,
+            ..
+        } = self.acl_statement(st, k, is_grant)?;
+        let (object_type, owner_default, public_default) =
+            match st.text(on + 1).to_lowercase().as_str() {
+                "tables" => ("r", TABLE_PRIVILEGES, None),
+                "sequences" => ("S", "rwU", None),
+                "functions" | "routines" => ("f", "X", Some("X")),
+                "types" => ("T", "U", Some("U")),
+                "schemas" => ("n", "UC", None),
+                other => {
+                    return Err(format!(
+                        "default privileges ON {} are not supported",
+                        other.to_uppercase()
+                    ));
+                }
+            };
+        let letters = if all {
+            owner_default
+        } else {
+            privileges.as_str()
+        };
+        for role in &roles {
+            for schema in &schemas {
+                let position = self.dump.default_privileges.iter().position(|p| {
+                    p.role_name == *role && p.schema_name == *schema && p.object_type == object_type
+                });
+                let mut acl = position
+                    .map(|p| self.dump.default_privileges[p].acl.clone())
+                    .unwrap_or_default();
+                // Entries for one schema add to the global defaults, so
+                // only the global ones start from the built-in ACL.
+                let (owner_default, public_default) = if schema.is_empty() {
+                    (owner_default, public_default)
+                } else {
+                    ("", None)
+                };
+                change.apply(&mut acl, role, owner_default, public_default, letters);
+                let mut built_in = Vec::new();
+                if let Some(public) = public_default {
+                    built_in.push(format!("={public}/{}", acl_role(&unquote(role))));
+                }
+                if !owner_default.is_empty() {
+                    let owner = acl_role(&unquote(role));
+                    built_in.push(format!("{owner}={owner_default}/{owner}"));
+                }
+                let unchanged = acl.is_empty() || acl == built_in;
+                match (position, unchanged) {
+                    (Some(p), true) => {
+                        self.dump.default_privileges.remove(p);
+                    }
+                    (Some(p), false) => {
+                        self.dump.default_privileges[p].acl = acl;
+                        self.dump.default_privileges[p].hash();
+                    }
+                    (None, false) => {
+                        let mut privilege = DefaultPrivilege {
+                            role_name: role.clone(),
+                            schema_name: schema.clone(),
+                            object_type: object_type.to_string(),
+                            acl,
+                            hash: None,
+                        };
+                        privilege.hash();
+                        self.dump.default_privileges.push(privilege);
+                    }
+                    (None, true) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The role named at `i`, with `CURRENT_USER` and friends taken as the
+    /// importing role.
+    pub(super) fn role_at(&self, st: &Statement, i: usize) -> Option<String> {
+        if ["current_user", "session_user", "current_role"]
+            .iter()
+            .any(|w| st.is_word(i, w))
+        {
+            return Some(self.owner.clone());
+        }
+        st.relation(&[i]).map(|r| r.key)
+    }
+
+    /// The privileges, grantees and `ON` position of the GRANT or REVOKE
+    /// starting at `start`.
+    fn acl_statement(
+        &self,
+        st: &Statement,
+        start: usize,
+        is_grant: bool,
+    ) -> Result<AclStatement, String> {
+        let mut i = start + 1;
+        let option_only = !is_grant && st.skip(i, &["grant", "option", "for"]) != i;
+        if option_only {
+            i += 3;
+        }
+        let on = st
+            .find_word(i, "on")
+            .ok_or("role membership is not modelled")?;
+        // Privilege letters per item, with column lists kept apart.
+        let mut all = false;
+        let mut privileges = String::new();
+        let mut column_privileges: Vec<(String, Vec<String>)> = Vec::new();
+        while i < on {
+            let word = st.text(i).to_lowercase();
+            let mut k = i + 1;
+            if word == "all" {
+                all = true;
+                k = st.skip(k, &["privileges"]);
+            }
+            let letter = match word.as_str() {
+                "all" => "",
+                "select" => "r",
+                "insert" => "a",
+                "update" => "w",
+                "delete" => "d",
+                "truncate" => "D",
+                "references" => "x",
+                "trigger" => "t",
+                "execute" => "X",
+                "usage" => "U",
+                "create" => "C",
+                "temporary" | "temp" => "T",
+                "connect" => "c",
+                "maintain" => "m",
+                _ => {
+                    return Err(format!(
+                        "privilege {} is not supported",
+                        word.to_uppercase()
+                    ));
+                }
+            };
+            if st.is_punct(k, "(") {
+                let (items, close) = list_items(st, k);
+                let columns = items
+                    .iter()
+                    .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                    .collect();
+                let letters = if all { "arwx" } else { letter };
+                column_privileges.push((letters.to_string(), columns));
+                k = close + 1;
+            } else {
+                privileges.push_str(letter);
+            }
+            i = k + usize::from(st.is_punct(k, ","));
+        }
+
+        let to = st
+            .find_word(on, if is_grant { "to" } else { "from" })
+            .ok_or("missing grantee")?;
+        let mut grantees = Vec::new();
+        let mut k = to + 1;
+        while k < st.tokens.len() && st.is_name_part(k) {
+            let grantee = st.relation(&[k]).map(|r| r.key).unwrap_or_default();
+            grantees.push(match grantee.as_str() {
+                _ if st.is_word(k, "public") => String::new(),
+                _ if st.is_word(k, "current_user") || st.is_word(k, "session_user") => {
+                    acl_role(&self.owner)
+                }
+                _ => acl_role(&grantee),
+            });
+            k += 1 + usize::from(st.is_punct(k + 1, ","));
+            if st.is_word(k, "with") || st.is_word(k, "granted") {
+                break;
+            }
+        }
+        let with_grant_option = is_grant && st.skip(k, &["with", "grant", "option"]) != k;
+        Ok(AclStatement {
+            all,
+            privileges,
+            column_privileges,
+            change: AclChange {
+                grantees,
+                grant: is_grant,
+                option_only,
+                with_grant_option,
+            },
+            on,
+        })
+    }
+
+    pub(super) fn grant(&mut self, st: &Statement, is_grant: bool) -> Result<(), String> {
+        let AclStatement {
+            all,
+            privileges,
+            column_privileges,
+            change,
+            on,
+        } = self.acl_statement(st, 0, is_grant)?;
+
+        let kind = st.text(on + 1).to_lowercase();
+        let mut k = on + 1;
+        match kind.as_str() {
+            "all" => {
+                let kind = st.text(on + 2).to_lowercase();
+                if !st.is_word(on + 3, "in") || !st.is_word(on + 4, "schema") {
+                    return Err("ALL ... IN SCHEMA expected".to_string());
+                }
+                for schema in st.names_at(on + 5) {
+                    let schema = schema.key;
+                    match kind.as_str() {
+                        "tables" => {
+                            for t in 0..self.dump.tables.len() {
+                                if self.dump.tables[t].raw_schema == schema {
+                                    self.grant_table(
+                                        t,
+                                        all,
+                                        &privileges,
+                                        &column_privileges,
+                                        &change,
+                                    )?;
+                                }
+                            }
+                            for v in 0..self.dump.views.len() {
+                                if unquote(&self.dump.views[v].schema) == schema {
+                                    self.grant_view(v, all, &privileges, &change);
+                                }
+                            }
+                        }
+                        "sequences" => {
+                            let quoted = quote_ident(&schema);
+                            for s in 0..self.dump.sequences.len() {
+                                if self.dump.sequences[s].schema == quoted {
+                                    self.grant_sequence(s, all, &privileges, &change);
+                                }
+                            }
+                        }
+                        "functions" | "procedures" | "routines" => {
+                            let quoted = quote_ident(&schema);
+                            for r in 0..self.dump.routines.len() {
+                                let routine = &self.dump.routines[r];
+                                let wanted = match kind.as_str() {
+                                    "functions" => routine.kind != "procedure",
+                                    "procedures" => routine.kind == "procedure",
+                                    _ => true,
+                                };
+                                if routine.schema == quoted && wanted {
+                                    self.grant_routine(r, all, &privileges, &change);
+                                }
+                            }
+                        }
+                        _ => {
+                            return Err(format!(
+                                "GRANT ON ALL {} is not supported",
+                                kind.to_uppercase()
+                            ));
+                        }
+                    }
+                }
+            }
+            "function" | "procedure" | "routine" => {
+                k += 1;
+                loop {
+                    let (index, next) = self.routine_at(st, k)?;
+                    self.grant_routine(index, all, &privileges, &change);
+                    if !st.is_punct(next, ",") {
+                        break;
+                    }
+                    k = next + 1;
+                }
+            }
+            "schema" => {
+                for schema in st.names_at(on + 2) {
+                    let index = self
+                        .find_schema(&schema.key)
+                        .ok_or_else(|| format!("schema {} not found", schema.display))?;
+                    let s = &mut self.dump.schemas[index];
+                    let letters = if all { "UC" } else { privileges.as_str() };
+                    change.apply(&mut s.acl, &s.owner, "UC", None, letters);
+                    s.hash();
+                }
+            }
+            "type" | "domain" => {
+                for ty in st.names_at(on + 2) {
+                    let index = self
+                        .find_type(&ty)
+                        .ok_or_else(|| format!("type {} not found", ty.display))?;
+                    let t = &mut self.dump.types[index];
+                    let letters = if all { "U" } else { privileges.as_str() };
+                    change.apply(&mut t.acl, &t.owner, "U", Some("U"), letters);
+                    t.hash();
+                }
+            }
+            "sequence" => {
+                for relation in st.names_at(on + 2) {
+                    let index = self
+                        .find_sequence(&relation)
+                        .ok_or_else(|| format!("sequence {} not found", relation.display))?;
+                    self.grant_sequence(index, all, &privileges, &change);
+                }
+            }
+            _ => {
+                if kind == "table" {
+                    k += 1;
+                } else if st.is_word(k + 1, "to")
+                    || st.is_word(k + 1, "from")
+                    || st.is_punct(k + 1, ".")
+                    || st.is_punct(k + 1, ",")
+                {
+                    // `ON name` without an object kind means a table.
+                } else {
+                    return Err(format!("GRANT ON {} is not supported", kind.to_uppercase()));
+                }
+                for relation in st.names_at(k) {
+                    if let Some(index) = self.find_table(&relation) {
+                        self.grant_table(index, all, &privileges, &column_privileges, &change)?;
+                    } else if let Some(index) = self.find_view(&relation) {
+                        self.grant_view(index, all, &privileges, &change);
+                    } else if let Some(index) = self.find_sequence(&relation) {
+                        self.grant_sequence(index, all, &privileges, &change);
+                    } else {
+                        return Err(format!("relation {} not found", relation.display));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn grant_table(
+        &mut self,
+        index: usize,
+        all: bool,
+        privileges: &str,
+        column_privileges: &[(String, Vec<String>)],
+        change: &AclChange,
+    ) -> Result<(), String> {
+        let table = &mut self.dump.tables[index];
+        if all || !privileges.is_empty() {
+            let letters = if all && column_privileges.is_empty() {
+                TABLE_PRIVILEGES
+            } else {
+                privileges
+            };
+            change.apply(
+                &mut table.acl,
+                &table.owner,
+                TABLE_PRIVILEGES,
+                None,
+                letters,
+            );
+        }
+        for (letters, columns) in column_privileges {
+            for name in columns {
+                let column = table
+                    .columns
+                    .iter_mut()
+                    .find(|c| unquote(&c.name) == *name)
+                    .ok_or_else(|| format!("column {name} not found"))?;
+                change.apply(&mut column.acl, &table.owner, "", None, letters);
+            }
+        }
+        table.hash();
+        Ok(())
+    }
+
+    fn grant_view(&mut self, index: usize, all: bool, privileges: &str, change: &AclChange) {
+        let view = &mut self.dump.views[index];
+        let letters = if all { TABLE_PRIVILEGES } else { privileges };
+        change.apply(&mut view.acl, &view.owner, TABLE_PRIVILEGES, None, letters);
+        view.hash();
+    }
+
+    fn grant_sequence(&mut self, index: usize, all: bool, privileges: &str, change: &AclChange) {
+        let sequence = &mut self.dump.sequences[index];
+        let letters = if all { "rwU" } else { privileges };
+        change.apply(&mut sequence.acl, &sequence.owner, "rwU", None, letters);
+        sequence.hash();
+    }
+
+    fn grant_routine(&mut self, index: usize, all: bool, privileges: &str, change: &AclChange) {
+        let routine = &mut self.dump.routines[index];
+        let letters = if all { "X" } else { privileges };
+        change.apply(&mut routine.acl, &routine.owner, "X", Some("X"), letters);
+        routine.hash();
+    }
+}
+
+/// Privileges `ALL` grants on a table, view or materialized view.
+const TABLE_PRIVILEGES: &str = "arwdDxt";
+
+/// Order in which `aclitem` prints privilege letters.
+const PRIVILEGE_ORDER: &str = "arwdDxtXUCTcsAm";
+
+/// A GRANT or REVOKE: its privileges (with column lists kept apart) and
+/// grantees, and where its `ON` clause starts.
+struct AclStatement {
+    all: bool,
+    privileges: String,
+    column_privileges: Vec<(String, Vec<String>)>,
+    change: AclChange,
+    on: usize,
+}
+
+/// A change an ACL statement makes for each grantee.
+struct AclChange {
+    /// Grantees as `aclitem` prints them; empty for PUBLIC.
+    grantees: Vec<String>,
+    grant: bool,
+    /// `REVOKE GRANT OPTION FOR`.
+    option_only: bool,
+    with_grant_option: bool,
+}
+
+impl AclChange {
+    /// Applies the change to `acl`, first materializing the default ACL
+    /// (`owner_default` for the owner, `public_default` for PUBLIC) the
+    /// catalog leaves implicit until the first GRANT or REVOKE.
+    fn apply(
+        &self,
+        acl: &mut Vec<String>,
+        owner: &str,
+        owner_default: &str,
+        public_default: Option<&str>,
+        letters: &str,
+    ) {
+        let owner = acl_role(&unquote(owner));
+        if acl.is_empty() && !owner_default.is_empty() {
+            if let Some(public) = public_default {
+                acl.push(format!("={public}/{owner}"));
+            }
+            acl.push(format!("{owner}={owner_default}/{owner}"));
+        }
+        for grantee in &self.grantees {
+            let position = acl.iter().position(|item| {
+                item.split_once('=').is_some_and(|(g, rest)| {
+                    g == grantee && rest.rsplit_once('/').is_some_and(|(_, by)| by == owner)
+                })
+            });
+            let current = position
+                .and_then(|p| acl[p].split_once('='))
+                .and_then(|(_, rest)| rest.rsplit_once('/'))
+                .map(|(privileges, _)| privileges.to_string())
+                .unwrap_or_default();
+            let mut held: Vec<(char, bool)> = Vec::new();
+            let mut chars = current.chars().peekable();
+            while let Some(c) = chars.next() {
+                let option = chars.next_if_eq(&'*').is_some();
+                held.push((c, option));
+            }
+            for letter in letters.chars() {
+                let existing = held.iter().position(|(c, _)| *c == letter);
+                match (self.grant, existing) {
+                    (true, Some(e)) => held[e].1 |= self.with_grant_option,
+                    (true, None) => held.push((letter, self.with_grant_option)),
+                    (false, Some(e)) if self.option_only => held[e].1 = false,
+                    (false, Some(e)) => {
+                        held.remove(e);
+                    }
+                    (false, None) => {}
+                }
+            }
+            held.sort_by_key(|(c, _)| PRIVILEGE_ORDER.find(*c));
+            let privileges: String = held
+                .iter()
+                .map(|(c, option)| {
+                    if *option {
+                        format!("{c}*")
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect();
+            let item = format!("{grantee}={privileges}/{owner}");
+            match (position, privileges.is_empty()) {
+                (Some(p), true) => {
+                    acl.remove(p);
+                }
+                (Some(p), false) => acl[p] = item,
+                (None, false) => acl.push(item),
+                (None, true) => {}
+            }
+        }
+    }
+}
+
+/// A role name as `aclitem` prints it.
+fn acl_role(role: &str) -> String {
+    if role.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        role.to_string()
+    } else {
+        format!("\"{}\"", role.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+#[path = "acl_tests.rs"]
+mod tests;
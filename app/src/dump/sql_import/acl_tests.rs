@@ -0,0 +1,116 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn applies_grants_and_revokes() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+         GRANT SELECT, INSERT ON app.users TO reader;
+         GRANT USAGE ON SCHEMA app TO reader;
+         REVOKE INSERT ON app.users FROM reader;"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let users = table(&dump, "users");
+    assert_eq!(
+        users.acl,
+        vec![
+            "postgres=arwdDxt/postgres".to_string(),
+            "reader=r/postgres".to_string()
+        ]
+    );
+    assert_eq!(
+        dump.schemas[0].acl,
+        vec![
+            "postgres=UC/postgres".to_string(),
+            "reader=U/postgres".to_string()
+        ]
+    );
+}
+
+#[test]
+fn imports_default_privileges() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER DEFAULT PRIVILEGES IN SCHEMA app GRANT SELECT ON TABLES TO reader;
+ALTER DEFAULT PRIVILEGES FOR ROLE admin REVOKE EXECUTE ON FUNCTIONS FROM PUBLIC;
+ALTER DEFAULT PRIVILEGES IN SCHEMA other GRANT USAGE ON TYPES TO reader;
+ALTER DEFAULT PRIVILEGES GRANT SELECT ON SEQUENCES TO reader;
+ALTER DEFAULT PRIVILEGES REVOKE SELECT ON SEQUENCES FROM reader;"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let entries: Vec<(&str, &str, &str, Vec<String>)> = dump
+        .default_privileges
+        .iter()
+        .map(|p| {
+            (
+                p.role_name.as_str(),
+                p.schema_name.as_str(),
+                p.object_type.as_str(),
+                p.acl.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("admin", "", "f", vec!["admin=X/admin".to_string()]),
+            (
+                "postgres",
+                "app",
+                "r",
+                vec!["reader=r/postgres".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn grants_on_every_kind_of_object() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE VIEW app.emails AS SELECT email FROM app.users;
+CREATE FUNCTION app.one() RETURNS integer LANGUAGE sql AS $$ SELECT 1 $$;
+GRANT SELECT ON ALL TABLES IN SCHEMA app TO reader;
+GRANT USAGE ON SEQUENCE app.users_id_seq TO writer WITH GRANT OPTION;
+GRANT EXECUTE ON FUNCTION app.one() TO reader;
+GRANT USAGE ON TYPE app.mood TO reader;
+GRANT UPDATE (name) ON app.users TO writer;
+REVOKE ALL ON app.emails FROM postgres;
+GRANT SELECT ON DATABASE offline TO reader;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["GRANT ON DATABASE is not supported"]);
+    assert_eq!(
+        table(&dump, "orders").acl,
+        vec![
+            "postgres=arwdDxt/postgres".to_string(),
+            "reader=r/postgres".to_string()
+        ]
+    );
+    let users = table(&dump, "users");
+    assert_eq!(users.columns[3].acl, vec!["writer=w/postgres".to_string()]);
+    // ALL TABLES covers views too.
+    assert_eq!(dump.views[0].acl, vec!["reader=r/postgres".to_string()]);
+    assert_eq!(
+        dump.sequences[0].acl,
+        vec![
+            "postgres=rwU/postgres".to_string(),
+            "writer=U*/postgres".to_string()
+        ]
+    );
+    assert_eq!(
+        dump.routines[0].acl,
+        vec![
+            "=X/postgres".to_string(),
+            "postgres=X/postgres".to_string(),
+            "reader=X/postgres".to_string()
+        ]
+    );
+    assert_eq!(
+        dump.types[0].acl,
+        vec![
+            "=U/postgres".to_string(),
+            "postgres=U/postgres".to_string(),
+            "reader=U/postgres".to_string()
+        ]
+    );
+}
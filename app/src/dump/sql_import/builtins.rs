@@ -0,0 +1,883 @@
+//! The built-in types the importer knows the catalog entries of.
+
+/// A built-in type as the catalog describes it: `typname`, the name
+/// `format_type` prints, `typlen`, `typbyval`, `typcategory`, `typalign`,
+/// `typstorage`, `typoutput` and `typsend`. Sorted by `typname`.
+type BuiltinType = (
+    &'static str,
+    &'static str,
+    i16,
+    bool,
+    u8,
+    u8,
+    u8,
+    &'static str,
+    &'static str,
+);
+
+const BUILTIN_TYPES: &[BuiltinType] = &[
+    (
+        "anyarray",
+        "anyarray",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "anyarray_out",
+        "anyarray_send",
+    ),
+    (
+        "anycompatible",
+        "anycompatible",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "anycompatible_out",
+        "-",
+    ),
+    (
+        "anycompatiblearray",
+        "anycompatiblearray",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "anycompatiblearray_out",
+        "anycompatiblearray_send",
+    ),
+    (
+        "anycompatiblenonarray",
+        "anycompatiblenonarray",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "anycompatiblenonarray_out",
+        "-",
+    ),
+    (
+        "anycompatiblerange",
+        "anycompatiblerange",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "anycompatiblerange_out",
+        "-",
+    ),
+    (
+        "anyelement",
+        "anyelement",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "anyelement_out",
+        "-",
+    ),
+    (
+        "anyenum",
+        "anyenum",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "anyenum_out",
+        "-",
+    ),
+    (
+        "anymultirange",
+        "anymultirange",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "anymultirange_out",
+        "-",
+    ),
+    (
+        "anynonarray",
+        "anynonarray",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "anynonarray_out",
+        "-",
+    ),
+    (
+        "anyrange",
+        "anyrange",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "anyrange_out",
+        "-",
+    ),
+    (
+        "bit", "bit", -1, false, b'V', b'i', b'x', "bit_out", "bit_send",
+    ),
+    (
+        "bool", "boolean", 1, true, b'B', b'c', b'p', "boolout", "boolsend",
+    ),
+    (
+        "box", "box", 32, false, b'G', b'd', b'p', "box_out", "box_send",
+    ),
+    (
+        "bpchar",
+        "character",
+        -1,
+        false,
+        b'S',
+        b'i',
+        b'x',
+        "bpcharout",
+        "bpcharsend",
+    ),
+    (
+        "bytea",
+        "bytea",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "byteaout",
+        "byteasend",
+    ),
+    (
+        "char", "\"char\"", 1, true, b'Z', b'c', b'p', "charout", "charsend",
+    ),
+    ("cid", "cid", 4, true, b'U', b'i', b'p', "cidout", "cidsend"),
+    (
+        "cidr",
+        "cidr",
+        -1,
+        false,
+        b'I',
+        b'i',
+        b'm',
+        "cidr_out",
+        "cidr_send",
+    ),
+    (
+        "circle",
+        "circle",
+        24,
+        false,
+        b'G',
+        b'd',
+        b'p',
+        "circle_out",
+        "circle_send",
+    ),
+    (
+        "cstring",
+        "cstring",
+        -2,
+        false,
+        b'P',
+        b'c',
+        b'p',
+        "cstring_out",
+        "cstring_send",
+    ),
+    (
+        "date",
+        "date",
+        4,
+        true,
+        b'D',
+        b'i',
+        b'p',
+        "date_out",
+        "date_send",
+    ),
+    (
+        "datemultirange",
+        "datemultirange",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "daterange",
+        "daterange",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    (
+        "event_trigger",
+        "event_trigger",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "event_trigger_out",
+        "-",
+    ),
+    (
+        "fdw_handler",
+        "fdw_handler",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "fdw_handler_out",
+        "-",
+    ),
+    (
+        "float4",
+        "real",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "float4out",
+        "float4send",
+    ),
+    (
+        "float8",
+        "double precision",
+        8,
+        true,
+        b'N',
+        b'd',
+        b'p',
+        "float8out",
+        "float8send",
+    ),
+    (
+        "index_am_handler",
+        "index_am_handler",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "index_am_handler_out",
+        "-",
+    ),
+    (
+        "inet",
+        "inet",
+        -1,
+        false,
+        b'I',
+        b'i',
+        b'm',
+        "inet_out",
+        "inet_send",
+    ),
+    (
+        "int2", "smallint", 2, true, b'N', b's', b'p', "int2out", "int2send",
+    ),
+    (
+        "int4", "integer", 4, true, b'N', b'i', b'p', "int4out", "int4send",
+    ),
+    (
+        "int4multirange",
+        "int4multirange",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "int4range",
+        "int4range",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    (
+        "int8", "bigint", 8, true, b'N', b'd', b'p', "int8out", "int8send",
+    ),
+    (
+        "int8multirange",
+        "int8multirange",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "int8range",
+        "int8range",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    (
+        "internal",
+        "internal",
+        8,
+        true,
+        b'P',
+        b'd',
+        b'p',
+        "internal_out",
+        "-",
+    ),
+    (
+        "interval",
+        "interval",
+        16,
+        false,
+        b'T',
+        b'd',
+        b'p',
+        "interval_out",
+        "interval_send",
+    ),
+    (
+        "json",
+        "json",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "json_out",
+        "json_send",
+    ),
+    (
+        "jsonb",
+        "jsonb",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "jsonb_out",
+        "jsonb_send",
+    ),
+    (
+        "jsonpath",
+        "jsonpath",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "jsonpath_out",
+        "jsonpath_send",
+    ),
+    (
+        "language_handler",
+        "language_handler",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "language_handler_out",
+        "-",
+    ),
+    (
+        "line",
+        "line",
+        24,
+        false,
+        b'G',
+        b'd',
+        b'p',
+        "line_out",
+        "line_send",
+    ),
+    (
+        "lseg",
+        "lseg",
+        32,
+        false,
+        b'G',
+        b'd',
+        b'p',
+        "lseg_out",
+        "lseg_send",
+    ),
+    (
+        "macaddr",
+        "macaddr",
+        6,
+        false,
+        b'U',
+        b'i',
+        b'p',
+        "macaddr_out",
+        "macaddr_send",
+    ),
+    (
+        "macaddr8",
+        "macaddr8",
+        8,
+        false,
+        b'U',
+        b'i',
+        b'p',
+        "macaddr8_out",
+        "macaddr8_send",
+    ),
+    (
+        "money",
+        "money",
+        8,
+        true,
+        b'N',
+        b'd',
+        b'p',
+        "cash_out",
+        "cash_send",
+    ),
+    (
+        "name", "name", 64, false, b'S', b'c', b'p', "nameout", "namesend",
+    ),
+    (
+        "numeric",
+        "numeric",
+        -1,
+        false,
+        b'N',
+        b'i',
+        b'm',
+        "numeric_out",
+        "numeric_send",
+    ),
+    (
+        "nummultirange",
+        "nummultirange",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "numrange",
+        "numrange",
+        -1,
+        false,
+        b'R',
+        b'i',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    ("oid", "oid", 4, true, b'N', b'i', b'p', "oidout", "oidsend"),
+    (
+        "path",
+        "path",
+        -1,
+        false,
+        b'G',
+        b'd',
+        b'x',
+        "path_out",
+        "path_send",
+    ),
+    (
+        "pg_lsn",
+        "pg_lsn",
+        8,
+        true,
+        b'U',
+        b'd',
+        b'p',
+        "pg_lsn_out",
+        "pg_lsn_send",
+    ),
+    (
+        "pg_snapshot",
+        "pg_snapshot",
+        -1,
+        false,
+        b'U',
+        b'd',
+        b'x',
+        "pg_snapshot_out",
+        "pg_snapshot_send",
+    ),
+    (
+        "point",
+        "point",
+        16,
+        false,
+        b'G',
+        b'd',
+        b'p',
+        "point_out",
+        "point_send",
+    ),
+    (
+        "polygon",
+        "polygon",
+        -1,
+        false,
+        b'G',
+        b'd',
+        b'x',
+        "poly_out",
+        "poly_send",
+    ),
+    (
+        "record",
+        "record",
+        -1,
+        false,
+        b'P',
+        b'd',
+        b'x',
+        "record_out",
+        "record_send",
+    ),
+    (
+        "refcursor",
+        "refcursor",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "textout",
+        "textsend",
+    ),
+    (
+        "regclass",
+        "regclass",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regclassout",
+        "regclasssend",
+    ),
+    (
+        "regconfig",
+        "regconfig",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regconfigout",
+        "regconfigsend",
+    ),
+    (
+        "regnamespace",
+        "regnamespace",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regnamespaceout",
+        "regnamespacesend",
+    ),
+    (
+        "regproc",
+        "regproc",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regprocout",
+        "regprocsend",
+    ),
+    (
+        "regprocedure",
+        "regprocedure",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regprocedureout",
+        "regproceduresend",
+    ),
+    (
+        "regrole",
+        "regrole",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regroleout",
+        "regrolesend",
+    ),
+    (
+        "regtype",
+        "regtype",
+        4,
+        true,
+        b'N',
+        b'i',
+        b'p',
+        "regtypeout",
+        "regtypesend",
+    ),
+    (
+        "table_am_handler",
+        "table_am_handler",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "table_am_handler_out",
+        "-",
+    ),
+    (
+        "text", "text", -1, false, b'S', b'i', b'x', "textout", "textsend",
+    ),
+    (
+        "tid", "tid", 6, false, b'U', b's', b'p', "tidout", "tidsend",
+    ),
+    (
+        "time",
+        "time without time zone",
+        8,
+        true,
+        b'D',
+        b'd',
+        b'p',
+        "time_out",
+        "time_send",
+    ),
+    (
+        "timestamp",
+        "timestamp without time zone",
+        8,
+        true,
+        b'D',
+        b'd',
+        b'p',
+        "timestamp_out",
+        "timestamp_send",
+    ),
+    (
+        "timestamptz",
+        "timestamp with time zone",
+        8,
+        true,
+        b'D',
+        b'd',
+        b'p',
+        "timestamptz_out",
+        "timestamptz_send",
+    ),
+    (
+        "timetz",
+        "time with time zone",
+        12,
+        false,
+        b'D',
+        b'd',
+        b'p',
+        "timetz_out",
+        "timetz_send",
+    ),
+    (
+        "trigger",
+        "trigger",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "trigger_out",
+        "-",
+    ),
+    (
+        "tsm_handler",
+        "tsm_handler",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "tsm_handler_out",
+        "-",
+    ),
+    (
+        "tsmultirange",
+        "tsmultirange",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "tsquery",
+        "tsquery",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'p',
+        "tsqueryout",
+        "tsquerysend",
+    ),
+    (
+        "tsrange",
+        "tsrange",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    (
+        "tstzmultirange",
+        "tstzmultirange",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "multirange_out",
+        "multirange_send",
+    ),
+    (
+        "tstzrange",
+        "tstzrange",
+        -1,
+        false,
+        b'R',
+        b'd',
+        b'x',
+        "range_out",
+        "range_send",
+    ),
+    (
+        "tsvector",
+        "tsvector",
+        -1,
+        false,
+        b'U',
+        b'i',
+        b'x',
+        "tsvectorout",
+        "tsvectorsend",
+    ),
+    (
+        "txid_snapshot",
+        "txid_snapshot",
+        -1,
+        false,
+        b'U',
+        b'd',
+        b'x',
+        "txid_snapshot_out",
+        "txid_snapshot_send",
+    ),
+    (
+        "uuid",
+        "uuid",
+        16,
+        false,
+        b'U',
+        b'c',
+        b'p',
+        "uuid_out",
+        "uuid_send",
+    ),
+    (
+        "varbit",
+        "bit varying",
+        -1,
+        false,
+        b'V',
+        b'i',
+        b'x',
+        "varbit_out",
+        "varbit_send",
+    ),
+    (
+        "varchar",
+        "character varying",
+        -1,
+        false,
+        b'S',
+        b'i',
+        b'x',
+        "varcharout",
+        "varcharsend",
+    ),
+    (
+        "void",
+        "void",
+        4,
+        true,
+        b'P',
+        b'i',
+        b'p',
+        "void_out",
+        "void_send",
+    ),
+    ("xid", "xid", 4, true, b'U', b'i', b'p', "xidout", "xidsend"),
+    (
+        "xid8", "xid8", 8, true, b'U', b'd', b'p', "xid8out", "xid8send",
+    ),
+    (
+        "xml", "xml", -1, false, b'U', b'i', b'x', "xml_out", "xml_send",
+    ),
+];
+
+pub(super) fn builtin(typname: &str) -> Option<&'static BuiltinType> {
+    BUILTIN_TYPES
+        .binary_search_by(|b| b.0.cmp(typname))
+        .ok()
+        .map(|i| &BUILTIN_TYPES[i])
+}
+
+#[cfg(test)]
+#[path = "builtins_tests.rs"]
+mod tests;
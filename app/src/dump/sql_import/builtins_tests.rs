@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn builtin_types_are_sorted_by_typname() {
+    assert!(BUILTIN_TYPES.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn builtin_looks_up_typname() {
+    assert_eq!(builtin("int4").map(|t| t.1), Some("integer"));
+    assert_eq!(builtin("varchar").map(|t| t.1), Some("character varying"));
+    assert!(builtin("integer").is_none());
+}
@@ -0,0 +1,150 @@
+//! `COMMENT ON`.
+
+use super::Importer;
+use super::statements::{literal_value, quote_ident};
+use crate::comparer::scanner::Statement;
+
+impl Importer {
+    pub(super) fn comment(&mut self, st: &Statement) -> Result<(), String> {
+        if !st.is_word(1, "on") {
+            return Err("COMMENT without ON".to_string());
+        }
+        let is = st.find_word(3, "is").ok_or("COMMENT without IS")?;
+        let text = if st.is_word(is + 1, "null") {
+            None
+        } else {
+            Some(literal_value(st.text(is + 1)).ok_or("missing comment text")?)
+        }
+        .filter(|t| !t.is_empty());
+        let kind = st.text(2).to_lowercase();
+        match kind.as_str() {
+            "table" => {
+                let (relation, _) = st.name_at(3).ok_or("missing table name")?;
+                let index = self
+                    .find_table(&relation)
+                    .ok_or_else(|| format!("table {} not found", relation.display))?;
+                self.dump.tables[index].comment = text;
+                self.dump.tables[index].hash();
+            }
+            "column" => {
+                let parts = st.name_parts(3);
+                if parts.len() < 2 {
+                    return Err("COMMENT ON COLUMN needs a qualified name".to_string());
+                }
+                let relation = st
+                    .relation(&parts[..parts.len() - 1])
+                    .ok_or("missing table name")?;
+                let column = st
+                    .relation(&parts[parts.len() - 1..])
+                    .ok_or("missing column name")?
+                    .key;
+                if let Some(index) = self.find_table(&relation) {
+                    let c = self.column_index(index, &column)?;
+                    self.dump.tables[index].columns[c].comment = text;
+                    self.dump.tables[index].hash();
+                } else if let Some(index) = self.find_view(&relation) {
+                    let view = &mut self.dump.views[index];
+                    let column = quote_ident(&column);
+                    view.column_comments.retain(|(c, _)| *c != column);
+                    if let Some(text) = text {
+                        view.column_comments.push((column, text));
+                    }
+                    view.hash();
+                } else {
+                    return Err(format!("relation {} not found", relation.display));
+                }
+            }
+            "view" | "materialized" => {
+                let i = if kind == "view" { 3 } else { 4 };
+                let (relation, _) = st.name_at(i).ok_or("missing view name")?;
+                let index = self
+                    .find_view(&relation)
+                    .ok_or_else(|| format!("view {} not found", relation.display))?;
+                self.dump.views[index].comment = text;
+                self.dump.views[index].hash();
+            }
+            "sequence" => {
+                let (relation, _) = st.name_at(3).ok_or("missing sequence name")?;
+                let index = self
+                    .find_sequence(&relation)
+                    .ok_or_else(|| format!("sequence {} not found", relation.display))?;
+                self.dump.sequences[index].comment = text;
+                self.dump.sequences[index].hash();
+            }
+            "function" | "procedure" | "routine" => {
+                let (index, _) = self.routine_at(st, 3)?;
+                self.dump.routines[index].comment = text;
+                self.dump.routines[index].hash();
+            }
+            "type" | "domain" => {
+                let (relation, _) = st.name_at(3).ok_or("missing type name")?;
+                let index = self
+                    .find_type(&relation)
+                    .ok_or_else(|| format!("type {} not found", relation.display))?;
+                self.dump.types[index].comment = text;
+                self.dump.types[index].hash();
+            }
+            "schema" => {
+                let name = st.relation(&[3]).ok_or("missing schema name")?.key;
+                let index = self
+                    .find_schema(&name)
+                    .ok_or_else(|| format!("schema {name} not found"))?;
+                self.dump.schemas[index].comment = text;
+                self.dump.schemas[index].hash();
+            }
+            "index" => {
+                let (relation, _) = st.name_at(3).ok_or("missing index name")?;
+                let (t, i) = self
+                    .find_index(&relation)
+                    .ok_or_else(|| format!("index {} not found", relation.display))?;
+                self.dump.tables[t].indexes[i].comment = text;
+                self.dump.tables[t].hash();
+            }
+            "trigger" => {
+                let name = quote_ident(&st.relation(&[3]).ok_or("missing trigger name")?.key);
+                if !st.is_word(4, "on") {
+                    return Err("COMMENT ON TRIGGER without ON".to_string());
+                }
+                let (relation, _) = st.name_at(5).ok_or("missing table name")?;
+                let index = self
+                    .find_table(&relation)
+                    .ok_or_else(|| format!("table {} not found", relation.display))?;
+                let trigger = self.dump.tables[index]
+                    .triggers
+                    .iter_mut()
+                    .find(|t| t.name == name)
+                    .ok_or_else(|| format!("trigger {name} not found"))?;
+                trigger.comment = text;
+                self.dump.tables[index].hash();
+            }
+            "constraint" => {
+                let name = quote_ident(&st.relation(&[3]).ok_or("missing constraint name")?.key);
+                if !st.is_word(4, "on") || st.is_word(5, "domain") {
+                    return Err("comments on domain constraints are not supported".to_string());
+                }
+                let (relation, _) = st.name_at(5).ok_or("missing table name")?;
+                let index = self
+                    .find_table(&relation)
+                    .ok_or_else(|| format!("table {} not found", relation.display))?;
+                let constraint = self.dump.tables[index]
+                    .constraints
+                    .iter_mut()
+                    .find(|c| c.name == name)
+                    .ok_or_else(|| format!("constraint {name} not found"))?;
+                constraint.comment = text;
+                self.dump.tables[index].hash();
+            }
+            _ => {
+                return Err(format!(
+                    "COMMENT ON {} is not supported",
+                    kind.to_uppercase()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "comments_tests.rs"]
+mod tests;
@@ -0,0 +1,55 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn comments_on_every_kind_of_object() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE VIEW app.emails AS SELECT email FROM app.users;
+CREATE FUNCTION app.one() RETURNS integer LANGUAGE sql AS $$ SELECT 1 $$;
+COMMENT ON SCHEMA app IS 'Application';
+COMMENT ON TABLE app.users IS 'People';
+COMMENT ON COLUMN app.users.email IS 'Login';
+COMMENT ON COLUMN app.emails.email IS 'Address';
+COMMENT ON VIEW app.emails IS 'Addresses';
+COMMENT ON SEQUENCE app.users_id_seq IS 'Ids';
+COMMENT ON FUNCTION app.one() IS 'One';
+COMMENT ON TYPE app.mood IS 'Feelings';
+COMMENT ON DOMAIN app.email IS 'An address';
+COMMENT ON INDEX app.orders_user_idx IS 'By user';
+COMMENT ON CONSTRAINT orders_pkey ON app.orders IS 'Key';
+COMMENT ON TABLE app.orders IS '';
+COMMENT ON TABLE app.users IS NULL;
+COMMENT ON EXTENSION citext IS 'Text';"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["COMMENT ON EXTENSION is not supported"]);
+    assert_eq!(dump.schemas[0].comment.as_deref(), Some("Application"));
+    let users = table(&dump, "users");
+    assert_eq!(users.comment, None);
+    assert_eq!(users.columns[1].comment.as_deref(), Some("Login"));
+    let orders = table(&dump, "orders");
+    assert_eq!(orders.comment, None);
+    assert_eq!(orders.indexes[0].comment.as_deref(), Some("By user"));
+    assert_eq!(orders.constraints[0].comment.as_deref(), Some("Key"));
+    let view = &dump.views[0];
+    assert_eq!(view.comment.as_deref(), Some("Addresses"));
+    assert_eq!(
+        view.column_comments,
+        vec![("email".to_string(), "Address".to_string())]
+    );
+    assert_eq!(dump.sequences[0].comment.as_deref(), Some("Ids"));
+    assert_eq!(dump.routines[0].comment.as_deref(), Some("One"));
+    let comments: Vec<(&str, Option<&str>)> = dump
+        .types
+        .iter()
+        .map(|t| (t.typname.as_str(), t.comment.as_deref()))
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            ("mood", Some("Feelings")),
+            ("pair", None),
+            ("email", Some("An address"))
+        ]
+    );
+}
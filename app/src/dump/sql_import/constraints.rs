@@ -0,0 +1,432 @@
+//! Table constraints, whether declared inline, in the table's element list
+//! or with `ALTER TABLE ... ADD CONSTRAINT`.
+
+use super::Importer;
+use super::statements::{
+    closing_paren, list_items, quote_ident, quote_list, render, unwrap_parens, visible_name,
+    wholly_wrapped,
+};
+use crate::comparer::scanner::Statement;
+use crate::dump::table_constraint::TableConstraint;
+
+/// A foreign key whose referenced columns default to the target's primary
+/// key, filled in once every file has been read.
+pub(super) struct PendingForeignKey {
+    pub(super) table: usize,
+    /// The constraint's quoted name; later statements may drop constraints
+    /// before it, so its position is not kept.
+    pub(super) constraint: String,
+    pub(super) columns: String,
+    pub(super) target: (String, String),
+    pub(super) tail: String,
+}
+
+impl Importer {
+    pub(super) fn add_table_constraint(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        from: usize,
+        to: usize,
+    ) -> Result<(), String> {
+        let (name, j) = if st.is_word(from, "constraint") {
+            (st.relation(&[from + 1]).map(|r| r.key), from + 2)
+        } else {
+            (None, from)
+        };
+        self.add_constraint(st, table, name, j, to, None)
+            .map(|_| ())
+    }
+
+    /// The first column of `table` that the expression between `from` and
+    /// `to` refers to; implicit CHECK constraint names are built from it.
+    fn first_column_in(
+        &self,
+        st: &Statement,
+        table: usize,
+        from: usize,
+        to: usize,
+    ) -> Option<String> {
+        (from..to)
+            .filter(|&i| st.is_name_part(i) && !st.is_punct(i + 1, "(") && !st.is_punct(i + 1, "."))
+            .filter_map(|i| st.relation(&[i]))
+            .map(|r| r.key)
+            .find(|name| self.column_index(table, name).is_ok())
+    }
+
+    /// A PRIMARY KEY, UNIQUE, CHECK, FOREIGN KEY or EXCLUDE constraint
+    /// starting at `j`. `column` is set for column constraints, which name
+    /// no column list. Returns the constraint's index and the index after it.
+    pub(super) fn add_constraint(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        name: Option<String>,
+        j: usize,
+        to: usize,
+        column: Option<&str>,
+    ) -> Result<(usize, usize), String> {
+        let (schema, table_name) = {
+            let t = &self.dump.tables[table];
+            (t.raw_schema.clone(), t.raw_name.clone())
+        };
+        let own_columns = |k: usize| -> Result<(Vec<String>, usize), String> {
+            if st.is_punct(k, "(") {
+                let (items, close) = list_items(st, k);
+                let names = items
+                    .iter()
+                    .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                    .collect();
+                Ok((names, close + 1))
+            } else {
+                column
+                    .map(|c| (vec![c.to_string()], k))
+                    .ok_or_else(|| "missing column list".to_string())
+            }
+        };
+        let word = st.text(j).to_lowercase();
+        let mut nulls_not_distinct = false;
+        let mut pending = None;
+        let (constraint_type, mut definition, default_name, mut k) = match word.as_str() {
+            "check" => {
+                if !st.is_punct(j + 1, "(") {
+                    return Err("CHECK without an expression".to_string());
+                }
+                let close = closing_paren(st, j + 1);
+                let first = self
+                    .first_column_in(st, table, j + 2, close)
+                    .unwrap_or_default();
+                (
+                    "CHECK",
+                    format!("CHECK ({})", pretty_check(st, j + 2, close)),
+                    self.choose_name(&schema, &table_name, &first, "check"),
+                    close + 1,
+                )
+            }
+            "unique" => {
+                let mut k = j + 1;
+                if st.skip(k, &["nulls", "not", "distinct"]) != k {
+                    nulls_not_distinct = true;
+                    k += 3;
+                }
+                k = st.skip(k, &["nulls", "distinct"]);
+                let (columns, k) = own_columns(k)?;
+                (
+                    "UNIQUE",
+                    format!(
+                        "UNIQUE {}({})",
+                        if nulls_not_distinct {
+                            "NULLS NOT DISTINCT "
+                        } else {
+                            ""
+                        },
+                        quote_list(&columns)
+                    ),
+                    self.choose_name(&schema, &table_name, &columns.join("_"), "key"),
+                    k,
+                )
+            }
+            "primary" if st.is_word(j + 1, "key") => {
+                if self.dump.tables[table]
+                    .constraints
+                    .iter()
+                    .any(|c| c.constraint_type == "PRIMARY KEY")
+                {
+                    return Err(format!("multiple primary keys for table {table_name}"));
+                }
+                let (columns, k) = own_columns(j + 2)?;
+                for name in &columns {
+                    let c = self.column_index(table, name)?;
+                    self.dump.tables[table].columns[c].is_nullable = false;
+                }
+                (
+                    "PRIMARY KEY",
+                    format!("PRIMARY KEY ({})", quote_list(&columns)),
+                    self.choose_name(&schema, &table_name, "", "pkey"),
+                    k,
+                )
+            }
+            "foreign" | "references" => {
+                let (columns, k) = if word == "foreign" {
+                    if !st.is_word(j + 1, "key") {
+                        return Err("FOREIGN without KEY".to_string());
+                    }
+                    own_columns(j + 2)?
+                } else {
+                    own_columns(j)?
+                };
+                if !st.is_word(k, "references") {
+                    return Err("FOREIGN KEY without REFERENCES".to_string());
+                }
+                let (target, mut k) = st.name_at(k + 1).ok_or("missing referenced table")?;
+                let target = self.target_or_found(&target);
+                let referenced = if st.is_punct(k, "(") {
+                    let (items, close) = list_items(st, k);
+                    k = close + 1;
+                    Some(
+                        items
+                            .iter()
+                            .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                            .collect::<Vec<_>>(),
+                    )
+                } else {
+                    None
+                };
+                let (tail, k) = foreign_key_actions(st, k, to);
+                let head = format!("FOREIGN KEY ({})", quote_list(&columns));
+                let definition = match &referenced {
+                    Some(referenced) => format!(
+                        "{head} REFERENCES {}({}){tail}",
+                        visible_name(&target.0, &target.1),
+                        quote_list(referenced)
+                    ),
+                    None => {
+                        pending = Some(PendingForeignKey {
+                            table,
+                            constraint: String::new(),
+                            columns: head.clone(),
+                            target: target.clone(),
+                            tail: tail.clone(),
+                        });
+                        head
+                    }
+                };
+                (
+                    "FOREIGN KEY",
+                    definition,
+                    self.choose_name(&schema, &table_name, &columns.join("_"), "fkey"),
+                    k,
+                )
+            }
+            "exclude" => {
+                let mut k = j + 1;
+                let mut method = "btree".to_string();
+                if st.is_word(k, "using") {
+                    method = st.text(k + 1).to_lowercase();
+                    k += 2;
+                }
+                if !st.is_punct(k, "(") {
+                    return Err("EXCLUDE without an element list".to_string());
+                }
+                let (items, close) = list_items(st, k);
+                let elements: Vec<String> = items
+                    .iter()
+                    .map(|&(from, to)| render(st, from, to, false))
+                    .collect();
+                let columns: Vec<String> = items
+                    .iter()
+                    .map(|&(from, _)| st.relation(&[from]).map_or("expr".to_string(), |r| r.key))
+                    .collect();
+                k = close + 1;
+                let mut definition = format!("EXCLUDE USING {method} ({})", elements.join(", "));
+                if st.is_word(k, "where") && st.is_punct(k + 1, "(") {
+                    let close = closing_paren(st, k + 1);
+                    definition.push_str(&format!(" WHERE ({})", render(st, k + 2, close, false)));
+                    k = close + 1;
+                }
+                (
+                    "EXCLUDE",
+                    definition,
+                    self.choose_name(&schema, &table_name, &columns.join("_"), "excl"),
+                    k,
+                )
+            }
+            _ => return Err(format!("{} is not supported", st.text(j).to_uppercase())),
+        };
+
+        let (mut deferrable, mut deferred, mut not_valid, mut no_inherit) =
+            (false, false, false, false);
+        while k < to && !st.is_punct(k, ";") {
+            let (next, d, i) = constraint_timing(st, k);
+            if next != k {
+                deferrable |= d;
+                deferred |= i;
+                k = next;
+            } else if st.is_word(k, "include") && st.is_punct(k + 1, "(") {
+                let (items, close) = list_items(st, k + 1);
+                let names: Vec<String> = items
+                    .iter()
+                    .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                    .collect();
+                definition.push_str(&format!(" INCLUDE ({})", quote_list(&names)));
+                k = close + 1;
+            } else if st.skip(k, &["not", "valid"]) != k {
+                not_valid = true;
+                k += 2;
+            } else if st.skip(k, &["no", "inherit"]) != k {
+                no_inherit = true;
+                k += 2;
+            } else if st.skip(k, &["using", "index", "tablespace"]) != k {
+                k += 4;
+            } else if column.is_some() {
+                break;
+            } else {
+                self.notes.push(format!(
+                    "constraint option {} is not supported",
+                    st.text(k).to_uppercase()
+                ));
+                k = to;
+            }
+        }
+        if no_inherit {
+            definition.push_str(" NO INHERIT");
+        }
+        if not_valid {
+            definition.push_str(" NOT VALID");
+        }
+        let t = &self.dump.tables[table];
+        let mut constraint = TableConstraint {
+            catalog: self.catalog.clone(),
+            schema: t.schema.clone(),
+            name: quote_ident(name.as_deref().unwrap_or(&default_name)),
+            table_name: t.name.clone(),
+            constraint_type: constraint_type.to_string(),
+            is_deferrable: false,
+            initially_deferred: false,
+            definition: Some(definition),
+            coninhcount: 0,
+            is_enforced: true,
+            no_inherit: constraint_type != "CHECK" || no_inherit,
+            nulls_not_distinct,
+            comment: None,
+        };
+        apply_timing(&mut constraint, deferrable, deferred);
+        self.dump.tables[table].constraints.push(constraint);
+        let index = self.dump.tables[table].constraints.len() - 1;
+        if let Some(mut pending) = pending {
+            pending.constraint = self.dump.tables[table].constraints[index].name.clone();
+            self.pending_foreign_keys.push(pending);
+        }
+        Ok((index, k))
+    }
+}
+
+/// A CHECK expression as the pretty `pg_get_constraintdef` prints it:
+/// without the outer parentheses pg_dump writes, or those around cast
+/// numbers.
+fn pretty_check(st: &Statement, from: usize, to: usize) -> String {
+    let mut expression = render(st, from, to, false);
+    while wholly_wrapped(&expression) {
+        expression = unwrap_parens(&expression);
+    }
+    let mut out = String::with_capacity(expression.len());
+    let mut rest = expression.as_str();
+    while let Some(open) = rest.find('(') {
+        out.push_str(&rest[..open]);
+        let inner = &rest[open + 1..];
+        let digits = inner
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(inner.len());
+        if digits > 0 && inner[digits..].starts_with(")::") {
+            out.push_str(&inner[..digits]);
+            rest = &inner[digits + 1..];
+        } else {
+            out.push('(');
+            rest = inner;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `[NOT] DEFERRABLE` and `INITIALLY DEFERRED|IMMEDIATE` at `k`: the index
+/// after them and whether they make the constraint deferrable / deferred.
+pub(super) fn constraint_timing(st: &Statement, mut k: usize) -> (usize, bool, bool) {
+    let (mut deferrable, mut deferred) = (false, false);
+    loop {
+        if st.is_word(k, "deferrable") {
+            deferrable = true;
+            k += 1;
+        } else if st.is_word(k, "not") && st.is_word(k + 1, "deferrable") {
+            k += 2;
+        } else if st.is_word(k, "initially") {
+            if st.is_word(k + 1, "deferred") {
+                deferrable = true;
+                deferred = true;
+            }
+            k += 2;
+        } else {
+            return (k, deferrable, deferred);
+        }
+    }
+}
+
+/// Marks a constraint deferrable, adding the clause before `NOT VALID`.
+pub(super) fn apply_timing(constraint: &mut TableConstraint, deferrable: bool, deferred: bool) {
+    if !deferrable || constraint.is_deferrable {
+        return;
+    }
+    constraint.is_deferrable = true;
+    constraint.initially_deferred = deferred;
+    let clause = if deferred {
+        " DEFERRABLE INITIALLY DEFERRED"
+    } else {
+        " DEFERRABLE"
+    };
+    if let Some(definition) = &mut constraint.definition {
+        match definition.strip_suffix(" NOT VALID") {
+            Some(head) => *definition = format!("{head}{clause} NOT VALID"),
+            None => definition.push_str(clause),
+        }
+    }
+}
+
+/// `MATCH`, `ON UPDATE` and `ON DELETE` of a foreign key, in the order
+/// `pg_get_constraintdef` prints them, and the index after them.
+fn foreign_key_actions(st: &Statement, mut k: usize, to: usize) -> (String, usize) {
+    let mut matching = String::new();
+    let mut on_update = String::new();
+    let mut on_delete = String::new();
+    while k < to {
+        if st.is_word(k, "match") {
+            let kind = st.text(k + 1).to_uppercase();
+            if kind != "SIMPLE" {
+                matching = format!(" MATCH {kind}");
+            }
+            k += 2;
+        } else if st.is_word(k, "on")
+            && (st.is_word(k + 1, "update") || st.is_word(k + 1, "delete"))
+        {
+            let event = st.text(k + 1).to_uppercase();
+            let mut j = k + 2;
+            let mut action = st.text(j).to_uppercase();
+            j += 1;
+            if matches!(action.as_str(), "NO" | "SET") {
+                action = format!("{action} {}", st.text(j).to_uppercase());
+                j += 1;
+            }
+            if st.is_punct(j, "(") {
+                let (items, close) = list_items(st, j);
+                let columns: Vec<String> = items
+                    .iter()
+                    .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                    .collect();
+                action = format!("{action} ({})", quote_list(&columns));
+                j = close + 1;
+            }
+            if action != "NO ACTION" {
+                let clause = format!(" ON {event} {action}");
+                if event == "UPDATE" {
+                    on_update = clause;
+                } else {
+                    on_delete = clause;
+                }
+            }
+            k = j;
+        } else {
+            break;
+        }
+    }
+    (format!("{matching}{on_update}{on_delete}"), k)
+}
+
+pub(super) fn is_table_constraint(st: &Statement, i: usize) -> bool {
+    ["constraint", "check", "unique", "foreign", "exclude"]
+        .iter()
+        .any(|w| st.is_word(i, w))
+        || st.is_word(i, "primary") && st.is_word(i + 1, "key")
+}
+
+#[cfg(test)]
+#[path = "constraints_tests.rs"]
+mod tests;
@@ -0,0 +1,93 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn imports_foreign_keys_checks_and_indexes() {
+    let (dump, _) = import(SCHEMA);
+    let orders = table(&dump, "orders");
+    let definitions: Vec<&str> = orders
+        .constraints
+        .iter()
+        .filter_map(|c| c.definition.as_deref())
+        .collect();
+    assert_eq!(
+        definitions,
+        vec![
+            "PRIMARY KEY (id)",
+            "CHECK (total >= 0)",
+            "FOREIGN KEY (user_id) REFERENCES app.users(id) ON DELETE CASCADE"
+        ]
+    );
+    assert_eq!(
+        orders.indexes[0].indexdef,
+        "CREATE INDEX orders_user_idx ON app.orders USING btree (user_id)"
+    );
+    assert!(orders.columns[0].is_identity);
+    assert_eq!(
+        orders.columns[0].identity_generation.as_deref(),
+        Some("ALWAYS")
+    );
+}
+
+#[test]
+fn adds_and_drops_constraints() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER TABLE app.orders DROP CONSTRAINT orders_total_check;
+ALTER TABLE app.orders ADD CONSTRAINT orders_user_fkey FOREIGN KEY (user_id)
+    REFERENCES app.users DEFERRABLE INITIALLY DEFERRED;
+ALTER TABLE app.orders ADD UNIQUE (user_id, total);
+ALTER TABLE app.orders ADD CONSTRAINT positive CHECK (id > 0) NOT VALID;
+ALTER TABLE app.orders DROP CONSTRAINT IF EXISTS nothing;
+ALTER TABLE app.orders DROP CONSTRAINT nothing;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["constraint nothing not found"]);
+    let constraints: Vec<(&str, &str)> = table(&dump, "orders")
+        .constraints
+        .iter()
+        .map(|c| (c.name.as_str(), c.definition.as_deref().unwrap_or_default()))
+        .collect();
+    assert_eq!(
+        constraints,
+        vec![
+            ("orders_pkey", "PRIMARY KEY (id)"),
+            (
+                "orders_user_fkey",
+                "FOREIGN KEY (user_id) REFERENCES app.users(id) DEFERRABLE INITIALLY DEFERRED"
+            ),
+            (
+                "orders_user_id_fkey",
+                "FOREIGN KEY (user_id) REFERENCES app.users(id) ON DELETE CASCADE"
+            ),
+            ("orders_user_id_total_key", "UNIQUE (user_id, total)"),
+            ("positive", "CHECK (id > 0) NOT VALID"),
+        ]
+    );
+}
+
+#[test]
+fn fills_in_referenced_keys_after_earlier_constraints_are_dropped() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER TABLE app.orders ADD CONSTRAINT orders_user_fkey FOREIGN KEY (user_id)
+    REFERENCES app.users;
+ALTER TABLE app.orders DROP CONSTRAINT orders_pkey;
+ALTER TABLE app.orders DROP CONSTRAINT orders_user_fkey;
+ALTER TABLE app.orders ADD CONSTRAINT orders_owner_fkey FOREIGN KEY (user_id)
+    REFERENCES app.users ON DELETE SET NULL;"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let definitions: Vec<&str> = table(&dump, "orders")
+        .constraints
+        .iter()
+        .filter_map(|c| c.definition.as_deref())
+        .collect();
+    assert_eq!(
+        definitions,
+        vec![
+            "FOREIGN KEY (user_id) REFERENCES app.users(id) ON DELETE SET NULL",
+            "CHECK (total >= 0)",
+            "FOREIGN KEY (user_id) REFERENCES app.users(id) ON DELETE CASCADE"
+        ]
+    );
+}
@@ -0,0 +1,291 @@
+//! `CREATE INDEX` and `ALTER INDEX`.
+
+use super::Importer;
+use super::partitions::PartitionedIndex;
+use super::statements::{
+    closing_paren, list_items, quote_ident, quote_list, render, skip_if_not_exists, unwrap_parens,
+};
+use crate::comparer::scanner::{Relation, Statement};
+use crate::dump::table_index::TableIndex;
+
+impl Importer {
+    /// The definition of the index `name` (quoted) of `table` built from
+    /// `spec`, as `pg_get_indexdef` prints it.
+    pub(super) fn index_definition(
+        &self,
+        table: usize,
+        name: &str,
+        spec: &PartitionedIndex,
+    ) -> String {
+        let t = &self.dump.tables[table];
+        format!(
+            "CREATE {}INDEX {name} ON {}{}.{} USING {} ({}){}",
+            if spec.unique { "UNIQUE " } else { "" },
+            if t.partition_key.is_some() {
+                "ONLY "
+            } else {
+                ""
+            },
+            quote_ident(&t.raw_schema),
+            quote_ident(&t.raw_name),
+            spec.method,
+            spec.elements,
+            spec.tail
+        )
+    }
+
+    /// Adds the index `name` built from `spec` to `table`. On a partitioned
+    /// table the index is also copied to its partitions, unless `only`, and
+    /// kept for the partitions created or attached later.
+    pub(super) fn push_index(
+        &mut self,
+        table: usize,
+        name: &str,
+        spec: &PartitionedIndex,
+        tablespace: Option<String>,
+        only: bool,
+        is_partition_index: bool,
+    ) {
+        let indexdef = self.index_definition(table, &quote_ident(name), spec);
+        let t = &mut self.dump.tables[table];
+        t.indexes.push(TableIndex {
+            schema: t.schema.clone(),
+            table: t.name.clone(),
+            name: quote_ident(name),
+            catalog: tablespace,
+            indexdef,
+            is_partition_index,
+            comment: None,
+        });
+        if t.partition_key.is_none() {
+            return;
+        }
+        self.partitioned_indexes
+            .entry(table)
+            .or_default()
+            .push(spec.clone());
+        if only {
+            return;
+        }
+        let parent = self.qualified_table(table);
+        let partitions: Vec<usize> = (0..self.dump.tables.len())
+            .filter(|&i| self.dump.tables[i].partition_of.as_deref() == Some(&parent))
+            .collect();
+        for partition in partitions {
+            self.clone_index(partition, spec);
+        }
+    }
+
+    pub(super) fn create_index(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        unique: bool,
+    ) -> Result<(), String> {
+        let i = st.skip(i, &["concurrently"]);
+        let (mut j, if_not_exists) = skip_if_not_exists(st, i);
+        let mut name = None;
+        if !st.is_word(j, "on") {
+            name = st.relation(&[j]).map(|r| r.key);
+            j += 1;
+        }
+        if !st.is_word(j, "on") {
+            return Err("CREATE INDEX without ON".to_string());
+        }
+        let only = st.is_word(j + 1, "only");
+        let (relation, mut j) = st
+            .name_at(st.skip(j + 1, &["only"]))
+            .ok_or("missing table name")?;
+        let Some(table) = self.find_table(&relation) else {
+            if self.find_view(&relation).is_some() {
+                return Err("indexes on materialized views are not supported".to_string());
+            }
+            return Err(format!("table {} not found", relation.display));
+        };
+        let mut method = "btree".to_string();
+        if st.is_word(j, "using") {
+            method = st.text(j + 1).to_lowercase();
+            j += 2;
+        }
+        if !st.is_punct(j, "(") {
+            return Err("CREATE INDEX without a column list".to_string());
+        }
+        let (items, close) = list_items(st, j);
+        let elements: Vec<String> = items
+            .iter()
+            .map(|&(from, to)| index_element(st, from, to))
+            .collect();
+        let columns: Vec<String> = items
+            .iter()
+            .map(|&(from, to)| {
+                if to == from + 1 || (st.is_name_part(from) && !st.is_punct(from + 1, "(")) {
+                    st.relation(&[from]).map_or("expr".to_string(), |r| r.key)
+                } else {
+                    "expr".to_string()
+                }
+            })
+            .collect();
+        let mut tail = String::new();
+        let mut tablespace = None;
+        j = close + 1;
+        while j < st.tokens.len() && !st.is_punct(j, ";") {
+            if st.is_word(j, "include") && st.is_punct(j + 1, "(") {
+                let (items, close) = list_items(st, j + 1);
+                let names: Vec<String> = items
+                    .iter()
+                    .filter_map(|&(from, _)| st.relation(&[from]).map(|r| r.key))
+                    .collect();
+                tail.push_str(&format!(" INCLUDE ({})", quote_list(&names)));
+                j = close + 1;
+            } else if st.skip(j, &["nulls", "not", "distinct"]) != j {
+                tail.push_str(" NULLS NOT DISTINCT");
+                j += 3;
+            } else if st.skip(j, &["nulls", "distinct"]) != j {
+                j += 2;
+            } else if st.is_word(j, "with") && st.is_punct(j + 1, "(") {
+                let close = closing_paren(st, j + 1);
+                tail.push_str(&format!(" WITH ({})", render(st, j + 2, close, false)));
+                j = close + 1;
+            } else if st.is_word(j, "tablespace") {
+                tablespace = st.relation(&[j + 1]).map(|r| r.key);
+                j += 2;
+            } else if st.is_word(j, "where") {
+                let end = st.tokens.len() - usize::from(st.is_punct(st.tokens.len() - 1, ";"));
+                tail.push_str(&format!(
+                    " WHERE ({})",
+                    unwrap_parens(&render(st, j + 1, end, false))
+                ));
+                j = end;
+            } else {
+                self.notes.push(format!(
+                    "index option {} is not supported",
+                    st.text(j).to_uppercase()
+                ));
+                break;
+            }
+        }
+        let (schema, table_name) = {
+            let t = &self.dump.tables[table];
+            (t.raw_schema.clone(), t.raw_name.clone())
+        };
+        let name = match name {
+            Some(name) => {
+                if self.name_taken(&schema, &name) {
+                    if if_not_exists {
+                        return Ok(());
+                    }
+                    return Err(format!("relation {name} already exists"));
+                }
+                name
+            }
+            None => self.choose_name(&schema, &table_name, &columns.join("_"), "idx"),
+        };
+        let spec = PartitionedIndex {
+            unique,
+            method,
+            elements: elements.join(", "),
+            columns: columns.join("_"),
+            tail,
+        };
+        self.push_index(table, &name, &spec, tablespace, only, false);
+        Ok(())
+    }
+
+    /// `ALTER INDEX parent ATTACH PARTITION name`, as pg_dump writes the
+    /// indexes of partitions: the index becomes a partition index, and a
+    /// primary key or unique constraint of the partition an inherited one.
+    pub(super) fn alter_index(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let i = st.skip(i, &["if", "exists"]);
+        let (relation, next) = st.name_at(i).ok_or("missing index name")?;
+        if !st.is_word(next, "attach") || !st.is_word(next + 1, "partition") {
+            return Err(format!(
+                "ALTER INDEX {} is not supported",
+                st.text(next).to_uppercase()
+            ));
+        }
+        if self.find_index(&relation).is_none() && self.find_index_constraint(&relation).is_none() {
+            return Err(format!("index {} not found", relation.display));
+        }
+        let (partition, _) = st.name_at(next + 2).ok_or("missing index name")?;
+        if let Some((t, i)) = self.find_index(&partition) {
+            self.dump.tables[t].indexes[i].is_partition_index = true;
+            self.dump.tables[t].hash();
+        } else if let Some((t, c)) = self.find_index_constraint(&partition) {
+            self.dump.tables[t].constraints.remove(c);
+            self.dump.tables[t].hash();
+        } else {
+            return Err(format!("index {} not found", partition.display));
+        }
+        Ok(())
+    }
+
+    /// The table and position of the index `relation`.
+    pub(super) fn find_index(&self, relation: &Relation) -> Option<(usize, usize)> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                let name = quote_ident(&name);
+                self.dump.tables.iter().enumerate().find_map(|(t, table)| {
+                    (table.raw_schema == schema)
+                        .then(|| table.indexes.iter().position(|i| i.name == name))
+                        .flatten()
+                        .map(|i| (t, i))
+                })
+            })
+    }
+
+    /// The table and position of the primary key, unique or exclusion
+    /// constraint whose index is `relation`.
+    fn find_index_constraint(&self, relation: &Relation) -> Option<(usize, usize)> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                let name = quote_ident(&name);
+                self.dump.tables.iter().enumerate().find_map(|(t, table)| {
+                    (table.raw_schema == schema)
+                        .then(|| {
+                            table.constraints.iter().position(|c| {
+                                c.name == name
+                                    && matches!(
+                                        c.constraint_type.as_str(),
+                                        "PRIMARY KEY" | "UNIQUE" | "EXCLUDE"
+                                    )
+                            })
+                        })
+                        .flatten()
+                        .map(|c| (t, c))
+                })
+            })
+    }
+}
+
+/// An index element as `pg_get_indexdef` prints it: default sort order and
+/// null placement are left out.
+fn index_element(st: &Statement, from: usize, mut to: usize) -> String {
+    let mut nulls = None;
+    if st.is_word(to.saturating_sub(2), "nulls") {
+        nulls = Some(st.text(to - 1).to_uppercase());
+        to -= 2;
+    }
+    let mut descending = false;
+    if st.is_word(to - 1, "desc") {
+        descending = true;
+        to -= 1;
+    } else if st.is_word(to - 1, "asc") {
+        to -= 1;
+    }
+    let mut element = render(st, from, to, false);
+    if descending {
+        element.push_str(" DESC");
+    }
+    match (nulls.as_deref(), descending) {
+        (Some("FIRST"), false) => element.push_str(" NULLS FIRST"),
+        (Some("LAST"), true) => element.push_str(" NULLS LAST"),
+        _ => {}
+    }
+    element
+}
+
+#[cfg(test)]
+#[path = "indexes_tests.rs"]
+mod tests;
@@ -0,0 +1,41 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn imports_indexes() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS users_lower_email
+    ON app.users USING btree (lower(email)) WHERE name IS NOT NULL;
+CREATE INDEX ON app.orders (total DESC NULLS LAST);
+CREATE INDEX IF NOT EXISTS orders_user_idx ON app.orders (user_id);
+CREATE INDEX missing_idx ON app.missing (id);
+ALTER INDEX app.orders_user_idx RENAME TO orders_idx;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "table app.missing not found",
+            "ALTER INDEX RENAME is not supported"
+        ]
+    );
+    let users = table(&dump, "users");
+    assert!(users.has_indexes);
+    assert_eq!(
+        users.indexes[0].indexdef,
+        "CREATE UNIQUE INDEX users_lower_email ON app.users USING btree (lower(email)) \
+         WHERE (name IS NOT NULL)"
+    );
+    let definitions: Vec<&str> = table(&dump, "orders")
+        .indexes
+        .iter()
+        .map(|i| i.indexdef.as_str())
+        .collect();
+    assert_eq!(
+        definitions,
+        vec![
+            "CREATE INDEX orders_total_idx ON app.orders USING btree (total DESC NULLS LAST)",
+            "CREATE INDEX orders_user_idx ON app.orders USING btree (user_id)"
+        ]
+    );
+}
@@ -0,0 +1,741 @@
+//! Offline dumps: builds a [`Dump`] from SQL DDL files instead of
+//! introspecting a live server (`dump --from-sql`).
+//!
+//! Statements are modelled into the same structures the catalog queries
+//! fill, with values normalised to the forms the server prints back, so the
+//! output of `pg_dump --schema-only` round-trips against a live dump.
+//! Hand-written expressions that the server rewrites on the way in
+//! (implicit casts, `IN` lists, view queries, index expressions) are kept
+//! as written and may show up as changes. Statements that cannot be
+//! modelled are reported as [`SkippedStatement`]s rather than guessed at.
+//!
+//! Dependency information (`related_views`, `column_dependents`) is not
+//! derived, so an offline dump compares best as the `--to` side.
+
+mod acl;
+mod builtins;
+mod comments;
+mod constraints;
+mod indexes;
+mod partitions;
+mod policies;
+mod routines;
+mod sequences;
+mod similar_to;
+mod statements;
+mod tables;
+mod triggers;
+mod types;
+mod views;
+
+pub(crate) use routines::argument_types;
+
+use crate::comparer::scanner::{Relation, Statement, TokenKind, split_statements};
+use crate::config::dump_config::DumpConfig;
+use crate::dump::core::Dump;
+use crate::dump::extension::Extension;
+use crate::dump::schema::Schema;
+use crate::dump::tree;
+use constraints::PendingForeignKey;
+use partitions::PartitionedIndex;
+use similar_to::similar_to;
+use sqlx::postgres::types::Oid;
+use statements::{
+    literal_value, make_object_name, quote_ident, skip_if_not_exists, statement_kind, unquote,
+    visible_name,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use types::TypeRef;
+use views::view_is;
+
+/// Owner assumed for objects when the config names no user.
+const DEFAULT_OWNER: &str = "postgres";
+
+/// First oid handed out to imported objects, as on a fresh cluster.
+const FIRST_OBJECT_ID: u32 = 16384;
+
+/// A statement the import could not model, reported instead of guessed at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedStatement {
+    pub file: String,
+    pub line: usize,
+    /// First line of the statement, shortened for display.
+    pub statement: String,
+    pub reason: String,
+}
+
+impl fmt::Display for SkippedStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ({})",
+            self.file, self.line, self.statement, self.reason
+        )
+    }
+}
+
+/// Builds a dump from `path`: a single SQL file, or every `*.sql` file
+/// below a directory, read in path order. A tree written by
+/// [`tree::export_tree`] is read in dependency order instead. Only schemas
+/// matching `config.scheme` are kept, as with a live dump.
+pub fn import_sql(config: DumpConfig, path: &str) -> Result<(Dump, Vec<SkippedStatement>), Error> {
+    let root = Path::new(path);
+    let mut files = Vec::new();
+    collect_sql_files(root, &mut files)?;
+    if files.is_empty() {
+        return Err(Error::other(format!("No .sql files found in {path}.")));
+    }
+    if tree::is_tree(root) {
+        files.sort_by_key(|file| tree::read_order(file.strip_prefix(root).unwrap_or(file)));
+    }
+    let mut sources = Vec::with_capacity(files.len());
+    for file in files {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| Error::other(format!("Failed to read {}: {e}.", file.display())))?;
+        sources.push((file.display().to_string(), text));
+    }
+    let (mut dump, skipped) = import_sources(config, &sources);
+    dump.metadata.source = Some(path.to_string());
+    Ok((dump, skipped))
+}
+
+fn collect_sql_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if !path.is_dir() {
+        fs::metadata(path)
+            .map_err(|e| Error::other(format!("Failed to read {}: {e}.", path.display())))?;
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_sql_files(&entry, files)?;
+        } else if entry.extension().is_some_and(|ext| ext == "sql") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Builds a dump from `(file name, SQL text)` pairs.
+///
+/// Schemas, extensions, types and domains are read first, from every file,
+/// so a column can use a type declared in a file that sorts after its table.
+pub(crate) fn import_sources(
+    config: DumpConfig,
+    sources: &[(String, String)],
+) -> (Dump, Vec<SkippedStatement>) {
+    let mut importer = Importer::new(config);
+    let mut skipped: Vec<(usize, SkippedStatement)> = Vec::new();
+    // psql meta-commands (`\connect`, `\restrict`, ...) are not SQL; blank
+    // them out so line numbers still point into the original file.
+    let texts: Vec<String> = sources
+        .iter()
+        .map(|(_, text)| {
+            text.lines()
+                .map(|line| {
+                    if line.trim_start().starts_with('\\') {
+                        ""
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+
+    for types_pass in [true, false] {
+        for (index, ((file, _), text)) in sources.iter().zip(&texts).enumerate() {
+            importer.search_path = vec!["public".to_string()];
+            for (_, sql) in split_statements(text) {
+                let st = Statement::parse(sql);
+                if st.tokens.is_empty() || st.is_punct(0, ";") {
+                    continue;
+                }
+                let is_type = st.is_word(0, "create")
+                    && ["schema", "extension", "type", "domain"]
+                        .iter()
+                        .any(|w| st.is_word(1, w));
+                let is_search_path = is_search_path_statement(&st);
+                if !is_search_path && is_type != types_pass {
+                    continue;
+                }
+                let outcome = importer.statement(&st);
+                let reasons = outcome.err().into_iter().chain(importer.notes.drain(..));
+                let reasons: Vec<String> = reasons.collect();
+                if reasons.is_empty() || (is_search_path && types_pass) {
+                    continue;
+                }
+                let offset = sql.as_ptr() as usize - text.as_ptr() as usize;
+                let line = text[..offset].matches('\n').count() + 1;
+                let first_line = sql.lines().next().unwrap_or_default().trim();
+                let statement = if first_line.chars().count() > 80 {
+                    format!("{}...", first_line.chars().take(77).collect::<String>())
+                } else {
+                    first_line.to_string()
+                };
+                for reason in reasons {
+                    skipped.push((
+                        index,
+                        SkippedStatement {
+                            file: file.clone(),
+                            line,
+                            statement: statement.clone(),
+                            reason,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    skipped.sort_by_key(|(index, s)| (*index, s.line));
+    let dump = importer.finish();
+    (dump, skipped.into_iter().map(|(_, s)| s).collect())
+}
+
+/// `SET search_path ...` or pg_dump's `SELECT pg_catalog.set_config(...)`.
+fn is_search_path_statement(st: &Statement) -> bool {
+    if st.is_word(0, "set") {
+        let i = st.skip(st.skip(1, &["session"]), &["local"]);
+        return st.is_word(i, "search_path");
+    }
+    st.is_word(0, "select")
+        && st
+            .tokens
+            .iter()
+            .enumerate()
+            .any(|(i, t)| t.kind == TokenKind::Word && st.is_word(i, "set_config"))
+}
+
+struct Importer {
+    dump: Dump,
+    /// Database name, used for the `*_catalog` fields.
+    catalog: String,
+    /// Role that owns objects no `OWNER TO` reassigns.
+    owner: String,
+    search_path: Vec<String>,
+    next_oid: u32,
+    /// Parts of the current statement that were not modelled.
+    notes: Vec<String>,
+    /// Declared type of each column, keyed by table index and raw name.
+    column_types: HashMap<(usize, String), TypeRef>,
+    /// Types declared in the files, keyed by raw schema and name.
+    type_refs: HashMap<(String, String), TypeRef>,
+    /// Identity argument types of each routine, parallel to `dump.routines`.
+    routine_signatures: Vec<Vec<String>>,
+    pending_foreign_keys: Vec<PendingForeignKey>,
+    /// Indexes of each partitioned table, keyed by table index.
+    partitioned_indexes: HashMap<usize, Vec<PartitionedIndex>>,
+}
+
+impl Importer {
+    fn new(config: DumpConfig) -> Self {
+        let catalog = config.database.clone();
+        let owner = if config.user.is_empty() {
+            DEFAULT_OWNER.to_string()
+        } else {
+            config.user.clone()
+        };
+        let mut dump = Dump::new(config);
+        // Every database starts with `public`; files only ever alter it.
+        let mut public = Schema::new(
+            "public".to_string(),
+            "public".to_string(),
+            Some("standard public schema".to_string()),
+        );
+        public.owner = "pg_database_owner".to_string();
+        public.acl = vec![
+            "pg_database_owner=UC/pg_database_owner".to_string(),
+            "=U/pg_database_owner".to_string(),
+        ];
+        dump.schemas.push(public);
+        Self {
+            dump,
+            catalog,
+            owner,
+            search_path: vec!["public".to_string()],
+            next_oid: FIRST_OBJECT_ID,
+            notes: Vec::new(),
+            column_types: HashMap::new(),
+            type_refs: HashMap::new(),
+            routine_signatures: Vec::new(),
+            pending_foreign_keys: Vec::new(),
+            partitioned_indexes: HashMap::new(),
+        }
+    }
+
+    fn oid(&mut self) -> Oid {
+        self.next_oid += 1;
+        Oid(self.next_oid - 1)
+    }
+
+    fn statement(&mut self, st: &Statement) -> Result<(), String> {
+        match st.text(0).to_lowercase().as_str() {
+            "create" => self.create(st),
+            "alter" => self.alter(st),
+            "comment" => self.comment(st),
+            "grant" => self.grant(st, true),
+            "revoke" => self.grant(st, false),
+            "set" => {
+                self.set(st);
+                Ok(())
+            }
+            "select" if is_search_path_statement(st) => {
+                self.set_config(st);
+                Ok(())
+            }
+            "begin" | "commit" | "start" | "end" => Ok(()),
+            _ => Err(format!("{} is not supported", statement_kind(st, 0))),
+        }
+    }
+
+    fn set(&mut self, st: &Statement) {
+        let mut i = st.skip(st.skip(1, &["session"]), &["local"]);
+        if !st.is_word(i, "search_path") {
+            return;
+        }
+        i += 1;
+        if st.is_word(i, "to") || st.is_punct(i, "=") {
+            i += 1;
+        }
+        let mut path = Vec::new();
+        while i < st.tokens.len() && !st.is_punct(i, ";") {
+            if !st.is_punct(i, ",") {
+                let value = match st.tokens[i].kind {
+                    TokenKind::Literal => literal_value(st.text(i)).unwrap_or_default(),
+                    _ => st.relation(&[i]).map(|r| r.key).unwrap_or_default(),
+                };
+                path.push(value);
+            }
+            i += 1;
+        }
+        self.search_path = path
+            .into_iter()
+            .filter(|s| !s.is_empty() && s != "$user")
+            .collect();
+    }
+
+    /// `SELECT pg_catalog.set_config('search_path', '...', false)`.
+    fn set_config(&mut self, st: &Statement) {
+        let literals: Vec<String> = (0..st.tokens.len())
+            .filter(|&i| st.tokens[i].kind == TokenKind::Literal)
+            .filter_map(|i| literal_value(st.text(i)))
+            .collect();
+        if let [name, value, ..] = literals.as_slice()
+            && name == "search_path"
+        {
+            self.search_path = value
+                .split(',')
+                .map(|s| unquote(s.trim()))
+                .filter(|s| !s.is_empty() && s != "$user")
+                .collect();
+        }
+    }
+
+    /// Schema and name of the object `relation` names when creating it.
+    fn target(&self, relation: &Relation) -> (String, String) {
+        let parts = &relation.parts;
+        let name = parts.last().cloned().unwrap_or_default();
+        let schema = if parts.len() >= 2 {
+            parts[parts.len() - 2].clone()
+        } else {
+            self.search_path
+                .first()
+                .cloned()
+                .unwrap_or_else(|| "public".to_string())
+        };
+        (schema, name)
+    }
+
+    /// Schema/name pairs to try, in order, when looking `relation` up.
+    fn candidates(&self, relation: &Relation) -> Vec<(String, String)> {
+        if relation.parts.len() >= 2 {
+            return vec![self.target(relation)];
+        }
+        let name = relation.key.clone();
+        self.search_path
+            .iter()
+            .map(|schema| (schema.clone(), name.clone()))
+            .collect()
+    }
+
+    fn find_schema(&self, name: &str) -> Option<usize> {
+        self.dump.schemas.iter().position(|s| s.raw_name == name)
+    }
+
+    fn find_table(&self, relation: &Relation) -> Option<usize> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                self.dump
+                    .tables
+                    .iter()
+                    .position(|t| t.raw_schema == schema && t.raw_name == name)
+            })
+    }
+
+    fn find_view(&self, relation: &Relation) -> Option<usize> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                self.dump
+                    .views
+                    .iter()
+                    .position(|v| view_is(v, &schema, &name))
+            })
+    }
+
+    fn find_sequence(&self, relation: &Relation) -> Option<usize> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                let (schema, name) = (quote_ident(&schema), quote_ident(&name));
+                self.dump
+                    .sequences
+                    .iter()
+                    .position(|s| s.schema == schema && s.name == name)
+            })
+    }
+
+    fn find_type(&self, relation: &Relation) -> Option<usize> {
+        self.candidates(relation)
+            .into_iter()
+            .find_map(|(schema, name)| {
+                let (schema, name) = (quote_ident(&schema), quote_ident(&name));
+                self.dump
+                    .types
+                    .iter()
+                    .position(|t| t.schema == schema && t.typname == name)
+            })
+    }
+
+    /// Whether `name` is already used by a relation or constraint in
+    /// `schema`.
+    fn name_taken(&self, schema: &str, name: &str) -> bool {
+        let quoted = quote_ident(name);
+        let quoted_schema = quote_ident(schema);
+        self.dump.tables.iter().any(|t| {
+            t.raw_schema == schema
+                && (t.raw_name == name
+                    || t.indexes.iter().any(|i| i.name == quoted)
+                    || t.constraints.iter().any(|c| c.name == quoted))
+        }) || self
+            .dump
+            .sequences
+            .iter()
+            .any(|s| s.schema == quoted_schema && s.name == quoted)
+            || self.dump.views.iter().any(|v| view_is(v, schema, name))
+            || self
+                .dump
+                .types
+                .iter()
+                .any(|t| t.schema == quoted_schema && t.typname == quoted)
+    }
+
+    /// Picks an unused name the way the server does for implicit indexes,
+    /// constraints and sequences: `table_column_label`, then `label1`, ...
+    fn choose_name(&self, schema: &str, table: &str, column: &str, label: &str) -> String {
+        let mut pass = 0;
+        loop {
+            let suffix = if pass == 0 {
+                label.to_string()
+            } else {
+                format!("{label}{pass}")
+            };
+            let name = make_object_name(table, column, &suffix);
+            if !self.name_taken(schema, &name) {
+                return name;
+            }
+            pass += 1;
+        }
+    }
+
+    fn create(&mut self, st: &Statement) -> Result<(), String> {
+        let mut i = st.skip(1, &["or", "replace"]);
+        let mut unlogged = false;
+        if st.is_word(i, "unlogged") {
+            unlogged = true;
+            i += 1;
+        }
+        if ["temp", "temporary", "global", "local"]
+            .iter()
+            .any(|w| st.is_word(i, w))
+        {
+            return Err("temporary objects are not supported".to_string());
+        }
+        let kind = st.text(i).to_lowercase();
+        match kind.as_str() {
+            "schema" => self.create_schema(st, i + 1),
+            "extension" => self.create_extension(st, i + 1),
+            "sequence" => self.create_sequence(st, i + 1, unlogged),
+            "table" => self.create_table(st, i + 1, unlogged),
+            "index" => self.create_index(st, i + 1, false),
+            "unique" if st.is_word(i + 1, "index") => self.create_index(st, i + 2, true),
+            "view" => self.create_view(st, i + 1, false),
+            "materialized" if st.is_word(i + 1, "view") => self.create_view(st, i + 2, true),
+            "function" | "procedure" => self.create_routine(st, i + 1, kind == "procedure"),
+            "type" => self.create_type(st, i + 1),
+            "domain" => self.create_domain(st, i + 1),
+            "trigger" => self.create_trigger(st, i + 1, false),
+            "constraint" if st.is_word(i + 1, "trigger") => self.create_trigger(st, i + 2, true),
+            "policy" => self.create_policy(st, i + 1),
+            _ => Err(format!("{} is not supported", statement_kind(st, 0))),
+        }
+    }
+
+    fn create_schema(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (i, if_not_exists) = skip_if_not_exists(st, i);
+        let (name, authorization) = if st.is_word(i, "authorization") {
+            let role = st.relation(&[i + 1]).ok_or("missing role name")?.key;
+            (role.clone(), Some(role))
+        } else {
+            let (relation, next) = st.name_at(i).ok_or("missing schema name")?;
+            let role = st
+                .is_word(next, "authorization")
+                .then(|| st.relation(&[next + 1]).map(|r| r.key))
+                .flatten();
+            (relation.key, role)
+        };
+        if self.find_schema(&name).is_some() {
+            // `public` always exists; older dumps still create it.
+            if if_not_exists || name == "public" {
+                return Ok(());
+            }
+            return Err(format!("schema {name} already exists"));
+        }
+        let mut schema = Schema::new(quote_ident(&name), name, None);
+        schema.owner = quote_ident(authorization.as_deref().unwrap_or(&self.owner));
+        schema.hash();
+        self.dump.schemas.push(schema);
+        Ok(())
+    }
+
+    fn create_extension(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (i, _) = skip_if_not_exists(st, i);
+        let (relation, next) = st.name_at(i).ok_or("missing extension name")?;
+        let mut schema = None;
+        let mut version = String::new();
+        let mut j = st.skip(next, &["with"]);
+        while j < st.tokens.len() && !st.is_punct(j, ";") {
+            if st.is_word(j, "schema") {
+                schema = st.relation(&[j + 1]).map(|r| r.key);
+                j += 1;
+            } else if st.is_word(j, "version") {
+                version =
+                    literal_value(st.text(j + 1)).unwrap_or_else(|| st.text(j + 1).to_string());
+                j += 1;
+            }
+            j += 1;
+        }
+        let name = quote_ident(&relation.key);
+        if self.dump.extensions.iter().any(|e| e.name == name) {
+            return Ok(());
+        }
+        let schema = schema.unwrap_or_else(|| self.target(&relation).0);
+        let mut extension = Extension::new(name, version, quote_ident(&schema));
+        extension.owner = quote_ident(&self.owner);
+        self.dump.extensions.push(extension);
+        Ok(())
+    }
+
+    fn require_schema(&self, schema: &str) -> Result<(), String> {
+        match self.find_schema(schema) {
+            Some(_) => Ok(()),
+            None => Err(format!("schema {schema} does not exist")),
+        }
+    }
+
+    fn alter(&mut self, st: &Statement) -> Result<(), String> {
+        match st.text(1).to_lowercase().as_str() {
+            "table" => self.alter_table(st, 2),
+            "sequence" => self.alter_sequence(st, 2),
+            "view" => self.alter_view(st, 2),
+            "materialized" if st.is_word(2, "view") => self.alter_view(st, 3),
+            "function" | "procedure" | "routine" => self.alter_routine(st, 2),
+            "type" | "domain" => self.alter_type(st, 2),
+            "schema" => self.alter_schema(st, 2),
+            "index" => self.alter_index(st, 2),
+            "default" if st.is_word(2, "privileges") => self.alter_default_privileges(st),
+            _ => Err(format!("{} is not supported", statement_kind(st, 0))),
+        }
+    }
+
+    fn alter_schema(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let name = st.relation(&[i]).ok_or("missing schema name")?.key;
+        let index = self
+            .find_schema(&name)
+            .ok_or_else(|| format!("schema {name} not found"))?;
+        if st.skip(i + 1, &["owner", "to"]) == i + 1 {
+            return Err(format!("{} is not supported", statement_kind(st, 0)));
+        }
+        let role = quote_ident(&self.role_at(st, i + 3).ok_or("missing role name")?);
+        let schema = &mut self.dump.schemas[index];
+        Self::reassign_acl(&mut schema.acl, &schema.owner, &role);
+        schema.owner = role;
+        schema.hash();
+        Ok(())
+    }
+
+    /// Settles what needs every file read first: foreign keys that
+    /// reference a primary key implicitly, the relation flags, and the
+    /// schema filter.
+    fn finish(mut self) -> Dump {
+        for pending in std::mem::take(&mut self.pending_foreign_keys) {
+            let (schema, name) = &pending.target;
+            let target = self
+                .dump
+                .tables
+                .iter()
+                .find(|t| t.raw_schema == *schema && t.raw_name == *name);
+            let key = target.and_then(|t| {
+                t.constraints
+                    .iter()
+                    .find(|c| c.constraint_type == "PRIMARY KEY")
+                    .and_then(|c| c.definition.as_deref())
+                    .and_then(|d| d.strip_prefix("PRIMARY KEY "))
+                    .map(str::to_string)
+            });
+            let reference = format!(
+                "{} REFERENCES {}{}{}",
+                pending.columns,
+                visible_name(schema, name),
+                key.unwrap_or_default(),
+                pending.tail
+            );
+            // Timing and NOT VALID clauses already follow the column list.
+            let constraint = self.dump.tables[pending.table]
+                .constraints
+                .iter_mut()
+                .find(|c| c.name == pending.constraint);
+            if let Some(definition) = constraint.and_then(|c| c.definition.as_mut()) {
+                *definition = definition.replacen(&pending.columns, &reference, 1);
+            }
+        }
+
+        let referenced: Vec<(String, String)> = self
+            .dump
+            .tables
+            .iter()
+            .flat_map(|t| &t.constraints)
+            .filter(|c| c.constraint_type == "FOREIGN KEY")
+            .filter_map(|c| c.definition.as_deref())
+            .filter_map(|d| {
+                let target = d.split(" REFERENCES ").nth(1)?;
+                let target = &target[..target.find('(').unwrap_or(target.len())];
+                Some(match target.split_once('.') {
+                    Some((schema, name)) => (unquote(schema), unquote(name)),
+                    None => ("public".to_string(), unquote(target)),
+                })
+            })
+            .collect();
+        let own: Vec<(bool, bool)> = self
+            .dump
+            .tables
+            .iter()
+            .map(|table| {
+                let indexed = table.constraints.iter().any(|c| {
+                    matches!(
+                        c.constraint_type.as_str(),
+                        "PRIMARY KEY" | "UNIQUE" | "EXCLUDE"
+                    )
+                });
+                let triggered = table
+                    .constraints
+                    .iter()
+                    .any(|c| c.constraint_type == "FOREIGN KEY")
+                    || referenced.iter().any(|(schema, name)| {
+                        table.raw_schema == *schema && table.raw_name == *name
+                    });
+                (indexed, triggered)
+            })
+            .collect();
+        // A partition has the indexes and foreign key triggers of the
+        // constraints it inherits, which the catalog does not list as its own.
+        let inherited: Vec<(bool, bool)> = (0..own.len())
+            .map(|table| {
+                let (mut indexed, mut triggered) = own[table];
+                let mut current = table;
+                for _ in 0..own.len() {
+                    let Some(parent) = self.parent_table(current) else {
+                        break;
+                    };
+                    indexed |= own[parent].0;
+                    triggered |= own[parent].1;
+                    current = parent;
+                }
+                (indexed, triggered)
+            })
+            .collect();
+        for (table, (indexed, triggered)) in self.dump.tables.iter_mut().zip(inherited) {
+            table.has_indexes = !table.indexes.is_empty() || indexed;
+            table.has_triggers = !table.triggers.is_empty() || triggered;
+            table.constraints.sort_by_key(|c| unquote(&c.name));
+            table.indexes.sort_by_key(|i| unquote(&i.name));
+        }
+
+        let pattern = self.dump.configuration.scheme.clone();
+        let keep = |schema: &str| similar_to(&unquote(schema), &pattern);
+        let dump = &mut self.dump;
+        dump.schemas.retain(|s| keep(&s.raw_name));
+        dump.extensions
+            .retain(|e| keep(&e.schema) || e.schema == "public");
+        dump.tables.retain(|t| keep(&t.raw_schema));
+        dump.views.retain(|v| keep(&v.schema));
+        dump.sequences.retain(|s| keep(&s.schema));
+        dump.routines.retain(|r| keep(&r.schema));
+        let enum_types: Vec<Oid> = dump
+            .types
+            .iter()
+            .filter(|t| !keep(&t.schema))
+            .map(|t| t.oid)
+            .collect();
+        dump.enums.retain(|e| !enum_types.contains(&e.enumtypid));
+        dump.types.retain(|t| keep(&t.schema));
+        dump.default_privileges
+            .retain(|p| p.schema_name.is_empty() || keep(&p.schema_name));
+        dump.default_privileges.sort_by(|a, b| {
+            (
+                unquote(&a.role_name),
+                unquote(&a.schema_name),
+                &a.object_type,
+            )
+                .cmp(&(
+                    unquote(&b.role_name),
+                    unquote(&b.schema_name),
+                    &b.object_type,
+                ))
+        });
+
+        for schema in &mut dump.schemas {
+            schema.hash();
+        }
+        for table in &mut dump.tables {
+            table.hash();
+        }
+        for view in &mut dump.views {
+            view.hash();
+        }
+        for sequence in &mut dump.sequences {
+            sequence.hash();
+        }
+        for routine in &mut dump.routines {
+            routine.hash();
+        }
+        for pg_type in &mut dump.types {
+            pg_type.hash();
+        }
+        self.dump
+    }
+}
+
+#[cfg(test)]
+#[path = "mod_tests.rs"]
+mod tests;
@@ -0,0 +1,244 @@
+use super::*;
+use crate::config::ssl_settings::SslSettings;
+use crate::dump::table::Table;
+
+pub(super) fn config(scheme: &str) -> DumpConfig {
+    DumpConfig {
+        host: String::new(),
+        port: String::new(),
+        user: "postgres".to_string(),
+        password: String::new(),
+        database: "offline".to_string(),
+        scheme: scheme.to_string(),
+        ssl: false,
+        ssl_settings: SslSettings::default(),
+        file: String::new(),
+    }
+}
+
+pub(super) fn import(sql: &str) -> (Dump, Vec<SkippedStatement>) {
+    import_sources(config("app"), &[("a.sql".to_string(), sql.to_string())])
+}
+
+pub(super) const SCHEMA: &str = "
+CREATE SCHEMA app;
+CREATE TYPE app.mood AS ENUM ('sad', 'ok', 'happy');
+CREATE TYPE app.pair AS (x integer, y text);
+CREATE DOMAIN app.email AS text CHECK (VALUE LIKE '%@%');
+CREATE TABLE app.users (
+    id serial PRIMARY KEY,
+    email app.email NOT NULL UNIQUE,
+    mood app.mood DEFAULT 'ok',
+    name varchar(100)
+);
+CREATE TABLE app.orders (
+    id bigint GENERATED ALWAYS AS IDENTITY,
+    user_id integer REFERENCES app.users (id) ON DELETE CASCADE,
+    total numeric(10,2) CHECK (total >= 0),
+    CONSTRAINT orders_pkey PRIMARY KEY (id)
+);
+CREATE INDEX orders_user_idx ON app.orders (user_id);
+";
+
+pub(super) fn table<'a>(dump: &'a Dump, name: &str) -> &'a Table {
+    dump.tables.iter().find(|t| t.name == name).unwrap()
+}
+
+#[test]
+fn reports_statements_it_cannot_model() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE RULE r AS ON INSERT TO app.users
+    DO INSTEAD NOTHING;
+GRANT SELECT ON app.missing TO reader;"
+    ));
+    assert_eq!(dump.tables.len(), 2);
+    let report: Vec<String> = skipped.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        report,
+        vec![
+            "a.sql:20: CREATE RULE r AS ON INSERT TO app.users (CREATE RULE is not supported)"
+                .to_string(),
+            "a.sql:22: GRANT SELECT ON app.missing TO reader; (relation app.missing not found)"
+                .to_string(),
+        ]
+    );
+}
+
+#[test]
+fn reports_every_statement_kind_it_does_not_model() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+DO $$ BEGIN END $$;
+INSERT INTO app.users (email) VALUES ('a@b');
+CREATE TEMP TABLE scratch (id int);
+CREATE AGGREGATE app.total (integer) (sfunc = int4pl, stype = integer);
+CREATE RULE r AS ON INSERT TO app.users DO INSTEAD NOTHING;
+CREATE TYPE app.span AS RANGE (subtype = integer);
+CREATE SERVER files FOREIGN DATA WRAPPER file_fdw;
+CREATE FOREIGN TABLE app.remote (id int) SERVER files;
+CREATE USER MAPPING FOR postgres SERVER files;
+CREATE STATISTICS app.users_stats ON email, mood FROM app.users;
+CREATE EVENT TRIGGER ddl ON ddl_command_end EXECUTE FUNCTION app.log_ddl();
+CREATE COLLATION app.german (locale = 'de_DE');
+CREATE TEXT SEARCH CONFIGURATION app.english (COPY = english);
+CREATE CAST (app.pair AS text) WITH INOUT;
+CREATE OPERATOR app.=== (leftarg = integer, rightarg = integer, function = int4eq);
+CREATE PUBLICATION everything FOR ALL TABLES;
+CREATE TABLE app.typed OF app.pair;
+CREATE TABLE app.child (extra int) INHERITS (app.users);
+ALTER TABLE app.users INHERIT app.orders;"
+    ));
+    let names: Vec<&str> = dump.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["users", "orders"]);
+    assert_eq!(dump.types.len(), 3);
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "DO is not supported",
+            "INSERT INTO is not supported",
+            "temporary objects are not supported",
+            "CREATE AGGREGATE is not supported",
+            "CREATE RULE is not supported",
+            "CREATE TYPE AS RANGE is not supported",
+            "CREATE SERVER is not supported",
+            "CREATE FOREIGN is not supported",
+            "CREATE USER is not supported",
+            "CREATE STATISTICS is not supported",
+            "CREATE EVENT is not supported",
+            "CREATE COLLATION is not supported",
+            "CREATE TEXT is not supported",
+            "CREATE CAST is not supported",
+            "CREATE OPERATOR is not supported",
+            "CREATE PUBLICATION is not supported",
+            "CREATE TABLE OF is not supported",
+            "INHERITS is not supported",
+            "ALTER TABLE INHERIT is not supported",
+        ]
+    );
+}
+
+#[test]
+fn creates_and_alters_schemas_and_extensions() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app AUTHORIZATION admin;
+CREATE SCHEMA IF NOT EXISTS app;
+CREATE SCHEMA public;
+CREATE SCHEMA AUTHORIZATION other;
+CREATE EXTENSION IF NOT EXISTS citext WITH SCHEMA app VERSION '1.6';
+CREATE EXTENSION citext;
+ALTER SCHEMA app OWNER TO owner;
+CREATE SCHEMA app;",
+    );
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["schema app already exists"]);
+    let schemas: Vec<(&str, &str)> = dump
+        .schemas
+        .iter()
+        .map(|s| (s.name.as_str(), s.owner.as_str()))
+        .collect();
+    assert_eq!(schemas, vec![("app", "owner")]);
+    let extensions: Vec<(&str, &str, &str)> = dump
+        .extensions
+        .iter()
+        .map(|e| (e.name.as_str(), e.version.as_str(), e.schema.as_str()))
+        .collect();
+    assert_eq!(extensions, vec![("citext", "1.6", "app")]);
+}
+
+#[test]
+fn reads_types_declared_in_later_files() {
+    let (dump, skipped) = import_sources(
+        config("app"),
+        &[
+            (
+                "1.sql".to_string(),
+                "CREATE TABLE app.t (m app.mood);".to_string(),
+            ),
+            (
+                "2.sql".to_string(),
+                "CREATE SCHEMA app; CREATE TYPE app.mood AS ENUM ('a');".to_string(),
+            ),
+        ],
+    );
+    assert!(skipped.is_empty(), "{skipped:?}");
+    assert_eq!(dump.tables[0].columns[0].udt_name.as_deref(), Some("mood"));
+}
+
+#[test]
+fn keeps_only_schemas_matching_the_scheme() {
+    let (dump, _) = import_sources(
+        config("app"),
+        &[(
+            "a.sql".to_string(),
+            "CREATE SCHEMA app; CREATE SCHEMA other;
+             CREATE TABLE app.a (id int); CREATE TABLE other.b (id int);"
+                .to_string(),
+        )],
+    );
+    let names: Vec<&str> = dump.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["a"]);
+    assert_eq!(dump.schemas.len(), 1);
+}
+
+#[test]
+fn follows_pg_dump_search_path() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+         SELECT pg_catalog.set_config('search_path', '', false);
+         SET search_path = app, pg_catalog;
+         CREATE TABLE t (id integer);",
+    );
+    assert!(skipped.is_empty(), "{skipped:?}");
+    assert_eq!(dump.tables[0].schema, "app");
+}
+
+#[test]
+fn import_sql_rejects_empty_directories() {
+    let dir = std::env::temp_dir().join(format!("pgc_sql_import_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let result = import_sql(config("app"), dir.to_str().unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn imports_the_partitions_of_the_test_schema() {
+    let (dump, skipped) = import_sources(
+        config("data|test_schema"),
+        &[(
+            "schema_a.sql".to_string(),
+            include_str!("../../../../data/test/schema_a.sql").to_string(),
+        )],
+    );
+    let partitions: Vec<&SkippedStatement> = skipped
+        .iter()
+        .filter(|s| s.to_string().to_lowercase().contains("partition"))
+        .collect();
+    assert!(partitions.is_empty(), "{partitions:?}");
+    let partition = |name: &str| {
+        let t = table(&dump, name);
+        (t.partition_of.as_deref(), t.partition_bound.as_deref())
+    };
+    assert_eq!(
+        table(&dump, "sensor_data").partition_key.as_deref(),
+        Some("LIST (region)")
+    );
+    assert_eq!(
+        partition("sensor_data_eu"),
+        (Some("data.sensor_data"), Some("FOR VALUES IN ('eu')"))
+    );
+    assert_eq!(
+        table(&dump, "sensor_data_eu").partition_key.as_deref(),
+        Some("RANGE (sensor)")
+    );
+    assert_eq!(
+        partition("partition_bound_test_active"),
+        (
+            Some("data.partition_bound_test"),
+            Some("FOR VALUES IN ('active')")
+        )
+    );
+    assert_eq!(partition("drop_child").0, Some("test_schema.drop_parent"));
+}
@@ -0,0 +1,281 @@
+//! Partitioned tables: `PARTITION OF`, `ATTACH PARTITION` and the indexes
+//! partitions get from their parents.
+
+use super::Importer;
+use super::constraints::is_table_constraint;
+use super::statements::{list_items, render, unquote};
+use super::tables::table_options;
+use crate::comparer::scanner::{Statement, TokenKind};
+
+/// An index of a partitioned table, which its partitions get a copy of.
+#[derive(Debug, Clone)]
+pub(super) struct PartitionedIndex {
+    pub(super) unique: bool,
+    pub(super) method: String,
+    pub(super) elements: String,
+    /// The column names the server builds the copies' names from.
+    pub(super) columns: String,
+    pub(super) tail: String,
+}
+
+impl Importer {
+    /// `CREATE TABLE name PARTITION OF parent ...` from the parent's name at
+    /// `i`: the partition gets the parent's columns and a copy of each of its
+    /// indexes, as on the server.
+    pub(super) fn create_partition(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        schema: &str,
+        name: &str,
+        unlogged: bool,
+    ) -> Result<(), String> {
+        let (relation, mut j) = st.name_at(i).ok_or("missing parent table name")?;
+        let parent = self
+            .find_table(&relation)
+            .ok_or_else(|| format!("table {} not found", relation.display))?;
+        if self.dump.tables[parent].partition_key.is_none() {
+            return Err(format!("table {} is not partitioned", relation.display));
+        }
+        let mut items = Vec::new();
+        if st.is_punct(j, "(") {
+            let (list, close) = list_items(st, j);
+            items = list;
+            j = close + 1;
+        }
+        let (bound, j) = partition_bound(st, j)?;
+        let options = table_options(st, j)?;
+        let index = self.push_table(schema, name, unlogged, options);
+        self.dump.tables[index].partition_of = Some(self.qualified_table(parent));
+        self.dump.tables[index].partition_bound = Some(bound);
+        self.inherit_columns(parent, index);
+
+        let mut constraints = Vec::new();
+        for (from, to) in items {
+            if is_table_constraint(st, from) {
+                constraints.push((from, to));
+                continue;
+            }
+            let result = st
+                .relation(&[from])
+                .ok_or_else(|| "missing column name".to_string())
+                .and_then(|column| {
+                    let j = st.skip(from + 1, &["with", "options"]);
+                    self.column_constraints(st, index, &column.key, j, to)
+                });
+            if let Err(reason) = result {
+                self.notes.push(reason);
+            }
+        }
+        for (from, to) in constraints {
+            if let Err(reason) = self.add_table_constraint(st, index, from, to) {
+                self.notes.push(reason);
+            }
+        }
+        self.clone_partitioned_indexes(parent, index);
+        Ok(())
+    }
+
+    /// The partitioned table `table` is a partition of.
+    pub(super) fn parent_table(&self, table: usize) -> Option<usize> {
+        let parent = self.dump.tables[table].partition_of.as_deref()?;
+        (0..self.dump.tables.len()).find(|&i| self.qualified_table(i) == parent)
+    }
+
+    /// `ALTER TABLE parent ATTACH PARTITION name ...` from the partition's
+    /// name at `k`. Constraints of the partition that match the parent's
+    /// become inherited ones, which the catalog does not list as its own.
+    pub(super) fn attach_partition(
+        &mut self,
+        st: &Statement,
+        parent: usize,
+        k: usize,
+    ) -> Result<(), String> {
+        if self.dump.tables[parent].partition_key.is_none() {
+            return Err(format!(
+                "table {} is not partitioned",
+                self.qualified_table(parent)
+            ));
+        }
+        let (relation, next) = st.name_at(k).ok_or("missing partition name")?;
+        let table = self
+            .find_table(&relation)
+            .ok_or_else(|| format!("table {} not found", relation.display))?;
+        if self.dump.tables[table].partition_of.is_some() {
+            return Err(format!("table {} is already a partition", relation.display));
+        }
+        let (bound, _) = partition_bound(st, next)?;
+        let parent_checks: Vec<String> = self.dump.tables[parent]
+            .constraints
+            .iter()
+            .filter(|c| c.constraint_type == "CHECK")
+            .map(|c| c.name.clone())
+            .collect();
+        let qualified = self.qualified_table(parent);
+        let t = &mut self.dump.tables[table];
+        t.partition_of = Some(qualified);
+        t.partition_bound = Some(bound);
+        t.constraints
+            .retain(|c| !(c.constraint_type == "CHECK" && parent_checks.contains(&c.name)));
+        self.clone_partitioned_indexes(parent, table);
+        self.dump.tables[table].hash();
+        Ok(())
+    }
+
+    /// Gives the partition `table` the columns of `parent` it does not have
+    /// yet, with their types, defaults and NOT NULL.
+    fn inherit_columns(&mut self, parent: usize, table: usize) {
+        let (schema, name) = {
+            let t = &self.dump.tables[table];
+            (t.schema.clone(), t.name.clone())
+        };
+        let columns = self.dump.tables[parent].columns.clone();
+        for mut column in columns {
+            let raw = unquote(&column.name);
+            if self.column_index(table, &raw).is_ok() {
+                continue;
+            }
+            if let Some(ty) = self.column_types.get(&(parent, raw.clone())).cloned() {
+                self.column_types.insert((table, raw), ty);
+            }
+            column.schema = schema.clone();
+            column.table = name.clone();
+            column.ordinal_position = self.dump.tables[table].columns.len() as i32 + 1;
+            column.comment = None;
+            column.statistics_target = None;
+            column.acl = Vec::new();
+            self.dump.tables[table].columns.push(column);
+        }
+    }
+
+    /// Gives the new partition `table` a copy of every index of `parent`,
+    /// or attaches an equal index it already has.
+    fn clone_partitioned_indexes(&mut self, parent: usize, table: usize) {
+        let specs = self
+            .partitioned_indexes
+            .get(&parent)
+            .cloned()
+            .unwrap_or_default();
+        for spec in specs {
+            let existing = self.dump.tables[table].indexes.iter().position(|i| {
+                !i.is_partition_index && i.indexdef == self.index_definition(table, &i.name, &spec)
+            });
+            match existing {
+                Some(i) => self.dump.tables[table].indexes[i].is_partition_index = true,
+                None => self.clone_index(table, &spec),
+            }
+        }
+    }
+
+    pub(super) fn clone_index(&mut self, table: usize, spec: &PartitionedIndex) {
+        let (schema, name) = {
+            let t = &self.dump.tables[table];
+            (t.raw_schema.clone(), t.raw_name.clone())
+        };
+        let index_name = self.choose_name(&schema, &name, &spec.columns, "idx");
+        self.push_index(table, &index_name, spec, None, false, true);
+    }
+
+    /// Adds the columns added to the partitioned table `parent` to its
+    /// partitions and drops the dropped ones, as `ALTER TABLE` without
+    /// `ONLY` recurses.
+    pub(super) fn sync_partition_columns(&mut self, parent: usize) {
+        if self.dump.tables[parent].partition_key.is_none() {
+            return;
+        }
+        let qualified = self.qualified_table(parent);
+        let partitions: Vec<usize> = (0..self.dump.tables.len())
+            .filter(|&i| self.dump.tables[i].partition_of.as_deref() == Some(&qualified))
+            .collect();
+        for partition in partitions {
+            let kept: Vec<String> = self.dump.tables[parent]
+                .columns
+                .iter()
+                .map(|c| c.name.clone())
+                .collect();
+            let dropped: Vec<String> = self.dump.tables[partition]
+                .columns
+                .iter()
+                .filter(|c| !kept.contains(&c.name))
+                .map(|c| unquote(&c.name))
+                .collect();
+            for column in dropped {
+                self.column_types.remove(&(partition, column));
+            }
+            self.dump.tables[partition]
+                .columns
+                .retain(|c| kept.contains(&c.name));
+            self.inherit_columns(parent, partition);
+            self.sync_partition_columns(partition);
+            self.dump.tables[partition].hash();
+        }
+    }
+}
+
+/// The partition bound at `j` (`FOR VALUES ...` or `DEFAULT`) as
+/// `pg_get_expr(relpartbound)` prints it, and the index after it.
+fn partition_bound(st: &Statement, j: usize) -> Result<(String, usize), String> {
+    if st.is_word(j, "default") {
+        return Ok(("DEFAULT".to_string(), j + 1));
+    }
+    if st.skip(j, &["for", "values"]) == j {
+        return Err("partition bound without FOR VALUES".to_string());
+    }
+    let values = |open: usize| -> (String, usize) {
+        let (items, close) = list_items(st, open);
+        let values: Vec<String> = items
+            .iter()
+            .map(|&(from, to)| bound_value(st, from, to))
+            .collect();
+        (values.join(", "), close + 1)
+    };
+    let k = j + 2;
+    if st.is_word(k, "in") && st.is_punct(k + 1, "(") {
+        let (list, next) = values(k + 1);
+        Ok((format!("FOR VALUES IN ({list})"), next))
+    } else if st.is_word(k, "from") && st.is_punct(k + 1, "(") {
+        let (lower, next) = values(k + 1);
+        if !st.is_word(next, "to") || !st.is_punct(next + 1, "(") {
+            return Err("FOR VALUES FROM without TO".to_string());
+        }
+        let (upper, next) = values(next + 1);
+        Ok((format!("FOR VALUES FROM ({lower}) TO ({upper})"), next))
+    } else if st.is_word(k, "with") && st.is_punct(k + 1, "(") {
+        let (items, close) = list_items(st, k + 1);
+        let options: Vec<String> = items
+            .iter()
+            .map(|&(from, to)| render(st, from, to, false))
+            .collect();
+        Ok((
+            format!("FOR VALUES WITH ({})", options.join(", ")),
+            close + 1,
+        ))
+    } else {
+        Err(format!(
+            "FOR VALUES {} is not supported",
+            st.text(k).to_uppercase()
+        ))
+    }
+}
+
+/// One value of a partition bound: literals without their cast, and
+/// `MINVALUE` / `MAXVALUE` / `NULL` in upper case.
+fn bound_value(st: &Statement, from: usize, to: usize) -> String {
+    let text = st.text(from);
+    if st
+        .tokens
+        .get(from)
+        .is_some_and(|t| t.kind == TokenKind::Literal)
+        && text.starts_with('\'')
+    {
+        return text.to_string();
+    }
+    if to == from + 1 && ["minvalue", "maxvalue", "null"].contains(&text.to_lowercase().as_str()) {
+        return text.to_uppercase();
+    }
+    render(st, from, to, false)
+}
+
+#[cfg(test)]
+#[path = "partitions_tests.rs"]
+mod tests;
@@ -0,0 +1,133 @@
+use crate::dump::sql_import::tests::{import, table};
+
+#[test]
+fn imports_partitioned_tables() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+CREATE TABLE app.events (
+    id integer NOT NULL,
+    year integer NOT NULL DEFAULT 2023,
+    name text,
+    PRIMARY KEY (id, year)
+) PARTITION BY RANGE (year);
+CREATE INDEX events_name_idx ON app.events (name);
+CREATE TABLE app.events_2023 PARTITION OF app.events FOR VALUES FROM (2023) TO (2024);
+CREATE TABLE app.events_def PARTITION OF app.events DEFAULT;
+CREATE TABLE app.sensor (region text, sensor int, at date) PARTITION BY LIST (region);
+CREATE TABLE app.sensor_eu PARTITION OF app.sensor (sensor NOT NULL)
+    FOR VALUES IN ('eu', 'uk') PARTITION BY RANGE (sensor);
+CREATE TABLE app.sensor_eu_1 PARTITION OF app.sensor_eu FOR VALUES FROM (MINVALUE) TO (100);
+CREATE TABLE app.r (d date) PARTITION BY RANGE (d);
+CREATE TABLE app.r1 PARTITION OF app.r FOR VALUES FROM ('2024-01-01') TO ('2025-01-01'::date);
+CREATE TABLE app.h (id int) PARTITION BY HASH (id);
+CREATE TABLE app.h0 PARTITION OF app.h FOR VALUES WITH (MODULUS 2, REMAINDER 0);
+ALTER TABLE app.sensor ADD COLUMN reading numeric, DROP COLUMN at;",
+    );
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let events = table(&dump, "events");
+    assert_eq!(events.partition_key.as_deref(), Some("RANGE (year)"));
+    assert_eq!(
+        events.indexes[0].indexdef,
+        "CREATE INDEX events_name_idx ON ONLY app.events USING btree (name)"
+    );
+
+    let events_2023 = table(&dump, "events_2023");
+    assert_eq!(events_2023.partition_of.as_deref(), Some("app.events"));
+    assert_eq!(
+        events_2023.partition_bound.as_deref(),
+        Some("FOR VALUES FROM (2023) TO (2024)")
+    );
+    let columns: Vec<(&str, Option<&str>, bool)> = events_2023
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.column_default.as_deref(), c.is_nullable))
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("id", None, false),
+            ("year", Some("2023"), false),
+            ("name", None, true)
+        ]
+    );
+    // Inherited constraints are not the partition's own, but its copy of
+    // the parent's index is.
+    assert!(events_2023.constraints.is_empty());
+    assert!(events_2023.has_indexes);
+    assert_eq!(events_2023.indexes.len(), 1);
+    assert!(events_2023.indexes[0].is_partition_index);
+    assert_eq!(
+        events_2023.indexes[0].indexdef,
+        "CREATE INDEX events_2023_name_idx ON app.events_2023 USING btree (name)"
+    );
+    assert_eq!(
+        table(&dump, "events_def").partition_bound.as_deref(),
+        Some("DEFAULT")
+    );
+
+    let sensor_eu = table(&dump, "sensor_eu");
+    assert_eq!(sensor_eu.partition_key.as_deref(), Some("RANGE (sensor)"));
+    assert_eq!(
+        sensor_eu.partition_bound.as_deref(),
+        Some("FOR VALUES IN ('eu', 'uk')")
+    );
+    assert!(!sensor_eu.columns[1].is_nullable);
+    // ALTER TABLE recurses to the partitions.
+    for name in ["sensor_eu", "sensor_eu_1"] {
+        let columns: Vec<&str> = table(&dump, name)
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(columns, vec!["region", "sensor", "reading"]);
+    }
+    assert_eq!(
+        table(&dump, "sensor_eu_1").partition_bound.as_deref(),
+        Some("FOR VALUES FROM (MINVALUE) TO (100)")
+    );
+    assert_eq!(
+        table(&dump, "r1").partition_bound.as_deref(),
+        Some("FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')")
+    );
+    assert_eq!(
+        table(&dump, "h0").partition_bound.as_deref(),
+        Some("FOR VALUES WITH (modulus 2, remainder 0)")
+    );
+}
+
+#[test]
+fn imports_partitions_attached_by_pg_dump() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+CREATE TABLE app.events (
+    id integer NOT NULL,
+    name text,
+    CONSTRAINT events_name_check CHECK ((name <> ''::text))
+)
+PARTITION BY LIST (id);
+CREATE TABLE app.events_1 (
+    id integer NOT NULL,
+    name text,
+    CONSTRAINT events_name_check CHECK ((name <> ''::text))
+);
+ALTER TABLE ONLY app.events ATTACH PARTITION app.events_1 FOR VALUES IN (1);
+ALTER TABLE ONLY app.events
+    ADD CONSTRAINT events_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY app.events_1
+    ADD CONSTRAINT events_1_pkey PRIMARY KEY (id);
+CREATE INDEX events_name_idx ON ONLY app.events USING btree (name);
+CREATE INDEX events_1_name_idx ON app.events_1 USING btree (name);
+ALTER INDEX app.events_name_idx ATTACH PARTITION app.events_1_name_idx;
+ALTER INDEX app.events_pkey ATTACH PARTITION app.events_1_pkey;",
+    );
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let events_1 = table(&dump, "events_1");
+    assert_eq!(events_1.partition_of.as_deref(), Some("app.events"));
+    assert_eq!(
+        events_1.partition_bound.as_deref(),
+        Some("FOR VALUES IN (1)")
+    );
+    assert!(events_1.constraints.is_empty());
+    assert!(events_1.indexes[0].is_partition_index);
+    assert_eq!(table(&dump, "events").constraints.len(), 2);
+}
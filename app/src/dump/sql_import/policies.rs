@@ -0,0 +1,97 @@
+//! `CREATE POLICY`.
+
+use super::Importer;
+use super::statements::{closing_paren, quote_ident, render, unquote, wrap};
+use crate::comparer::scanner::Statement;
+use crate::dump::table_policy::TablePolicy;
+
+impl Importer {
+    pub(super) fn create_policy(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let name = st.relation(&[i]).ok_or("missing policy name")?.key;
+        if !st.is_word(i + 1, "on") {
+            return Err("CREATE POLICY without ON".to_string());
+        }
+        let (relation, mut k) = st.name_at(i + 2).ok_or("missing table name")?;
+        let table = self
+            .find_table(&relation)
+            .ok_or_else(|| format!("table {} not found", relation.display))?;
+        let mut permissive = true;
+        let mut command = "all".to_string();
+        let mut roles = Vec::new();
+        let (mut using_clause, mut check_clause) = (None, None);
+        let end = st.tokens.len() - usize::from(st.is_punct(st.tokens.len() - 1, ";"));
+        while k < end {
+            if st.is_word(k, "as") {
+                permissive = !st.is_word(k + 1, "restrictive");
+                k += 2;
+            } else if st.is_word(k, "for") {
+                command = st.text(k + 1).to_lowercase();
+                k += 2;
+            } else if st.is_word(k, "to") {
+                k += 1;
+                while k < end && st.is_name_part(k) {
+                    if !st.is_word(k, "public") {
+                        roles.push(unquote(&self.role_at(st, k).unwrap_or_default()));
+                    }
+                    k += 1 + usize::from(st.is_punct(k + 1, ","));
+                    if st.is_word(k, "using") || st.is_word(k, "with") {
+                        break;
+                    }
+                }
+            } else if st.is_word(k, "using") && st.is_punct(k + 1, "(")
+                || st.skip(k, &["with", "check"]) == k + 2 && st.is_punct(k + 2, "(")
+            {
+                let open = if st.is_word(k, "using") { k + 1 } else { k + 2 };
+                let close = closing_paren(st, open);
+                let clause = Some(policy_expression(st, open + 1, close));
+                if st.is_word(k, "using") {
+                    using_clause = clause;
+                } else {
+                    check_clause = clause;
+                }
+                k = close + 1;
+            } else {
+                return Err(format!(
+                    "policy option {} is not supported",
+                    st.text(k).to_uppercase()
+                ));
+            }
+        }
+        roles.sort_unstable();
+        let t = &mut self.dump.tables[table];
+        let name = quote_ident(&name);
+        t.policies.retain(|policy| policy.name != name);
+        t.policies.push(TablePolicy {
+            schema: t.schema.clone(),
+            table: t.name.clone(),
+            name,
+            command,
+            permissive,
+            roles,
+            using_clause,
+            check_clause,
+        });
+        t.hash();
+        Ok(())
+    }
+}
+
+/// A policy expression between parentheses as `pg_get_expr` prints it:
+/// operator expressions in parentheses, plain values and calls without.
+fn policy_expression(st: &Statement, from: usize, to: usize) -> String {
+    let expression = render(st, from, to, false);
+    let call = st.is_name_part(from) && {
+        let parts = st.name_parts(from);
+        let open = parts.last().map_or(from, |p| p + 1);
+        st.is_punct(open, "(") && closing_paren(st, open) + 1 == to
+    };
+    if to == from + 1 || call {
+        expression
+    } else {
+        wrap(&expression)
+    }
+}
+
+#[cfg(test)]
+#[path = "policies_tests.rs"]
+mod tests;
@@ -0,0 +1,27 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn imports_policies() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER TABLE app.users ENABLE ROW LEVEL SECURITY;
+CREATE POLICY own ON app.users AS RESTRICTIVE FOR UPDATE TO writer, \"Reader\"
+    USING (name = current_user) WITH CHECK (mood <> 'sad');
+CREATE POLICY everyone ON app.users USING (true);"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let users = table(&dump, "users");
+    let own = &users.policies[0];
+    assert_eq!(own.name, "own");
+    assert_eq!(own.command, "update");
+    assert!(!own.permissive);
+    assert_eq!(own.roles, vec!["Reader".to_string(), "writer".to_string()]);
+    assert_eq!(own.using_clause.as_deref(), Some("(name = CURRENT_USER)"));
+    assert_eq!(own.check_clause.as_deref(), Some("(mood <> 'sad')"));
+    let everyone = &users.policies[1];
+    assert_eq!(everyone.command, "all");
+    assert!(everyone.permissive);
+    assert!(everyone.roles.is_empty());
+    assert_eq!(everyone.using_clause.as_deref(), Some("true"));
+    assert_eq!(everyone.check_clause, None);
+}
@@ -0,0 +1,400 @@
+//! `CREATE FUNCTION`, `CREATE PROCEDURE` and their `ALTER`s.
+
+use super::Importer;
+use super::statements::{list_items, literal_value, quote_ident, statement_kind};
+use super::tables::normalize_default;
+use super::types::TypeRef;
+use crate::comparer::scanner::Statement;
+use crate::config::dump_config::DumpConfig;
+use crate::dump::routine::Routine;
+
+/// The input argument types of the argument list `arguments` (without the
+/// parentheses), spelled the same way whichever alias the list uses, so
+/// `x int` and `integer` both give `integer`. `None` when it does not parse.
+pub(crate) fn argument_types(arguments: &str) -> Option<Vec<String>> {
+    let importer = Importer::new(DumpConfig::default());
+    let list = format!("({arguments})");
+    let st = Statement::parse(&list);
+    let (arguments, _) = importer.arguments(&st, 0).ok()?;
+    Some(
+        arguments
+            .iter()
+            .filter(|a| a.mode != "out")
+            .map(|a| a.ty.format(false))
+            .collect(),
+    )
+}
+
+impl Importer {
+    pub(super) fn alter_routine(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (index, next) = self.routine_at(st, i)?;
+        if st.skip(next, &["owner", "to"]) == next {
+            return Err(format!("{} is not supported", statement_kind(st, 0)));
+        }
+        let role = quote_ident(&self.role_at(st, next + 2).ok_or("missing role name")?);
+        let routine = &mut self.dump.routines[index];
+        Self::reassign_acl(&mut routine.acl, &routine.owner, &role);
+        routine.owner = role;
+        routine.hash();
+        Ok(())
+    }
+
+    /// Parses a parenthesized argument list starting at `open`.
+    pub(super) fn arguments(
+        &self,
+        st: &Statement,
+        open: usize,
+    ) -> Result<(Vec<Argument>, usize), String> {
+        let (items, close) = list_items(st, open);
+        let mut arguments = Vec::new();
+        for (from, to) in items {
+            let mut k = from;
+            let mut mode = "in";
+            for m in ["in", "out", "inout", "variadic"] {
+                if st.is_word(k, m) && k + 1 < to {
+                    mode = m;
+                    k += 1;
+                    break;
+                }
+            }
+            let at_end = |j: usize| j >= to || st.is_word(j, "default") || st.is_punct(j, "=");
+            let (mut ty, mut j) = self.parse_type(st, k)?;
+            let mut name = None;
+            if !at_end(j) {
+                name = st.relation(&[k]).map(|r| r.key);
+                (ty, j) = self.parse_type(st, k + 1)?;
+            }
+            // `DEFAULT expr` and `= expr` both take one token.
+            let default = if j < to {
+                Some(normalize_default(st, j + 1, to, &ty).unwrap_or_else(|| "NULL".to_string()))
+            } else {
+                None
+            };
+            arguments.push(Argument {
+                mode,
+                name,
+                ty,
+                default,
+            });
+        }
+        Ok((arguments, close))
+    }
+
+    /// Finds the routine named at `i`, with or without an argument list,
+    /// and returns its index and the token after the name.
+    pub(super) fn routine_at(&self, st: &Statement, i: usize) -> Result<(usize, usize), String> {
+        let (relation, mut next) = st.name_at(i).ok_or("missing routine name")?;
+        let mut wanted = None;
+        if st.is_punct(next, "(") {
+            let (arguments, close) = self.arguments(st, next)?;
+            let all: Vec<String> = arguments.iter().map(|a| a.ty.format(false)).collect();
+            let inputs: Vec<String> = arguments
+                .iter()
+                .filter(|a| a.mode != "out")
+                .map(|a| a.ty.format(false))
+                .collect();
+            wanted = Some((inputs, all));
+            next = close + 1;
+        }
+        for (schema, name) in self.candidates(&relation) {
+            let (schema, name) = (quote_ident(&schema), quote_ident(&name));
+            let found: Vec<usize> = self
+                .dump
+                .routines
+                .iter()
+                .enumerate()
+                .filter(|(index, r)| {
+                    r.schema == schema
+                        && r.name == name
+                        && wanted.as_ref().is_none_or(|(inputs, all)| {
+                            let signature = &self.routine_signatures[*index];
+                            signature == inputs || signature == all
+                        })
+                })
+                .map(|(index, _)| index)
+                .collect();
+            match found.as_slice() {
+                [] => {}
+                [index] => return Ok((*index, next)),
+                _ => return Err(format!("routine {} is ambiguous", relation.display)),
+            }
+        }
+        Err(format!("routine {} not found", relation.display))
+    }
+
+    pub(super) fn create_routine(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        procedure: bool,
+    ) -> Result<(), String> {
+        let (relation, open) = st.name_at(i).ok_or("missing routine name")?;
+        if !st.is_punct(open, "(") {
+            return Err("routine without an argument list".to_string());
+        }
+        let (schema, name) = self.target(&relation);
+        self.require_schema(&schema)?;
+        let (arguments, close) = self.arguments(st, open)?;
+        let mut j = close + 1;
+
+        let mut return_type = None;
+        let mut set_returning = false;
+        if st.is_word(j, "returns") {
+            if st.is_word(j + 1, "table") && st.is_punct(j + 2, "(") {
+                let (columns, close) = self.arguments(st, j + 2)?;
+                let columns: Vec<String> = columns
+                    .iter()
+                    .map(|c| {
+                        let name = c.name.as_deref().map(quote_ident).unwrap_or_default();
+                        format!("{} {}", name, c.ty.format(false))
+                    })
+                    .collect();
+                return_type = Some(format!("TABLE({})", columns.join(", ")));
+                set_returning = true;
+                j = close + 1;
+            } else {
+                let setof = st.is_word(j + 1, "setof");
+                let k = if setof { j + 2 } else { j + 1 };
+                let (ty, next) = self.parse_type(st, k)?;
+                let ty = ty.format(false);
+                return_type = Some(if setof { format!("SETOF {ty}") } else { ty });
+                set_returning = setof;
+                j = next;
+            }
+        }
+        let outputs: Vec<&Argument> = arguments
+            .iter()
+            .filter(|a| a.mode == "out" || a.mode == "inout")
+            .collect();
+        let return_type = if procedure {
+            "void".to_string()
+        } else {
+            match (return_type, outputs.as_slice()) {
+                (Some(ty), _) => ty,
+                (None, [output]) => output.ty.format(false),
+                (None, [_, ..]) => "record".to_string(),
+                (None, []) => return Err("function without a result type".to_string()),
+            }
+        };
+
+        let mut lang = "sql".to_string();
+        let mut volatility = "volatile";
+        let mut is_strict = false;
+        let mut is_leakproof = false;
+        let mut security_definer = false;
+        let mut parallel = "unsafe";
+        let mut cost = None;
+        let mut rows = None;
+        let mut support_function = None;
+        let mut config = Vec::new();
+        let mut source = None;
+        let end = st.tokens.len() - usize::from(st.is_punct(st.tokens.len() - 1, ";"));
+        while j < end {
+            let word = st.text(j).to_lowercase();
+            match word.as_str() {
+                "language" => {
+                    lang = st.relation(&[j + 1]).ok_or("missing language")?.key;
+                    j += 2;
+                }
+                "immutable" | "stable" | "volatile" => {
+                    volatility = match word.as_str() {
+                        "immutable" => "immutable",
+                        "stable" => "stable",
+                        _ => "volatile",
+                    };
+                    j += 1;
+                }
+                "leakproof" => {
+                    is_leakproof = true;
+                    j += 1;
+                }
+                "not" if st.is_word(j + 1, "leakproof") => {
+                    is_leakproof = false;
+                    j += 2;
+                }
+                "strict" => {
+                    is_strict = true;
+                    j += 1;
+                }
+                "returns" if st.skip(j, &["returns", "null", "on", "null", "input"]) != j => {
+                    is_strict = true;
+                    j += 5;
+                }
+                "called" if st.skip(j, &["called", "on", "null", "input"]) != j => {
+                    is_strict = false;
+                    j += 4;
+                }
+                "external" | "security" => {
+                    let k = st.skip(j, &["external"]);
+                    security_definer = st.is_word(k + 1, "definer");
+                    j = k + 2;
+                }
+                "parallel" => {
+                    parallel = match st.text(j + 1).to_lowercase().as_str() {
+                        "safe" => "safe",
+                        "restricted" => "restricted",
+                        _ => "unsafe",
+                    };
+                    j += 2;
+                }
+                "cost" => {
+                    cost = st.text(j + 1).parse::<f64>().ok();
+                    j += 2;
+                }
+                "rows" => {
+                    rows = st.text(j + 1).parse::<f64>().ok();
+                    j += 2;
+                }
+                "support" => {
+                    let (support, next) = st.name_at(j + 1).ok_or("missing support function")?;
+                    support_function = Some(support.display);
+                    j = next;
+                }
+                "set" => {
+                    let parameter = st.text(j + 1).to_lowercase();
+                    let k = j + 2;
+                    if !(st.is_word(k, "to") || st.is_punct(k, "=")) {
+                        return Err("SET ... FROM CURRENT is not supported".to_string());
+                    }
+                    let mut values = Vec::new();
+                    let mut k = k + 1;
+                    while k < end {
+                        values.push(
+                            literal_value(st.text(k)).unwrap_or_else(|| st.text(k).to_string()),
+                        );
+                        if !st.is_punct(k + 1, ",") {
+                            break;
+                        }
+                        k += 2;
+                    }
+                    config.push(format!("{parameter}={}", values.join(", ")));
+                    j = k + 1;
+                }
+                "window" => return Err("window functions are not supported".to_string()),
+                "transform" => return Err("TRANSFORM is not supported".to_string()),
+                "as" => {
+                    let body = literal_value(st.text(j + 1)).ok_or("missing routine body")?;
+                    if st.is_punct(j + 2, ",") {
+                        j += 2;
+                    }
+                    source = Some(body);
+                    j += 2;
+                }
+                "begin" | "return" => {
+                    return Err("SQL-standard routine bodies are not supported".to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "routine option {} is not supported",
+                        st.text(j).to_uppercase()
+                    ));
+                }
+            }
+        }
+        let source = source.ok_or("missing routine body")?;
+        if matches!(lang.as_str(), "c" | "internal") {
+            // The server dump leaves these to extensions as well.
+            return Ok(());
+        }
+
+        let identity: Vec<String> = arguments
+            .iter()
+            .filter(|a| procedure || a.mode != "out")
+            .map(|a| {
+                let mode = if procedure || a.mode != "in" {
+                    format!("{} ", a.mode.to_uppercase())
+                } else {
+                    String::new()
+                };
+                let name = a
+                    .name
+                    .as_deref()
+                    .map(|n| format!("{} ", quote_ident(n)))
+                    .unwrap_or_default();
+                format!("{mode}{name}{}", a.ty.format(false))
+            })
+            .collect();
+        let signature: Vec<String> = arguments
+            .iter()
+            .filter(|a| procedure || a.mode != "out")
+            .map(|a| a.ty.format(false))
+            .collect();
+        let defaults: Vec<&str> = arguments
+            .iter()
+            .filter_map(|a| a.default.as_deref())
+            .collect();
+
+        let (quoted_schema, quoted_name) = (quote_ident(&schema), quote_ident(&name));
+        let existing = self
+            .routine_signatures
+            .iter()
+            .enumerate()
+            .position(|(index, s)| {
+                let r = &self.dump.routines[index];
+                r.schema == quoted_schema && r.name == quoted_name && *s == signature
+            });
+        if existing.is_some() && !st.is_word(1, "or") {
+            return Err(format!("routine {} already exists", relation.display));
+        }
+
+        let oid = self.oid();
+        let mut routine = Routine::new(
+            quoted_schema,
+            oid,
+            quoted_name,
+            lang,
+            if procedure { "procedure" } else { "function" }.to_string(),
+            return_type,
+            identity.join(", "),
+            (!defaults.is_empty()).then(|| defaults.join(", ")),
+            None,
+            source,
+        );
+        routine.owner = quote_ident(&self.owner);
+        routine.volatility = volatility.to_string();
+        routine.is_strict = is_strict;
+        routine.is_leakproof = is_leakproof;
+        routine.security_definer = security_definer;
+        routine.parallel = parallel.to_string();
+        routine.config = config;
+        let default_cost = 100.0;
+        routine.cost = cost.filter(|c| !procedure && (*c - default_cost).abs() > f64::EPSILON);
+        routine.rows = if procedure {
+            None
+        } else if set_returning {
+            Some(rows.unwrap_or(1000.0))
+        } else {
+            None
+        };
+        routine.support_function = support_function;
+        match existing {
+            Some(index) => {
+                let old = &self.dump.routines[index];
+                routine.oid = old.oid;
+                routine.owner = old.owner.clone();
+                routine.acl = old.acl.clone();
+                routine.comment = old.comment.clone();
+                routine.hash();
+                self.dump.routines[index] = routine;
+            }
+            None => {
+                routine.hash();
+                self.dump.routines.push(routine);
+                self.routine_signatures.push(signature);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A routine argument: mode, optional name, type and default.
+pub(super) struct Argument {
+    mode: &'static str,
+    name: Option<String>,
+    ty: TypeRef,
+    default: Option<String>,
+}
+
+#[cfg(test)]
+#[path = "routines_tests.rs"]
+mod tests;
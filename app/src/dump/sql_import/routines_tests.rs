@@ -0,0 +1,78 @@
+use super::*;
+use crate::dump::sql_import::tests::import;
+
+#[test]
+fn imports_routines() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+         CREATE FUNCTION app.add(a integer, b integer DEFAULT 1) RETURNS integer
+             LANGUAGE sql IMMUTABLE AS $$ SELECT a + b $$;
+         CREATE PROCEDURE app.touch(IN x text) LANGUAGE plpgsql AS $$ BEGIN END $$;
+         CREATE FUNCTION app.list() RETURNS TABLE(id integer, email text)
+             LANGUAGE sql AS $$ SELECT 1, 'a' $$;",
+    );
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let add = &dump.routines[0];
+    assert_eq!(add.kind, "function");
+    assert_eq!(add.arguments, "a integer, b integer");
+    assert_eq!(add.arguments_defaults.as_deref(), Some("1"));
+    assert_eq!(add.return_type, "integer");
+    assert_eq!(add.volatility, "immutable");
+    assert_eq!(add.source_code, " SELECT a + b ");
+
+    let touch = &dump.routines[1];
+    assert_eq!(touch.kind, "procedure");
+    assert_eq!(touch.arguments, "IN x text");
+
+    assert_eq!(
+        dump.routines[2].return_type,
+        "TABLE(id integer, email text)"
+    );
+}
+
+#[test]
+fn argument_types_ignore_names_modes_and_aliases() {
+    assert_eq!(
+        argument_types("a int, OUT b text, VARIADIC c character varying[]"),
+        Some(vec![
+            "integer".to_string(),
+            "character varying[]".to_string()
+        ])
+    );
+    assert_eq!(argument_types("integer"), argument_types("x int4"));
+    assert_eq!(argument_types(""), Some(Vec::new()));
+}
+
+#[test]
+fn alters_routine_owners() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+CREATE FUNCTION app.add(a integer, b integer) RETURNS integer
+    LANGUAGE sql AS $$ SELECT a + b $$;
+CREATE FUNCTION app.add(a text, b text) RETURNS text
+    LANGUAGE sql AS $$ SELECT a || b $$;
+ALTER FUNCTION app.add(integer, int4) OWNER TO admin;
+ALTER ROUTINE app.add(text, text) RENAME TO concat;
+ALTER FUNCTION app.add(boolean, boolean) OWNER TO admin;",
+    );
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "ALTER ROUTINE is not supported",
+            "routine app.add not found"
+        ]
+    );
+    let owners: Vec<(&str, &str)> = dump
+        .routines
+        .iter()
+        .map(|r| (r.arguments.as_str(), r.owner.as_str()))
+        .collect();
+    assert_eq!(
+        owners,
+        vec![
+            ("a integer, b integer", "admin"),
+            ("a text, b text", "postgres")
+        ]
+    );
+}
@@ -0,0 +1,296 @@
+//! `CREATE SEQUENCE` and `ALTER SEQUENCE`, and the sequences behind
+//! `serial` and identity columns.
+
+use super::Importer;
+use super::builtins::builtin;
+use super::statements::{number_at, quote_ident, skip_if_not_exists};
+use super::types::{integer_bounds, integer_typname};
+use crate::comparer::scanner::{Relation, Statement};
+use crate::dump::sequence::Sequence;
+
+impl Importer {
+    pub(super) fn create_sequence(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        unlogged: bool,
+    ) -> Result<(), String> {
+        let (i, if_not_exists) = skip_if_not_exists(st, i);
+        let (relation, next) = st.name_at(i).ok_or("missing sequence name")?;
+        let (schema, name) = self.target(&relation);
+        if self.name_taken(&schema, &name) {
+            if if_not_exists {
+                return Ok(());
+            }
+            return Err(format!("relation {} already exists", relation.display));
+        }
+        let options = parse_sequence_options(st, next, st.tokens.len())?;
+        let data_type = options
+            .data_type
+            .clone()
+            .unwrap_or_else(|| "int8".to_string());
+        let mut sequence = self.new_sequence(&schema, &name, &data_type, &options);
+        sequence.is_unlogged = unlogged;
+        if let Some(Some((table, column))) = &options.owned_by {
+            self.set_sequence_owner(&mut sequence, table, column)?;
+        }
+        sequence.hash();
+        self.dump.sequences.push(sequence);
+        Ok(())
+    }
+
+    /// A sequence as `CREATE SEQUENCE` would create it, with the server's
+    /// defaults for every option left out.
+    pub(super) fn new_sequence(
+        &self,
+        schema: &str,
+        name: &str,
+        typname: &str,
+        options: &SequenceOptions,
+    ) -> Sequence {
+        let (type_min, type_max) = integer_bounds(typname);
+        let increment = options.increment.unwrap_or(1);
+        let min = match options.min {
+            Some(Some(value)) => value,
+            _ if increment > 0 => 1,
+            _ => type_min,
+        };
+        let max = match options.max {
+            Some(Some(value)) => value,
+            _ if increment > 0 => type_max,
+            _ => -1,
+        };
+        let start = options
+            .start
+            .unwrap_or(if increment > 0 { min } else { max });
+        Sequence::new(
+            quote_ident(schema),
+            quote_ident(name),
+            quote_ident(&self.owner),
+            builtin(typname).map_or(typname, |b| b.1).to_string(),
+            Some(start),
+            Some(min),
+            Some(max),
+            Some(increment),
+            options.cycle.unwrap_or(false),
+            Some(options.cache.unwrap_or(1)),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// `OWNED BY table.column`: the column must exist.
+    fn set_sequence_owner(
+        &self,
+        sequence: &mut Sequence,
+        table: &Relation,
+        column: &str,
+    ) -> Result<(), String> {
+        let index = self
+            .find_table(table)
+            .ok_or_else(|| format!("table {} not found", table.display))?;
+        let table = &self.dump.tables[index];
+        let quoted = quote_ident(column);
+        if !table.columns.iter().any(|c| c.name == quoted) {
+            return Err(format!("column {column} not found"));
+        }
+        sequence.owned_by_schema = Some(table.schema.clone());
+        sequence.owned_by_table = Some(table.name.clone());
+        sequence.owned_by_column = Some(quoted);
+        Ok(())
+    }
+
+    /// Applies the options an `ALTER SEQUENCE` names, leaving the rest.
+    fn alter_sequence_options(
+        &self,
+        sequence: &mut Sequence,
+        options: &SequenceOptions,
+    ) -> Result<(), String> {
+        if let Some(typname) = &options.data_type {
+            sequence.data_type = builtin(typname)
+                .map_or(typname.as_str(), |b| b.1)
+                .to_string();
+        }
+        if let Some(increment) = options.increment {
+            sequence.increment_by = Some(increment);
+        }
+        let typname = integer_typname(&sequence.data_type);
+        let (type_min, type_max) = integer_bounds(typname);
+        let ascending = sequence.increment_by.unwrap_or(1) > 0;
+        match options.min {
+            Some(Some(value)) => sequence.min_value = Some(value),
+            Some(None) => sequence.min_value = Some(if ascending { 1 } else { type_min }),
+            None => {}
+        }
+        match options.max {
+            Some(Some(value)) => sequence.max_value = Some(value),
+            Some(None) => sequence.max_value = Some(if ascending { type_max } else { -1 }),
+            None => {}
+        }
+        if let Some(start) = options.start {
+            sequence.start_value = Some(start);
+        }
+        if let Some(cache) = options.cache {
+            sequence.cache_size = Some(cache);
+        }
+        if let Some(cycle) = options.cycle {
+            sequence.cycle = cycle;
+        }
+        match &options.owned_by {
+            Some(Some((table, column))) => self.set_sequence_owner(sequence, table, column)?,
+            Some(None) => {
+                sequence.owned_by_schema = None;
+                sequence.owned_by_table = None;
+                sequence.owned_by_column = None;
+            }
+            None => {}
+        }
+        sequence.hash();
+        Ok(())
+    }
+
+    pub(super) fn alter_sequence(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let i = st.skip(i, &["if", "exists"]);
+        let (relation, next) = st.name_at(i).ok_or("missing sequence name")?;
+        let index = self
+            .find_sequence(&relation)
+            .ok_or_else(|| format!("sequence {} not found", relation.display))?;
+        let mut sequence = self.dump.sequences[index].clone();
+        if st.skip(next, &["owner", "to"]) != next {
+            let role = quote_ident(&self.role_at(st, next + 2).ok_or("missing role name")?);
+            Self::reassign_acl(&mut sequence.acl, &sequence.owner, &role);
+            sequence.owner = role;
+            sequence.hash();
+        } else {
+            let options = parse_sequence_options(st, next, st.tokens.len())?;
+            self.alter_sequence_options(&mut sequence, &options)?;
+        }
+        self.dump.sequences[index] = sequence;
+        Ok(())
+    }
+}
+
+/// Options of `CREATE SEQUENCE`, `ALTER SEQUENCE` and identity columns;
+/// `None` means the statement left the option alone.
+#[derive(Default)]
+pub(super) struct SequenceOptions {
+    /// Integer `typname` from `AS`.
+    pub(super) data_type: Option<String>,
+    pub(super) increment: Option<i64>,
+    /// `Some(None)` for `NO MINVALUE`.
+    pub(super) min: Option<Option<i64>>,
+    /// `Some(None)` for `NO MAXVALUE`.
+    pub(super) max: Option<Option<i64>>,
+    pub(super) start: Option<i64>,
+    pub(super) cache: Option<i64>,
+    pub(super) cycle: Option<bool>,
+    /// `Some(None)` for `OWNED BY NONE`.
+    pub(super) owned_by: Option<Option<(Relation, String)>>,
+    /// `SEQUENCE NAME`, only valid for identity columns.
+    pub(super) name: Option<Relation>,
+}
+
+pub(super) fn parse_sequence_options(
+    st: &Statement,
+    from: usize,
+    to: usize,
+) -> Result<SequenceOptions, String> {
+    let mut options = SequenceOptions::default();
+    let mut k = from;
+    while k < to && !st.is_punct(k, ";") {
+        match st.text(k).to_lowercase().as_str() {
+            "as" => {
+                let typname = match st.text(k + 1).to_lowercase().as_str() {
+                    "smallint" | "int2" => "int2",
+                    "integer" | "int" | "int4" => "int4",
+                    "bigint" | "int8" => "int8",
+                    other => return Err(format!("sequence type {other} is not supported")),
+                };
+                options.data_type = Some(typname.to_string());
+                k += 2;
+            }
+            "increment" => {
+                let (value, next) = number_at(st, st.skip(k + 1, &["by"]))?;
+                options.increment = Some(value);
+                k = next;
+            }
+            "minvalue" => {
+                let (value, next) = number_at(st, k + 1)?;
+                options.min = Some(Some(value));
+                k = next;
+            }
+            "maxvalue" => {
+                let (value, next) = number_at(st, k + 1)?;
+                options.max = Some(Some(value));
+                k = next;
+            }
+            "no" => {
+                match st.text(k + 1).to_lowercase().as_str() {
+                    "minvalue" => options.min = Some(None),
+                    "maxvalue" => options.max = Some(None),
+                    "cycle" => options.cycle = Some(false),
+                    other => return Err(format!("NO {} is not supported", other.to_uppercase())),
+                }
+                k += 2;
+            }
+            "start" => {
+                let (value, next) = number_at(st, st.skip(k + 1, &["with"]))?;
+                options.start = Some(value);
+                k = next;
+            }
+            "restart" => {
+                // Only the current position moves; nothing a dump records.
+                k = st.skip(k + 1, &["with"]);
+                if let Ok((_, next)) = number_at(st, k) {
+                    k = next;
+                }
+            }
+            "cache" => {
+                let (value, next) = number_at(st, k + 1)?;
+                options.cache = Some(value);
+                k = next;
+            }
+            "cycle" => {
+                options.cycle = Some(true);
+                k += 1;
+            }
+            "owned" if st.is_word(k + 1, "by") => {
+                if st.is_word(k + 2, "none") {
+                    options.owned_by = Some(None);
+                    k += 3;
+                } else {
+                    let parts = st.name_parts(k + 2);
+                    if parts.len() < 2 {
+                        return Err("OWNED BY needs table.column".to_string());
+                    }
+                    let table = st
+                        .relation(&parts[..parts.len() - 1])
+                        .ok_or("missing table")?;
+                    let column = st
+                        .relation(&parts[parts.len() - 1..])
+                        .ok_or("missing column")?;
+                    options.owned_by = Some(Some((table, column.key)));
+                    k = parts[parts.len() - 1] + 1;
+                }
+            }
+            "sequence" if st.is_word(k + 1, "name") => {
+                let (name, next) = st.name_at(k + 2).ok_or("missing sequence name")?;
+                options.name = Some(name);
+                k = next;
+            }
+            _ => {
+                return Err(format!(
+                    "sequence option {} is not supported",
+                    st.text(k).to_uppercase()
+                ));
+            }
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+#[path = "sequences_tests.rs"]
+mod tests;
@@ -0,0 +1,60 @@
+use crate::dump::sql_import::tests::{SCHEMA, import};
+
+#[test]
+fn serial_and_identity_columns_own_their_sequences() {
+    let (dump, _) = import(SCHEMA);
+    let serial = dump
+        .sequences
+        .iter()
+        .find(|s| s.name == "users_id_seq")
+        .unwrap();
+    assert_eq!(serial.data_type, "integer");
+    assert_eq!(serial.max_value, Some(i32::MAX as i64));
+    assert_eq!(serial.owned_by_column.as_deref(), Some("id"));
+    assert!(!serial.is_identity);
+
+    let identity = dump
+        .sequences
+        .iter()
+        .find(|s| s.name == "orders_id_seq")
+        .unwrap();
+    assert!(identity.is_identity);
+    assert_eq!(identity.data_type, "bigint");
+}
+
+#[test]
+fn creates_and_alters_sequences() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE SEQUENCE app.counter AS smallint INCREMENT BY 5 MINVALUE 10 START WITH 20 CACHE 4 CYCLE;
+ALTER SEQUENCE app.counter RESTART WITH 30 NO CYCLE;
+ALTER SEQUENCE app.counter OWNER TO admin;
+ALTER SEQUENCE app.counter OWNED BY app.users.name;
+ALTER SEQUENCE app.missing OWNER TO admin;
+CREATE SEQUENCE app.counter;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "sequence app.missing not found",
+            "relation app.counter already exists"
+        ]
+    );
+    let counter = dump.sequences.iter().find(|s| s.name == "counter").unwrap();
+    assert_eq!(counter.data_type, "smallint");
+    assert_eq!(
+        (
+            counter.start_value,
+            counter.min_value,
+            counter.max_value,
+            counter.increment_by,
+            counter.cache_size
+        ),
+        (Some(20), Some(10), Some(i16::MAX as i64), Some(5), Some(4))
+    );
+    assert!(!counter.cycle);
+    assert_eq!(counter.owner, "admin");
+    assert_eq!(counter.owned_by_table.as_deref(), Some("users"));
+    assert_eq!(counter.owned_by_column.as_deref(), Some("name"));
+}
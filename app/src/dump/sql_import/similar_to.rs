@@ -0,0 +1,160 @@
+//! The `SIMILAR TO` matching the scheme filter uses, without a regex
+//! engine.
+
+/// One element of a `SIMILAR TO` pattern.
+#[derive(Debug)]
+enum Pattern {
+    Char(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Group(Vec<Vec<Pattern>>),
+    Repeat(Box<Pattern>, usize, Option<usize>),
+}
+
+/// Whether `value` matches the `SIMILAR TO` `pattern` as a whole.
+pub(super) fn similar_to(value: &str, pattern: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let mut pos = 0;
+    let alternatives = parse_alternatives(&pattern, &mut pos);
+    let value: Vec<char> = value.chars().collect();
+    match_sequence(&[Pattern::Group(alternatives)], &value, 0, &mut |end| {
+        end == value.len()
+    })
+}
+
+fn parse_alternatives(pattern: &[char], pos: &mut usize) -> Vec<Vec<Pattern>> {
+    let mut alternatives = vec![Vec::new()];
+    while *pos < pattern.len() {
+        let c = pattern[*pos];
+        *pos += 1;
+        let atom = match c {
+            '|' => {
+                alternatives.push(Vec::new());
+                continue;
+            }
+            ')' => break,
+            '(' => Pattern::Group(parse_alternatives(pattern, pos)),
+            '%' => Pattern::Repeat(Box::new(Pattern::Any), 0, None),
+            '_' => Pattern::Any,
+            '\\' if *pos < pattern.len() => {
+                *pos += 1;
+                Pattern::Char(pattern[*pos - 1])
+            }
+            '[' => {
+                let negated = pattern.get(*pos) == Some(&'^');
+                if negated {
+                    *pos += 1;
+                }
+                let mut ranges = Vec::new();
+                while *pos < pattern.len() && pattern[*pos] != ']' {
+                    let start = pattern[*pos];
+                    if pattern.get(*pos + 1) == Some(&'-')
+                        && pattern.get(*pos + 2).is_some_and(|c| *c != ']')
+                    {
+                        ranges.push((start, pattern[*pos + 2]));
+                        *pos += 3;
+                    } else {
+                        ranges.push((start, start));
+                        *pos += 1;
+                    }
+                }
+                *pos += 1;
+                Pattern::Class(ranges, negated)
+            }
+            '*' | '+' | '?' | '{' => {
+                let Some(last) = alternatives.last_mut().and_then(|a| a.pop()) else {
+                    continue;
+                };
+                let (min, max) = match c {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    '?' => (0, Some(1)),
+                    _ => {
+                        let close = pattern[*pos..]
+                            .iter()
+                            .position(|c| *c == '}')
+                            .map_or(pattern.len(), |p| *pos + p);
+                        let bounds: String = pattern[*pos..close].iter().collect();
+                        *pos = close + 1;
+                        match bounds.split_once(',') {
+                            Some((min, max)) => {
+                                (min.trim().parse().unwrap_or(0), max.trim().parse().ok())
+                            }
+                            None => {
+                                let n = bounds.trim().parse().unwrap_or(0);
+                                (n, Some(n))
+                            }
+                        }
+                    }
+                };
+                Pattern::Repeat(Box::new(last), min, max)
+            }
+            c => Pattern::Char(c),
+        };
+        if let Some(current) = alternatives.last_mut() {
+            current.push(atom);
+        }
+    }
+    alternatives
+}
+
+/// Matches `items` against `value` from `at`, calling `rest` with each
+/// position a match could end at until it accepts one.
+fn match_sequence(
+    items: &[Pattern],
+    value: &[char],
+    at: usize,
+    rest: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    let Some((first, tail)) = items.split_first() else {
+        return rest(at);
+    };
+    match first {
+        Pattern::Char(c) => value.get(at) == Some(c) && match_sequence(tail, value, at + 1, rest),
+        Pattern::Any => at < value.len() && match_sequence(tail, value, at + 1, rest),
+        Pattern::Class(ranges, negated) => {
+            value
+                .get(at)
+                .is_some_and(|c| ranges.iter().any(|(lo, hi)| lo <= c && c <= hi) != *negated)
+                && match_sequence(tail, value, at + 1, rest)
+        }
+        Pattern::Group(alternatives) => alternatives.iter().any(|alternative| {
+            match_sequence(alternative, value, at, &mut |end| {
+                match_sequence(tail, value, end, rest)
+            })
+        }),
+        Pattern::Repeat(item, min, max) => match_repeat(item, *min, *max, value, at, &mut |end| {
+            match_sequence(tail, value, end, rest)
+        }),
+    }
+}
+
+fn match_repeat(
+    item: &Pattern,
+    min: usize,
+    max: Option<usize>,
+    value: &[char],
+    at: usize,
+    rest: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    if max != Some(0)
+        && match_sequence(std::slice::from_ref(item), value, at, &mut |end| {
+            end > at
+                && match_repeat(
+                    item,
+                    min.saturating_sub(1),
+                    max.map(|m| m - 1),
+                    value,
+                    end,
+                    rest,
+                )
+        })
+    {
+        return true;
+    }
+    min == 0 && rest(at)
+}
+
+#[cfg(test)]
+#[path = "similar_to_tests.rs"]
+mod tests;
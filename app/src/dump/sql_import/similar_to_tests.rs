@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn similar_to_matches_like_the_server() {
+    assert!(similar_to("app", "app|public"));
+    assert!(similar_to("public", "app|public"));
+    assert!(!similar_to("apps", "app|public"));
+    assert!(similar_to("app_v2", "app%"));
+    assert!(similar_to("ab", "a_"));
+    assert!(!similar_to("abc", "a_"));
+    assert!(similar_to("aaa", "a*"));
+    assert!(similar_to("tenant_1", "tenant_[0-9]+"));
+}
@@ -0,0 +1,516 @@
+//! Helpers for reading statements token by token and printing names and
+//! expressions back the way the server does.
+
+use crate::comparer::scanner::{Statement, TokenKind};
+
+/// Identifier length limit (`NAMEDATALEN - 1`).
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// Keywords `quote_ident` always quotes: every keyword category except the
+/// unreserved one.
+const KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "between",
+    "bigint",
+    "binary",
+    "bit",
+    "boolean",
+    "both",
+    "case",
+    "cast",
+    "char",
+    "character",
+    "check",
+    "coalesce",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "dec",
+    "decimal",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "extract",
+    "false",
+    "fetch",
+    "float",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "greatest",
+    "group",
+    "grouping",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "inout",
+    "int",
+    "integer",
+    "intersect",
+    "interval",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "json",
+    "json_array",
+    "json_arrayagg",
+    "json_exists",
+    "json_object",
+    "json_objectagg",
+    "json_query",
+    "json_scalar",
+    "json_serialize",
+    "json_table",
+    "json_value",
+    "lateral",
+    "leading",
+    "least",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "merge_action",
+    "national",
+    "natural",
+    "nchar",
+    "none",
+    "normalize",
+    "not",
+    "notnull",
+    "null",
+    "nullif",
+    "numeric",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "out",
+    "outer",
+    "overlaps",
+    "overlay",
+    "placing",
+    "position",
+    "precision",
+    "primary",
+    "real",
+    "references",
+    "returning",
+    "right",
+    "row",
+    "select",
+    "session_user",
+    "setof",
+    "similar",
+    "smallint",
+    "some",
+    "substring",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "time",
+    "timestamp",
+    "to",
+    "trailing",
+    "treat",
+    "trim",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "values",
+    "varchar",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+    "xmlattributes",
+    "xmlconcat",
+    "xmlelement",
+    "xmlexists",
+    "xmlforest",
+    "xmlnamespaces",
+    "xmlparse",
+    "xmlpi",
+    "xmlroot",
+    "xmlserialize",
+    "xmltable",
+];
+
+/// Words the server prints in upper case when it deparses an expression.
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "all",
+    "and",
+    "any",
+    "array",
+    "between",
+    "case",
+    "collate",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "distinct",
+    "else",
+    "end",
+    "exists",
+    "ilike",
+    "in",
+    "is",
+    "like",
+    "localtime",
+    "localtimestamp",
+    "not",
+    "null",
+    "or",
+    "row",
+    "session_user",
+    "similar",
+    "some",
+    "then",
+    "when",
+];
+
+/// A signed integer literal at `k` and the index after it.
+pub(super) fn number_at(st: &Statement, k: usize) -> Result<(i64, usize), String> {
+    let (sign, k) = if st.is_punct(k, "-") {
+        (-1, k + 1)
+    } else {
+        (1, k + usize::from(st.is_punct(k, "+")))
+    };
+    st.text(k)
+        .parse::<i64>()
+        .map(|value| (sign * value, k + 1))
+        .map_err(|_| format!("expected a number at {}", st.text(k)))
+}
+
+pub(super) fn skip_if_not_exists(st: &Statement, i: usize) -> (usize, bool) {
+    let next = st.skip(i, &["if", "not", "exists"]);
+    (next, next != i)
+}
+
+/// The leading keywords of a statement, e.g. `CREATE TRIGGER`.
+pub(super) fn statement_kind(st: &Statement, i: usize) -> String {
+    let mut words = vec![st.text(i).to_uppercase()];
+    let j = st.skip(i + 1, &["or", "replace"]);
+    if st.tokens.get(j).is_some_and(|t| t.kind == TokenKind::Word) && !st.is_punct(j + 1, ".") {
+        words.push(st.text(j).to_uppercase());
+    }
+    words.join(" ")
+}
+
+/// Quotes an identifier the way the server's `quote_ident` does.
+pub(super) fn quote_ident(name: &str) -> String {
+    let safe = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !KEYWORDS.contains(&name);
+    if safe {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+pub(super) fn unquote(name: &str) -> String {
+    match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => name.to_string(),
+    }
+}
+
+/// A name as the server prints it with `public` on the search path.
+pub(super) fn visible_name(schema: &str, name: &str) -> String {
+    if schema == "public" || schema == "pg_catalog" {
+        quote_ident(name)
+    } else {
+        format!("{}.{}", quote_ident(schema), quote_ident(name))
+    }
+}
+
+/// `makeObjectName`: joins the parts with `_`, truncating the longer of the
+/// first two until the result fits in an identifier.
+pub(super) fn make_object_name(name1: &str, name2: &str, label: &str) -> String {
+    let mut len1 = name1.chars().count();
+    let mut len2 = name2.chars().count();
+    let overhead = label.chars().count() + 1 + usize::from(!name2.is_empty());
+    while len1 + len2 + overhead > MAX_IDENTIFIER_LEN {
+        if len1 > len2 {
+            len1 -= 1;
+        } else {
+            len2 -= 1;
+        }
+    }
+    let mut name: String = name1.chars().take(len1).collect();
+    if !name2.is_empty() {
+        name.push('_');
+        name.extend(name2.chars().take(len2));
+    }
+    name.push('_');
+    name.push_str(label);
+    name
+}
+
+/// Top-level comma-separated items of the list opened at `open`, and the
+/// index of its closing parenthesis.
+pub(super) fn list_items(st: &Statement, open: usize) -> (Vec<(usize, usize)>, usize) {
+    let close = closing_paren(st, open);
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = open + 1;
+    for k in open + 1..close {
+        if st.is_punct(k, "(") || st.is_punct(k, "[") {
+            depth += 1;
+        } else if st.is_punct(k, ")") || st.is_punct(k, "]") {
+            depth -= 1;
+        } else if depth == 0 && st.is_punct(k, ",") {
+            items.push((start, k));
+            start = k + 1;
+        }
+    }
+    if start < close {
+        items.push((start, close));
+    }
+    (items, close)
+}
+
+pub(super) fn closing_paren(st: &Statement, open: usize) -> usize {
+    let mut depth = 0;
+    for k in open..st.tokens.len() {
+        if st.is_punct(k, "(") {
+            depth += 1;
+        } else if st.is_punct(k, ")") {
+            depth -= 1;
+            if depth == 0 {
+                return k;
+            }
+        }
+    }
+    st.tokens.len()
+}
+
+/// Tokens `from..to` as the server deparses them: single spaces, lower-case
+/// identifiers, upper-case expression keywords (and `VALUE` in domain
+/// checks), and no `public.` qualification.
+pub(super) fn render(st: &Statement, from: usize, to: usize, domain: bool) -> String {
+    let mut out = String::new();
+    let mut k = from;
+    let to = to.min(st.tokens.len());
+    while k < to {
+        let token = &st.tokens[k];
+        if k > from && st.tokens[k - 1].end < token.start {
+            out.push(' ');
+        }
+        let text = st.text(k);
+        match token.kind {
+            TokenKind::Word => {
+                let lower = text.to_lowercase();
+                if lower == "public" && st.is_punct(k + 1, ".") && st.is_name_part(k + 2) {
+                    k += 2;
+                    continue;
+                }
+                if EXPRESSION_KEYWORDS.contains(&lower.as_str()) || domain && lower == "value" {
+                    out.push_str(&lower.to_uppercase());
+                } else {
+                    out.push_str(&lower);
+                }
+            }
+            TokenKind::Quoted => {
+                let name = unquote(text);
+                if name == "public" && st.is_punct(k + 1, ".") && st.is_name_part(k + 2) {
+                    k += 2;
+                    continue;
+                }
+                out.push_str(&quote_ident(&name));
+            }
+            TokenKind::Literal
+                if text.starts_with("'public.")
+                    && st.is_punct(k + 1, ":")
+                    && st.is_word(k + 3, "regclass") =>
+            {
+                out.push('\'');
+                out.push_str(&text["'public.".len()..]);
+            }
+            _ => out.push_str(text),
+        }
+        k += 1;
+    }
+    out
+}
+
+/// Removes `public.` qualifiers outside string literals.
+pub(super) fn strip_public(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_literal = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' {
+            in_literal = !in_literal;
+        }
+        let boundary = out
+            .chars()
+            .last()
+            .is_none_or(|p| !(p.is_alphanumeric() || p == '_' || p == '"' || p == '.'));
+        if !in_literal && boundary && rest.starts_with("public.") {
+            rest = &rest["public.".len()..];
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Whether one pair of parentheses encloses all of `expression`.
+pub(super) fn wholly_wrapped(expression: &str) -> bool {
+    if !expression.starts_with('(') || !expression.ends_with(')') {
+        return false;
+    }
+    let mut depth = 0;
+    let mut in_literal = false;
+    for (i, c) in expression.char_indices() {
+        match c {
+            '\'' => in_literal = !in_literal,
+            '(' if !in_literal => depth += 1,
+            ')' if !in_literal => {
+                depth -= 1;
+                if depth == 0 {
+                    return i == expression.len() - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+pub(super) fn wrap(expression: &str) -> String {
+    if wholly_wrapped(expression) {
+        expression.to_string()
+    } else {
+        format!("({expression})")
+    }
+}
+
+pub(super) fn unwrap_parens(expression: &str) -> String {
+    if wholly_wrapped(expression) {
+        expression[1..expression.len() - 1].to_string()
+    } else {
+        expression.to_string()
+    }
+}
+
+/// Index of the first top-level word from `stops` after `from`, or `to`.
+pub(super) fn expression_end(st: &Statement, from: usize, to: usize, stops: &[&str]) -> usize {
+    let mut depth = 0;
+    for k in from..to.min(st.tokens.len()) {
+        if st.is_punct(k, "(") {
+            depth += 1;
+        } else if st.is_punct(k, ")") {
+            depth -= 1;
+        } else if depth == 0
+            && (k > from && stops.iter().any(|w| st.is_word(k, w)) || st.is_punct(k, ";"))
+        {
+            return k;
+        }
+    }
+    to.min(st.tokens.len())
+}
+
+pub(super) fn quote_list(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|n| quote_ident(n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The value of a string literal token: `'...'`, `E'...'` or dollar-quoted.
+pub(super) fn literal_value(text: &str) -> Option<String> {
+    if let Some(inner) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        return Some(inner.replace("''", "'"));
+    }
+    if let Some(inner) = text
+        .strip_prefix(['E', 'e'])
+        .and_then(|t| t.strip_prefix('\''))
+        .and_then(|t| t.strip_suffix('\''))
+    {
+        let mut value = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match (c, c == '\\') {
+                (_, true) => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(other) => value.push(other),
+                    None => {}
+                },
+                ('\'', false) => {
+                    chars.next();
+                    value.push('\'');
+                }
+                _ => value.push(c),
+            }
+        }
+        return Some(value);
+    }
+    if let Some(rest) = text.strip_prefix('$') {
+        let tag_end = rest.find('$')? + 2;
+        let tag = &text[..tag_end];
+        return text
+            .strip_prefix(tag)
+            .and_then(|t| t.strip_suffix(tag))
+            .map(str::to_string);
+    }
+    None
+}
+
+#[cfg(test)]
+#[path = "statements_tests.rs"]
+mod tests;
@@ -0,0 +1,20 @@
+use super::*;
+
+#[test]
+fn quote_ident_quotes_only_when_needed() {
+    assert_eq!(quote_ident("users"), "users");
+    assert_eq!(quote_ident("Users"), "\"Users\"");
+    assert_eq!(quote_ident("order"), "\"order\"");
+    assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+    assert_eq!(unquote("\"a\"\"b\""), "a\"b");
+}
+
+#[test]
+fn make_object_name_truncates_like_the_server() {
+    assert_eq!(make_object_name("users", "email", "key"), "users_email_key");
+    assert_eq!(make_object_name("users", "", "pkey"), "users_pkey");
+    let long = "a".repeat(70);
+    let name = make_object_name(&long, "id", "fkey");
+    assert_eq!(name.len(), MAX_IDENTIFIER_LEN);
+    assert!(name.ends_with("_id_fkey"));
+}
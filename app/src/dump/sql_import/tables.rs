@@ -0,0 +1,798 @@
+//! `CREATE TABLE`, `ALTER TABLE` and the columns of tables.
+
+use super::Importer;
+use super::constraints::{apply_timing, constraint_timing, is_table_constraint};
+use super::sequences::{SequenceOptions, parse_sequence_options};
+use super::statements::{
+    closing_paren, expression_end, list_items, literal_value, quote_ident, render,
+    skip_if_not_exists, visible_name,
+};
+use super::types::{TypeKind, TypeRef};
+use crate::comparer::scanner::{Relation, Statement, TokenKind};
+use crate::dump::table::Table;
+use crate::dump::table_column::TableColumn;
+use crate::dump::table_constraint::TableConstraint;
+
+/// The clauses after the column list of `CREATE TABLE`.
+#[derive(Debug, Default)]
+pub(super) struct TableOptions {
+    pub(super) partition_key: Option<String>,
+    pub(super) access_method: Option<String>,
+    pub(super) storage_parameters: Option<Vec<String>>,
+    pub(super) space: Option<String>,
+}
+
+impl Importer {
+    pub(super) fn create_table(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        unlogged: bool,
+    ) -> Result<(), String> {
+        let (i, if_not_exists) = skip_if_not_exists(st, i);
+        let (relation, next) = st.name_at(i).ok_or("missing table name")?;
+        let (schema, name) = self.target(&relation);
+        self.require_schema(&schema)?;
+        if self.name_taken(&schema, &name) {
+            if if_not_exists {
+                return Ok(());
+            }
+            return Err(format!("relation {} already exists", relation.display));
+        }
+        if st.is_word(next, "partition") && st.is_word(next + 1, "of") {
+            return self.create_partition(st, next + 2, &schema, &name, unlogged);
+        }
+        if !st.is_punct(next, "(") {
+            return Err(format!(
+                "CREATE TABLE {} is not supported",
+                st.text(next).to_uppercase()
+            ));
+        }
+        let (items, close) = list_items(st, next);
+        let options = table_options(st, close + 1)?;
+        let index = self.push_table(&schema, &name, unlogged, options);
+
+        // Columns first, so table constraints can name any of them.
+        let mut constraints = Vec::new();
+        for (from, to) in items {
+            if is_table_constraint(st, from) {
+                constraints.push((from, to));
+            } else if let Err(reason) = self.add_column(st, index, from, to) {
+                self.notes.push(reason);
+            }
+        }
+        for (from, to) in constraints {
+            if let Err(reason) = self.add_table_constraint(st, index, from, to) {
+                self.notes.push(reason);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds an empty table with the clauses of `options`; returns its index.
+    pub(super) fn push_table(
+        &mut self,
+        schema: &str,
+        name: &str,
+        unlogged: bool,
+        options: TableOptions,
+    ) -> usize {
+        let mut table = Table::new(
+            quote_ident(schema),
+            quote_ident(name),
+            schema.to_string(),
+            name.to_string(),
+            quote_ident(&self.owner),
+            None,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        );
+        table.replica_identity = Some("d".to_string());
+        table.is_unlogged = unlogged;
+        table.partition_key = options.partition_key;
+        table.access_method = options.access_method;
+        table.storage_parameters = options.storage_parameters;
+        table.space = options.space;
+        self.dump.tables.push(table);
+        self.dump.tables.len() - 1
+    }
+
+    /// `schema.name` of a table, as [`Table::partition_of`] names parents.
+    pub(super) fn qualified_table(&self, table: usize) -> String {
+        let t = &self.dump.tables[table];
+        format!("{}.{}", t.schema, t.name)
+    }
+
+    /// A column as the catalog describes it, before defaults and
+    /// constraints are applied.
+    fn new_column(&self, table: usize, name: &str, position: i32, ty: &TypeRef) -> TableColumn {
+        let table = &self.dump.tables[table];
+        let info = ty.info();
+        let (udt_schema, udt_name) = ty.udt();
+        let domain = ty.domain();
+        TableColumn {
+            catalog: self.catalog.clone(),
+            schema: table.schema.clone(),
+            table: table.name.clone(),
+            name: quote_ident(name),
+            ordinal_position: position,
+            column_default: None,
+            is_nullable: !self.domain_not_null(ty),
+            data_type: ty.column_data_type(),
+            character_maximum_length: info.character_maximum_length,
+            character_octet_length: info.character_octet_length,
+            numeric_precision: info.numeric_precision,
+            numeric_precision_radix: info.numeric_precision_radix,
+            numeric_scale: info.numeric_scale,
+            datetime_precision: info.datetime_precision,
+            interval_type: info.interval_type,
+            interval_precision: None,
+            character_set_catalog: None,
+            character_set_schema: None,
+            character_set_name: None,
+            collation_catalog: None,
+            collation_schema: None,
+            collation_name: None,
+            domain_catalog: domain.map(|_| self.catalog.clone()),
+            domain_schema: domain.map(|(schema, _)| schema.to_string()),
+            domain_name: domain.map(|(_, name)| name.to_string()),
+            udt_catalog: Some(self.catalog.clone()),
+            udt_schema: Some(udt_schema),
+            udt_name: Some(udt_name),
+            scope_catalog: None,
+            scope_schema: None,
+            scope_name: None,
+            maximum_cardinality: None,
+            dtd_identifier: None,
+            is_self_referencing: false,
+            is_identity: false,
+            identity_generation: None,
+            identity_start: None,
+            identity_increment: None,
+            identity_maximum: None,
+            identity_minimum: None,
+            identity_cycle: false,
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            generation_type: None,
+            is_updatable: true,
+            related_views: None,
+            comment: None,
+            storage: Some(storage_name(ty.shape().storage).to_string()),
+            compression: None,
+            statistics_target: None,
+            acl: Vec::new(),
+            serial_type: None,
+        }
+    }
+
+    pub(super) fn column_index(&self, table: usize, column: &str) -> Result<usize, String> {
+        let quoted = quote_ident(column);
+        self.dump.tables[table]
+            .columns
+            .iter()
+            .position(|c| c.name == quoted)
+            .ok_or_else(|| format!("column {column} not found"))
+    }
+
+    /// A column definition: name, type and column constraints.
+    fn add_column(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        from: usize,
+        to: usize,
+    ) -> Result<(), String> {
+        if st.is_word(from, "like") {
+            return Err("LIKE is not supported".to_string());
+        }
+        let column = st.relation(&[from]).ok_or("missing column name")?.key;
+        if self.column_index(table, &column).is_ok() {
+            return Err(format!("column {column} specified more than once"));
+        }
+        let (ty, j) = self.parse_type(st, from + 1)?;
+        let position = self.dump.tables[table].columns.len() as i32 + 1;
+        let new_column = self.new_column(table, &column, position, &ty);
+        self.dump.tables[table].columns.push(new_column);
+        self.column_types
+            .insert((table, column.clone()), ty.clone());
+        if ty.serial {
+            self.make_serial(table, &column, &ty);
+        }
+        self.column_constraints(st, table, &column, j, to)
+    }
+
+    /// The defaults, constraints and other options of `column` from `j`
+    /// to `to`, as in a column definition.
+    pub(super) fn column_constraints(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        column: &str,
+        mut j: usize,
+        to: usize,
+    ) -> Result<(), String> {
+        let c = self.column_index(table, column)?;
+        let ty = self
+            .column_types
+            .get(&(table, column.to_string()))
+            .cloned()
+            .ok_or("column type unknown")?;
+        let mut constraint_name = None;
+        let mut last_constraint = None;
+        while j < to {
+            let word = st.text(j).to_lowercase();
+            match word.as_str() {
+                "constraint" => {
+                    constraint_name = st.relation(&[j + 1]).map(|r| r.key);
+                    j += 2;
+                }
+                "not" if st.is_word(j + 1, "null") => {
+                    self.dump.tables[table].columns[c].is_nullable = false;
+                    j += 2;
+                }
+                "null" => j += 1,
+                "default" => {
+                    let end = expression_end(st, j + 1, to, COLUMN_CONSTRAINT_WORDS);
+                    self.dump.tables[table].columns[c].column_default =
+                        normalize_default(st, j + 1, end, &ty);
+                    j = end;
+                }
+                "collate" => {
+                    let parts = st.name_parts(j + 1);
+                    let relation = st.relation(&parts).ok_or("missing collation name")?;
+                    let schema = if relation.parts.len() >= 2 {
+                        relation.parts[0].clone()
+                    } else {
+                        "pg_catalog".to_string()
+                    };
+                    let name = relation.parts.last().cloned().unwrap_or_default();
+                    let col = &mut self.dump.tables[table].columns[c];
+                    if name != "default" {
+                        col.collation_catalog = Some(self.catalog.clone());
+                        col.collation_schema = Some(schema);
+                        col.collation_name = Some(name);
+                    }
+                    j = parts.last().map_or(j + 1, |p| p + 1);
+                }
+                "storage" => {
+                    self.dump.tables[table].columns[c].storage =
+                        Some(st.text(j + 1).to_uppercase());
+                    j += 2;
+                }
+                "compression" => {
+                    let method = st.text(j + 1).to_lowercase();
+                    self.dump.tables[table].columns[c].compression =
+                        (method != "default").then_some(method);
+                    j += 2;
+                }
+                "generated" => {
+                    j = self.generated_column(st, table, column, j, to)?;
+                }
+                "deferrable" | "initially" | "not" => {
+                    let (next, deferrable, deferred) = constraint_timing(st, j);
+                    if let Some(index) = last_constraint {
+                        let constraint: &mut TableConstraint =
+                            &mut self.dump.tables[table].constraints[index];
+                        apply_timing(constraint, deferrable, deferred);
+                    }
+                    if next == j {
+                        return Err(format!("{} is not supported", st.text(j).to_uppercase()));
+                    }
+                    j = next;
+                }
+                "check" | "unique" | "primary" | "references" => {
+                    let (index, next) = self.add_constraint(
+                        st,
+                        table,
+                        constraint_name.take(),
+                        j,
+                        to,
+                        Some(column),
+                    )?;
+                    last_constraint = Some(index);
+                    j = next;
+                }
+                _ => {
+                    return Err(format!(
+                        "column {column}: {} is not supported",
+                        st.text(j).to_uppercase()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `serial`: an owned sequence and a `nextval` default.
+    fn make_serial(&mut self, table: usize, column: &str, ty: &TypeRef) {
+        let (schema, table_name) = {
+            let t = &self.dump.tables[table];
+            (t.raw_schema.clone(), t.raw_name.clone())
+        };
+        let name = self.choose_name(&schema, &table_name, column, "seq");
+        let mut sequence =
+            self.new_sequence(&schema, &name, &ty.typname, &SequenceOptions::default());
+        let t = &self.dump.tables[table];
+        sequence.owned_by_schema = Some(t.schema.clone());
+        sequence.owned_by_table = Some(t.name.clone());
+        sequence.owned_by_column = Some(quote_ident(column));
+        sequence.hash();
+        self.dump.sequences.push(sequence);
+        let regclass = visible_name(&schema, &name).replace('\'', "''");
+        let c = self.dump.tables[table].columns.len() - 1;
+        let col = &mut self.dump.tables[table].columns[c];
+        col.column_default = Some(format!("nextval('{regclass}'::regclass)"));
+        col.is_nullable = false;
+    }
+
+    /// `GENERATED ALWAYS AS (expr) STORED` or `GENERATED ... AS IDENTITY`;
+    /// returns the index after the clause.
+    fn generated_column(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        column: &str,
+        j: usize,
+        to: usize,
+    ) -> Result<usize, String> {
+        let c = self.column_index(table, column)?;
+        let (generation, mut k) = if st.is_word(j + 1, "always") {
+            ("ALWAYS", j + 2)
+        } else if st.skip(j + 1, &["by", "default"]) != j + 1 {
+            ("BY DEFAULT", j + 3)
+        } else {
+            return Err("GENERATED without ALWAYS or BY DEFAULT".to_string());
+        };
+        if !st.is_word(k, "as") {
+            return Err("GENERATED without AS".to_string());
+        }
+        k += 1;
+        if st.is_punct(k, "(") {
+            let close = closing_paren(st, k);
+            let col = &mut self.dump.tables[table].columns[c];
+            col.is_generated = "ALWAYS".to_string();
+            col.generation_expression = Some(render(st, k + 1, close, false));
+            col.generation_type = Some("s".to_string());
+            let next = close + 1;
+            return Ok(st.skip(next, &["stored"]));
+        }
+        if !st.is_word(k, "identity") {
+            return Err(format!(
+                "GENERATED AS {} is not supported",
+                st.text(k).to_uppercase()
+            ));
+        }
+        k += 1;
+        let options = if st.is_punct(k, "(") {
+            let close = closing_paren(st, k);
+            let options = parse_sequence_options(st, k + 1, close)?;
+            k = close + 1;
+            options
+        } else {
+            SequenceOptions::default()
+        };
+        self.make_identity(table, column, generation, &options)?;
+        Ok(k.min(to))
+    }
+
+    /// An identity column and the sequence behind it.
+    fn make_identity(
+        &mut self,
+        table: usize,
+        column: &str,
+        generation: &str,
+        options: &SequenceOptions,
+    ) -> Result<(), String> {
+        let c = self.column_index(table, column)?;
+        let (schema, table_name) = {
+            let t = &self.dump.tables[table];
+            (t.raw_schema.clone(), t.raw_name.clone())
+        };
+        let (seq_schema, seq_name) = match &options.name {
+            Some(relation) => self.target(relation),
+            None => (
+                schema.clone(),
+                self.choose_name(&schema, &table_name, column, "seq"),
+            ),
+        };
+        let typname = self
+            .column_types
+            .get(&(table, column.to_string()))
+            .map_or("int4".to_string(), |ty| ty.typname.clone());
+        let mut sequence = self.new_sequence(&seq_schema, &seq_name, &typname, options);
+        let t = &self.dump.tables[table];
+        sequence.owned_by_schema = Some(t.schema.clone());
+        sequence.owned_by_table = Some(t.name.clone());
+        sequence.owned_by_column = Some(quote_ident(column));
+        sequence.set_is_identity(true);
+        let col = &mut self.dump.tables[table].columns[c];
+        col.is_identity = true;
+        col.is_nullable = false;
+        col.column_default = None;
+        col.identity_generation = Some(generation.to_string());
+        col.identity_start = sequence.start_value.map(|v| v.to_string());
+        col.identity_increment = sequence.increment_by.map(|v| v.to_string());
+        col.identity_maximum = sequence.max_value.map(|v| v.to_string());
+        col.identity_minimum = sequence.min_value.map(|v| v.to_string());
+        col.identity_cycle = sequence.cycle;
+        self.dump.sequences.push(sequence);
+        Ok(())
+    }
+
+    /// Schema and name of a referenced table: the one found on the search
+    /// path, or where it would be created.
+    pub(super) fn target_or_found(&self, relation: &Relation) -> (String, String) {
+        match self.find_table(relation) {
+            Some(index) => {
+                let t = &self.dump.tables[index];
+                (t.raw_schema.clone(), t.raw_name.clone())
+            }
+            None => self.target(relation),
+        }
+    }
+
+    pub(super) fn alter_table(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let i = st.skip(i, &["if", "exists"]);
+        let only = st.is_word(i, "only");
+        let i = st.skip(i, &["only"]);
+        let (relation, next) = st.name_at(i).ok_or("missing table name")?;
+        let Some(table) = self.find_table(&relation) else {
+            // pg_dump sets the owner of sequences and views with ALTER TABLE.
+            if st.skip(next, &["owner", "to"]) != next
+                && (self.find_sequence(&relation).is_some() || self.find_view(&relation).is_some())
+            {
+                return if self.find_sequence(&relation).is_some() {
+                    self.alter_sequence(st, i)
+                } else {
+                    self.alter_view(st, i)
+                };
+            }
+            return Err(format!("table {} not found", relation.display));
+        };
+        for (from, to) in st.subcommands(next) {
+            if let Err(reason) = self.alter_table_subcommand(st, table, from, to) {
+                self.notes.push(reason);
+            }
+        }
+        if !only {
+            self.sync_partition_columns(table);
+        }
+        self.dump.tables[table].hash();
+        Ok(())
+    }
+
+    fn alter_table_subcommand(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        from: usize,
+        to: usize,
+    ) -> Result<(), String> {
+        let word = st.text(from).to_lowercase();
+        match word.as_str() {
+            "add" if is_table_constraint(st, from + 1) => {
+                self.add_table_constraint(st, table, from + 1, to)
+            }
+            "add" => {
+                let (k, if_not_exists) = skip_if_not_exists(st, st.skip(from + 1, &["column"]));
+                if if_not_exists
+                    && let Some(column) = st.relation(&[k])
+                    && self.column_index(table, &column.key).is_ok()
+                {
+                    return Ok(());
+                }
+                self.add_column(st, table, k, to)
+            }
+            "alter" => self.alter_column(st, table, st.skip(from + 1, &["column"]), to),
+            "attach" if st.is_word(from + 1, "partition") => {
+                self.attach_partition(st, table, from + 2)
+            }
+            "owner" if st.is_word(from + 1, "to") => {
+                let role = self.role_at(st, from + 2).ok_or("missing role name")?;
+                let role = quote_ident(&role);
+                let t = &mut self.dump.tables[table];
+                Self::reassign_acl(&mut t.acl, &t.owner, &role);
+                for column in t.columns.iter_mut() {
+                    Self::reassign_acl(&mut column.acl, &t.owner, &role);
+                }
+                t.owner = role.clone();
+                let (schema, name) = (t.schema.clone(), t.name.clone());
+                for sequence in self.dump.sequences.iter_mut().filter(|s| {
+                    s.owned_by_schema.as_deref() == Some(&schema)
+                        && s.owned_by_table.as_deref() == Some(&name)
+                }) {
+                    Self::reassign_acl(&mut sequence.acl, &sequence.owner, &role);
+                    sequence.owner = role.clone();
+                    sequence.hash();
+                }
+                Ok(())
+            }
+            "enable" | "disable"
+                if st
+                    .text(st.skip(from + 1, &["replica", "always"]))
+                    .eq_ignore_ascii_case("trigger") =>
+            {
+                let k = st.skip(from + 1, &["replica", "always"]);
+                let enabled = match (word.as_str(), st.text(from + 1).to_lowercase().as_str()) {
+                    ("disable", _) => "D",
+                    (_, "replica") => "R",
+                    (_, "always") => "A",
+                    _ => "O",
+                };
+                let name = quote_ident(&st.relation(&[k + 1]).ok_or("missing trigger name")?.key);
+                let trigger = self.dump.tables[table]
+                    .triggers
+                    .iter_mut()
+                    .find(|t| t.name == name)
+                    .ok_or_else(|| format!("trigger {name} not found"))?;
+                trigger.enabled = enabled.to_string();
+                Ok(())
+            }
+            "enable" | "disable"
+                if st.skip(from + 1, &["row", "level", "security"]) != from + 1 =>
+            {
+                self.dump.tables[table].has_rowsecurity = word == "enable";
+                Ok(())
+            }
+            "force" if st.skip(from + 1, &["row", "level", "security"]) != from + 1 => {
+                self.dump.tables[table].force_rowsecurity = true;
+                Ok(())
+            }
+            "no" if st.skip(from + 1, &["force", "row", "level", "security"]) != from + 1 => {
+                self.dump.tables[table].force_rowsecurity = false;
+                Ok(())
+            }
+            "replica" if st.is_word(from + 1, "identity") => {
+                let identity = match st.text(from + 2).to_lowercase().as_str() {
+                    "default" => "d",
+                    "full" => "f",
+                    "nothing" => "n",
+                    "using" => "i",
+                    other => {
+                        return Err(format!(
+                            "REPLICA IDENTITY {} is not supported",
+                            other.to_uppercase()
+                        ));
+                    }
+                };
+                self.dump.tables[table].replica_identity = Some(identity.to_string());
+                Ok(())
+            }
+            "set" if st.is_punct(from + 1, "(") => {
+                let (items, _) = list_items(st, from + 1);
+                let t = &mut self.dump.tables[table];
+                let parameters = t.storage_parameters.get_or_insert_with(Vec::new);
+                for (from, to) in items {
+                    let parameter = storage_parameter(st, from, to);
+                    let key = parameter.split('=').next().unwrap_or_default().to_string();
+                    parameters.retain(|p| p.split('=').next() != Some(key.as_str()));
+                    parameters.push(parameter);
+                }
+                Ok(())
+            }
+            "set" if st.is_word(from + 1, "logged") || st.is_word(from + 1, "unlogged") => {
+                self.dump.tables[table].is_unlogged = st.is_word(from + 1, "unlogged");
+                Ok(())
+            }
+            "set" if st.is_word(from + 1, "tablespace") => {
+                self.dump.tables[table].space = st.relation(&[from + 2]).map(|r| r.key);
+                Ok(())
+            }
+            "drop" if st.is_word(from + 1, "constraint") => {
+                let k = st.skip(from + 2, &["if", "exists"]);
+                let name = quote_ident(&st.relation(&[k]).ok_or("missing constraint name")?.key);
+                let t = &mut self.dump.tables[table];
+                let before = t.constraints.len();
+                t.constraints.retain(|c| c.name != name);
+                if t.constraints.len() == before && k == from + 2 {
+                    return Err(format!("constraint {name} not found"));
+                }
+                Ok(())
+            }
+            "drop" => {
+                let k = st.skip(st.skip(from + 1, &["column"]), &["if", "exists"]);
+                let column = st.relation(&[k]).ok_or("missing column name")?.key;
+                let Ok(c) = self.column_index(table, &column) else {
+                    return if st.is_word(k - 1, "exists") {
+                        Ok(())
+                    } else {
+                        Err(format!("column {column} not found"))
+                    };
+                };
+                self.dump.tables[table].columns.remove(c);
+                self.column_types.remove(&(table, column));
+                Ok(())
+            }
+            _ => Err(format!(
+                "ALTER TABLE {} is not supported",
+                st.text(from).to_uppercase()
+            )),
+        }
+    }
+
+    fn alter_column(
+        &mut self,
+        st: &Statement,
+        table: usize,
+        k: usize,
+        to: usize,
+    ) -> Result<(), String> {
+        let column = st.relation(&[k]).ok_or("missing column name")?.key;
+        let c = self.column_index(table, &column)?;
+        let k = k + 1;
+        let ty = self.column_types.get(&(table, column.clone())).cloned();
+        if st.skip(k, &["set", "default"]) != k {
+            let ty = ty.ok_or("column type unknown")?;
+            self.dump.tables[table].columns[c].column_default =
+                normalize_default(st, k + 2, to, &ty);
+        } else if st.skip(k, &["drop", "default"]) != k {
+            self.dump.tables[table].columns[c].column_default = None;
+        } else if st.skip(k, &["set", "not", "null"]) != k {
+            self.dump.tables[table].columns[c].is_nullable = false;
+        } else if st.skip(k, &["drop", "not", "null"]) != k {
+            self.dump.tables[table].columns[c].is_nullable = true;
+        } else if st.is_word(k, "add") && st.is_word(k + 1, "generated") {
+            self.generated_column(st, table, &column, k + 1, to)?;
+        } else if st.skip(k, &["set", "statistics"]) != k {
+            self.dump.tables[table].columns[c].statistics_target =
+                st.text(k + 2).parse::<i32>().ok().filter(|v| *v >= 0);
+        } else if st.skip(k, &["set", "storage"]) != k {
+            self.dump.tables[table].columns[c].storage = Some(st.text(k + 2).to_uppercase());
+        } else if st.skip(k, &["set", "compression"]) != k {
+            let method = st.text(k + 2).to_lowercase();
+            self.dump.tables[table].columns[c].compression =
+                (method != "default").then_some(method);
+        } else if st.is_word(k, "type") || st.skip(k, &["set", "data", "type"]) != k {
+            let start = if st.is_word(k, "type") { k + 1 } else { k + 3 };
+            let (ty, _) = self.parse_type(st, start)?;
+            let old = self.dump.tables[table].columns[c].clone();
+            let mut column_def = self.new_column(table, &column, old.ordinal_position, &ty);
+            column_def.column_default = old.column_default;
+            column_def.is_nullable = old.is_nullable && column_def.is_nullable;
+            column_def.comment = old.comment;
+            column_def.acl = old.acl;
+            self.dump.tables[table].columns[c] = column_def;
+            self.column_types.insert((table, column), ty);
+        } else {
+            return Err(format!(
+                "ALTER COLUMN {} is not supported",
+                st.text(k).to_uppercase()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Words that end a `DEFAULT` expression in a column definition.
+const COLUMN_CONSTRAINT_WORDS: &[&str] = &[
+    "constraint",
+    "not",
+    "null",
+    "check",
+    "unique",
+    "primary",
+    "references",
+    "default",
+    "generated",
+    "collate",
+    "deferrable",
+    "initially",
+    "storage",
+    "compression",
+];
+
+/// A `name = value` storage parameter as `reloptions` stores it.
+pub(super) fn storage_parameter(st: &Statement, from: usize, to: usize) -> String {
+    let name: String = (from..to)
+        .take_while(|&k| !st.is_punct(k, "="))
+        .map(|k| st.text(k).to_lowercase())
+        .collect();
+    let value = match (from..to).find(|&k| st.is_punct(k, "=")) {
+        Some(eq) => (eq + 1..to)
+            .map(|k| literal_value(st.text(k)).unwrap_or_else(|| st.text(k).to_lowercase()))
+            .collect::<String>(),
+        None => "true".to_string(),
+    };
+    format!("{name}={value}")
+}
+
+/// The clauses of `CREATE TABLE` from `j` to the end of the statement.
+pub(super) fn table_options(st: &Statement, mut j: usize) -> Result<TableOptions, String> {
+    let mut options = TableOptions::default();
+    while j < st.tokens.len() && !st.is_punct(j, ";") {
+        if st.is_word(j, "partition") && st.is_word(j + 1, "by") {
+            let strategy = st.text(j + 2).to_lowercase();
+            if !["range", "list", "hash"].contains(&strategy.as_str()) || !st.is_punct(j + 3, "(") {
+                return Err(format!(
+                    "PARTITION BY {} is not supported",
+                    strategy.to_uppercase()
+                ));
+            }
+            let close = closing_paren(st, j + 3);
+            options.partition_key = Some(format!(
+                "{} ({})",
+                strategy.to_uppercase(),
+                render(st, j + 4, close, false)
+            ));
+            j = close + 1;
+        } else if st.is_word(j, "using") {
+            let method = st.text(j + 1).to_lowercase();
+            options.access_method = (method != "heap").then_some(method);
+            j += 2;
+        } else if st.is_word(j, "with") && st.is_punct(j + 1, "(") {
+            let (items, close) = list_items(st, j + 1);
+            options.storage_parameters = Some(
+                items
+                    .iter()
+                    .map(|&(from, to)| storage_parameter(st, from, to))
+                    .collect(),
+            );
+            j = close + 1;
+        } else if st.is_word(j, "tablespace") {
+            options.space = st.relation(&[j + 1]).map(|r| r.key);
+            j += 2;
+        } else if st.is_word(j, "without") && st.is_word(j + 1, "oids") {
+            j += 2;
+        } else {
+            return Err(format!("{} is not supported", st.text(j).to_uppercase()));
+        }
+    }
+    Ok(options)
+}
+
+fn storage_name(storage: u8) -> &'static str {
+    match storage {
+        b'p' => "PLAIN",
+        b'e' => "EXTERNAL",
+        b'm' => "MAIN",
+        _ => "EXTENDED",
+    }
+}
+
+/// A column default as `pg_get_expr` prints it: literals carry their cast,
+/// and `nextval` its `regclass`.
+pub(super) fn normalize_default(
+    st: &Statement,
+    from: usize,
+    to: usize,
+    ty: &TypeRef,
+) -> Option<String> {
+    if from >= to || to - from == 1 && st.is_word(from, "null") {
+        return None;
+    }
+    let cast = || {
+        if matches!(ty.kind, TypeKind::Builtin) && ty.typname == "bpchar" && !ty.is_array {
+            "bpchar".to_string()
+        } else {
+            ty.format(false)
+        }
+    };
+    let first = st.text(from);
+    if to - from == 1 && st.tokens[from].kind == TokenKind::Literal && first.ends_with('\'') {
+        return Some(format!("{first}::{}", cast()));
+    }
+    if to - from == 2
+        && st.is_punct(from, "-")
+        && st
+            .text(from + 1)
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.')
+    {
+        return Some(format!("'-{}'::{}", st.text(from + 1), cast()));
+    }
+    let rendered = render(st, from, to, false);
+    if rendered.starts_with("nextval(") && !rendered.contains("::regclass") {
+        let inner = &rendered["nextval(".len()..rendered.len() - 1];
+        let name = inner.trim_matches('\'').trim_start_matches("public.");
+        return Some(format!("nextval('{name}'::regclass)"));
+    }
+    Some(rendered)
+}
+
+#[cfg(test)]
+#[path = "tables_tests.rs"]
+mod tests;
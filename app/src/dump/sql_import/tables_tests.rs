@@ -0,0 +1,119 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn imports_tables_columns_and_constraints() {
+    let (dump, skipped) = import(SCHEMA);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    assert_eq!(dump.schemas.len(), 1);
+    assert_eq!(dump.schemas[0].name, "app");
+
+    let users = table(&dump, "users");
+    let id = &users.columns[0];
+    assert_eq!(id.data_type, "integer");
+    assert_eq!(
+        id.column_default.as_deref(),
+        Some("nextval('app.users_id_seq'::regclass)")
+    );
+    assert!(!id.is_nullable);
+    let name = &users.columns[3];
+    assert_eq!(name.data_type, "character varying");
+    assert_eq!(name.character_maximum_length, Some(100));
+    assert_eq!(users.columns[1].domain_name.as_deref(), Some("email"));
+
+    let constraints: Vec<(&str, &str)> = users
+        .constraints
+        .iter()
+        .map(|c| (c.name.as_str(), c.definition.as_deref().unwrap_or_default()))
+        .collect();
+    assert_eq!(
+        constraints,
+        vec![
+            ("users_email_key", "UNIQUE (email)"),
+            ("users_pkey", "PRIMARY KEY (id)")
+        ]
+    );
+}
+
+#[test]
+fn rejects_tables_with_clauses_it_cannot_model() {
+    let (dump, skipped) = import(
+        "CREATE SCHEMA app;
+CREATE TABLE app.parent (id int);
+CREATE TABLE app.child (extra int) INHERITS (app.parent);
+CREATE TABLE app.plain (id int);
+CREATE TABLE app.part PARTITION OF app.plain FOR VALUES IN (1);",
+    );
+    let names: Vec<&str> = dump.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["parent", "plain"]);
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "INHERITS is not supported",
+            "table app.plain is not partitioned"
+        ]
+    );
+}
+
+#[test]
+fn alters_tables_and_columns() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER TABLE app.users ADD COLUMN IF NOT EXISTS name text;
+ALTER TABLE app.users ADD COLUMN age smallint NOT NULL DEFAULT 0;
+ALTER TABLE ONLY app.users ALTER COLUMN mood SET DEFAULT 'happy', ALTER name SET NOT NULL;
+ALTER TABLE app.users ALTER COLUMN name TYPE text;
+ALTER TABLE app.users ALTER COLUMN age DROP DEFAULT;
+ALTER TABLE app.users DROP COLUMN IF EXISTS nickname;
+ALTER TABLE app.orders DROP COLUMN total;
+ALTER TABLE app.orders OWNER TO admin;
+ALTER TABLE app.orders SET (fillfactor = 70), SET UNLOGGED;
+ALTER TABLE app.orders REPLICA IDENTITY FULL;
+ALTER TABLE app.orders CLUSTER ON orders_user_idx;
+ALTER TABLE app.missing OWNER TO admin;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "ALTER TABLE CLUSTER is not supported",
+            "table app.missing not found"
+        ]
+    );
+    let users = table(&dump, "users");
+    let columns: Vec<(&str, &str, Option<&str>, bool)> = users
+        .columns
+        .iter()
+        .map(|c| {
+            (
+                c.name.as_str(),
+                c.data_type.as_str(),
+                c.column_default.as_deref(),
+                c.is_nullable,
+            )
+        })
+        .collect();
+    assert_eq!(
+        columns[2..],
+        [
+            ("mood", "app.mood", Some("'happy'::app.mood"), true),
+            ("name", "text", None, false),
+            ("age", "smallint", None, false),
+        ]
+    );
+    let orders = table(&dump, "orders");
+    assert_eq!(orders.columns.len(), 2);
+    assert_eq!(orders.owner, "admin");
+    assert_eq!(
+        orders.storage_parameters,
+        Some(vec!["fillfactor=70".to_string()])
+    );
+    assert!(orders.is_unlogged);
+    assert_eq!(orders.replica_identity.as_deref(), Some("f"));
+    let sequence = dump
+        .sequences
+        .iter()
+        .find(|s| s.name == "orders_id_seq")
+        .unwrap();
+    assert_eq!(sequence.owner, "admin");
+}
@@ -0,0 +1,116 @@
+//! `CREATE TRIGGER` and `CREATE CONSTRAINT TRIGGER`.
+
+use super::Importer;
+use super::statements::{closing_paren, quote_ident, render};
+use crate::comparer::scanner::{Statement, TokenKind};
+use crate::dump::table_trigger::TableTrigger;
+
+impl Importer {
+    pub(super) fn create_trigger(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        constraint: bool,
+    ) -> Result<(), String> {
+        let name = st.relation(&[i]).ok_or("missing trigger name")?.key;
+        let on = st
+            .find_word(i + 1, "on")
+            .ok_or("CREATE TRIGGER without ON")?;
+        let (relation, _) = st.name_at(on + 1).ok_or("missing table name")?;
+        let Some(table) = self.find_table(&relation) else {
+            if self.find_view(&relation).is_some() {
+                return Err("triggers on views are not supported".to_string());
+            }
+            return Err(format!("table {} not found", relation.display));
+        };
+        let definition = format!(
+            "CREATE {}TRIGGER {} {}",
+            if constraint { "CONSTRAINT " } else { "" },
+            quote_ident(&name),
+            trigger_clauses(st, i + 1)
+        );
+        let name = quote_ident(&name);
+        let oid = self.oid();
+        let t = &mut self.dump.tables[table];
+        t.triggers.retain(|trigger| trigger.name != name);
+        t.triggers.push(TableTrigger {
+            oid,
+            name,
+            definition,
+            enabled: "O".to_string(),
+            comment: None,
+        });
+        t.hash();
+        Ok(())
+    }
+}
+
+/// Words of CREATE TRIGGER that `pg_get_triggerdef` prints in upper case.
+const TRIGGER_KEYWORDS: &[&str] = &[
+    "after",
+    "as",
+    "before",
+    "deferrable",
+    "deferred",
+    "delete",
+    "each",
+    "execute",
+    "for",
+    "from",
+    "function",
+    "immediate",
+    "initially",
+    "insert",
+    "instead",
+    "new",
+    "not",
+    "of",
+    "old",
+    "on",
+    "or",
+    "referencing",
+    "row",
+    "statement",
+    "table",
+    "truncate",
+    "update",
+    "when",
+];
+
+/// The clauses of CREATE TRIGGER from `from` on, as `pg_get_triggerdef`
+/// prints them: keywords in upper case, `EXECUTE FUNCTION` for the older
+/// `EXECUTE PROCEDURE`, and the WHEN condition and arguments as written.
+fn trigger_clauses(st: &Statement, from: usize) -> String {
+    let end = st.tokens.len() - usize::from(st.is_punct(st.tokens.len() - 1, ";"));
+    let mut out = String::new();
+    let mut k = from;
+    while k < end {
+        let next = if st.is_punct(k, "(") {
+            closing_paren(st, k) + 1
+        } else {
+            st.name_parts(k).last().map_or(k, |&last| last) + 1
+        };
+        let attached = st.is_punct(k, ",")
+            || st.is_punct(k, "(") && st.tokens[k - 1].end == st.tokens[k].start;
+        if k > from && !attached {
+            out.push(' ');
+        }
+        let lower = st.text(k).to_lowercase();
+        if st.is_word(k, "procedure") && st.is_word(k - 1, "execute") {
+            out.push_str("FUNCTION");
+        } else if next == k + 1
+            && st.tokens[k].kind == TokenKind::Word
+            && TRIGGER_KEYWORDS.contains(&lower.as_str())
+        {
+            out.push_str(&lower.to_uppercase());
+        } else {
+            out.push_str(&render(st, k, next, false));
+        }
+        k = next;
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "triggers_tests.rs"]
+mod tests;
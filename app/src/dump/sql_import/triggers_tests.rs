@@ -0,0 +1,34 @@
+use crate::dump::sql_import::tests::{SCHEMA, import, table};
+
+#[test]
+fn imports_triggers_and_their_state() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE TRIGGER audit AFTER INSERT OR UPDATE OF email ON app.users
+    FOR EACH ROW EXECUTE PROCEDURE public.audit('users');
+CREATE CONSTRAINT TRIGGER \"Check\" AFTER DELETE ON app.users
+    DEFERRABLE INITIALLY DEFERRED FOR EACH ROW EXECUTE FUNCTION app.check();
+ALTER TABLE app.users DISABLE TRIGGER audit;
+COMMENT ON TRIGGER audit ON app.users IS 'Audit';"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let users = table(&dump, "users");
+    assert!(users.has_triggers);
+    let audit = &users.triggers[0];
+    assert_eq!(audit.name, "audit");
+    assert_eq!(
+        audit.definition,
+        "CREATE TRIGGER audit AFTER INSERT OR UPDATE OF email ON app.users \
+         FOR EACH ROW EXECUTE FUNCTION audit('users')"
+    );
+    assert_eq!(audit.enabled, "D");
+    assert_eq!(audit.comment.as_deref(), Some("Audit"));
+    let check = &users.triggers[1];
+    assert_eq!(check.name, "\"Check\"");
+    assert_eq!(
+        check.definition,
+        "CREATE CONSTRAINT TRIGGER \"Check\" AFTER DELETE ON app.users \
+         DEFERRABLE INITIALLY DEFERRED FOR EACH ROW EXECUTE FUNCTION app.check()"
+    );
+    assert_eq!(check.enabled, "O");
+}
@@ -0,0 +1,847 @@
+//! `CREATE TYPE`, `CREATE DOMAIN` and their `ALTER`s, and the types
+//! columns, arguments and attributes are declared with.
+
+use super::Importer;
+use super::builtins::builtin;
+use super::statements::{
+    closing_paren, expression_end, list_items, literal_value, quote_ident, render,
+    skip_if_not_exists, statement_kind, unquote, visible_name, wrap,
+};
+use crate::comparer::scanner::{Relation, Statement, TokenKind};
+use crate::dump::pg_enum::PgEnum;
+use crate::dump::pg_type::{CompositeAttribute, DomainConstraint, PgType};
+use sqlx::postgres::types::Oid;
+
+/// `character_octet_length` of unconstrained character types.
+const MAX_TEXT_OCTETS: i32 = 1073741824;
+
+/// Bytes per character assumed for `character_octet_length` (UTF8).
+const MAX_CHAR_OCTETS: i32 = 4;
+
+impl Importer {
+    /// Parses the type name at `i`: built-in spellings (`double precision`,
+    /// `timestamp(3) with time zone`, `serial`, ...), declared types and
+    /// array suffixes.
+    pub(super) fn parse_type(&self, st: &Statement, i: usize) -> Result<(TypeRef, usize), String> {
+        if !st.is_name_part(i) {
+            return Err(format!("expected a type at {}", st.text(i)));
+        }
+        let word = st.text(i).to_lowercase();
+        let quoted = st.tokens[i].kind == TokenKind::Quoted;
+        let mut j = i + 1;
+        let mut serial = false;
+        let mut fields = None;
+        let typname = match word.as_str() {
+            _ if quoted || st.is_punct(i + 1, ".") => None,
+            "double" if st.is_word(j, "precision") => {
+                j += 1;
+                Some("float8")
+            }
+            "character" | "char" if st.is_word(j, "varying") => {
+                j += 1;
+                Some("varchar")
+            }
+            "bit" if st.is_word(j, "varying") => {
+                j += 1;
+                Some("varbit")
+            }
+            "int" | "integer" | "int4" => Some("int4"),
+            "smallint" | "int2" => Some("int2"),
+            "bigint" | "int8" => Some("int8"),
+            "serial" | "serial4" | "bigserial" | "serial8" | "smallserial" | "serial2" => {
+                serial = true;
+                Some(match word.as_str() {
+                    "bigserial" | "serial8" => "int8",
+                    "smallserial" | "serial2" => "int2",
+                    _ => "int4",
+                })
+            }
+            "real" | "float4" => Some("float4"),
+            "float" | "float8" => Some("float8"),
+            "decimal" | "dec" | "numeric" => Some("numeric"),
+            "boolean" | "bool" => Some("bool"),
+            "character" | "char" => Some("bpchar"),
+            "interval" => {
+                let mut words = Vec::new();
+                while ["year", "month", "day", "hour", "minute", "second", "to"]
+                    .iter()
+                    .any(|w| st.is_word(j, w))
+                {
+                    words.push(st.text(j).to_lowercase());
+                    j += 1;
+                }
+                fields = (!words.is_empty()).then(|| words.join(" "));
+                Some("interval")
+            }
+            _ => builtin(&word).map(|b| b.0),
+        };
+        let mut modifiers = Vec::new();
+        if st.is_punct(j, "(") {
+            let (items, close) = list_items(st, j);
+            modifiers = items
+                .iter()
+                .map(|&(from, to)| render(st, from, to, false))
+                .collect();
+            j = close + 1;
+        }
+        let mut ty = match typname {
+            Some(mut typname) => {
+                if matches!(word.as_str(), "timestamp" | "time") {
+                    if st.skip(j, &["with", "time", "zone"]) != j {
+                        typname = if word == "time" {
+                            "timetz"
+                        } else {
+                            "timestamptz"
+                        };
+                    }
+                    j = st.skip(
+                        st.skip(j, &["with", "time", "zone"]),
+                        &["without", "time", "zone"],
+                    );
+                }
+                if typname == "float8" && word == "float" {
+                    if let Some(bits) = modifiers.first().and_then(|m| m.parse::<i32>().ok())
+                        && bits <= 24
+                    {
+                        typname = "float4";
+                    }
+                    modifiers.clear();
+                }
+                if matches!(typname, "bpchar" | "bit") && modifiers.is_empty() {
+                    modifiers.push("1".to_string());
+                }
+                TypeRef::builtin(typname)
+            }
+            None => {
+                let parts = st.name_parts(i);
+                let relation = st.relation(&parts).ok_or("missing type name")?;
+                j = j.max(parts.last().map_or(j, |p| p + 1));
+                if st.is_punct(j, "(") {
+                    let (items, close) = list_items(st, j);
+                    modifiers = items
+                        .iter()
+                        .map(|&(from, to)| render(st, from, to, false))
+                        .collect();
+                    j = close + 1;
+                }
+                self.resolve_type(&relation)
+            }
+        };
+        ty.modifiers = modifiers;
+        ty.fields = fields;
+        ty.serial = serial;
+        loop {
+            if st.is_punct(j, "[") {
+                j += 1;
+                while j < st.tokens.len() && !st.is_punct(j, "]") {
+                    j += 1;
+                }
+                j += 1;
+                ty.is_array = true;
+            } else if st.is_word(j, "array") {
+                j += 1;
+                ty.is_array = true;
+            } else {
+                break;
+            }
+        }
+        Ok((ty, j))
+    }
+
+    /// The type `relation` names: a built-in, a type declared in the files,
+    /// a table's row type, or one declared elsewhere (by an extension).
+    fn resolve_type(&self, relation: &Relation) -> TypeRef {
+        let qualified = relation.parts.len() >= 2;
+        let (schema, name) = self.target(relation);
+        if (!qualified || schema == "pg_catalog")
+            && let Some(b) = builtin(&name)
+        {
+            return TypeRef::builtin(b.0);
+        }
+        for key in self.candidates(relation) {
+            if let Some(ty) = self.type_refs.get(&key) {
+                return ty.clone();
+            }
+        }
+        if let Some(index) = self.find_table(relation) {
+            let table = &self.dump.tables[index];
+            return TypeRef::declared(&table.raw_schema, &table.raw_name, TypeKind::Composite);
+        }
+        TypeRef::declared(&schema, &name, TypeKind::Unknown)
+    }
+
+    pub(super) fn create_type(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (relation, next) = st.name_at(i).ok_or("missing type name")?;
+        if !st.is_word(next, "as") {
+            return Err("base and shell types are not supported".to_string());
+        }
+        let (schema, name) = self.target(&relation);
+        if self.type_refs.contains_key(&(schema.clone(), name.clone())) {
+            return Err(format!("type {} already exists", relation.display));
+        }
+        if st.is_word(next + 1, "enum") && st.is_punct(next + 2, "(") {
+            let (items, _) = list_items(st, next + 2);
+            let labels: Vec<String> = items
+                .iter()
+                .filter_map(|&(from, _)| literal_value(st.text(from)))
+                .collect();
+            let mut pg_type = self.new_type(&schema, &name, b'e', ENUM_SHAPE);
+            for (position, label) in labels.iter().enumerate() {
+                let oid = self.oid();
+                self.dump.enums.push(PgEnum {
+                    oid,
+                    enumtypid: pg_type.oid,
+                    enumsortorder: (position + 1) as f32,
+                    enumlabel: label.clone(),
+                });
+            }
+            pg_type.enum_labels = labels;
+            self.add_type(pg_type, TypeRef::declared(&schema, &name, TypeKind::Enum));
+            Ok(())
+        } else if st.is_punct(next + 1, "(") {
+            let (items, _) = list_items(st, next + 1);
+            let mut attributes = Vec::new();
+            for (from, to) in items {
+                let attribute = st.relation(&[from]).ok_or("missing attribute name")?.key;
+                let (ty, end) = self.parse_type(st, from + 1)?;
+                if end < to {
+                    self.notes.push(format!(
+                        "attribute {attribute}: {} is not supported",
+                        st.text(end)
+                    ));
+                }
+                attributes.push(CompositeAttribute {
+                    name: quote_ident(&attribute),
+                    data_type: ty.format(true),
+                });
+            }
+            let mut pg_type = self.new_type(&schema, &name, b'c', COMPOSITE_SHAPE);
+            pg_type.typrelid = Some(self.oid());
+            pg_type.composite_attributes = attributes;
+            self.add_type(
+                pg_type,
+                TypeRef::declared(&schema, &name, TypeKind::Composite),
+            );
+            Ok(())
+        } else {
+            Err(format!(
+                "CREATE TYPE AS {} is not supported",
+                st.text(next + 1).to_uppercase()
+            ))
+        }
+    }
+
+    pub(super) fn create_domain(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (relation, next) = st.name_at(i).ok_or("missing domain name")?;
+        let (schema, name) = self.target(&relation);
+        if self.type_refs.contains_key(&(schema.clone(), name.clone())) {
+            return Err(format!("type {} already exists", relation.display));
+        }
+        let (base, mut j) = self.parse_type(st, st.skip(next, &["as"]))?;
+        let mut pg_type = self.new_type(&schema, &name, b'd', base.shape());
+        pg_type.typinput = "domain_in".to_string();
+        pg_type.typreceive = Some("domain_recv".to_string());
+        pg_type.typbasetype = Some(Oid(0));
+        pg_type.typtypmod = Some(base.typmod());
+        pg_type.typndims = i32::from(base.is_array);
+        pg_type.formatted_basetype = Some(base.format(true));
+        pg_type.domain_collation_name = base.default_collation().map(str::to_string);
+        let mut constraint_name = None;
+        while j < st.tokens.len() && !st.is_punct(j, ";") {
+            if st.is_word(j, "constraint") {
+                constraint_name = st.relation(&[j + 1]).map(|r| r.key);
+                j += 2;
+            } else if st.is_word(j, "not") && st.is_word(j + 1, "null") {
+                pg_type.typnotnull = true;
+                j += 2;
+            } else if st.is_word(j, "null") {
+                j += 1;
+            } else if st.is_word(j, "default") {
+                let end = expression_end(
+                    st,
+                    j + 1,
+                    st.tokens.len(),
+                    &["constraint", "not", "null", "check", "collate"],
+                );
+                pg_type.typdefault = Some(render(st, j + 1, end, true));
+                j = end;
+            } else if st.is_word(j, "collate") {
+                let parts = st.name_parts(j + 1);
+                let collation = st.relation(&parts).ok_or("missing collation name")?;
+                let name = collation.parts.last().cloned().unwrap_or_default();
+                let schema = match collation.parts.len() {
+                    1 => "pg_catalog".to_string(),
+                    _ => collation.parts[0].clone(),
+                };
+                pg_type.domain_collation_name =
+                    Some(format!("{}.{}", quote_ident(&schema), quote_ident(&name)));
+                j = parts.last().map_or(j + 2, |p| p + 1);
+            } else if st.is_word(j, "check") && st.is_punct(j + 1, "(") {
+                let close = closing_paren(st, j + 1);
+                let expression = render(st, j + 2, close, true);
+                let mut definition = format!("CHECK ({})", wrap(&expression));
+                j = close + 1;
+                if st.is_word(j, "not") && st.is_word(j + 1, "valid") {
+                    definition.push_str(" NOT VALID");
+                    j += 2;
+                }
+                let constraint = match constraint_name.take() {
+                    Some(name) => name,
+                    None => self.choose_name(&schema, &name, "", "check"),
+                };
+                pg_type.domain_constraints.push(DomainConstraint {
+                    name: quote_ident(&constraint),
+                    definition,
+                });
+            } else {
+                self.notes
+                    .push(format!("domain option {} is not supported", st.text(j)));
+                break;
+            }
+        }
+        self.add_type(
+            pg_type,
+            TypeRef::declared(&schema, &name, TypeKind::Domain(Box::new(base))),
+        );
+        Ok(())
+    }
+
+    /// A type row as the catalog shows a freshly created enum, composite or
+    /// domain; callers fill in the kind-specific parts.
+    fn new_type(&mut self, schema: &str, name: &str, typtype: u8, shape: TypeShape) -> PgType {
+        let (input, receive) = match typtype {
+            b'e' => ("enum_in", "enum_recv"),
+            _ => ("record_in", "record_recv"),
+        };
+        let oid = self.oid();
+        let array = self.oid();
+        PgType::new(
+            oid,
+            quote_ident(schema),
+            quote_ident(name),
+            Oid(0),
+            Oid(10),
+            quote_ident(&self.owner),
+            shape.typlen,
+            shape.byval,
+            typtype as i8,
+            shape.category as i8,
+            false,
+            true,
+            b',' as i8,
+            Some(Oid(0)),
+            Some("-".to_string()),
+            Some(Oid(0)),
+            Some(array),
+            input.to_string(),
+            shape.output.to_string(),
+            Some(receive.to_string()),
+            Some(shape.send.to_string()),
+            Some("-".to_string()),
+            Some("-".to_string()),
+            Some("-".to_string()),
+            shape.align as i8,
+            shape.storage as i8,
+            false,
+            Some(Oid(0)),
+            Some(-1),
+            0,
+            Some(Oid(0)),
+            None,
+            Some("-".to_string()),
+            Vec::new(),
+            Vec::new(),
+            None,
+        )
+    }
+
+    fn add_type(&mut self, pg_type: PgType, ty: TypeRef) {
+        self.type_refs
+            .insert((ty.schema.clone(), ty.typname.clone()), ty);
+        self.dump.types.push(pg_type);
+    }
+
+    pub(super) fn domain_not_null(&self, ty: &TypeRef) -> bool {
+        ty.domain().is_some_and(|(schema, name)| {
+            let (schema, name) = (quote_ident(schema), quote_ident(name));
+            self.dump
+                .types
+                .iter()
+                .any(|t| t.schema == schema && t.typname == name && t.typnotnull)
+        })
+    }
+
+    pub(super) fn alter_type(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let (relation, next) = st.name_at(i).ok_or("missing type name")?;
+        let index = self
+            .find_type(&relation)
+            .ok_or_else(|| format!("type {} not found", relation.display))?;
+        if st.skip(next, &["owner", "to"]) != next {
+            let role = quote_ident(&self.role_at(st, next + 2).ok_or("missing role name")?);
+            let pg_type = &mut self.dump.types[index];
+            Self::reassign_acl(&mut pg_type.acl, &pg_type.owner, &role);
+            pg_type.owner = role;
+        } else if st.skip(next, &["add", "value"]) != next {
+            let (k, if_not_exists) = skip_if_not_exists(st, next + 2);
+            let label = literal_value(st.text(k)).ok_or("missing enum label")?;
+            let pg_type = &self.dump.types[index];
+            if pg_type.enum_labels.contains(&label) {
+                return if if_not_exists {
+                    Ok(())
+                } else {
+                    Err(format!("enum label {label} already exists"))
+                };
+            }
+            let typid = pg_type.oid;
+            let neighbour = literal_value(st.text(k + 2));
+            let position = match neighbour {
+                Some(ref n) if st.is_word(k + 1, "before") || st.is_word(k + 1, "after") => {
+                    let at = pg_type
+                        .enum_labels
+                        .iter()
+                        .position(|l| l == n)
+                        .ok_or_else(|| format!("enum label {n} not found"))?;
+                    at + usize::from(st.is_word(k + 1, "after"))
+                }
+                _ => pg_type.enum_labels.len(),
+            };
+            self.dump.types[index]
+                .enum_labels
+                .insert(position, label.clone());
+            let oid = self.oid();
+            let orders: Vec<f32> = self
+                .dump
+                .enums
+                .iter()
+                .filter(|e| e.enumtypid == typid)
+                .map(|e| e.enumsortorder)
+                .collect();
+            let mut sorted = orders.clone();
+            sorted.sort_by(f32::total_cmp);
+            let before = position.checked_sub(1).and_then(|p| sorted.get(p)).copied();
+            let after = sorted.get(position).copied();
+            let enumsortorder = match (before, after) {
+                (Some(b), Some(a)) => (a + b) / 2.0,
+                (None, Some(a)) => a - 1.0,
+                (Some(b), None) => b + 1.0,
+                (None, None) => 1.0,
+            };
+            self.dump.enums.push(PgEnum {
+                oid,
+                enumtypid: typid,
+                enumsortorder,
+                enumlabel: label,
+            });
+        } else if self.dump.types[index].typtype as u8 == b'd' {
+            self.alter_domain(st, index, next)?;
+        } else {
+            return Err(format!("{} is not supported", statement_kind(st, 0)));
+        }
+        self.dump.types[index].hash();
+        Ok(())
+    }
+
+    fn alter_domain(&mut self, st: &Statement, index: usize, k: usize) -> Result<(), String> {
+        let end = st.tokens.len() - usize::from(st.is_punct(st.tokens.len() - 1, ";"));
+        if st.is_word(k, "add") {
+            let mut j = k + 1;
+            let mut name = None;
+            if st.is_word(j, "constraint") {
+                name = st.relation(&[j + 1]).map(|r| r.key);
+                j += 2;
+            }
+            if !st.is_word(j, "check") || !st.is_punct(j + 1, "(") {
+                return Err("only CHECK constraints can be added to a domain".to_string());
+            }
+            let close = closing_paren(st, j + 1);
+            let expression = render(st, j + 2, close, true);
+            let mut definition = format!("CHECK ({})", wrap(&expression));
+            if st.skip(close + 1, &["not", "valid"]) != close + 1 {
+                definition.push_str(" NOT VALID");
+            }
+            let (schema, typname) = {
+                let t = &self.dump.types[index];
+                (unquote(&t.schema), t.typname.clone())
+            };
+            let name = match name {
+                Some(name) => name,
+                None => self.choose_name(&schema, &typname, "", "check"),
+            };
+            let name = quote_ident(&name);
+            let pg_type = &mut self.dump.types[index];
+            if pg_type.domain_constraints.iter().any(|c| c.name == name) {
+                return Err(format!("constraint {name} already exists"));
+            }
+            pg_type
+                .domain_constraints
+                .push(DomainConstraint { name, definition });
+        } else if st.skip(k, &["drop", "constraint"]) != k {
+            let j = st.skip(k + 2, &["if", "exists"]);
+            let name = quote_ident(&st.relation(&[j]).ok_or("missing constraint name")?.key);
+            let pg_type = &mut self.dump.types[index];
+            let before = pg_type.domain_constraints.len();
+            pg_type.domain_constraints.retain(|c| c.name != name);
+            if pg_type.domain_constraints.len() == before && j == k + 2 {
+                return Err(format!("constraint {name} not found"));
+            }
+        } else if st.skip(k, &["set", "not", "null"]) != k
+            || st.skip(k, &["drop", "not", "null"]) != k
+        {
+            self.dump.types[index].typnotnull = st.is_word(k, "set");
+        } else if st.skip(k, &["set", "default"]) != k {
+            self.dump.types[index].typdefault = Some(render(st, k + 2, end, true));
+        } else if st.skip(k, &["drop", "default"]) != k {
+            self.dump.types[index].typdefault = None;
+        } else {
+            return Err(format!(
+                "ALTER DOMAIN {} is not supported",
+                st.text(k).to_uppercase()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A type as a statement names it, resolved far enough to fill in the
+/// catalog columns that depend on it.
+#[derive(Debug, Clone)]
+pub(super) struct TypeRef {
+    pub(super) schema: String,
+    pub(super) typname: String,
+    /// Rendered type modifiers, e.g. `["10", "2"]` for `numeric(10,2)`.
+    pub(super) modifiers: Vec<String>,
+    /// Interval fields, e.g. `day to second`.
+    pub(super) fields: Option<String>,
+    pub(super) is_array: bool,
+    pub(super) kind: TypeKind,
+    /// Declared as `serial`, `bigserial` or `smallserial`.
+    pub(super) serial: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum TypeKind {
+    Builtin,
+    Enum,
+    Composite,
+    /// A domain over the given base type.
+    Domain(Box<TypeRef>),
+    /// A type the files use but do not declare.
+    Unknown,
+}
+
+/// Catalog columns derived from a column's type.
+#[derive(Debug, Default)]
+pub(super) struct TypeInfo {
+    pub(super) character_maximum_length: Option<i32>,
+    pub(super) character_octet_length: Option<i32>,
+    pub(super) numeric_precision: Option<i32>,
+    pub(super) numeric_precision_radix: Option<i32>,
+    pub(super) numeric_scale: Option<i32>,
+    pub(super) datetime_precision: Option<i32>,
+    pub(super) interval_type: Option<String>,
+}
+
+/// Storage properties of a type: `typlen`, `typbyval`, `typcategory`,
+/// `typalign`, `typstorage`, `typoutput` and `typsend`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TypeShape {
+    pub(super) typlen: i16,
+    pub(super) byval: bool,
+    pub(super) category: u8,
+    pub(super) align: u8,
+    pub(super) storage: u8,
+    pub(super) output: &'static str,
+    pub(super) send: &'static str,
+}
+
+const ENUM_SHAPE: TypeShape = TypeShape {
+    typlen: 4,
+    byval: true,
+    category: b'E',
+    align: b'i',
+    storage: b'p',
+    output: "enum_out",
+    send: "enum_send",
+};
+
+const COMPOSITE_SHAPE: TypeShape = TypeShape {
+    typlen: -1,
+    byval: false,
+    category: b'C',
+    align: b'd',
+    storage: b'x',
+    output: "record_out",
+    send: "record_send",
+};
+
+const UNKNOWN_SHAPE: TypeShape = TypeShape {
+    typlen: -1,
+    byval: false,
+    category: b'U',
+    align: b'i',
+    storage: b'x',
+    output: "-",
+    send: "-",
+};
+
+impl TypeRef {
+    fn builtin(typname: &str) -> Self {
+        Self::declared("pg_catalog", typname, TypeKind::Builtin)
+    }
+
+    fn declared(schema: &str, name: &str, kind: TypeKind) -> Self {
+        Self {
+            schema: schema.to_string(),
+            typname: name.to_string(),
+            modifiers: Vec::new(),
+            fields: None,
+            is_array: false,
+            kind,
+            serial: false,
+        }
+    }
+
+    /// The type as `format_type` prints it, with or without modifiers.
+    pub(super) fn format(&self, typmod: bool) -> String {
+        let modifiers = self.modifiers.join(",");
+        let mut name = match (&self.kind, builtin(&self.typname)) {
+            (TypeKind::Builtin, Some(b)) => {
+                let base = b.1;
+                match self.typname.as_str() {
+                    "timestamp" | "timestamptz" | "time" | "timetz"
+                        if typmod && !modifiers.is_empty() =>
+                    {
+                        let (head, zone) = base.split_once(' ').unwrap_or((base, ""));
+                        format!("{head}({modifiers}) {zone}")
+                    }
+                    "interval" if typmod => {
+                        let mut name = base.to_string();
+                        if let Some(fields) = &self.fields {
+                            name.push(' ');
+                            name.push_str(fields);
+                        }
+                        if !modifiers.is_empty() {
+                            name.push_str(&format!("({modifiers})"));
+                        }
+                        name
+                    }
+                    _ if typmod && !modifiers.is_empty() => format!("{base}({modifiers})"),
+                    _ => base.to_string(),
+                }
+            }
+            _ if typmod && !modifiers.is_empty() => {
+                format!("{}({modifiers})", visible_name(&self.schema, &self.typname))
+            }
+            _ => visible_name(&self.schema, &self.typname),
+        };
+        if self.is_array {
+            name.push_str("[]");
+        }
+        name
+    }
+
+    /// `data_type` of a column of this type: built-in scalars print without
+    /// their modifiers, everything else with them.
+    pub(super) fn column_data_type(&self) -> String {
+        let plain = matches!(self.kind, TypeKind::Builtin) && !self.is_array;
+        self.format(!plain)
+    }
+
+    /// `udt_schema` and `udt_name`: a domain's base type, or the type itself.
+    pub(super) fn udt(&self) -> (String, String) {
+        if self.is_array {
+            return (self.schema.clone(), format!("_{}", self.typname));
+        }
+        match &self.kind {
+            TypeKind::Domain(base) => base.udt(),
+            _ => (self.schema.clone(), self.typname.clone()),
+        }
+    }
+
+    pub(super) fn domain(&self) -> Option<(&str, &str)> {
+        match self.kind {
+            TypeKind::Domain(_) if !self.is_array => Some((&self.schema, &self.typname)),
+            _ => None,
+        }
+    }
+
+    pub(super) fn info(&self) -> TypeInfo {
+        if self.is_array {
+            return TypeInfo::default();
+        }
+        match &self.kind {
+            TypeKind::Domain(base) => return base.info(),
+            TypeKind::Builtin => {}
+            _ => return TypeInfo::default(),
+        }
+        let typmod = self.typmod();
+        let known = typmod != -1;
+        let mut info = TypeInfo::default();
+        match self.typname.as_str() {
+            "bpchar" | "varchar" | "text" => {
+                if self.typname != "text" && known {
+                    info.character_maximum_length = Some(typmod - 4);
+                    info.character_octet_length = Some((typmod - 4) * MAX_CHAR_OCTETS);
+                } else {
+                    info.character_octet_length = Some(MAX_TEXT_OCTETS);
+                }
+            }
+            "bit" | "varbit" => info.character_maximum_length = known.then_some(typmod),
+            "int2" | "int4" | "int8" | "float4" | "float8" => {
+                info.numeric_precision = Some(match self.typname.as_str() {
+                    "int2" => 16,
+                    "int4" => 32,
+                    "int8" => 64,
+                    "float4" => 24,
+                    _ => 53,
+                });
+                info.numeric_precision_radix = Some(2);
+                if self.typname.starts_with("int") {
+                    info.numeric_scale = Some(0);
+                }
+            }
+            "numeric" => {
+                info.numeric_precision_radix = Some(10);
+                if known {
+                    info.numeric_precision = Some(((typmod - 4) >> 16) & 0xffff);
+                    info.numeric_scale = Some((typmod - 4) & 0xffff);
+                }
+            }
+            "date" => info.datetime_precision = Some(0),
+            "time" | "timetz" | "timestamp" | "timestamptz" => {
+                info.datetime_precision = Some(if typmod < 0 { 6 } else { typmod });
+            }
+            "interval" => {
+                let precision = typmod & 0xffff;
+                info.datetime_precision = Some(if typmod < 0 || precision == 0xffff {
+                    6
+                } else {
+                    precision
+                });
+                info.interval_type = self.fields.as_ref().map(|f| f.to_uppercase());
+            }
+            _ => {}
+        }
+        info
+    }
+
+    pub(super) fn shape(&self) -> TypeShape {
+        if self.is_array {
+            let element = Self {
+                is_array: false,
+                ..self.clone()
+            };
+            return TypeShape {
+                typlen: -1,
+                byval: false,
+                category: b'A',
+                align: if element.shape().align == b'd' {
+                    b'd'
+                } else {
+                    b'i'
+                },
+                storage: b'x',
+                output: "array_out",
+                send: "array_send",
+            };
+        }
+        match &self.kind {
+            TypeKind::Builtin => builtin(&self.typname).map_or(UNKNOWN_SHAPE, |b| TypeShape {
+                typlen: b.2,
+                byval: b.3,
+                category: b.4,
+                align: b.5,
+                storage: b.6,
+                output: b.7,
+                send: b.8,
+            }),
+            TypeKind::Enum => ENUM_SHAPE,
+            TypeKind::Composite => COMPOSITE_SHAPE,
+            TypeKind::Domain(base) => base.shape(),
+            TypeKind::Unknown => UNKNOWN_SHAPE,
+        }
+    }
+
+    /// The collation a column or domain of this type gets by default.
+    fn default_collation(&self) -> Option<&'static str> {
+        match &self.kind {
+            TypeKind::Builtin => match self.typname.as_str() {
+                "text" | "varchar" | "bpchar" => Some("pg_catalog.\"default\""),
+                "name" => Some("pg_catalog.\"C\""),
+                _ => None,
+            },
+            TypeKind::Domain(base) => base.default_collation(),
+            _ => None,
+        }
+    }
+
+    /// The `atttypmod` the modifiers encode, or -1.
+    fn typmod(&self) -> i32 {
+        let numbers: Vec<i32> = self
+            .modifiers
+            .iter()
+            .filter_map(|m| m.trim().parse().ok())
+            .collect();
+        if !matches!(self.kind, TypeKind::Builtin) {
+            return -1;
+        }
+        match (self.typname.as_str(), numbers.as_slice()) {
+            ("bpchar" | "varchar", [n]) => n + 4,
+            ("numeric", [p]) => (p << 16) + 4,
+            ("numeric", [p, s]) => ((p << 16) | (s & 0xffff)) + 4,
+            ("bit" | "varbit" | "time" | "timetz" | "timestamp" | "timestamptz", [n]) => *n,
+            ("interval", _) if self.fields.is_some() || !numbers.is_empty() => {
+                let range = self.fields.as_deref().map_or(0x7fff, interval_range);
+                (range << 16) | numbers.first().copied().unwrap_or(0xffff)
+            }
+            _ => -1,
+        }
+    }
+}
+
+/// The `INTERVAL_MASK` bits of an interval field list.
+fn interval_range(fields: &str) -> i32 {
+    const MONTH: i32 = 1 << 1;
+    const YEAR: i32 = 1 << 2;
+    const DAY: i32 = 1 << 3;
+    const HOUR: i32 = 1 << 10;
+    const MINUTE: i32 = 1 << 11;
+    const SECOND: i32 = 1 << 12;
+    match fields {
+        "year" => YEAR,
+        "month" => MONTH,
+        "day" => DAY,
+        "hour" => HOUR,
+        "minute" => MINUTE,
+        "second" => SECOND,
+        "year to month" => YEAR | MONTH,
+        "day to hour" => DAY | HOUR,
+        "day to minute" => DAY | HOUR | MINUTE,
+        "day to second" => DAY | HOUR | MINUTE | SECOND,
+        "hour to minute" => HOUR | MINUTE,
+        "hour to second" => HOUR | MINUTE | SECOND,
+        "minute to second" => MINUTE | SECOND,
+        _ => 0x7fff,
+    }
+}
+
+pub(super) fn integer_bounds(typname: &str) -> (i64, i64) {
+    match typname {
+        "int2" => (i16::MIN.into(), i16::MAX.into()),
+        "int4" => (i32::MIN.into(), i32::MAX.into()),
+        _ => (i64::MIN, i64::MAX),
+    }
+}
+
+/// `typname` of an integer type from the name `format_type` prints.
+pub(super) fn integer_typname(data_type: &str) -> &'static str {
+    match data_type {
+        "smallint" => "int2",
+        "integer" => "int4",
+        _ => "int8",
+    }
+}
+
+#[cfg(test)]
+#[path = "types_tests.rs"]
+mod tests;
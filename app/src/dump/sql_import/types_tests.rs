@@ -0,0 +1,73 @@
+use crate::dump::sql_import::tests::{SCHEMA, import};
+
+#[test]
+fn imports_enum_composite_and_domain_types() {
+    let (dump, _) = import(SCHEMA);
+    let kinds: Vec<(&str, u8)> = dump
+        .types
+        .iter()
+        .map(|t| (t.typname.as_str(), t.typtype as u8))
+        .collect();
+    assert_eq!(kinds, vec![("mood", b'e'), ("pair", b'c'), ("email", b'd')]);
+    let labels: Vec<&str> = dump.enums.iter().map(|e| e.enumlabel.as_str()).collect();
+    assert_eq!(labels, vec!["sad", "ok", "happy"]);
+}
+
+#[test]
+fn alters_domains() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER DOMAIN app.email ADD CONSTRAINT email_length CHECK (length(VALUE) < 200);
+ALTER DOMAIN app.email DROP CONSTRAINT email_check;
+ALTER DOMAIN app.email ADD CHECK (VALUE <> '');
+ALTER DOMAIN app.email SET NOT NULL;
+ALTER DOMAIN app.email SET DEFAULT 'a@b';"
+    ));
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let email = dump.types.iter().find(|t| t.typname == "email").unwrap();
+    let constraints: Vec<(&str, &str)> = email
+        .domain_constraints
+        .iter()
+        .map(|c| (c.name.as_str(), c.definition.as_str()))
+        .collect();
+    assert_eq!(
+        constraints,
+        vec![
+            ("email_length", "CHECK ((length(VALUE) < 200))"),
+            ("email_check", "CHECK ((VALUE <> ''))"),
+        ]
+    );
+}
+
+#[test]
+fn alters_types() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+ALTER TYPE app.mood ADD VALUE 'meh' BEFORE 'ok';
+ALTER TYPE app.mood ADD VALUE IF NOT EXISTS 'sad';
+ALTER TYPE app.mood ADD VALUE 'ecstatic';
+ALTER TYPE app.mood ADD VALUE 'sad';
+ALTER TYPE app.pair OWNER TO admin;
+ALTER TYPE app.pair ADD ATTRIBUTE z integer;
+ALTER TYPE app.missing OWNER TO admin;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "enum label sad already exists",
+            "ALTER TYPE is not supported",
+            "type app.missing not found"
+        ]
+    );
+    let mut labels: Vec<(&str, f32)> = dump
+        .enums
+        .iter()
+        .map(|e| (e.enumlabel.as_str(), e.enumsortorder))
+        .collect();
+    labels.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let labels: Vec<&str> = labels.into_iter().map(|(l, _)| l).collect();
+    assert_eq!(labels, vec!["sad", "meh", "ok", "happy", "ecstatic"]);
+    let pair = dump.types.iter().find(|t| t.typname == "pair").unwrap();
+    assert_eq!(pair.owner, "admin");
+}
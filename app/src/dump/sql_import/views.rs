@@ -0,0 +1,189 @@
+//! `CREATE VIEW`, `CREATE MATERIALIZED VIEW` and `ALTER VIEW`.
+
+use super::Importer;
+use super::statements::{
+    closing_paren, list_items, quote_ident, skip_if_not_exists, statement_kind, strip_public,
+    unquote,
+};
+use super::tables::storage_parameter;
+use crate::comparer::scanner::Statement;
+use crate::dump::view::View;
+
+impl Importer {
+    pub(super) fn alter_view(&mut self, st: &Statement, i: usize) -> Result<(), String> {
+        let i = st.skip(i, &["if", "exists"]);
+        let (relation, next) = st.name_at(i).ok_or("missing view name")?;
+        let index = self
+            .find_view(&relation)
+            .ok_or_else(|| format!("view {} not found", relation.display))?;
+        if st.skip(next, &["owner", "to"]) == next {
+            return Err(format!("{} is not supported", statement_kind(st, 0)));
+        }
+        let role = self.role_at(st, next + 2).ok_or("missing role name")?;
+        let view = &mut self.dump.views[index];
+        let role = if view.is_materialized {
+            role
+        } else {
+            quote_ident(&role)
+        };
+        Self::reassign_acl(&mut view.acl, &view.owner, &role);
+        view.owner = role;
+        view.hash();
+        Ok(())
+    }
+
+    pub(super) fn create_view(
+        &mut self,
+        st: &Statement,
+        i: usize,
+        materialized: bool,
+    ) -> Result<(), String> {
+        let (i, if_not_exists) = skip_if_not_exists(st, i);
+        let (relation, mut j) = st.name_at(i).ok_or("missing view name")?;
+        let (schema, name) = self.target(&relation);
+        self.require_schema(&schema)?;
+        let replace = st.is_word(1, "or");
+        let existing = self
+            .dump
+            .views
+            .iter()
+            .position(|v| view_is(v, &schema, &name));
+        if existing.is_none() && self.name_taken(&schema, &name) || existing.is_some() && !replace {
+            if if_not_exists {
+                return Ok(());
+            }
+            return Err(format!("relation {} already exists", relation.display));
+        }
+        if st.is_punct(j, "(") {
+            j = closing_paren(st, j) + 1;
+        }
+        let mut security_invoker = false;
+        let mut check_option = None;
+        let mut storage_parameters = Vec::new();
+        let mut tablespace = None;
+        while j < st.tokens.len() && !st.is_word(j, "as") {
+            if st.is_word(j, "with") && st.is_punct(j + 1, "(") {
+                let (items, close) = list_items(st, j + 1);
+                for (from, to) in items {
+                    let parameter = storage_parameter(st, from, to);
+                    match parameter.split_once('=') {
+                        Some(("security_invoker", value)) => {
+                            security_invoker = matches!(value, "true" | "on" | "1");
+                        }
+                        Some(("check_option", value)) => check_option = Some(value.to_lowercase()),
+                        _ => storage_parameters.push(parameter),
+                    }
+                }
+                j = close + 1;
+            } else if st.is_word(j, "tablespace") {
+                tablespace = st.relation(&[j + 1]).map(|r| r.key);
+                j += 2;
+            } else if st.is_word(j, "using") {
+                j += 2;
+            } else {
+                return Err(format!(
+                    "view option {} is not supported",
+                    st.text(j).to_uppercase()
+                ));
+            }
+        }
+        if !st.is_word(j, "as") || j + 1 >= st.tokens.len() {
+            return Err("view without a query".to_string());
+        }
+        let mut end = st.tokens.len();
+        if st.is_punct(end - 1, ";") {
+            end -= 1;
+        }
+        // Trailing clauses are stored apart from the query.
+        if materialized {
+            if st.is_word(end - 1, "data") && st.is_word(end - 3, "with") {
+                end -= 3;
+            } else if st.is_word(end - 1, "data") && st.is_word(end - 2, "with") {
+                end -= 2;
+            }
+        } else if st.is_word(end - 1, "option") && st.is_word(end - 2, "check") {
+            let with = if st.is_word(end - 3, "with") {
+                end - 3
+            } else {
+                end - 4
+            };
+            check_option = Some(if with == end - 4 {
+                st.text(end - 3).to_lowercase()
+            } else {
+                "cascaded".to_string()
+            });
+            end = with;
+        }
+        let body = &st.sql[st.tokens[j].end..st.tokens[end - 1].end];
+        let mut definition = strip_public(body.trim_start_matches(['\r', '\n']));
+        definition.push(';');
+
+        let mut table_relation = Vec::new();
+        if !materialized {
+            for k in j + 1..end {
+                if (st.is_word(k, "from") || st.is_word(k, "join"))
+                    && let Some((used, _)) = st.name_at(st.skip(k + 1, &["only"]))
+                {
+                    let found = self
+                        .find_table(&used)
+                        .map(|t| {
+                            let t = &self.dump.tables[t];
+                            format!("{}.{}", t.raw_schema, t.raw_name)
+                        })
+                        .or_else(|| {
+                            self.find_view(&used).map(|v| {
+                                let v = &self.dump.views[v];
+                                format!("{}.{}", unquote(&v.schema), unquote(&v.name))
+                            })
+                        });
+                    table_relation.extend(found);
+                }
+            }
+            table_relation.sort();
+            table_relation.dedup();
+        }
+
+        let (view_schema, view_name, owner) = if materialized {
+            (schema.clone(), name.clone(), self.owner.clone())
+        } else {
+            (
+                quote_ident(&schema),
+                quote_ident(&name),
+                quote_ident(&self.owner),
+            )
+        };
+        let mut view = View::new(view_name, definition, view_schema, table_relation);
+        view.owner = owner;
+        view.is_materialized = materialized;
+        view.security_invoker = security_invoker;
+        view.check_option = check_option;
+        view.storage_parameters = (!storage_parameters.is_empty()).then_some(storage_parameters);
+        view.tablespace = tablespace;
+        if let Some(index) = existing {
+            let old = &self.dump.views[index];
+            view.owner = old.owner.clone();
+            view.acl = old.acl.clone();
+            view.comment = old.comment.clone();
+            view.column_comments = old.column_comments.clone();
+            view.hash();
+            self.dump.views[index] = view;
+        } else {
+            view.hash();
+            self.dump.views.push(view);
+        }
+        Ok(())
+    }
+}
+
+/// Materialized views keep raw names, plain views quoted ones.
+pub(super) fn view_is(view: &View, schema: &str, name: &str) -> bool {
+    if view.is_materialized {
+        view.schema == schema && view.name == name
+    } else {
+        view.schema == quote_ident(schema) && view.name == quote_ident(name)
+    }
+}
+
+#[cfg(test)]
+#[path = "views_tests.rs"]
+mod tests;
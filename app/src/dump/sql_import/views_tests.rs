@@ -0,0 +1,43 @@
+use crate::dump::sql_import::tests::{SCHEMA, import};
+
+#[test]
+fn quotes_view_names_but_not_materialized_view_names() {
+    let (dump, _) = import(&format!(
+        "{SCHEMA}
+         CREATE VIEW app.\"Active Users\" AS SELECT id, email FROM app.users;
+         CREATE MATERIALIZED VIEW app.\"Order Totals\" AS SELECT user_id FROM app.orders;"
+    ));
+    let view = &dump.views[0];
+    assert_eq!(view.name, "\"Active Users\"");
+    assert_eq!(view.definition, " SELECT id, email FROM app.users;");
+    assert_eq!(view.table_relation, vec!["app.users".to_string()]);
+    assert!(!view.is_materialized);
+
+    let matview = &dump.views[1];
+    assert_eq!(matview.name, "Order Totals");
+    assert!(matview.is_materialized);
+}
+
+#[test]
+fn alters_view_owners() {
+    let (dump, skipped) = import(&format!(
+        "{SCHEMA}
+CREATE VIEW app.emails AS SELECT email FROM app.users;
+CREATE MATERIALIZED VIEW app.totals AS SELECT sum(total) FROM app.orders;
+ALTER VIEW app.emails OWNER TO admin;
+ALTER MATERIALIZED VIEW app.totals OWNER TO reporter;
+ALTER VIEW app.emails RENAME TO addresses;
+ALTER VIEW app.missing OWNER TO admin;"
+    ));
+    let reasons: Vec<&str> = skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(
+        reasons,
+        vec!["ALTER VIEW is not supported", "view app.missing not found"]
+    );
+    let owners: Vec<(&str, &str)> = dump
+        .views
+        .iter()
+        .map(|v| (v.name.as_str(), v.owner.as_str()))
+        .collect();
+    assert_eq!(owners, vec![("emails", "admin"), ("totals", "reporter")]);
+}
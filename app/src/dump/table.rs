@@ -1,7 +1,7 @@
 use crate::{
     dump::{
-        table_column::TableColumn, table_constraint::TableConstraint, table_index::TableIndex,
-        table_policy::TablePolicy, table_trigger::TableTrigger,
+        sequence::Sequence, table_column::TableColumn, table_constraint::TableConstraint,
+        table_index::TableIndex, table_policy::TablePolicy, table_trigger::TableTrigger,
     },
     utils::string_extensions::StringExt,
 };
@@ -80,6 +80,27 @@ fn extract_partition_key_identifiers(pk: &str) -> Vec<String> {
     identifiers
 }
 
+/// True when a partition bound (`pg_get_expr(relpartbound)` output) accepts
+/// every row of its parent: `DEFAULT`, `FOR VALUES WITH (modulus 1,
+/// remainder 0)`, or a range from `MINVALUE` to `MAXVALUE` in every column.
+fn partition_bound_accepts_all_rows(bound: &str) -> bool {
+    let compact: String = bound
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if compact == "default" || compact == "forvalueswith(modulus1,remainder0)" {
+        return true;
+    }
+    if let Some(rest) = compact.strip_prefix("forvaluesfrom(")
+        && let Some((from, to)) = rest.split_once(")to(")
+    {
+        let to = to.strip_suffix(')').unwrap_or(to);
+        return from.split(',').all(|v| v == "minvalue") && to.split(',').all(|v| v == "maxvalue");
+    }
+    false
+}
+
 /// Pre-computed catalog capability flags.
 ///
 /// These booleans indicate whether certain columns / features exist in the
@@ -175,20 +196,52 @@ pub struct Table {
     pub typed_table_type: Option<String>, // OF type name for typed tables
 }
 
+/// Direction of an in-place conversion between a plain table and a
+/// declaratively partitioned parent (see [`Table::partitioning_change`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitioningChange {
+    /// A plain table becomes a partitioned parent.
+    ToPartitioned,
+    /// A partitioned parent becomes a plain table.
+    ToPlain,
+}
+
 /// Structured result of [`Table::index_alter_plan`]: which indexes to create,
 /// drop, or merely re-comment when altering a table. Borrows from both the FROM
 /// and TO tables. Consumed by the production output path.
 #[derive(Debug, Default)]
 pub struct IndexAlterPlan<'a> {
-    /// Indexes to (re)create — brand-new, or whose definition changed.
+    /// Brand-new indexes to create.
     pub create: Vec<&'a TableIndex>,
-    /// Old indexes to drop — definition changed (paired with a `create`) or
-    /// removed entirely.
+    /// Old indexes removed entirely.
     pub drop: Vec<&'a TableIndex>,
+    /// Indexes whose definition changed, as `(old, new)` pairs under the same
+    /// name.
+    pub replace: Vec<(&'a TableIndex, &'a TableIndex)>,
     /// Indexes whose comment changed but whose definition did not.
     pub comment_changes: Vec<&'a TableIndex>,
 }
 
+/// Result of [`Table::partition_membership_change`]. Parent names are in the
+/// qualified form stored in [`Table::partition_of`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionMembershipChange<'a> {
+    /// Parent the table is detached from.
+    pub detach_from: Option<&'a str>,
+    /// Parent and bound (`FOR VALUES ...` / `DEFAULT`) the table is attached to.
+    pub attach_to: Option<(&'a str, &'a str)>,
+}
+
+/// Result of [`Table::column_order_mismatch`]: the physical column order of
+/// the TO table and the order an in-place ALTER would leave behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnOrderMismatch {
+    /// Column names in TO order.
+    pub expected: Vec<String>,
+    /// Column names in the order they end up in after the ALTER.
+    pub actual: Vec<String>,
+}
+
 impl Table {
     /// Creates a new Table with the given name
     #[allow(clippy::too_many_arguments)] // Table metadata naturally includes these fields (from pg_class and related catalogs).
@@ -1188,9 +1241,10 @@ impl Table {
 
                 // Use standard column definition
                 let col_script = column.get_script();
-                // Extract just the type and constraints part (skip the quoted name)
-                if let Some(type_start) = col_script.find(' ') {
-                    let mut col_part = col_script[type_start + 1..].to_string();
+                // Extract just the type and constraints part (skip the name,
+                // which may itself contain spaces when quoted)
+                if let Some(rest) = col_script.strip_prefix(&format!("{} ", column.name)) {
+                    let mut col_part = rest.to_string();
 
                     // If there is a named NOT NULL constraint for this column,
                     // replace the plain "not null" with "constraint <name> not null"
@@ -1509,9 +1563,7 @@ impl Table {
             if let Some(old_index) = self.indexes.iter().find(|i| i.name == new_index.name) {
                 if old_index != new_index {
                     if old_index.indexdef != new_index.indexdef {
-                        // Definition changed: drop the old, build the new.
-                        plan.drop.push(old_index);
-                        plan.create.push(new_index);
+                        plan.replace.push((old_index, new_index));
                     } else {
                         // Comment-only change.
                         plan.comment_changes.push(new_index);
@@ -1639,7 +1691,7 @@ impl Table {
         to_table: &Table,
         use_drop: bool,
         include_triggers: bool,
-        defer_indexes: bool,
+        for_production: bool,
     ) -> String {
         // If partition key changes (e.g. from LIST to RANGE, or different column), we must recreate the table.
         // Also if table changes from partitioned to non-partitioned or vice versa.
@@ -1670,12 +1722,13 @@ impl Table {
         let mut policy_drop_script = String::new();
         let mut row_security_script = String::new();
 
-        // Handle partition changes
-        if self.partition_of != to_table.partition_of
-            || (self.partition_of.is_some() && self.partition_bound != to_table.partition_bound)
+        // Handle partition changes; the production path detaches and
+        // attaches after commit itself.
+        if let Some(change) = self.partition_membership_change(to_table)
+            && !for_production
         {
             // If it was a partition, detach it
-            if let Some(old_parent) = &self.partition_of {
+            if let Some(old_parent) = change.detach_from {
                 let detach_cmd = format!(
                     "alter table {} detach partition {}.{};",
                     old_parent, self.schema, self.name
@@ -1689,9 +1742,7 @@ impl Table {
             }
 
             // If it is now a partition, attach it
-            if let Some(new_parent) = &to_table.partition_of
-                && let Some(bound) = &to_table.partition_bound
-            {
+            if let Some((new_parent, bound)) = change.attach_to {
                 partition_script.append_block(&format!(
                     "alter table {} attach partition {}.{} {};",
                     new_parent, self.schema, self.name, bound
@@ -1708,6 +1759,31 @@ impl Table {
         // changes may be required to make it compatible with the parent first.
         let is_target_partition = self.partition_of.is_some() && to_table.partition_of.is_some();
 
+        // Columns the production path makes NOT NULL itself (see
+        // `not_null_additions`); their SET NOT NULL is left out here.
+        let deferred_not_null: Vec<&TableColumn> = if for_production {
+            self.not_null_additions(to_table)
+        } else {
+            Vec::new()
+        };
+        // Likewise for UNIQUE / PRIMARY KEY constraints built from a
+        // concurrent index and CHECK constraints added NOT VALID (see
+        // `unique_constraint_additions` / `check_constraint_additions`).
+        let deferred_constraints: Vec<&TableConstraint> = if for_production {
+            let mut constraints = self.unique_constraint_additions(to_table);
+            constraints.extend(self.check_constraint_additions(to_table));
+            constraints
+        } else {
+            Vec::new()
+        };
+        // Changed UNIQUE / PRIMARY KEY constraints the production path swaps
+        // onto a concurrently built index (see `unique_constraint_replacements`).
+        let replaced_constraints = if for_production {
+            self.unique_constraint_replacements(to_table)
+        } else {
+            Vec::new()
+        };
+
         // Collect column additions or alterations
         for new_col in &to_table.columns {
             if let Some(old_col) = self.columns.iter().find(|c| c.name == new_col.name) {
@@ -1745,7 +1821,15 @@ impl Table {
                         );
                     }
 
-                    if let Some(alter_col_script) = new_col.get_alter_script(old_col, use_drop) {
+                    let alter_col_script =
+                        if deferred_not_null.iter().any(|c| c.name == new_col.name) {
+                            let mut still_nullable = new_col.clone();
+                            still_nullable.is_nullable = true;
+                            still_nullable.get_alter_script(old_col, use_drop)
+                        } else {
+                            new_col.get_alter_script(old_col, use_drop)
+                        };
+                    if let Some(alter_col_script) = alter_col_script {
                         column_alter_script.push_str(&alter_col_script);
                     }
                 }
@@ -1839,6 +1923,11 @@ impl Table {
                         if !is_fk {
                             constraint_post_script.push_str(&alter_script);
                         }
+                    } else if replaced_constraints
+                        .iter()
+                        .any(|(_, new)| std::ptr::eq(*new, new_constraint))
+                    {
+                        // Replaced by the production path.
                     } else {
                         let drop_cmd = old_constraint.get_drop_script();
                         if use_drop {
@@ -1858,7 +1947,22 @@ impl Table {
                     }
                 }
             } else if !is_fk {
-                constraint_post_script.push_str(&new_constraint.get_script());
+                // The auto-named NOT NULL constraint of a deferred column is
+                // created by its post-commit SET NOT NULL.
+                let deferred = new_constraint
+                    .auto_not_null_column(&to_table.name)
+                    .is_some_and(|col| {
+                        deferred_not_null.iter().any(|c| {
+                            TableConstraint::parse_pg_identifier(&c.name).as_deref()
+                                == Some(col.as_str())
+                        })
+                    })
+                    || deferred_constraints
+                        .iter()
+                        .any(|c| std::ptr::eq(*c, new_constraint));
+                if !deferred {
+                    constraint_post_script.push_str(&new_constraint.get_script());
+                }
             }
         }
 
@@ -2028,7 +2132,7 @@ impl Table {
         script.push_str(&partition_script);
         script.push_str(&constraint_pre_script);
         script.push_str(&column_alter_script);
-        if !defer_indexes {
+        if !for_production {
             script.push_str(&index_drop_script);
         }
         if include_triggers {
@@ -2037,7 +2141,7 @@ impl Table {
         script.push_str(&policy_drop_script);
         script.push_str(&column_drop_script);
         script.push_str(&constraint_post_script);
-        if !defer_indexes {
+        if !for_production {
             script.push_str(&index_script);
         }
         if include_triggers {
@@ -2178,18 +2282,162 @@ impl Table {
         self.build_alter_script(to_table, use_drop, false, false)
     }
 
-    /// Like [`Table::get_alter_script_without_triggers`] but omits index
-    /// creation/drop statements. Used by the production output path, which
-    /// emits index changes separately (concurrently / partition-aware) via
-    /// [`Table::index_alter_plan`].
-    pub fn get_alter_script_without_triggers_no_indexes(
-        &self,
-        to_table: &Table,
-        use_drop: bool,
-    ) -> String {
+    /// Like [`Table::get_alter_script_without_triggers`] but omits the DDL the
+    /// production output path emits itself, in a lock-friendly form:
+    ///
+    /// * index creation, drop and replacement (see [`Table::index_alter_plan`]);
+    /// * `SET NOT NULL` on existing columns (see [`Table::not_null_additions`]);
+    /// * new `UNIQUE` / `PRIMARY KEY` constraints (see
+    ///   [`Table::unique_constraint_additions`]);
+    /// * new CHECK constraints (see [`Table::check_constraint_additions`]);
+    /// * changed `UNIQUE` / `PRIMARY KEY` constraints (see
+    ///   [`Table::unique_constraint_replacements`]);
+    /// * partition detach / attach (see [`Table::partition_membership_change`]).
+    pub fn get_alter_script_for_production(&self, to_table: &Table, use_drop: bool) -> String {
         self.build_alter_script(to_table, use_drop, false, true)
     }
 
+    /// Existing columns that become NOT NULL when altering `self` (FROM) into
+    /// `to_table` (TO). The production path adds them through a validated
+    /// `CHECK (col IS NOT NULL)` instead of a plain `SET NOT NULL`, which
+    /// scans the whole table under an ACCESS EXCLUSIVE lock.
+    ///
+    /// Partitions are excluded because they inherit NOT NULL from the parent,
+    /// and partitioned parents keep the plain `SET NOT NULL` that PostgreSQL
+    /// recurses to every partition. Columns that are dropped and re-added
+    /// come back with their full definition anyway.
+    pub fn not_null_additions<'a>(&self, to_table: &'a Table) -> Vec<&'a TableColumn> {
+        if self.partition_key.is_some()
+            || to_table.partition_key.is_some()
+            || self.partition_of.is_some()
+            || to_table.partition_of.is_some()
+        {
+            return Vec::new();
+        }
+        to_table
+            .columns
+            .iter()
+            .filter(|new_col| {
+                !new_col.is_nullable
+                    && self.columns.iter().any(|old_col| {
+                        old_col.name == new_col.name
+                            && old_col.is_nullable
+                            && !new_col.would_drop_and_re_add(old_col)
+                    })
+            })
+            .collect()
+    }
+
+    /// `UNIQUE` / `PRIMARY KEY` constraints of `to_table` (TO) that do not
+    /// exist on `self` (FROM). Adding one directly builds its index while
+    /// holding an ACCESS EXCLUSIVE lock, so the production path builds the
+    /// index concurrently after commit and attaches the constraint with
+    /// `ADD CONSTRAINT ... USING INDEX`.
+    ///
+    /// Partitioned tables and partitions are excluded: `CONCURRENTLY` is
+    /// rejected on a partitioned parent, and a partition's constraint index
+    /// is owned by the parent's.
+    pub fn unique_constraint_additions<'a>(&self, to_table: &'a Table) -> Vec<&'a TableConstraint> {
+        if self.partition_key.is_some()
+            || to_table.partition_key.is_some()
+            || self.partition_of.is_some()
+            || to_table.partition_of.is_some()
+        {
+            return Vec::new();
+        }
+        to_table
+            .constraints
+            .iter()
+            .filter(|c| {
+                (c.constraint_type.eq_ignore_ascii_case("primary key")
+                    || c.constraint_type.eq_ignore_ascii_case("unique"))
+                    && c.coninhcount == 0
+                    && !self.constraints.iter().any(|old| old.name == c.name)
+            })
+            .collect()
+    }
+
+    /// Enforced CHECK constraints of `to_table` (TO) that do not exist on
+    /// `self` (FROM). The production path adds them `NOT VALID` and
+    /// validates them after commit, so existing rows are checked without an
+    /// ACCESS EXCLUSIVE lock. Partitioned tables and partitions are excluded,
+    /// as in [`Table::unique_constraint_additions`].
+    pub fn check_constraint_additions<'a>(&self, to_table: &'a Table) -> Vec<&'a TableConstraint> {
+        if self.partition_key.is_some()
+            || to_table.partition_key.is_some()
+            || self.partition_of.is_some()
+            || to_table.partition_of.is_some()
+        {
+            return Vec::new();
+        }
+        to_table
+            .constraints
+            .iter()
+            .filter(|c| {
+                c.constraint_type.eq_ignore_ascii_case("check")
+                    && c.is_enforced
+                    && c.coninhcount == 0
+                    && !self.constraints.iter().any(|old| old.name == c.name)
+            })
+            .collect()
+    }
+
+    /// How `self` (FROM) moves between partitioned parents when altered into
+    /// `to_table` (TO): detached from its old parent, attached to its new one
+    /// with the TO bound, or both for a bound change under the same parent.
+    /// `None` when the partition membership is unchanged.
+    pub fn partition_membership_change<'a>(
+        &'a self,
+        to_table: &'a Table,
+    ) -> Option<PartitionMembershipChange<'a>> {
+        if self.partition_of == to_table.partition_of
+            && (self.partition_of.is_none() || self.partition_bound == to_table.partition_bound)
+        {
+            return None;
+        }
+        Some(PartitionMembershipChange {
+            detach_from: self.partition_of.as_deref(),
+            attach_to: to_table
+                .partition_of
+                .as_deref()
+                .zip(to_table.partition_bound.as_deref()),
+        })
+    }
+
+    /// `UNIQUE` / `PRIMARY KEY` constraints whose definition changed between
+    /// `self` (FROM) and `to_table` (TO), as `(old, new)` pairs under the same
+    /// name. `build_alter_script` drops and re-adds them, leaving the table
+    /// without the key while the new index is built; the production path
+    /// builds the new index concurrently and swaps the constraint onto it.
+    /// Partitioned tables and partitions are excluded, as in
+    /// [`Table::unique_constraint_additions`].
+    pub fn unique_constraint_replacements<'a>(
+        &'a self,
+        to_table: &'a Table,
+    ) -> Vec<(&'a TableConstraint, &'a TableConstraint)> {
+        if self.partition_key.is_some()
+            || to_table.partition_key.is_some()
+            || self.partition_of.is_some()
+            || to_table.partition_of.is_some()
+        {
+            return Vec::new();
+        }
+        let is_key = |c: &TableConstraint| {
+            c.constraint_type.eq_ignore_ascii_case("primary key")
+                || c.constraint_type.eq_ignore_ascii_case("unique")
+        };
+        to_table
+            .constraints
+            .iter()
+            .filter(|c| is_key(c) && c.coninhcount == 0)
+            .filter_map(|new| {
+                let old = self.constraints.iter().find(|old| old.name == new.name)?;
+                (is_key(old) && old != new && old.get_alter_script(new).is_none())
+                    .then_some((old, new))
+            })
+            .collect()
+    }
+
     /// True when comparing `self` (FROM) against `to_table` (TO) would
     /// force `build_alter_script` down a wholesale `DROP TABLE` +
     /// `CREATE TABLE` path rather than an in-place ALTER. The comparer
@@ -2217,6 +2465,356 @@ impl Table {
         false
     }
 
+    /// Compare the physical column order of `to_table` with the order left
+    /// behind by altering `self` in place: PostgreSQL can only append
+    /// columns, so the surviving columns keep their current order and new
+    /// (or dropped and re-added) columns follow in TO order. Returns `None`
+    /// when both orders agree.
+    ///
+    /// Partitions are skipped — their columns follow the parent.
+    pub fn column_order_mismatch(&self, to_table: &Table) -> Option<ColumnOrderMismatch> {
+        if self.partition_of.is_some() || to_table.partition_of.is_some() {
+            return None;
+        }
+
+        let mut from_columns: Vec<&TableColumn> = self.columns.iter().collect();
+        from_columns.sort_by_key(|c| c.ordinal_position);
+        let mut to_columns: Vec<&TableColumn> = to_table.columns.iter().collect();
+        to_columns.sort_by_key(|c| c.ordinal_position);
+
+        let kept = |old: &TableColumn| {
+            to_columns
+                .iter()
+                .any(|new| new.name == old.name && !new.would_drop_and_re_add(old))
+        };
+        let mut actual: Vec<String> = from_columns
+            .iter()
+            .filter(|old| kept(old))
+            .map(|c| c.name.clone())
+            .collect();
+        for new in &to_columns {
+            if !actual.contains(&new.name) {
+                actual.push(new.name.clone());
+            }
+        }
+        let expected: Vec<String> = to_columns.iter().map(|c| c.name.clone()).collect();
+
+        (expected != actual).then_some(ColumnOrderMismatch { expected, actual })
+    }
+
+    /// Kind of plain ↔ partitioned conversion needed to turn `self` (FROM)
+    /// into `to_table` (TO), or `None` when the partitioning status is
+    /// unchanged. Only top-level tables qualify: a partition child that gains
+    /// or loses a partition key keeps the wholesale drop+recreate path of
+    /// `build_alter_script`, as does a change from one partition key to
+    /// another.
+    pub fn partitioning_change(&self, to_table: &Table) -> Option<PartitioningChange> {
+        if self.partition_of.is_some() || to_table.partition_of.is_some() {
+            return None;
+        }
+        match (&self.partition_key, &to_table.partition_key) {
+            (None, Some(_)) => Some(PartitioningChange::ToPartitioned),
+            (Some(_), None) => Some(PartitioningChange::ToPlain),
+            _ => None,
+        }
+    }
+
+    /// Column-only copy of this (TO) table: the starting point of a rebuild
+    /// (see [`Table::get_rebuild_script`]). It keeps columns, NOT NULL constraints,
+    /// partition key, storage options and owner, and drops everything that is
+    /// named in the schema namespace or that the comparer re-applies
+    /// afterwards (indexes, other constraints, triggers, policies, comments).
+    /// `shell.get_alter_script(to_table)` therefore yields exactly the DDL
+    /// that completes the converted table.
+    ///
+    /// Serial markers are cleared so the column keeps its existing
+    /// `nextval(...)` default instead of creating a fresh sequence.
+    pub fn rebuild_shell(&self) -> Table {
+        let mut shell = self.clone();
+        shell
+            .constraints
+            .retain(|c| c.constraint_type.eq_ignore_ascii_case("not null"));
+        shell.indexes.clear();
+        shell.triggers.clear();
+        shell.policies.clear();
+        shell.comment = None;
+        shell.has_rowsecurity = false;
+        shell.force_rowsecurity = false;
+        shell.replica_identity = None;
+        for column in &mut shell.columns {
+            column.comment = None;
+            column.serial_type = None;
+        }
+        shell.hash();
+        shell
+    }
+
+    /// Script that converts `self` (FROM) into the shape of `to_table` (TO)
+    /// when [`Table::partitioning_change`] reports a conversion, instead of
+    /// dropping and recreating the table with data loss. `partitions` are the
+    /// new partitions from the TO dump; when the old table can serve as the
+    /// only one (see [`Table::partition_attach_candidate`]) it is renamed and
+    /// attached in place instead of copying its rows. See
+    /// [`Table::get_rebuild_script`] for the remaining steps.
+    pub fn get_partitioning_change_script(
+        &self,
+        to_table: &Table,
+        partitions: &[&Table],
+        owned_sequences: &[&Sequence],
+        use_drop: bool,
+    ) -> String {
+        let Some(change) = self.partitioning_change(to_table) else {
+            return String::new();
+        };
+
+        let mut script = match change {
+            PartitioningChange::ToPartitioned => format!(
+                "/* Table {}.{} is converted to a partitioned table (partition by {}). */\n",
+                to_table.schema,
+                to_table.name,
+                to_table.partition_key.as_deref().unwrap_or_default()
+            ),
+            PartitioningChange::ToPlain => format!(
+                "/* Table {}.{} is converted from a partitioned table to a plain table. */\n",
+                to_table.schema, to_table.name
+            ),
+        };
+        let attached = match change {
+            PartitioningChange::ToPartitioned => {
+                self.partition_attach_candidate(to_table, partitions)
+            }
+            PartitioningChange::ToPlain => None,
+        };
+        script.push_str(&self.get_rebuild_script(
+            to_table,
+            partitions,
+            attached,
+            owned_sequences,
+            use_drop,
+        ));
+        script
+    }
+
+    /// Script that rebuilds `self` (FROM) as `to_table` (TO) while keeping its
+    /// rows.
+    ///
+    /// The replacement is created under a temporary name (`<name>__pgc_new`)
+    /// together with `partitions`. The rows are then moved: either `attached`
+    /// (one of `partitions`) is taken over by renaming the old table to it and
+    /// attaching it, or the rows are copied with `INSERT ... SELECT`. Finally
+    /// the old table is dropped (or, without `use_drop`, renamed to
+    /// `<name>__pgc_old`) and the replacement takes the final name.
+    /// `owned_sequences` (serial sequences owned by the old table) are
+    /// released before the drop and re-owned by the new table.
+    ///
+    /// The result is a bare column shell: constraints, indexes, triggers,
+    /// policies and comments are emitted by the caller from
+    /// [`Table::rebuild_shell`], and foreign keys that reference the table
+    /// have to be dropped beforehand and re-added afterwards.
+    pub fn get_rebuild_script(
+        &self,
+        to_table: &Table,
+        partitions: &[&Table],
+        attached: Option<&Table>,
+        owned_sequences: &[&Sequence],
+        use_drop: bool,
+    ) -> String {
+        let raw_name = TableConstraint::parse_pg_identifier(&to_table.name)
+            .unwrap_or_else(|| to_table.name.clone());
+        let staged_name = quote_ident(&format!("{raw_name}__pgc_new"));
+        let staged_qualified = format!("{}.{}", to_table.schema, staged_name);
+
+        let mut script = String::new();
+
+        // 1. The replacement table (and its partitions) under a temporary name.
+        let mut staged = to_table.rebuild_shell();
+        staged.name = staged_name.clone();
+        script.push_str(&staged.build_script(false, true));
+
+        for partition in partitions {
+            if attached.is_some_and(|a| a.name == partition.name) {
+                continue;
+            }
+            let mut staged_partition = (*partition).clone();
+            staged_partition.partition_of = Some(staged_qualified.clone());
+            script.push_str(&staged_partition.build_script(false, false));
+        }
+
+        // 2. Move the rows.
+        let copied_columns: Vec<&TableColumn> = to_table
+            .columns
+            .iter()
+            .filter(|c| {
+                c.is_generated.to_lowercase() != "always"
+                    && self.columns.iter().any(|old| old.name == c.name)
+            })
+            .collect();
+        if let Some(partition) = attached {
+            // Attaching requires the partition to be NOT NULL wherever the
+            // parent is.
+            for new_col in to_table.columns.iter().filter(|c| !c.is_nullable) {
+                if self
+                    .columns
+                    .iter()
+                    .any(|old| old.name == new_col.name && old.is_nullable)
+                {
+                    script.append_block(&format!(
+                        "alter table {}.{} alter column {} set not null;",
+                        self.schema, self.name, new_col.name
+                    ));
+                }
+            }
+            let raw_partition = TableConstraint::parse_pg_identifier(&partition.name)
+                .unwrap_or_else(|| partition.name.clone());
+            script.push_str(&self.get_rename_aside_script(&raw_partition));
+            script.append_block(&format!(
+                "alter table {} attach partition {}.{} {};",
+                staged_qualified,
+                partition.schema,
+                partition.name,
+                partition.partition_bound.as_deref().unwrap_or("default")
+            ));
+        } else if !copied_columns.is_empty() {
+            let column_list = copied_columns
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let overriding = if copied_columns.iter().any(|c| {
+                c.is_identity
+                    && c.identity_generation
+                        .as_deref()
+                        .is_some_and(|g| g.eq_ignore_ascii_case("always"))
+            }) {
+                " overriding system value"
+            } else {
+                ""
+            };
+            script.append_block(&format!(
+                "insert into {staged_qualified} ({column_list}){overriding}\nselect {column_list} from {}.{};",
+                self.schema, self.name
+            ));
+        }
+
+        // 3. Retire the old table and give the replacement its final name.
+        for sequence in owned_sequences {
+            script.append_block(&format!(
+                "alter sequence {}.{} owned by none;",
+                sequence.schema, sequence.name
+            ));
+        }
+        if attached.is_none() {
+            if use_drop {
+                script.push_str(&self.get_drop_script());
+            } else {
+                script.push_str(&format!(
+                    "/* use_drop is off: the old table is kept as {}.{}. */\n",
+                    self.schema,
+                    quote_ident(&format!("{raw_name}__pgc_old"))
+                ));
+                script.push_str(&self.get_rename_aside_script(&format!("{raw_name}__pgc_old")));
+            }
+        }
+        script.append_block(&format!(
+            "alter table {} rename to {};",
+            staged_qualified, to_table.name
+        ));
+        for sequence in owned_sequences {
+            if let Some(column) = &sequence.owned_by_column
+                && to_table.columns.iter().any(|c| &c.name == column)
+            {
+                script.append_block(&format!(
+                    "alter sequence {}.{} owned by {}.{}.{};",
+                    sequence.schema, sequence.name, to_table.schema, to_table.name, column
+                ));
+            }
+        }
+
+        // 4. Copied identity values must not be handed out again.
+        if attached.is_none() {
+            for column in copied_columns.iter().filter(|c| c.is_identity) {
+                let raw_column = TableConstraint::parse_pg_identifier(&column.name)
+                    .unwrap_or_else(|| column.name.clone());
+                script.append_block(&format!(
+                    "select setval(pg_get_serial_sequence('{}', '{}'), max({})) from {}.{} having max({}) is not null;",
+                    escape_single_quotes(&format!("{}.{}", to_table.schema, to_table.name)),
+                    escape_single_quotes(&raw_column),
+                    column.name,
+                    to_table.schema,
+                    to_table.name,
+                    column.name
+                ));
+            }
+        }
+
+        script
+    }
+
+    /// The TO partition the old plain table can become in place of copying
+    /// its rows: the only new partition, in the same schema, not itself
+    /// partitioned, with a bound that accepts every row (`DEFAULT`, a single
+    /// `MINVALUE`..`MAXVALUE` range, or `modulus 1`), and with columns that
+    /// match the TO table by name and type.
+    pub fn partition_attach_candidate<'a>(
+        &self,
+        to_table: &Table,
+        partitions: &[&'a Table],
+    ) -> Option<&'a Table> {
+        let [partition] = partitions else {
+            return None;
+        };
+        if partition.partition_key.is_some()
+            || partition.schema != self.schema
+            || !partition
+                .partition_bound
+                .as_deref()
+                .is_some_and(partition_bound_accepts_all_rows)
+            || self.columns.len() != to_table.columns.len()
+        {
+            return None;
+        }
+        let columns_match = to_table.columns.iter().all(|new_col| {
+            self.columns.iter().any(|old_col| {
+                old_col.name == new_col.name
+                    && old_col.data_type == new_col.data_type
+                    && old_col.udt_name == new_col.udt_name
+                    && old_col.character_maximum_length == new_col.character_maximum_length
+                    && old_col.numeric_precision == new_col.numeric_precision
+                    && old_col.numeric_scale == new_col.numeric_scale
+                    && old_col.collation_name == new_col.collation_name
+                    && old_col
+                        .is_generated
+                        .eq_ignore_ascii_case(&new_col.is_generated)
+                    && old_col.generation_expression == new_col.generation_expression
+            })
+        });
+        columns_match.then_some(*partition)
+    }
+
+    /// Rename this table to `new_raw_name` along with its own indexes (prefixed
+    /// with the new name), so the index and constraint names are free for the
+    /// table that replaces it.
+    fn get_rename_aside_script(&self, new_raw_name: &str) -> String {
+        let mut script = String::new();
+        for index in self.indexes.iter().filter(|i| !i.is_partition_index) {
+            let raw_index = TableConstraint::parse_pg_identifier(&index.name)
+                .unwrap_or_else(|| index.name.clone());
+            script.append_block(&format!(
+                "alter index {}.{} rename to {};",
+                index.schema,
+                index.name,
+                quote_ident(&format!("{new_raw_name}_{raw_index}"))
+            ));
+        }
+        script.append_block(&format!(
+            "alter table {}.{} rename to {};",
+            self.schema,
+            self.name,
+            quote_ident(new_raw_name)
+        ));
+        script
+    }
+
     /// True when a column's type changed *and* the column either
     /// participates in this table's partition key or the table is a
     /// partition child — both cases require dropping and recreating
@@ -182,6 +182,62 @@ impl TableColumn {
                     || self.effective_generation_type() == "v"))
     }
 
+    /// The old and new type when changing `existing` (the FROM-side column)
+    /// to `self` may lose data: a different type that is not a known lossless
+    /// widening (`smallint` → `integer` → `bigint` → `numeric`, `real` →
+    /// `double precision`, character types → `text`), or the same type with a
+    /// shorter length or smaller precision / scale. `None` when the type is
+    /// unchanged or only widened.
+    pub fn narrowing_from(&self, existing: &TableColumn) -> Option<(String, String)> {
+        if !self.type_clause_differs(existing) {
+            return None;
+        }
+        let old_type = existing.data_type.to_lowercase();
+        let new_type = self.data_type.to_lowercase();
+        let widens = |a: Option<i32>, b: Option<i32>| match (a, b) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(old), Some(new)) => new >= old,
+        };
+        let lossless = if old_type == new_type {
+            let numeric_widens = widens(existing.numeric_precision, self.numeric_precision)
+                && widens(existing.numeric_scale, self.numeric_scale)
+                && widens(
+                    existing
+                        .numeric_precision
+                        .map(|p| p - existing.numeric_scale.unwrap_or(0)),
+                    self.numeric_precision
+                        .map(|p| p - self.numeric_scale.unwrap_or(0)),
+                );
+            widens(
+                existing.character_maximum_length,
+                self.character_maximum_length,
+            ) && (!(new_type.contains("numeric") || new_type.contains("decimal")) || numeric_widens)
+                && existing.interval_type == self.interval_type
+        } else {
+            matches!(
+                (old_type.as_str(), new_type.as_str()),
+                ("smallint", "integer" | "bigint")
+                    | ("integer", "bigint")
+                    | ("real", "double precision")
+                    | ("character" | "character varying", "text")
+            ) || (matches!(old_type.as_str(), "smallint" | "integer" | "bigint")
+                && new_type == "numeric"
+                && self.numeric_precision.is_none())
+                || (old_type == "character"
+                    && new_type == "character varying"
+                    && widens(
+                        existing.character_maximum_length,
+                        self.character_maximum_length,
+                    ))
+        };
+        if lossless {
+            None
+        } else {
+            Some((existing.render_type_clause(), self.render_type_clause()))
+        }
+    }
+
     fn normalized_generation_expression(expr: &str) -> String {
         let mut trimmed = expr.trim();
         // Strip redundant outer parentheses to avoid emitted ((expr)) which some servers reject
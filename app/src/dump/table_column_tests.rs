@@ -1196,3 +1196,61 @@ fn test_serde_default_generation_type() {
         "missing generation_type should default to None"
     );
 }
+
+fn typed_column(
+    data_type: &str,
+    length: Option<i32>,
+    precision: Option<i32>,
+    scale: Option<i32>,
+) -> TableColumn {
+    let mut column = create_test_column();
+    column.data_type = data_type.to_string();
+    column.character_maximum_length = length;
+    column.numeric_precision = precision;
+    column.numeric_scale = scale;
+    column
+}
+
+#[test]
+fn test_narrowing_from_flags_shorter_length_and_precision() {
+    let wide = typed_column("character varying", Some(100), None, None);
+    let narrow = typed_column("character varying", Some(20), None, None);
+    assert_eq!(
+        narrow.narrowing_from(&wide),
+        Some((
+            "character varying(100)".to_string(),
+            "character varying(20)".to_string()
+        ))
+    );
+    assert_eq!(wide.narrowing_from(&narrow), None);
+
+    let unbounded = typed_column("character varying", None, None, None);
+    assert!(wide.narrowing_from(&unbounded).is_some());
+    assert_eq!(unbounded.narrowing_from(&wide), None);
+
+    let money = typed_column("numeric", None, Some(12), Some(2));
+    let less_scale = typed_column("numeric", None, Some(12), Some(0));
+    let more_digits = typed_column("numeric", None, Some(14), Some(2));
+    assert!(less_scale.narrowing_from(&money).is_some());
+    assert_eq!(more_digits.narrowing_from(&money), None);
+}
+
+#[test]
+fn test_narrowing_from_allows_known_widenings() {
+    let small = typed_column("smallint", None, Some(16), Some(0));
+    let int = typed_column("integer", None, Some(32), Some(0));
+    let big = typed_column("bigint", None, Some(64), Some(0));
+    let numeric = typed_column("numeric", None, None, None);
+    let text = typed_column("text", None, None, None);
+    let varchar = typed_column("character varying", Some(10), None, None);
+
+    assert_eq!(int.narrowing_from(&small), None);
+    assert_eq!(big.narrowing_from(&int), None);
+    assert_eq!(numeric.narrowing_from(&big), None);
+    assert_eq!(text.narrowing_from(&varchar), None);
+
+    assert!(int.narrowing_from(&big).is_some());
+    assert!(varchar.narrowing_from(&text).is_some());
+    assert!(int.narrowing_from(&text).is_some());
+    assert_eq!(int.narrowing_from(&int.clone()), None);
+}
@@ -411,6 +411,26 @@ fn test_get_script_identity_column_not_serial() {
     assert!(!script.contains("serial"));
 }
 
+#[test]
+fn test_get_script_keeps_quoted_column_names_with_spaces() {
+    let table = Table::new(
+        "public".to_string(),
+        "odd".to_string(),
+        "public".to_string(),
+        "odd".to_string(),
+        "postgres".to_string(),
+        None,
+        vec![base_column("\"A b\"", 1)],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+
+    let script = table.get_script();
+    assert!(script.contains("    \"A b\" text\n"), "{script}");
+}
+
 #[test]
 fn test_get_drop_script_returns_statement() {
     let table = basic_table();
@@ -1117,6 +1137,18 @@ fn test_get_alter_script_partition_bound_change() {
 
     assert!(script.contains("detach partition"));
     assert!(script.contains("attach partition"));
+
+    assert_eq!(
+        from_table.partition_membership_change(&to_table),
+        Some(PartitionMembershipChange {
+            detach_from: Some("\"data\".\"test\""),
+            attach_to: Some(("\"data\".\"test\"", "FOR VALUES IN (2)")),
+        })
+    );
+    assert_eq!(from_table.partition_membership_change(&from_table), None);
+    let production = from_table.get_alter_script_for_production(&to_table, true);
+    assert!(!production.contains("detach partition"), "{production}");
+    assert!(!production.contains("attach partition"), "{production}");
 }
 
 #[test]
@@ -2948,3 +2980,413 @@ fn test_named_to_auto_named_not_null_still_diffs() {
         "renaming away from a user-chosen name must still emit a drop: {script}"
     );
 }
+
+fn events_table(partition_key: Option<&str>) -> Table {
+    let mut id = create_dummy_column("id", "bigint");
+    id.schema = "data".to_string();
+    id.table = "events".to_string();
+    id.is_nullable = false;
+    id.column_default = Some("nextval('data.events_id_seq'::regclass)".to_string());
+    let mut created = create_dummy_column("created", "date");
+    created.schema = "data".to_string();
+    created.table = "events".to_string();
+    created.ordinal_position = 2;
+    let mut table = Table::new(
+        "data".to_string(),
+        "events".to_string(),
+        "data".to_string(),
+        "events".to_string(),
+        "owner".to_string(),
+        None,
+        vec![id, created],
+        vec![],
+        vec![TableIndex {
+            schema: "data".to_string(),
+            table: "events".to_string(),
+            name: "events_created_idx".to_string(),
+            catalog: None,
+            indexdef: "create index events_created_idx on data.events using btree (created)"
+                .to_string(),
+            comment: None,
+            is_partition_index: false,
+        }],
+        vec![],
+        None,
+    );
+    table.partition_key = partition_key.map(str::to_string);
+    table.hash();
+    table
+}
+
+fn events_partition(name: &str, bound: &str) -> Table {
+    let mut table = Table::new(
+        "data".to_string(),
+        name.to_string(),
+        "data".to_string(),
+        name.to_string(),
+        "owner".to_string(),
+        None,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        None,
+    );
+    table.partition_of = Some("data.events".to_string());
+    table.partition_bound = Some(bound.to_string());
+    table
+}
+
+fn events_sequence() -> Sequence {
+    Sequence::new(
+        "data".to_string(),
+        "events_id_seq".to_string(),
+        "owner".to_string(),
+        "bigint".to_string(),
+        Some(1),
+        Some(1),
+        None,
+        Some(1),
+        false,
+        Some(1),
+        None,
+        Some("data".to_string()),
+        Some("events".to_string()),
+        Some("id".to_string()),
+    )
+}
+
+#[test]
+fn test_partitioning_change_detects_direction() {
+    let plain = events_table(None);
+    let partitioned = events_table(Some("RANGE (created)"));
+    let other_key = events_table(Some("LIST (created)"));
+
+    assert_eq!(
+        plain.partitioning_change(&partitioned),
+        Some(PartitioningChange::ToPartitioned)
+    );
+    assert_eq!(
+        partitioned.partitioning_change(&plain),
+        Some(PartitioningChange::ToPlain)
+    );
+    assert_eq!(partitioned.partitioning_change(&other_key), None);
+    assert_eq!(plain.partitioning_change(&plain), None);
+
+    let mut child = events_table(None);
+    child.partition_of = Some("data.parent".to_string());
+    let mut partitioned_child = events_table(Some("RANGE (created)"));
+    partitioned_child.partition_of = Some("data.parent".to_string());
+    assert_eq!(child.partitioning_change(&partitioned_child), None);
+}
+
+#[test]
+fn test_rebuild_shell_keeps_only_columns_and_not_null() {
+    let mut table = basic_table();
+    let mut not_null = check_constraint("users_id_not_null", "NOT NULL id");
+    not_null.constraint_type = "NOT NULL".to_string();
+    table.constraints.push(not_null);
+    table.comment = Some("users".to_string());
+    table.columns[1].comment = Some("name".to_string());
+
+    let shell = table.rebuild_shell();
+    assert_eq!(shell.columns.len(), 2);
+    assert_eq!(shell.constraints.len(), 1);
+    assert!(shell.indexes.is_empty());
+    assert!(shell.triggers.is_empty());
+    assert!(shell.comment.is_none());
+    assert!(shell.columns.iter().all(|c| c.comment.is_none()));
+    assert_ne!(shell.hash, table.hash);
+}
+
+#[test]
+fn test_partitioning_change_script_copies_rows() {
+    let from = events_table(None);
+    let to = events_table(Some("RANGE (created)"));
+    let p2024 = events_partition(
+        "events_2024",
+        "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')",
+    );
+    let p2025 = events_partition(
+        "events_2025",
+        "FOR VALUES FROM ('2025-01-01') TO ('2026-01-01')",
+    );
+    let sequence = events_sequence();
+
+    let script = from.get_partitioning_change_script(&to, &[&p2024, &p2025], &[&sequence], true);
+
+    let create = script
+        .find("create table data.\"events__pgc_new\"")
+        .expect("staged parent created");
+    let child = script
+        .find("create table data.events_2024 partition of data.\"events__pgc_new\"")
+        .expect("partition created against staged parent");
+    let copy = script
+        .find("insert into data.\"events__pgc_new\" (id, created)\nselect id, created from data.events;")
+        .expect("rows copied");
+    let release = script
+        .find("alter sequence data.events_id_seq owned by none;")
+        .expect("sequence released");
+    let drop = script
+        .find("drop table if exists data.events")
+        .expect("old table dropped");
+    let rename = script
+        .find("alter table data.\"events__pgc_new\" rename to events;")
+        .expect("staged table renamed");
+    let reown = script
+        .find("alter sequence data.events_id_seq owned by data.events.id;")
+        .expect("sequence re-owned");
+    assert!(create < child && child < copy && copy < release);
+    assert!(release < drop && drop < rename && rename < reown);
+    assert!(script.contains("partition by RANGE (created)"));
+    assert!(
+        !script.contains("create index"),
+        "indexes are left to the follow-up alter: {script}"
+    );
+}
+
+#[test]
+fn test_partitioning_change_script_without_drop_keeps_old_table() {
+    let from = events_table(Some("RANGE (created)"));
+    let to = events_table(None);
+
+    let script = from.get_partitioning_change_script(&to, &[], &[], false);
+
+    assert!(script.contains("converted from a partitioned table to a plain table"));
+    assert!(script.contains("insert into data.\"events__pgc_new\" (id, created)"));
+    assert!(!script.contains("drop table"));
+    assert!(script.contains(
+        "alter index data.events_created_idx rename to \"events__pgc_old_events_created_idx\";"
+    ));
+    assert!(script.contains("alter table data.events rename to \"events__pgc_old\";"));
+    assert!(script.contains("alter table data.\"events__pgc_new\" rename to events;"));
+}
+
+#[test]
+fn test_partitioning_change_script_attaches_catch_all_partition() {
+    let mut from = events_table(None);
+    from.columns[1].is_nullable = true;
+    let mut to = events_table(Some("LIST (created)"));
+    to.columns[1].is_nullable = false;
+    let default = events_partition("events_default", "DEFAULT");
+
+    let script = from.get_partitioning_change_script(&to, &[&default], &[], true);
+
+    assert!(!script.contains("insert into"), "no copy needed: {script}");
+    assert!(!script.contains("create table data.events_default"));
+    assert!(!script.contains("drop table"));
+    let not_null = script
+        .find("alter table data.events alter column created set not null;")
+        .expect("nullability aligned with the parent");
+    let rename = script
+        .find("alter table data.events rename to \"events_default\";")
+        .expect("old table renamed to the partition");
+    let attach = script
+        .find("alter table data.\"events__pgc_new\" attach partition data.events_default DEFAULT;")
+        .expect("old table attached");
+    let final_rename = script
+        .find("alter table data.\"events__pgc_new\" rename to events;")
+        .expect("staged table renamed");
+    assert!(not_null < rename && rename < attach && attach < final_rename);
+    assert!(script.contains(
+        "alter index data.events_created_idx rename to \"events_default_events_created_idx\";"
+    ));
+}
+
+#[test]
+fn test_partition_bound_accepts_all_rows() {
+    assert!(partition_bound_accepts_all_rows("DEFAULT"));
+    assert!(partition_bound_accepts_all_rows(
+        "FOR VALUES WITH (modulus 1, remainder 0)"
+    ));
+    assert!(partition_bound_accepts_all_rows(
+        "FOR VALUES FROM (MINVALUE, MINVALUE) TO (MAXVALUE, MAXVALUE)"
+    ));
+    assert!(!partition_bound_accepts_all_rows(
+        "FOR VALUES FROM (MINVALUE) TO ('2025-01-01')"
+    ));
+    assert!(!partition_bound_accepts_all_rows(
+        "FOR VALUES WITH (modulus 2, remainder 0)"
+    ));
+    assert!(!partition_bound_accepts_all_rows("FOR VALUES IN ('a')"));
+}
+
+fn ordered_table(columns: &[(&str, i32)]) -> Table {
+    Table::new(
+        "public".to_string(),
+        "users".to_string(),
+        "public".to_string(),
+        "users".to_string(),
+        "postgres".to_string(),
+        None,
+        columns
+            .iter()
+            .map(|(name, ordinal)| base_column(name, *ordinal))
+            .collect(),
+        vec![],
+        vec![],
+        vec![],
+        None,
+    )
+}
+
+#[test]
+fn test_column_order_mismatch_detects_column_inserted_in_the_middle() {
+    let from = ordered_table(&[("id", 1), ("email", 2)]);
+    let to = ordered_table(&[("id", 1), ("name", 2), ("email", 3)]);
+
+    let mismatch = from.column_order_mismatch(&to).expect("order differs");
+    assert_eq!(mismatch.expected, vec!["id", "name", "email"]);
+    assert_eq!(mismatch.actual, vec!["id", "email", "name"]);
+}
+
+#[test]
+fn test_column_order_mismatch_ignores_appended_and_dropped_columns() {
+    // Gaps left by dropped columns do not matter, only the relative order.
+    let from = ordered_table(&[("id", 1), ("legacy", 2), ("email", 3)]);
+    let to = ordered_table(&[("id", 1), ("email", 3), ("name", 4)]);
+    assert!(from.column_order_mismatch(&to).is_none());
+}
+
+#[test]
+fn test_column_order_mismatch_skips_partitions() {
+    let mut from = ordered_table(&[("id", 1), ("email", 2)]);
+    let mut to = ordered_table(&[("email", 1), ("id", 2)]);
+    from.partition_of = Some("public.parent".to_string());
+    to.partition_of = Some("public.parent".to_string());
+    assert!(from.column_order_mismatch(&to).is_none());
+}
+
+#[test]
+fn test_rebuild_script_copies_rows_in_target_order() {
+    let from = ordered_table(&[("id", 1), ("email", 2)]);
+    let to = ordered_table(&[("id", 1), ("name", 2), ("email", 3)]);
+
+    let script = from.get_rebuild_script(&to, &[], None, &[], true);
+
+    let create = script
+        .find("create table public.\"users__pgc_new\" (\n    id text,\n    name text,\n    email text")
+        .expect("replacement created in target order");
+    let copy = script
+        .find("insert into public.\"users__pgc_new\" (id, email)\nselect id, email from public.users;")
+        .expect("rows copied");
+    let rename = script
+        .find("alter table public.\"users__pgc_new\" rename to users;")
+        .expect("replacement renamed");
+    assert!(create < copy && copy < rename);
+}
+
+#[test]
+fn test_alter_script_for_production_defers_set_not_null() {
+    let from = ordered_table(&[("id", 1), ("email", 2)]);
+    let mut to = ordered_table(&[("id", 1), ("email", 2)]);
+    to.columns[1].is_nullable = false;
+    let mut not_null = check_constraint("users_email_not_null", "NOT NULL email");
+    not_null.constraint_type = "NOT NULL".to_string();
+    to.constraints.push(not_null);
+
+    let additions = from.not_null_additions(&to);
+    assert_eq!(additions.len(), 1);
+    assert_eq!(additions[0].name, "email");
+
+    let regular = from.get_alter_script_without_triggers(&to, true);
+    assert!(
+        regular.contains("alter column email set not null;"),
+        "{regular}"
+    );
+
+    let production = from.get_alter_script_for_production(&to, true);
+    assert!(!production.contains("set not null"), "{production}");
+    assert!(
+        !production.contains("add constraint users_email_not_null"),
+        "{production}"
+    );
+}
+
+#[test]
+fn test_not_null_additions_skip_partitioned_tables() {
+    let mut from = ordered_table(&[("id", 1)]);
+    let mut to = ordered_table(&[("id", 1)]);
+    to.columns[0].is_nullable = false;
+    from.partition_key = Some("RANGE (id)".to_string());
+    to.partition_key = Some("RANGE (id)".to_string());
+    assert!(from.not_null_additions(&to).is_empty());
+}
+
+#[test]
+fn test_alter_script_for_production_defers_new_unique_constraints() {
+    let mut from = ordered_table(&[("id", 1), ("email", 2)]);
+    let mut existing = check_constraint("users_id_key", "UNIQUE (id)");
+    existing.constraint_type = "UNIQUE".to_string();
+    from.constraints.push(existing.clone());
+    let mut to = ordered_table(&[("id", 1), ("email", 2)]);
+    to.constraints.push(existing);
+    let mut email_key = check_constraint("users_email_key", "UNIQUE (email)");
+    email_key.constraint_type = "UNIQUE".to_string();
+    to.constraints.push(email_key);
+
+    let additions = from.unique_constraint_additions(&to);
+    assert_eq!(additions.len(), 1);
+    assert_eq!(additions[0].name, "users_email_key");
+
+    let regular = from.get_alter_script_without_triggers(&to, true);
+    assert!(
+        regular.contains("add constraint users_email_key unique (email)"),
+        "{regular}"
+    );
+    let production = from.get_alter_script_for_production(&to, true);
+    assert!(!production.contains("users_email_key"), "{production}");
+
+    from.partition_key = Some("RANGE (id)".to_string());
+    to.partition_key = Some("RANGE (id)".to_string());
+    assert!(from.unique_constraint_additions(&to).is_empty());
+}
+
+#[test]
+fn test_alter_script_for_production_defers_new_check_constraints() {
+    let from = ordered_table(&[("id", 1)]);
+    let mut to = ordered_table(&[("id", 1)]);
+    to.constraints.push(check_constraint(
+        "users_id_check",
+        "CHECK ((id <> ''::text))",
+    ));
+    let mut not_enforced =
+        check_constraint("users_id_len", "CHECK ((length(id) < 10)) NOT ENFORCED");
+    not_enforced.is_enforced = false;
+    to.constraints.push(not_enforced);
+
+    let additions = from.check_constraint_additions(&to);
+    assert_eq!(additions.len(), 1);
+    assert_eq!(additions[0].name, "users_id_check");
+
+    let production = from.get_alter_script_for_production(&to, true);
+    assert!(!production.contains("users_id_check"), "{production}");
+    assert!(production.contains("users_id_len"), "{production}");
+}
+
+#[test]
+fn test_unique_constraint_replacements_pairs_changed_keys() {
+    let mut unique = check_constraint("users_email_key", "UNIQUE (email)");
+    unique.constraint_type = "UNIQUE".to_string();
+    let mut from = ordered_table(&[("id", 1), ("email", 2)]);
+    from.constraints.push(unique.clone());
+    let mut to = ordered_table(&[("id", 1), ("email", 2)]);
+    to.constraints.push(unique.clone());
+    assert!(from.unique_constraint_replacements(&to).is_empty());
+
+    to.constraints[0].definition = Some("UNIQUE (email, id)".to_string());
+    let replacements = from.unique_constraint_replacements(&to);
+    assert_eq!(replacements.len(), 1);
+    assert_eq!(
+        replacements[0].0.definition.as_deref(),
+        Some("UNIQUE (email)")
+    );
+
+    let regular = from.get_alter_script_without_triggers(&to, true);
+    assert!(
+        regular.contains("drop constraint users_email_key"),
+        "{regular}"
+    );
+    let production = from.get_alter_script_for_production(&to, true);
+    assert!(!production.contains("users_email_key"), "{production}");
+}
@@ -0,0 +1,478 @@
+//! Dumps as a directory of SQL files, one file per object.
+//!
+//! The zip dump is opaque in code review; the tree keeps the same objects as
+//! plain DDL that diffs line by line:
+//!
+//! ```text
+//! extensions/<name>.sql
+//! schemas/<schema>/schema.sql          schema, grants, default privileges
+//! schemas/<schema>/collations/<name>.sql
+//! schemas/<schema>/types/<name>.sql    enums, composites, domains, ranges
+//! schemas/<schema>/sequences/<name>.sql
+//! schemas/<schema>/tables/<name>.sql   columns, constraints, foreign keys,
+//!                                      indexes, triggers, policies, rules,
+//!                                      statistics, comments, grants
+//! schemas/<schema>/foreign_tables/<name>.sql
+//! schemas/<schema>/functions/<name>.sql  every overload of the name
+//! schemas/<schema>/views/<name>.sql
+//! schemas/<schema>/operators.sql
+//! schemas/<schema>/text_search/<name>.sql
+//! casts.sql, event_triggers.sql, foreign_data.sql, publications.sql,
+//! default_privileges.sql              database-wide objects
+//! ```
+//!
+//! The DDL is the one the comparer emits for a new object, so the same dump
+//! always writes the same bytes. Sequences created by a serial or identity
+//! column live with their table. Reading a tree back goes through the SQL
+//! import, which takes the files in the order above rather than path order.
+
+use crate::dump::acl;
+use crate::dump::core::Dump;
+use crate::dump::routine::Routine;
+use crate::dump::table::Table;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+/// Directory holding one subdirectory per schema.
+pub(crate) const SCHEMAS_DIR: &str = "schemas";
+
+/// Object directories inside a schema, in the order they are read back.
+const SCHEMA_DIRS: &[&str] = &[
+    "collations",
+    "types",
+    "sequences",
+    "tables",
+    "foreign_tables",
+    "functions",
+    "views",
+    "text_search",
+];
+
+/// Files and directories at the top of the tree, besides `schemas`.
+const DATABASE_ENTRIES: &[&str] = &[
+    "extensions",
+    "casts.sql",
+    "event_triggers.sql",
+    "foreign_data.sql",
+    "publications.sql",
+    "default_privileges.sql",
+];
+
+/// Writes `dump` below `dir` and returns the written files, relative to
+/// `dir`. SQL files of an earlier export that no longer match an object are
+/// removed; anything else in `dir` is left alone.
+pub fn export_tree(dump: &Dump, dir: &str) -> Result<Vec<String>, Error> {
+    let root = Path::new(dir);
+    let files = tree_files(dump);
+    for (path, content) in &files {
+        let target = root.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, content)?;
+    }
+    let written: HashSet<PathBuf> = files.keys().map(|p| root.join(p)).collect();
+    for entry in std::iter::once(SCHEMAS_DIR).chain(DATABASE_ENTRIES.iter().copied()) {
+        remove_stale(&root.join(entry), &written)?;
+    }
+    Ok(files.keys().map(|p| p.display().to_string()).collect())
+}
+
+/// Whether `path` is a directory written by [`export_tree`].
+pub(crate) fn is_tree(path: &Path) -> bool {
+    path.join(SCHEMAS_DIR).is_dir()
+}
+
+/// Sort key that reads the files of a tree in dependency order: schemas,
+/// extensions, then each kind of object in [`SCHEMA_DIRS`] order, then the
+/// rest. `relative` is the path below the tree root.
+pub(crate) fn read_order(relative: &Path) -> (usize, PathBuf) {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let rank = match parts.as_slice() {
+        [schemas, _, file] if schemas == SCHEMAS_DIR && file == "schema.sql" => 0,
+        [extensions, _] if extensions == "extensions" => 1,
+        [schemas, _, kind, _] if schemas == SCHEMAS_DIR => SCHEMA_DIRS
+            .iter()
+            .position(|d| d == kind)
+            .map_or(SCHEMA_DIRS.len() + 2, |p| p + 2),
+        _ => SCHEMA_DIRS.len() + 2,
+    };
+    (rank, relative.to_path_buf())
+}
+
+/// Every file of the tree of `dump`, keyed by its path below the root.
+fn tree_files(dump: &Dump) -> BTreeMap<PathBuf, String> {
+    let mut files: BTreeMap<PathBuf, String> = BTreeMap::new();
+    let mut add = |path: PathBuf, script: &str| {
+        if !script.trim().is_empty() {
+            files.entry(path).or_default().push_str(script);
+        }
+    };
+    for schema in &dump.schemas {
+        let mut script = schema.get_script();
+        script.push_str(&grants(
+            &schema.acl,
+            "SCHEMA",
+            &schema.name,
+            &schema.owner,
+            None,
+        ));
+        for privilege in &dump.default_privileges {
+            if privilege.schema_name == schema.name {
+                script.push_str(&privilege.get_script());
+            }
+        }
+        add(
+            Path::new(SCHEMAS_DIR)
+                .join(file_name(&schema.name))
+                .join("schema.sql"),
+            &script,
+        );
+    }
+    let global_privileges: String = dump
+        .default_privileges
+        .iter()
+        .filter(|p| p.schema_name.is_empty())
+        .map(|p| p.get_script())
+        .collect();
+    add(PathBuf::from("default_privileges.sql"), &global_privileges);
+
+    for extension in &dump.extensions {
+        add(
+            Path::new("extensions").join(format!("{}.sql", file_name(&extension.name))),
+            &extension.get_script(),
+        );
+    }
+
+    for collation in &dump.collations {
+        add(
+            object_path(&collation.schema, "collations", &collation.name),
+            &collation.get_script(),
+        );
+    }
+
+    for pg_type in &dump.types {
+        // A multirange comes with its range type.
+        if pg_type.typtype as u8 == b'm' {
+            continue;
+        }
+        let name = format!("{}.{}", pg_type.schema, pg_type.typname);
+        let mut script = pg_type.get_script();
+        let public_default = format!("=U/{}", pg_type.owner);
+        script.push_str(&grants(
+            &pg_type.acl,
+            "TYPE",
+            &name,
+            &pg_type.owner,
+            Some(&public_default),
+        ));
+        add(
+            object_path(&pg_type.schema, "types", &pg_type.typname),
+            &script,
+        );
+    }
+
+    // Sequences created by a serial or identity column, keyed by table.
+    let mut column_sequences: BTreeMap<(&str, &str), Vec<usize>> = BTreeMap::new();
+    let mut serial_columns: HashSet<(&str, &str, &str)> = HashSet::new();
+    for (index, sequence) in dump.sequences.iter().enumerate() {
+        if let Some((table, column)) = owning_column(dump, index) {
+            column_sequences
+                .entry((table.schema.as_str(), table.name.as_str()))
+                .or_default()
+                .push(index);
+            if !column.is_identity {
+                serial_columns.insert((&table.schema, &table.name, &column.name));
+            }
+            continue;
+        }
+        let name = format!("{}.{}", sequence.schema, sequence.name);
+        let mut script = sequence.get_script();
+        script.push_str(&grants(
+            &sequence.acl,
+            "SEQUENCE",
+            &name,
+            &sequence.owner,
+            None,
+        ));
+        add(
+            object_path(&sequence.schema, "sequences", &sequence.name),
+            &script,
+        );
+    }
+
+    for table in &dump.tables {
+        let mut table = table.clone();
+        for column in &mut table.columns {
+            if serial_columns.contains(&(
+                table.schema.as_str(),
+                table.name.as_str(),
+                column.name.as_str(),
+            )) {
+                column.serial_type = Some(serial_type(&column.data_type).to_string());
+            }
+        }
+        let mut script = table.get_script();
+        script.push_str(&table.get_foreign_key_script());
+        for rule in &dump.rules {
+            if rule.schema == table.schema && rule.table_name == table.name {
+                script.push_str(&rule.get_script());
+            }
+        }
+        for statistic in &dump.statistics {
+            if statistic.table_schema == table.schema && statistic.table_name == table.name {
+                script.push_str(&statistic.get_script());
+            }
+        }
+        script.push_str(&table_grants(&table));
+        for &index in column_sequences
+            .get(&(table.schema.as_str(), table.name.as_str()))
+            .into_iter()
+            .flatten()
+        {
+            let sequence = &dump.sequences[index];
+            let name = format!("{}.{}", sequence.schema, sequence.name);
+            script.push_str(&grants(
+                &sequence.acl,
+                "SEQUENCE",
+                &name,
+                &sequence.owner,
+                None,
+            ));
+        }
+        add(
+            object_path(&table.schema, "tables", &table.raw_name),
+            &script,
+        );
+    }
+
+    for table in &dump.foreign_tables {
+        let name = format!("{}.{}", table.schema, table.name);
+        let mut script = table.get_script();
+        script.push_str(&grants(&table.acl, "TABLE", &name, &table.owner, None));
+        add(
+            object_path(&table.schema, "foreign_tables", &table.name),
+            &script,
+        );
+    }
+
+    let mut routines: Vec<&Routine> = dump.routines.iter().collect();
+    routines.sort_by(|a, b| {
+        (&a.schema, &a.name, &a.arguments).cmp(&(&b.schema, &b.name, &b.arguments))
+    });
+    for routine in routines {
+        let kind = match routine.kind.as_str() {
+            "procedure" => "PROCEDURE",
+            _ => "FUNCTION",
+        };
+        let name = format!("{}.{}({})", routine.schema, routine.name, routine.arguments);
+        let public_default = format!("=X/{}", routine.owner);
+        let mut script = routine.get_script();
+        script.push_str(&grants(
+            &routine.acl,
+            kind,
+            &name,
+            &routine.owner,
+            Some(&public_default),
+        ));
+        add(
+            object_path(&routine.schema, "functions", &routine.name),
+            &script,
+        );
+    }
+
+    for view in &dump.views {
+        let name = format!("{}.{}", view.schema, view.name);
+        let mut script = view.get_script();
+        for rule in &dump.rules {
+            if rule.schema == view.schema && rule.table_name == view.name {
+                script.push_str(&rule.get_script());
+            }
+        }
+        script.push_str(&grants(&view.acl, "TABLE", &name, &view.owner, None));
+        add(object_path(&view.schema, "views", &view.name), &script);
+    }
+
+    for operator in &dump.operators {
+        add(
+            Path::new(SCHEMAS_DIR)
+                .join(file_name(&operator.schema))
+                .join("operators.sql"),
+            &operator.get_script(),
+        );
+    }
+    for dict in &dump.ts_dicts {
+        add(
+            object_path(&dict.schema, "text_search", &dict.name),
+            &dict.get_script(),
+        );
+    }
+    for config in &dump.ts_configs {
+        add(
+            object_path(&config.schema, "text_search", &config.name),
+            &config.get_script(),
+        );
+    }
+
+    let casts: String = dump.casts.iter().map(|c| c.get_script()).collect();
+    add(PathBuf::from("casts.sql"), &casts);
+    let event_triggers: String = dump.event_triggers.iter().map(|t| t.get_script()).collect();
+    add(PathBuf::from("event_triggers.sql"), &event_triggers);
+    let foreign_data: String = dump
+        .foreign_data_wrappers
+        .iter()
+        .map(|w| w.get_script())
+        .chain(dump.foreign_servers.iter().map(|s| s.get_script()))
+        .chain(dump.user_mappings.iter().map(|m| m.get_script()))
+        .collect();
+    add(PathBuf::from("foreign_data.sql"), &foreign_data);
+    let publications: String = dump
+        .publications
+        .iter()
+        .map(|p| p.get_script())
+        .chain(dump.subscriptions.iter().map(|s| s.get_script()))
+        .collect();
+    add(PathBuf::from("publications.sql"), &publications);
+
+    files
+        .into_iter()
+        .map(|(path, script)| (path, normalize(&script)))
+        .collect()
+}
+
+/// The `.sql` file of the object `name` of `kind` in `schema`.
+fn object_path(schema: &str, kind: &str, name: &str) -> PathBuf {
+    Path::new(SCHEMAS_DIR)
+        .join(file_name(schema))
+        .join(kind)
+        .join(format!("{}.sql", file_name(name)))
+}
+
+/// `name` without identifier quotes, with characters that are not safe in
+/// a file name replaced by `_`, and so are the dots of a name made only of
+/// dots (`.` and `..` would point at a directory of the tree).
+fn file_name(name: &str) -> String {
+    let name = name
+        .strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .map_or_else(|| name.to_string(), |n| n.replace("\"\"", "\""));
+    if name.chars().all(|c| c == '.') {
+        return "_".repeat(name.len());
+    }
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '$') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The table and column whose serial default or identity created the
+/// sequence at `index`, as the comparer's creation path decides it.
+fn owning_column(
+    dump: &Dump,
+    index: usize,
+) -> Option<(&Table, &crate::dump::table_column::TableColumn)> {
+    let sequence = &dump.sequences[index];
+    let (schema, table, column) = (
+        sequence.owned_by_schema.as_deref()?,
+        sequence.owned_by_table.as_deref()?,
+        sequence.owned_by_column.as_deref()?,
+    );
+    let table = dump
+        .tables
+        .iter()
+        .find(|t| t.schema == schema && t.name == table)?;
+    let column = table.columns.iter().find(|c| c.name == column)?;
+    let serial = column
+        .column_default
+        .as_ref()
+        .is_some_and(|d| d.to_lowercase().contains("nextval("));
+    (column.is_identity || serial).then_some((table, column))
+}
+
+fn serial_type(data_type: &str) -> &'static str {
+    match data_type.to_lowercase().as_str() {
+        "bigint" => "bigserial",
+        "smallint" => "smallserial",
+        _ => "serial",
+    }
+}
+
+/// GRANT statements that give a new object `acl`. `public_default` is the
+/// entry PostgreSQL grants to PUBLIC on creation, revoked when `acl` is
+/// explicit and lacks it.
+fn grants(
+    acl: &[String],
+    kind: &str,
+    name: &str,
+    owner: &str,
+    public_default: Option<&str>,
+) -> String {
+    let from: Vec<String> = match public_default {
+        Some(entry) if !acl.is_empty() => vec![entry.to_string()],
+        _ => Vec::new(),
+    };
+    let owners: Vec<&str> = [owner].into_iter().filter(|o| !o.is_empty()).collect();
+    acl::generate_grants_script(&from, acl, true, kind, name, &[], &owners)
+}
+
+fn table_grants(table: &Table) -> String {
+    let name = format!("{}.{}", table.schema, table.name);
+    let mut script = grants(&table.acl, "TABLE", &name, &table.owner, None);
+    let owners: Vec<&str> = [table.owner.as_str()]
+        .into_iter()
+        .filter(|o| !o.is_empty())
+        .collect();
+    for column in &table.columns {
+        if !column.acl.is_empty() {
+            script.push_str(&acl::generate_column_grants_script(
+                &[],
+                &column.acl,
+                true,
+                &name,
+                &column.name,
+                &[],
+                &owners,
+            ));
+        }
+    }
+    script
+}
+
+/// `script` ending in a single newline. Statements are kept byte for byte:
+/// blank lines and trailing spaces can be part of a routine body.
+fn normalize(script: &str) -> String {
+    format!("{}\n", script.trim_end())
+}
+
+/// Removes the `.sql` files below `path` that are not in `written`, and the
+/// directories that end up empty.
+fn remove_stale(path: &Path, written: &HashSet<PathBuf>) -> Result<(), Error> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            remove_stale(&entry?.path(), written)?;
+        }
+        if fs::read_dir(path)?.next().is_none() {
+            fs::remove_dir(path)?;
+        }
+    } else if path.extension().is_some_and(|ext| ext == "sql")
+        && path.is_file()
+        && !written.contains(path)
+    {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "tree_tests.rs"]
+mod tests;
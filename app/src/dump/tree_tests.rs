@@ -0,0 +1,234 @@
+use super::*;
+use crate::config::dump_config::DumpConfig;
+use crate::config::ssl_settings::SslSettings;
+use crate::dump::sql_import::{import_sources, import_sql};
+
+fn config() -> DumpConfig {
+    DumpConfig {
+        host: String::new(),
+        port: String::new(),
+        user: "postgres".to_string(),
+        password: String::new(),
+        database: "offline".to_string(),
+        scheme: "app".to_string(),
+        ssl: false,
+        ssl_settings: SslSettings::default(),
+        file: String::new(),
+    }
+}
+
+const SCHEMA: &str = "
+CREATE SCHEMA app;
+GRANT USAGE ON SCHEMA app TO reader;
+ALTER DEFAULT PRIVILEGES IN SCHEMA app GRANT SELECT ON TABLES TO reader;
+CREATE TYPE app.mood AS ENUM ('sad', 'ok');
+CREATE SEQUENCE app.tickets START WITH 100;
+CREATE TABLE app.users (
+    id serial PRIMARY KEY,
+    mood app.mood DEFAULT 'ok',
+    \"Full name\" text
+);
+CREATE TABLE app.orders (
+    id bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id integer REFERENCES app.users (id)
+);
+GRANT SELECT ON app.users TO reader;
+GRANT USAGE ON SEQUENCE app.users_id_seq TO reader;
+CREATE FUNCTION app.add(a integer) RETURNS integer LANGUAGE sql AS $$ select a $$;
+CREATE FUNCTION app.add(a integer, b integer) RETURNS integer LANGUAGE sql AS $$ select a + b $$;
+REVOKE EXECUTE ON FUNCTION app.add(integer) FROM PUBLIC;
+CREATE VIEW app.names AS SELECT id FROM app.users;
+";
+
+fn dump() -> Dump {
+    let (dump, skipped) = import_sources(config(), &[("a.sql".to_string(), SCHEMA.to_string())]);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    dump
+}
+
+fn file<'a>(files: &'a BTreeMap<PathBuf, String>, path: &str) -> &'a str {
+    files
+        .get(Path::new(path))
+        .unwrap_or_else(|| panic!("{path} not in {:?}", files.keys()))
+}
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pgc_tree_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn writes_one_file_per_object() {
+    let files = tree_files(&dump());
+    let paths: Vec<String> = files.keys().map(|p| p.display().to_string()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "schemas/app/functions/add.sql",
+            "schemas/app/schema.sql",
+            "schemas/app/sequences/tickets.sql",
+            "schemas/app/tables/orders.sql",
+            "schemas/app/tables/users.sql",
+            "schemas/app/types/mood.sql",
+            "schemas/app/views/names.sql",
+        ]
+    );
+    assert!(
+        files
+            .values()
+            .all(|s| s.ends_with(";\n") && !s.ends_with("\n\n"))
+    );
+
+    let schema = file(&files, "schemas/app/schema.sql");
+    assert!(
+        schema.contains("create schema if not exists app;"),
+        "{schema}"
+    );
+    assert!(
+        schema.contains("GRANT USAGE ON SCHEMA app TO reader;"),
+        "{schema}"
+    );
+    assert!(
+        schema.contains("GRANT SELECT ON TABLES TO reader;"),
+        "{schema}"
+    );
+}
+
+#[test]
+fn keeps_column_sequences_with_their_table() {
+    let files = tree_files(&dump());
+    let users = file(&files, "schemas/app/tables/users.sql");
+    assert!(users.contains("    id serial not null,"), "{users}");
+    assert!(users.contains("\"Full name\" text"), "{users}");
+    assert!(
+        users.contains("GRANT SELECT ON TABLE app.users TO reader;"),
+        "{users}"
+    );
+    assert!(
+        users.contains("GRANT USAGE ON SEQUENCE app.users_id_seq TO reader;"),
+        "{users}"
+    );
+    let orders = file(&files, "schemas/app/tables/orders.sql");
+    assert!(orders.contains("generated ALWAYS as identity"), "{orders}");
+    assert!(
+        orders.contains("foreign key (user_id) references app.users(id)"),
+        "{orders}"
+    );
+}
+
+#[test]
+fn writes_overloads_to_one_file_with_their_grants() {
+    let files = tree_files(&dump());
+    let add = file(&files, "schemas/app/functions/add.sql");
+    let one = add.find("app.add(a integer)").unwrap();
+    let two = add.find("app.add(a integer, b integer)").unwrap();
+    assert!(one < two, "{add}");
+    assert!(
+        add.contains("REVOKE EXECUTE ON FUNCTION app.add(a integer) FROM PUBLIC;"),
+        "{add}"
+    );
+}
+
+#[test]
+fn file_names_drop_quotes_and_unsafe_characters() {
+    assert_eq!(file_name("users"), "users");
+    assert_eq!(file_name("\"Full name\""), "Full_name");
+    assert_eq!(file_name("\"a\"\"b\""), "a_b");
+    assert_eq!(file_name("../x"), ".._x");
+    assert_eq!(file_name("\"..\""), "__");
+    assert_eq!(file_name("."), "_");
+}
+
+#[test]
+fn names_mixed_case_objects_without_their_quotes() {
+    let sql = format!("{SCHEMA}CREATE VIEW app.\"MyView\" AS SELECT id FROM app.users;\n");
+    let (dump, skipped) = import_sources(config(), &[("a.sql".to_string(), sql)]);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let files = tree_files(&dump);
+    let view = file(&files, "schemas/app/views/MyView.sql");
+    assert!(view.contains("\"MyView\""), "{view}");
+}
+
+#[test]
+fn keeps_a_dot_dot_schema_inside_the_tree() {
+    let sql = "CREATE SCHEMA \"..\";\nCREATE TABLE \"..\".t (id integer);\n";
+    let mut config = config();
+    config.scheme = "..".to_string();
+    let (dump, skipped) = import_sources(config, &[("a.sql".to_string(), sql.to_string())]);
+    assert!(skipped.is_empty(), "{skipped:?}");
+    let files = tree_files(&dump);
+    let paths: Vec<String> = files.keys().map(|p| p.display().to_string()).collect();
+    assert_eq!(
+        paths,
+        vec!["schemas/__/schema.sql", "schemas/__/tables/t.sql"]
+    );
+}
+
+#[test]
+fn reads_schemas_and_types_before_tables() {
+    let mut paths = vec![
+        "casts.sql",
+        "schemas/app/views/v.sql",
+        "schemas/app/tables/t.sql",
+        "extensions/hstore.sql",
+        "schemas/app/types/t.sql",
+        "schemas/b/schema.sql",
+        "schemas/app/schema.sql",
+    ];
+    paths.sort_by_key(|p| read_order(Path::new(p)));
+    assert_eq!(
+        paths,
+        vec![
+            "schemas/app/schema.sql",
+            "schemas/b/schema.sql",
+            "extensions/hstore.sql",
+            "schemas/app/types/t.sql",
+            "schemas/app/tables/t.sql",
+            "schemas/app/views/v.sql",
+            "casts.sql",
+        ]
+    );
+}
+
+#[test]
+fn export_removes_stale_files_only() {
+    let dir = TempDir::new("stale");
+    let stale = dir.0.join("schemas/old/tables/gone.sql");
+    fs::create_dir_all(stale.parent().unwrap()).unwrap();
+    fs::write(&stale, "create table old.gone ();\n").unwrap();
+    fs::write(dir.0.join("README.md"), "notes").unwrap();
+
+    let written = export_tree(&dump(), dir.path()).unwrap();
+    assert_eq!(written.len(), 7);
+    assert!(!stale.exists());
+    assert!(!dir.0.join("schemas/old").exists());
+    assert!(dir.0.join("README.md").exists());
+    assert!(dir.0.join("schemas/app/tables/users.sql").exists());
+}
+
+#[test]
+fn exported_tree_imports_back_to_the_same_dump() {
+    let dir = TempDir::new("round_trip");
+    let original = dump();
+    export_tree(&original, dir.path()).unwrap();
+    assert!(is_tree(&dir.0));
+
+    let (imported, skipped) = import_sql(config(), dir.path()).unwrap();
+    assert!(skipped.is_empty(), "{skipped:?}");
+    assert_eq!(tree_files(&imported), tree_files(&original));
+}
@@ -1,7 +1,23 @@
 use crate::{
     comparer::core::Comparer,
-    config::{core::Config, dump_config::DumpConfig, grants_mode::GrantsMode},
-    dump::core::Dump,
+    config::{
+        column_order_mode::ColumnOrderMode,
+        core::Config,
+        dump_config::DumpConfig,
+        grants_mode::GrantsMode,
+        lock_settings::{LockSettings, parse_duration},
+        migration_format::MigrationFormat,
+        object_filter::{FilterRule, ObjectFilter},
+        ssl_settings::{SslSettings, parse_ssl_mode},
+    },
+    dump::{
+        core::Dump,
+        filter::apply_filter,
+        secrets::{Secrets, redact_secrets},
+        show::show,
+        sql_import::import_sql,
+        tree::export_tree,
+    },
 };
 use chrono::Datelike;
 use clap::{CommandFactory, Parser};
@@ -22,28 +38,29 @@ pub mod utils;
     long_about = None,
 )]
 struct Args {
-    /// Command to execute: dump, compare or clear
+    /// Command to execute: dump, compare, script, export, show or clear
     #[arg(long)]
     command: Option<String>,
 
-    /// PostgreSQL server hostname
-    #[arg(long, default_value = "localhost")]
+    /// PostgreSQL server hostname, Unix socket directory or postgres:// URL
+    /// (empty: PGHOST, the service file or localhost)
+    #[arg(long, default_value = "")]
     server: Option<String>,
 
-    /// PostgreSQL server port
-    #[arg(long, default_value = "5432")]
+    /// PostgreSQL server port (empty: PGPORT or 5432)
+    #[arg(long, default_value = "")]
     port: Option<String>,
 
-    /// PostgreSQL user name
+    /// PostgreSQL user name (empty: PGUSER or the operating-system user)
     #[arg(long, default_value = "")]
     user: Option<String>,
 
-    /// PostgreSQL user password
+    /// PostgreSQL user password (empty: PGPASSWORD or the password file)
     #[arg(long, default_value = "")]
     password: Option<String>,
 
-    /// PostgreSQL database name
-    #[arg(long, default_value = "postgres")]
+    /// PostgreSQL database name (empty: PGDATABASE or postgres)
+    #[arg(long, default_value = "")]
     database: Option<String>,
 
     /// PostgreSQL schema name — matched against pg_namespace.nspname using
@@ -52,11 +69,11 @@ struct Args {
     #[arg(long, default_value = "public")]
     scheme: Option<String>,
 
-    /// Output file name for the command
+    /// Output file name for the command (a directory for export)
     #[arg(long, default_value = "data.out")]
     output: Option<String>,
 
-    /// From file for comparison
+    /// From file for comparison, or the dump to export or show
     #[arg(long, default_value = "dump.from")]
     from: Option<String>,
 
@@ -64,6 +81,46 @@ struct Args {
     #[arg(long, default_value = "dump.to")]
     to: Option<String>,
 
+    /// With --command dump: build the dump from SQL DDL instead of a live
+    /// database — a .sql file or a directory of them (e.g. `pg_dump
+    /// --schema-only` output or a migrations folder). Statements that cannot
+    /// be modelled are listed with their file and line.
+    #[arg(long)]
+    from_sql: Option<String>,
+
+    /// With --command show: the objects to show. `tables` or
+    /// `tables:app.*` lists the objects of a kind with their hashes;
+    /// `table:public.users` or `function:app.f(int)` prints their DDL.
+    /// Without it the dump header and the object counts are printed.
+    #[arg(long)]
+    object: Option<String>,
+
+    /// With --command dump or compare: keep only the objects of a kind whose
+    /// name matches, given as `kind:pattern` (e.g. `tables:app.*`). Kinds:
+    /// tables, views, routines, types, sequences, triggers, indexes,
+    /// policies, grants, extensions or all. Repeatable.
+    #[arg(long, value_parser = parse_include)]
+    include: Vec<FilterRule>,
+
+    /// With --command dump or compare: leave out the objects whose name
+    /// matches, given as `kind:pattern` (e.g. `tables:*.*_tmp` or
+    /// `triggers:*.*.audit_*`). `grants:pattern` keeps the objects and drops
+    /// their privileges. Repeatable; wins over --include.
+    #[arg(long, value_parser = parse_exclude)]
+    exclude: Vec<FilterRule>,
+
+    /// Replace the passwords stored in subscription connection strings and
+    /// foreign server and user mapping options with placeholders, in dumps
+    /// and in everything generated from them. Default: true.
+    #[arg(long, default_value_t = true, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    redact_secrets: bool,
+
+    /// File of NAME=value lines with the real values of redacted secrets,
+    /// written into compare and script output. Environment variables of the
+    /// same names take precedence.
+    #[arg(long)]
+    secrets_file: Option<String>,
+
     /// Configuration file for the comparer
     #[arg(long)]
     config: Option<String>,
@@ -72,6 +129,27 @@ struct Args {
     #[arg(long)]
     use_ssl: bool,
 
+    /// libpq sslmode: disable, allow, prefer, require, verify-ca or
+    /// verify-full (wins over --use-ssl)
+    #[arg(long, value_parser = parse_ssl_mode)]
+    sslmode: Option<String>,
+
+    /// CA certificates file the server certificate is verified against
+    #[arg(long)]
+    sslrootcert: Option<String>,
+
+    /// Client certificate file
+    #[arg(long)]
+    sslcert: Option<String>,
+
+    /// Client private key file
+    #[arg(long)]
+    sslkey: Option<String>,
+
+    /// Passphrase of an encrypted (PKCS#8) client key
+    #[arg(long)]
+    sslpassword: Option<String>,
+
     /// Use DROP statements in the output
     #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
     use_drop: bool,
@@ -105,6 +183,90 @@ struct Args {
     #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
     output_for_production: bool,
 
+    /// What to do when a table's column order cannot be reproduced by
+    /// ALTER TABLE (new columns are always appended):
+    ///   ignore  - keep the appended order (default);
+    ///   report  - add a comment describing the mismatch to the script;
+    ///   rebuild - rebuild the table with a data copy in the target order.
+    #[arg(long, value_parser = parse_column_order_mode, default_value = "ignore")]
+    column_order: ColumnOrderMode,
+
+    /// With --output-for-production: `lock_timeout` set at the top of the
+    /// script and before each post-commit statement, so DDL fails fast instead
+    /// of queueing behind a long-running query (e.g. 5s, 500ms).
+    #[arg(long, value_parser = parse_duration)]
+    lock_timeout: Option<String>,
+
+    /// With --output-for-production: `statement_timeout` set at the top of the
+    /// script and before each post-commit statement (e.g. 15min).
+    #[arg(long, value_parser = parse_duration)]
+    statement_timeout: Option<String>,
+
+    /// With --output-for-production: retry each DDL statement up to this many
+    /// times, with backoff, when it hits the lock timeout. Requires
+    /// --lock-timeout. Default: 0 (no retries).
+    #[arg(long, default_value = "0", requires = "lock_timeout")]
+    lock_retries: u32,
+
+    /// Put a `-- lock: MODE on relation` comment before every statement that
+    /// locks an existing table, index, view or sequence. Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    lock_annotations: bool,
+
+    /// Group consecutive single-table statements (ALTER TABLE, CREATE INDEX,
+    /// COMMENT ON) per table, so each table's changes are applied together.
+    /// Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    group_by_table: bool,
+
+    /// How the compare result is written:
+    ///   single         - one script file at --output (default);
+    ///   flyway         - Flyway V__ migrations in the --output directory;
+    ///   golang-migrate - golang-migrate up/down files in the --output directory;
+    ///   sqitch         - Sqitch deploy/revert/verify scripts and sqitch.plan
+    ///                    entries in the --output directory;
+    ///   liquibase-xml  - a Liquibase XML changelog in the --output directory;
+    ///   liquibase-yaml - a Liquibase YAML changelog in the --output directory.
+    /// The post-commit section goes to its own, non-transactional migration
+    /// (non-transactional changeSets for Liquibase).
+    #[arg(long, value_parser = parse_migration_format, default_value = "single")]
+    migration_format: MigrationFormat,
+
+    /// Name used in the migration file names (with --migration-format).
+    #[arg(long, default_value = "pgc_delta")]
+    migration_name: String,
+
+    /// Also write a rollback script to this file: the same comparison in the
+    /// opposite direction (TO back to FROM), with the same options. Its header
+    /// lists the steps that cannot be reversed without data loss.
+    #[arg(long)]
+    rollback_output: Option<String>,
+
+    /// Give every statement a stable step id and guard it with a check of the
+    /// public.pgc_migration_progress ledger, so a migration that failed can be
+    /// rerun from where it stopped. Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    progress_ledger: bool,
+
+    /// Start the script with a DO block that recomputes a fingerprint of the
+    /// target's tables and sequences and aborts when it does not match the
+    /// FROM dump. Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    fingerprint_guard: bool,
+
+    /// Start the script with a check per new NOT NULL, UNIQUE, PRIMARY KEY,
+    /// CHECK or FOREIGN KEY constraint that aborts it, with a sample of the
+    /// offending keys, when existing rows violate it. Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    data_checks: bool,
+
+    /// Make the script re-runnable against a database it has already
+    /// (partly) built: `if [not] exists` / `or replace` guards, and the CREATE
+    /// statements without such a clause wrapped in a DO block that skips
+    /// existing objects. Default: false.
+    #[arg(long, default_value_t = false, num_args = 0..=1, default_missing_value = "true", value_parser = clap::builder::BoolishValueParser::new(), action = clap::ArgAction::Set)]
+    idempotent: bool,
+
     /// Use CASCADE in DROP statements for the clear command. WARNING: CASCADE can drop
     /// dependent objects outside the selected schema(s) (e.g., foreign keys or views in
     /// other schemas that reference the dropped objects). Without this flag, drops rely
@@ -116,6 +278,22 @@ struct Args {
 fn parse_grants_mode(src: &str) -> Result<GrantsMode, String> {
     src.parse::<GrantsMode>()
 }
+
+fn parse_column_order_mode(src: &str) -> Result<ColumnOrderMode, String> {
+    src.parse::<ColumnOrderMode>()
+}
+
+fn parse_migration_format(src: &str) -> Result<MigrationFormat, String> {
+    src.parse::<MigrationFormat>()
+}
+
+fn parse_include(src: &str) -> Result<FilterRule, String> {
+    FilterRule::parse(src, true)
+}
+
+fn parse_exclude(src: &str) -> Result<FilterRule, String> {
+    FilterRule::parse(src, false)
+}
 // Main entry point for the program.
 #[tokio::main]
 pub async fn main() -> Result<(), Error> {
@@ -138,21 +316,39 @@ async fn run_main() -> Result<(), Error> {
         println!("Using configuration file: {config}");
         return run_by_config(config).await;
     } else if let Some(command) = args.command.as_deref() {
+        let filter = ObjectFilter {
+            rules: [args.include.clone(), args.exclude.clone()].concat(),
+        };
+        let ssl_settings = SslSettings {
+            mode: args.sslmode.clone(),
+            root_cert: args.sslrootcert.clone(),
+            cert: args.sslcert.clone(),
+            key: args.sslkey.clone(),
+            password: args.sslpassword.clone(),
+        };
         match command {
             "dump" => {
+                let dump_config = DumpConfig {
+                    host: args.server.unwrap(),
+                    port: args.port.unwrap(),
+                    user: args.user.unwrap(),
+                    password: args.password.unwrap(),
+                    database: args.database.unwrap(),
+                    scheme: args.scheme.unwrap(),
+                    ssl: args.use_ssl,
+                    ssl_settings,
+                    file: args.output.unwrap(),
+                };
+                if let Some(path) = args.from_sql {
+                    println!("Dumping SQL files...");
+                    return create_dump_from_sql(dump_config, &path, &filter, args.redact_secrets);
+                }
                 println!("Dumping database...");
                 return create_dump(
-                    DumpConfig {
-                        host: args.server.unwrap(),
-                        port: args.port.unwrap(),
-                        user: args.user.unwrap(),
-                        password: args.password.unwrap(),
-                        database: args.database.unwrap(),
-                        scheme: args.scheme.unwrap(),
-                        ssl: args.use_ssl,
-                        file: args.output.unwrap(),
-                    },
+                    dump_config,
                     args.max_connections,
+                    &filter,
+                    args.redact_secrets,
                 )
                 .await;
             }
@@ -167,9 +363,62 @@ async fn run_main() -> Result<(), Error> {
                     args.use_comments,
                     args.grants_mode,
                     args.output_for_production,
+                    args.column_order,
+                    LockSettings {
+                        lock_timeout: args.lock_timeout,
+                        statement_timeout: args.statement_timeout,
+                        retries: args.lock_retries,
+                    },
+                    args.lock_annotations,
+                    args.group_by_table,
+                    args.migration_format,
+                    args.migration_name,
+                    args.rollback_output,
+                    args.progress_ledger,
+                    args.fingerprint_guard,
+                    args.data_checks,
+                    filter,
+                    args.redact_secrets,
+                    args.secrets_file,
+                )
+                .await;
+            }
+            "script" => {
+                println!("Generating creation script...");
+                return create_script(
+                    args.to.unwrap(),
+                    args.output.unwrap(),
+                    args.use_single_transaction,
+                    args.use_comments,
+                    args.output_for_production,
+                    LockSettings {
+                        lock_timeout: args.lock_timeout,
+                        statement_timeout: args.statement_timeout,
+                        retries: args.lock_retries,
+                    },
+                    args.lock_annotations,
+                    args.group_by_table,
+                    args.migration_format,
+                    args.migration_name,
+                    args.progress_ledger,
+                    args.idempotent,
+                    args.redact_secrets,
+                    args.secrets_file,
                 )
                 .await;
             }
+            "export" => {
+                println!("Exporting dump...");
+                return export_dump(
+                    args.from.unwrap(),
+                    args.output.unwrap(),
+                    args.redact_secrets,
+                )
+                .await;
+            }
+            "show" | "inspect" => {
+                return show_dump(args.from.unwrap(), args.object, args.redact_secrets).await;
+            }
             "clear" => {
                 println!("Generating clear script...");
                 return clear_database(
@@ -181,6 +430,7 @@ async fn run_main() -> Result<(), Error> {
                         database: args.database.unwrap(),
                         scheme: args.scheme.unwrap(),
                         ssl: args.use_ssl,
+                        ssl_settings,
                         file: String::new(),
                     },
                     args.output.unwrap(),
@@ -236,9 +486,12 @@ async fn run_by_config(config: String) -> Result<(), Error> {
                 database: cfg.from.database,
                 scheme: cfg.from.scheme,
                 ssl: cfg.from.ssl,
+                ssl_settings: cfg.from.ssl_settings,
                 file: from_file.clone(),
             },
             cfg.max_connections,
+            &cfg.object_filter,
+            cfg.redact_secrets,
         )
         .await;
         if let Err(e) = result {
@@ -254,9 +507,12 @@ async fn run_by_config(config: String) -> Result<(), Error> {
                 database: cfg.to.database,
                 scheme: cfg.to.scheme,
                 ssl: cfg.to.ssl,
+                ssl_settings: cfg.to.ssl_settings,
                 file: to_file.clone(),
             },
             cfg.max_connections,
+            &cfg.object_filter,
+            cfg.redact_secrets,
         )
         .await;
         if let Err(e) = result {
@@ -274,6 +530,19 @@ async fn run_by_config(config: String) -> Result<(), Error> {
             cfg.use_comments,
             cfg.grants_mode,
             cfg.output_for_production,
+            cfg.column_order_mode,
+            cfg.lock_settings,
+            cfg.lock_annotations,
+            cfg.group_by_table,
+            cfg.migration_format,
+            cfg.migration_name,
+            cfg.rollback_output,
+            cfg.progress_ledger,
+            cfg.fingerprint_guard,
+            cfg.data_checks,
+            cfg.object_filter,
+            cfg.redact_secrets,
+            cfg.secrets_file,
         )
         .await;
 
@@ -291,17 +560,113 @@ async fn run_by_config(config: String) -> Result<(), Error> {
     }
 }
 
-async fn create_dump(dump_config: DumpConfig, max_connections: u32) -> Result<(), Error> {
+async fn create_dump(
+    dump_config: DumpConfig,
+    max_connections: u32,
+    filter: &ObjectFilter,
+    redact: bool,
+) -> Result<(), Error> {
     let mut dump = Dump::new(dump_config);
     println!("Creating dump...");
-    let result = dump.process(max_connections).await;
-    if let Err(e) = result {
-        eprintln!("Error creating dump: {e}");
-        return Err(e);
+    match dump.process(max_connections, filter, redact).await {
+        Ok(excluded) => print_excluded(&excluded),
+        Err(e) => {
+            eprintln!("Error creating dump: {e}");
+            return Err(e);
+        }
     }
     Ok(())
 }
 
+// Reads a dump, redacting its secrets unless `redact` is off: a dump taken
+// with --redact-secrets false, or by an older pgc, may hold them in plain text.
+async fn read_dump(path: &str, redact: bool) -> Result<Dump, Error> {
+    let mut dump = Dump::read_from_file(path).await?;
+    if redact {
+        redact_secrets(&mut dump);
+    }
+    Ok(dump)
+}
+
+// Loads the values for redacted secrets from `file`, if given; the
+// environment is read when the script is rendered.
+fn load_secrets(file: Option<&str>) -> Result<Secrets, Error> {
+    file.map(Secrets::load)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+// Warns about the redacted secrets a generated script still holds as
+// placeholders.
+fn warn_missing_secrets(comparer: &Comparer) {
+    let missing = comparer.missing_secrets();
+    if !missing.is_empty() {
+        eprintln!(
+            "Warning: the script holds {} redacted secret(s) without a value; set them \
+             in the environment or the secrets file: {}.",
+            missing.len(),
+            missing.join(", ")
+        );
+    }
+}
+
+// Prints the objects the include/exclude filters left out.
+fn print_excluded(excluded: &[String]) {
+    if !excluded.is_empty() {
+        println!("Excluded by filters ({}):", excluded.len());
+        for object in excluded {
+            println!(" - {object}");
+        }
+    }
+}
+
+fn create_dump_from_sql(
+    dump_config: DumpConfig,
+    path: &str,
+    filter: &ObjectFilter,
+    redact: bool,
+) -> Result<(), Error> {
+    let output = dump_config.file.clone();
+    let (mut dump, skipped) = import_sql(dump_config, path)?;
+    if !skipped.is_empty() {
+        println!("Statements not modelled ({}):", skipped.len());
+        for statement in &skipped {
+            println!(" - {statement}");
+        }
+    }
+    print_excluded(&apply_filter(&mut dump, filter));
+    if redact {
+        redact_secrets(&mut dump);
+    }
+    println!("--> Database info:\n{}\n", dump.get_info());
+    dump.write_to_file(&output)?;
+    println!("Dump created successfully: {output}");
+    Ok(())
+}
+
+async fn export_dump(from: String, output: String, redact: bool) -> Result<(), Error> {
+    println!("Reading dump...");
+    let dump = read_dump(&from, redact).await?;
+    println!(
+        "--> Dump:\n\t{}\n{}\n",
+        dump.metadata.summary(),
+        dump.get_info()
+    );
+    let files = export_tree(&dump, &output)?;
+    println!(
+        "Dump exported successfully: {output} ({} files)",
+        files.len()
+    );
+    Ok(())
+}
+
+async fn show_dump(from: String, object: Option<String>, redact: bool) -> Result<(), Error> {
+    let dump = read_dump(&from, redact).await?;
+    let text = show(&dump, object.as_deref()).map_err(Error::other)?;
+    print!("{text}");
+    Ok(())
+}
+
 async fn clear_database(
     dump_config: DumpConfig,
     output: String,
@@ -332,13 +697,61 @@ async fn compare_dumps(
     use_comments: bool,
     grants_mode: GrantsMode,
     output_for_production: bool,
+    column_order_mode: ColumnOrderMode,
+    lock_settings: LockSettings,
+    lock_annotations: bool,
+    group_by_table: bool,
+    migration_format: MigrationFormat,
+    migration_name: String,
+    rollback_output: Option<String>,
+    progress_ledger: bool,
+    fingerprint_guard: bool,
+    data_checks: bool,
+    filter: ObjectFilter,
+    redact: bool,
+    secrets_file: Option<String>,
 ) -> Result<(), Error> {
+    let secrets = load_secrets(secrets_file.as_deref())?;
     println!("Reading dumps...");
-    let from = Dump::read_from_file(&from).await?;
-    let to = Dump::read_from_file(&to).await?;
-    println!("--> Dump from:\n{}\n", from.get_info());
-    println!("--> Dump to:\n{}\n", to.get_info());
+    let mut from = read_dump(&from, redact).await?;
+    let mut to = read_dump(&to, redact).await?;
+    // Dumps taken without the filter, or with another one, are filtered
+    // again so both sides leave out the same objects.
+    let mut excluded = apply_filter(&mut from, &filter);
+    for object in apply_filter(&mut to, &filter) {
+        if !excluded.contains(&object) {
+            excluded.push(object);
+        }
+    }
+    print_excluded(&excluded);
+    println!(
+        "--> Dump from:\n\t{}\n{}\n",
+        from.metadata.summary(),
+        from.get_info()
+    );
+    println!(
+        "--> Dump to:\n\t{}\n{}\n",
+        to.metadata.summary(),
+        to.get_info()
+    );
     println!("Comparing dumps...");
+    // The rollback compares the same dumps in the opposite direction. It is
+    // also the down / revert side of the migration formats that have one.
+    let rollback_dumps = (rollback_output.is_some() || migration_format.has_reverse())
+        .then(|| (to.clone(), from.clone()));
+    let configure = |comparer: &mut Comparer| {
+        comparer.set_output_for_production(output_for_production);
+        comparer.set_column_order_mode(column_order_mode);
+        comparer.set_lock_settings(lock_settings.clone());
+        comparer.set_lock_annotations(lock_annotations);
+        comparer.set_group_by_table(group_by_table);
+        comparer.set_progress_ledger(progress_ledger);
+        comparer.set_fingerprint_guard(fingerprint_guard);
+        comparer.set_data_checks(data_checks);
+        comparer.set_excluded_objects(excluded.clone());
+        comparer.set_object_filter(filter.clone());
+        comparer.set_secrets(secrets.clone());
+    };
     let mut comparer = Comparer::new(
         from,
         to,
@@ -347,9 +760,111 @@ async fn compare_dumps(
         use_comments,
         grants_mode,
     );
+    if let Some(warning) = comparer.major_version_warning() {
+        eprintln!("Warning: {warning}.");
+    }
+    configure(&mut comparer);
+    comparer.compare().await?;
+    warn_missing_secrets(&comparer);
+    let rollback = match rollback_dumps {
+        Some((rollback_from, rollback_to)) => {
+            println!("Generating rollback script...");
+            let mut rollback = Comparer::new(
+                rollback_from,
+                rollback_to,
+                use_drop,
+                use_single_transaction,
+                use_comments,
+                grants_mode,
+            );
+            configure(&mut rollback);
+            rollback.set_rollback(true);
+            rollback.compare().await?;
+            Some(rollback)
+        }
+        None => None,
+    };
+    if migration_format == MigrationFormat::Single {
+        comparer.save_script(&output).await?;
+        println!("Dump compared successfully. Result script: {output}");
+    } else {
+        let files = comparer
+            .save_migration(
+                &output,
+                migration_format,
+                &migration_name,
+                rollback.as_ref(),
+            )
+            .await?;
+        println!("Dump compared successfully. {migration_format} migration files:");
+        for file in files {
+            println!("  {file}");
+        }
+    }
+    if let Some(rollback) = &rollback {
+        let data_loss = rollback.data_loss_warnings().len();
+        let data_loss = if data_loss > 0 {
+            format!(" ({data_loss} step(s) lose data, see its header)")
+        } else {
+            String::new()
+        };
+        match &rollback_output {
+            Some(rollback_output) => {
+                rollback.save_script(rollback_output).await?;
+                println!("Rollback script: {rollback_output}{data_loss}");
+            }
+            None => println!("Reverse migrations written with the migration files{data_loss}"),
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_script(
+    to: String,
+    output: String,
+    use_single_transaction: bool,
+    use_comments: bool,
+    output_for_production: bool,
+    lock_settings: LockSettings,
+    lock_annotations: bool,
+    group_by_table: bool,
+    migration_format: MigrationFormat,
+    migration_name: String,
+    progress_ledger: bool,
+    idempotent: bool,
+    redact: bool,
+    secrets_file: Option<String>,
+) -> Result<(), Error> {
+    let secrets = load_secrets(secrets_file.as_deref())?;
+    println!("Reading dump...");
+    let to = read_dump(&to, redact).await?;
+    println!(
+        "--> Dump:\n\t{}\n{}\n",
+        to.metadata.summary(),
+        to.get_info()
+    );
+    let mut comparer = Comparer::new_creation_script(to, use_single_transaction, use_comments);
     comparer.set_output_for_production(output_for_production);
+    comparer.set_lock_settings(lock_settings);
+    comparer.set_lock_annotations(lock_annotations);
+    comparer.set_group_by_table(group_by_table);
+    comparer.set_progress_ledger(progress_ledger);
+    comparer.set_idempotent(idempotent);
+    comparer.set_secrets(secrets);
     comparer.compare().await?;
-    comparer.save_script(&output).await?;
-    println!("Dump compared successfully. Result script: {output}");
+    warn_missing_secrets(&comparer);
+    if migration_format == MigrationFormat::Single {
+        comparer.save_script(&output).await?;
+        println!("Creation script generated successfully: {output}");
+    } else {
+        let files = comparer
+            .save_migration(&output, migration_format, &migration_name, None)
+            .await?;
+        println!("Creation script generated successfully. {migration_format} migration files:");
+        for file in files {
+            println!("  {file}");
+        }
+    }
     Ok(())
 }
@@ -12,3 +12,16 @@ fn append_block_appends_content_and_two_newlines() {
     s.append_block("b");
     assert_eq!(s, "ab\n\n");
 }
+
+#[test]
+fn glob_match_supports_stars_and_question_marks() {
+    assert!(glob_match("*_tmp", "orders_tmp"));
+    assert!(!glob_match("*_tmp", "orders_tmp2"));
+    assert!(glob_match("public.*", "public.users"));
+    assert!(glob_match("a*b*c", "axxbyybzc"));
+    assert!(glob_match("user?", "users"));
+    assert!(!glob_match("user?", "user"));
+    assert!(glob_match("*", ""));
+    assert!(glob_match("exact", "exact"));
+    assert!(!glob_match("exact", "exactly"));
+}